@@ -0,0 +1,136 @@
+// Throughput and allocations of the block flattening functions over a full-sized block.
+// Run with `cargo bench --bench index`.
+
+use bitcoin_sdk::{
+    BlockDelta, BlockVerbose, created_outputs, index_block_spends, iter_block_spends,
+    iter_created_outputs,
+};
+use serde_json::{Value, json};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+// Counts every allocation so the per-row cost of each form shows up
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const TRANSACTIONS: usize = 3_000;
+const ITERATIONS: u32 = 50;
+
+fn txid(n: usize) -> String {
+    format!("{:064x}", n)
+}
+
+fn output(n: usize) -> Value {
+    json!({
+        "value": 0.001 + n as f64 * 1e-8,
+        "n": n,
+        "scriptPubKey": {
+            "asm": "",
+            "hex": "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+            "type": "witness_v0_keyhash",
+            "address": "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+        },
+    })
+}
+
+// A coinbase followed by two-input, two-output spends, about the shape of a busy block
+fn block() -> BlockVerbose {
+    let mut tx = vec![json!({
+        "txid": txid(0), "hash": txid(0), "version": 2, "size": 100, "vsize": 100,
+        "weight": 400, "locktime": 0, "hex": "",
+        "vin": [{"coinbase": "03a08601", "sequence": 4294967295u32}],
+        "vout": [output(0)],
+    })];
+    for i in 1..TRANSACTIONS {
+        tx.push(json!({
+            "txid": txid(i), "hash": txid(i), "version": 2, "size": 222, "vsize": 141,
+            "weight": 561, "locktime": 0, "hex": "",
+            "vin": [
+                {"txid": txid(i + 1_000_000), "vout": 0, "scriptSig": {"asm": "", "hex": ""},
+                 "sequence": 4294967293u32},
+                {"txid": txid(i + 2_000_000), "vout": 1, "scriptSig": {"asm": "", "hex": ""},
+                 "sequence": 4294967293u32},
+            ],
+            "vout": [output(0), output(1)],
+        }));
+    }
+    serde_json::from_value(json!({
+        "hash": "22".repeat(32), "confirmations": 1, "size": 1_500_000, "weight": 3_990_000,
+        "height": 850_000, "version": 536870912, "versionHex": "20000000",
+        "merkleroot": "33".repeat(32), "tx": tx, "time": 1718000000,
+        "mediantime": 1718000000, "nonce": 0, "bits": "17034219",
+        "difficulty": 83148355189239.77, "chainwork": "00".repeat(32), "nTx": TRANSACTIONS,
+    }))
+    .unwrap()
+}
+
+// Runs `f` repeatedly and reports time and allocations per row
+fn bench(name: &str, rows: usize, mut f: impl FnMut() -> usize) -> usize {
+    assert_eq!(f(), rows);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+    let allocated = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let runs = rows * ITERATIONS as usize;
+    println!(
+        "{:<24} {:>8.1} ns/row {:>6.2} allocs/row",
+        name,
+        elapsed.as_nanos() as f64 / runs as f64,
+        allocated as f64 / runs as f64
+    );
+    allocated
+}
+
+fn main() {
+    let block = block();
+    let spends = (TRANSACTIONS - 1) * 2;
+    let outputs = 1 + (TRANSACTIONS - 1) * 2;
+    println!("{} spends, {} outputs per block", spends, outputs);
+
+    let borrowed = bench("iter_block_spends", spends, || {
+        iter_block_spends(black_box(&block)).count()
+    });
+    assert_eq!(borrowed, 0, "borrowing spends iterator allocated");
+    let borrowed = bench("iter_created_outputs", outputs, || {
+        iter_created_outputs(black_box(&block))
+            .filter(|created| created.is_ok())
+            .count()
+    });
+    assert_eq!(borrowed, 0, "borrowing outputs iterator allocated");
+
+    bench("index_block_spends", spends, || {
+        index_block_spends(black_box(&block)).len()
+    });
+    bench("created_outputs", outputs, || {
+        created_outputs(black_box(&block)).unwrap().len()
+    });
+    bench("BlockDelta::from_block", spends + outputs, || {
+        let delta = BlockDelta::from_block(black_box(&block)).unwrap();
+        delta.spent.len() + delta.created.len()
+    });
+    let delta = BlockDelta::from_block(&block).unwrap();
+    bench("BlockDelta to JSON", spends + outputs, || {
+        black_box(serde_json::to_vec(&delta).unwrap());
+        spends + outputs
+    });
+}
@@ -0,0 +1,73 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::BitcoinClient;
+use crate::error::BitcoinRpcError;
+
+// Why `abandon_transaction` refused. An unknown txid is `BitcoinRpcError::NotInWallet`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AbandonError {
+    // The transaction is in the mempool, confirmed or conflicted
+    #[error("Transaction not eligible for abandonment: {0}")]
+    NotEligible(String),
+}
+
+// What `abandon_if_dropped` found, and did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbandonOutcome {
+    // Still in the mempool, so left alone
+    InMempool,
+    Confirmed { confirmations: i64 },
+    // A conflicting transaction confirmed, which already frees the inputs
+    Conflicted { confirmations: i64 },
+    AlreadyAbandoned,
+    Abandoned,
+}
+
+impl BitcoinClient {
+    // Mark an unconfirmed wallet transaction that is not in the mempool as abandoned,
+    // so the wallet can spend its inputs again
+    pub async fn abandon_transaction(&self, txid: &str) -> Result<()> {
+        self.call::<Value>("abandontransaction", json!([txid]))
+            .await
+            .map_err(|e| match e.downcast::<BitcoinRpcError>() {
+                Ok(BitcoinRpcError::InvalidAddressOrKey(message))
+                    if message.contains("not eligible") =>
+                {
+                    AbandonError::NotEligible(message).into()
+                }
+                Ok(BitcoinRpcError::InvalidAddressOrKey(message)) => {
+                    BitcoinRpcError::NotInWallet(message).into()
+                }
+                Ok(other) => other.into(),
+                Err(e) => e,
+            })?;
+        Ok(())
+    }
+
+    // Abandon the transaction only when it has left the mempool without confirming
+    pub async fn abandon_if_dropped(&self, txid: &str) -> Result<AbandonOutcome> {
+        match self.get_mempool_entry(txid).await {
+            Ok(_) => return Ok(AbandonOutcome::InMempool),
+            Err(e) if matches!(e.downcast_ref(), Some(BitcoinRpcError::NotInMempool(_))) => {}
+            Err(e) => return Err(e),
+        }
+        let tx = self.get_transaction(txid, true, false).await?;
+        if tx.confirmations > 0 {
+            return Ok(AbandonOutcome::Confirmed {
+                confirmations: tx.confirmations,
+            });
+        }
+        if tx.confirmations < 0 {
+            return Ok(AbandonOutcome::Conflicted {
+                confirmations: tx.confirmations,
+            });
+        }
+        if tx.details.iter().any(|d| d.abandoned == Some(true)) {
+            return Ok(AbandonOutcome::AlreadyAbandoned);
+        }
+        self.abandon_transaction(txid).await?;
+        Ok(AbandonOutcome::Abandoned)
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::{Result, anyhow};
+use bech32::Variant;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fmt;
+
+use crate::BitcoinClient;
+use crate::crypto::{AddressType, BitcoinCrypto};
+use crate::error::BitcoinRpcError;
+
+const DEFAULT_ADDRESS_CHUNK_SIZE: u32 = 100;
+
+// Address kinds accepted by `getnewaddress`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NewAddressType {
+    #[serde(rename = "legacy")]
+    Legacy,
+    #[serde(rename = "p2sh-segwit")]
+    P2shSegwit,
+    #[serde(rename = "bech32")]
+    Bech32,
+    #[serde(rename = "bech32m")]
+    Bech32m,
+}
+
+impl NewAddressType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NewAddressType::Legacy => "legacy",
+            NewAddressType::P2shSegwit => "p2sh-segwit",
+            NewAddressType::Bech32 => "bech32",
+            NewAddressType::Bech32m => "bech32m",
+        }
+    }
+
+    // Check that an address string is of this kind
+    pub fn matches(&self, address: &str) -> bool {
+        let Ok(address_type) = BitcoinCrypto::get_address_type(address) else {
+            return false;
+        };
+        match self {
+            NewAddressType::Legacy => matches!(
+                address_type,
+                AddressType::P2PKHMainnet | AddressType::P2PKHTestnet
+            ),
+            NewAddressType::P2shSegwit => matches!(
+                address_type,
+                AddressType::P2SHMainnet | AddressType::P2SHTestnet
+            ),
+            NewAddressType::Bech32 => {
+                matches!(bech32::decode(address), Ok((_, _, Variant::Bech32)))
+            }
+            NewAddressType::Bech32m => {
+                matches!(bech32::decode(address), Ok((_, _, Variant::Bech32m)))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratedAddress {
+    pub address: String,
+    pub label: String,
+    pub index: u32,
+}
+
+// Returned (inside anyhow) when generation stops partway, listing what the wallet already created
+#[derive(Debug, Clone)]
+pub struct AddressGenerationError {
+    pub created: Vec<GeneratedAddress>,
+    pub message: String,
+}
+
+impl fmt::Display for AddressGenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Address generation failed after {} addresses: {}",
+            self.created.len(),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for AddressGenerationError {}
+
+impl BitcoinClient {
+    pub async fn generate_addresses(
+        &self,
+        count: u32,
+        address_type: NewAddressType,
+        label_prefix: Option<&str>,
+    ) -> Result<Vec<GeneratedAddress>> {
+        self.generate_addresses_chunked(
+            count,
+            address_type,
+            label_prefix,
+            DEFAULT_ADDRESS_CHUNK_SIZE,
+        )
+        .await
+    }
+
+    // Create `count` addresses through batched `getnewaddress` calls of `chunk_size` each.
+    // Labels are `<prefix>-0001`, `<prefix>-0002`, ... or empty without a prefix.
+    pub async fn generate_addresses_chunked(
+        &self,
+        count: u32,
+        address_type: NewAddressType,
+        label_prefix: Option<&str>,
+        chunk_size: u32,
+    ) -> Result<Vec<GeneratedAddress>> {
+        if chunk_size == 0 {
+            return Err(anyhow!("Chunk size must be positive"));
+        }
+        let label_for = |index: u32| match label_prefix {
+            Some(prefix) => format!("{}-{:04}", prefix, index),
+            None => String::new(),
+        };
+        let mut created = Vec::with_capacity(count as usize);
+        let mut next = 1;
+        while next <= count {
+            let end = next.saturating_add(chunk_size - 1).min(count);
+            let requests: Vec<(String, serde_json::Value)> = (next..=end)
+                .map(|index| {
+                    (
+                        "getnewaddress".to_string(),
+                        json!([label_for(index), address_type.as_str()]),
+                    )
+                })
+                .collect();
+            let responses = match self.batch_send(&requests).await {
+                Ok(responses) => responses,
+                Err(e) => {
+                    return Err(AddressGenerationError {
+                        created,
+                        message: e.to_string(),
+                    }
+                    .into());
+                }
+            };
+            let mut failure = None;
+            for (index, (result, error)) in (next..=end).zip(responses) {
+                let address = match (result, error) {
+                    (_, Some(error)) => {
+                        failure.get_or_insert(BitcoinRpcError::from(error).to_string());
+                        continue;
+                    }
+                    (Some(serde_json::Value::String(address)), None) => address,
+                    _ => {
+                        failure.get_or_insert("Malformed getnewaddress response".to_string());
+                        continue;
+                    }
+                };
+                if !address_type.matches(&address) {
+                    failure.get_or_insert(format!(
+                        "Address {} is not of type {}",
+                        address,
+                        address_type.as_str()
+                    ));
+                }
+                created.push(GeneratedAddress {
+                    address,
+                    label: label_for(index),
+                    index,
+                });
+            }
+            if let Some(message) = failure {
+                return Err(AddressGenerationError { created, message }.into());
+            }
+            // `end + 1` would overflow once `count` is `u32::MAX`
+            if end == count {
+                break;
+            }
+            next = end + 1;
+        }
+        Ok(created)
+    }
+}
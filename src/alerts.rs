@@ -0,0 +1,143 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::BitcoinClient;
+use crate::types::Warnings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlertKind {
+    UnknownSoftforkSignalling,
+    PreReleaseBuild,
+    LargeWorkInvalidChain,
+    Other,
+}
+
+impl AlertKind {
+    pub fn classify(message: &str) -> Self {
+        let lower = message.to_lowercase();
+        // Each kind also matches the wording of older releases
+        if lower.contains("unknown new rules")
+            || lower.contains("unknown block versions")
+            || lower.contains("unexpected version")
+        {
+            AlertKind::UnknownSoftforkSignalling
+        } else if lower.contains("pre-release test build") {
+            AlertKind::PreReleaseBuild
+        } else if lower.contains("invalid chain")
+            || lower.contains("large-work fork")
+            || lower.contains("appear to fully agree")
+        {
+            AlertKind::LargeWorkInvalidChain
+        } else {
+            AlertKind::Other
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlertSource {
+    BlockchainInfo,
+    NetworkInfo,
+    MiningInfo,
+}
+
+impl AlertSource {
+    pub fn rpc_method(&self) -> &'static str {
+        match self {
+            AlertSource::BlockchainInfo => "getblockchaininfo",
+            AlertSource::NetworkInfo => "getnetworkinfo",
+            AlertSource::MiningInfo => "getmininginfo",
+        }
+    }
+}
+
+// One distinct warning and every RPC that reported it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub message: String,
+    pub sources: Vec<AlertSource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeHealth {
+    pub blocks: u64,
+    pub headers: u64,
+    pub initial_block_download: bool,
+    pub verification_progress: f64,
+    pub connections: u32,
+    pub network_active: bool,
+    pub mempool_size: u32,
+    pub alerts: Vec<Alert>,
+}
+
+impl NodeHealth {
+    // Syncing, isolated, or warned about a competing invalid chain
+    pub fn is_degraded(&self) -> bool {
+        self.initial_block_download
+            || self.connections == 0
+            || !self.network_active
+            || self
+                .alerts
+                .iter()
+                .any(|a| a.kind == AlertKind::LargeWorkInvalidChain)
+    }
+}
+
+// Merge warnings from several RPCs, keeping first-seen order
+fn collect_alerts(reported: &[(AlertSource, &Warnings)]) -> Vec<Alert> {
+    let mut alerts: Vec<Alert> = Vec::new();
+    for (source, warnings) in reported {
+        for message in &warnings.0 {
+            let message = message.trim();
+            if message.is_empty() {
+                continue;
+            }
+            match alerts.iter_mut().find(|a| a.message == message) {
+                Some(alert) if !alert.sources.contains(source) => alert.sources.push(*source),
+                Some(_) => {}
+                None => alerts.push(Alert {
+                    kind: AlertKind::classify(message),
+                    message: message.to_string(),
+                    sources: vec![*source],
+                }),
+            }
+        }
+    }
+    alerts
+}
+
+impl BitcoinClient {
+    pub async fn alerts(&self) -> Result<Vec<Alert>> {
+        let blockchain = self.get_blockchain_info().await?;
+        let network = self.get_network_info().await?;
+        let mining = self.get_mining_info().await?;
+        Ok(collect_alerts(&[
+            (AlertSource::BlockchainInfo, &blockchain.warnings),
+            (AlertSource::NetworkInfo, &network.warnings),
+            (AlertSource::MiningInfo, &mining.warnings),
+        ]))
+    }
+
+    pub async fn health(&self) -> Result<NodeHealth> {
+        let blockchain = self.get_blockchain_info().await?;
+        let network = self.get_network_info().await?;
+        let mining = self.get_mining_info().await?;
+        let mempool = self.get_mempool_info().await?;
+        let alerts = collect_alerts(&[
+            (AlertSource::BlockchainInfo, &blockchain.warnings),
+            (AlertSource::NetworkInfo, &network.warnings),
+            (AlertSource::MiningInfo, &mining.warnings),
+        ]);
+        Ok(NodeHealth {
+            blocks: blockchain.blocks,
+            headers: blockchain.headers,
+            initial_block_download: blockchain.initialblockdownload,
+            verification_progress: blockchain.verificationprogress,
+            connections: network.connections,
+            network_active: network.networkactive,
+            mempool_size: mempool.size,
+            alerts,
+        })
+    }
+}
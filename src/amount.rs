@@ -0,0 +1,437 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub};
+use std::str::FromStr;
+
+pub const SATS_PER_BTC: i64 = 100_000_000;
+
+// A bitcoin amount held as an exact number of satoshis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+    pub const MAX_MONEY: Amount = Amount(21_000_000 * SATS_PER_BTC);
+
+    pub const fn from_sat(sat: i64) -> Self {
+        Amount(sat)
+    }
+
+    pub const fn to_sat(self) -> i64 {
+        self.0
+    }
+
+    // Satoshis of an amount that cannot be negative, such as an output value
+    pub fn to_unsigned_sat(self) -> Result<u64> {
+        u64::try_from(self.0).map_err(|_| anyhow!("Negative amount: {}", self))
+    }
+
+    // Convert a BTC value, rounding half-even to the nearest satoshi
+    pub fn from_btc(btc: f64) -> Result<Self> {
+        Ok(Amount(btc_to_signed_sat(btc, true)?.0))
+    }
+
+    // Convert a BTC value that must already be a whole number of satoshis
+    pub fn from_btc_exact(btc: f64) -> Result<Self> {
+        Ok(Amount(btc_to_signed_sat(btc, false)?.0))
+    }
+
+    // BTC value as f64. Every satoshi amount up to the money supply maps to a distinct
+    // double whose shortest representation is the exact 8-decimal value, so this is lossless
+    // on the wire.
+    pub fn to_btc(self) -> f64 {
+        self.0 as f64 / SATS_PER_BTC as f64
+    }
+
+    pub fn checked_add(self, other: Amount) -> Option<Amount> {
+        self.0.checked_add(other.0).map(Amount)
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        self.0.checked_sub(other.0).map(Amount)
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+}
+
+// A fee rate held as an exact number of satoshis per 1000 virtual bytes. Core reads
+// sat/vB rates with 3 decimals, so this is every rate it can be given.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FeeRate(u64);
+
+impl FeeRate {
+    pub const ZERO: FeeRate = FeeRate(0);
+
+    pub const fn from_sat_per_kvb(sat_per_kvb: u64) -> Self {
+        FeeRate(sat_per_kvb)
+    }
+
+    pub const fn to_sat_per_kvb(self) -> u64 {
+        self.0
+    }
+
+    // Convert a sat/vB value, rounding to the nearest thousandth
+    pub fn from_sat_per_vb(sat_per_vb: f64) -> Result<Self> {
+        let sat_per_kvb = (sat_per_vb * 1000.0).round();
+        if !sat_per_kvb.is_finite() || sat_per_kvb < 0.0 || sat_per_kvb >= u64::MAX as f64 {
+            return Err(anyhow!("Fee rate out of range: {}", sat_per_vb));
+        }
+        Ok(FeeRate(sat_per_kvb as u64))
+    }
+
+    pub fn to_sat_per_vb(self) -> f64 {
+        self.0 as f64 / 1000.0
+    }
+}
+
+// Result of a lenient conversion: the satoshi value and how far rounding moved it,
+// in satoshis (positive when rounded up)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundedSat {
+    pub sat: u64,
+    pub adjustment: f64,
+}
+
+impl RoundedSat {
+    pub fn is_exact(&self) -> bool {
+        self.adjustment == 0.0
+    }
+}
+
+// Convert a non-negative BTC value from the node to satoshis. The value is read through
+// its shortest decimal representation, and sub-satoshi digits are an error since the node
+// never produces them.
+pub fn btc_f64_to_sat(btc: f64) -> Result<u64> {
+    match btc_to_signed_sat(btc, false)? {
+        (sat, _) if sat < 0 => Err(anyhow!("Negative amount: {}", btc)),
+        (sat, _) => Ok(sat as u64),
+    }
+}
+
+// Like `btc_f64_to_sat`, but rounds sub-satoshi digits half-even and reports the change
+pub fn btc_f64_to_sat_lenient(btc: f64) -> Result<RoundedSat> {
+    match btc_to_signed_sat(btc, true)? {
+        (sat, _) if sat < 0 => Err(anyhow!("Negative amount: {}", btc)),
+        (sat, adjustment) => Ok(RoundedSat {
+            sat: sat as u64,
+            adjustment,
+        }),
+    }
+}
+
+fn btc_to_signed_sat(btc: f64, lenient: bool) -> Result<(i64, f64)> {
+    if !btc.is_finite() || btc.abs() > Amount::MAX_MONEY.to_btc() {
+        return Err(anyhow!("Amount out of range: {}", btc));
+    }
+    // Display for f64 is the shortest round-tripping decimal, never in exponent form
+    let text = format!("{}", btc.abs());
+    let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+    let fraction = fraction.trim_end_matches('0');
+    let (sat_digits, excess) = fraction.split_at(fraction.len().min(8));
+    if !excess.is_empty() && !lenient {
+        return Err(anyhow!("Amount has sub-satoshi precision: {}", text));
+    }
+    let mut sat =
+        whole.parse::<i64>()? * SATS_PER_BTC + format!("{:0<8}", sat_digits).parse::<i64>()?;
+    let mut adjustment = 0.0;
+    if !excess.is_empty() {
+        let half = format!("{:0<width$}", "5", width = excess.len());
+        let round_up = match excess.cmp(half.as_str()) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Equal => sat % 2 == 1,
+            std::cmp::Ordering::Less => false,
+        };
+        let dropped: f64 = format!("0.{}", excess).parse()?;
+        if round_up {
+            sat += 1;
+            adjustment = 1.0 - dropped;
+        } else {
+            adjustment = -dropped;
+        }
+    }
+    if btc < 0.0 {
+        Ok((-sat, -adjustment))
+    } else {
+        Ok((sat, adjustment))
+    }
+}
+
+impl fmt::Display for Amount {
+    // Fixed 8-decimal BTC representation, e.g. "0.07000000"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let sat = self.0.unsigned_abs();
+        let per_btc = SATS_PER_BTC as u64;
+        write!(f, "{}{}.{:08}", sign, sat / per_btc, sat % per_btc)
+    }
+}
+
+impl fmt::Display for FeeRate {
+    // sat/vB with 3 decimals, e.g. "1.500"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:03}", self.0 / 1000, self.0 % 1000)
+    }
+}
+
+impl Add for Amount {
+    type Output = Amount;
+
+    fn add(self, other: Amount) -> Amount {
+        Amount(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Amount {
+    fn add_assign(&mut self, other: Amount) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Amount {
+    type Output = Amount;
+
+    fn sub(self, other: Amount) -> Amount {
+        Amount(self.0 - other.0)
+    }
+}
+
+impl Neg for Amount {
+    type Output = Amount;
+
+    fn neg(self) -> Amount {
+        Amount(-self.0)
+    }
+}
+
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Amount {
+        Amount(iter.map(|a| a.0).sum())
+    }
+}
+
+// Amounts go to the node as JSON numbers with exactly 8 decimals in BTC, e.g.
+// 0.00000001. serde_json's arbitrary_precision keeps the digits as written instead of
+// going through f64 formatting, which would write 1 sat as 1e-8 and 0.1 + 0.2 as
+// 0.30000000000000004.
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let number =
+            serde_json::Number::from_str(&self.to_string()).map_err(serde::ser::Error::custom)?;
+        number.serialize(serializer)
+    }
+}
+
+// Fee rates go to the node the same way, as sat/vB numbers with exactly 3 decimals
+impl Serialize for FeeRate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let number =
+            serde_json::Number::from_str(&self.to_string()).map_err(serde::ser::Error::custom)?;
+        number.serialize(serializer)
+    }
+}
+
+// Parse a decimal BTC string with at most 8 fractional digits, e.g. "0.07" or "-1.5"
+impl FromStr for Amount {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if whole.is_empty() || !is_digits(whole) || !is_digits(fraction) {
+            return Err(anyhow!("Invalid amount: {:?}", s));
+        }
+        if fraction.len() > 8 {
+            return Err(anyhow!("Amount has sub-satoshi precision: {}", s));
+        }
+        let sat = whole
+            .parse::<i64>()
+            .ok()
+            .and_then(|whole| whole.checked_mul(SATS_PER_BTC))
+            .and_then(|sat| sat.checked_add(format!("{:0<8}", fraction).parse().ok()?))
+            .filter(|sat| *sat <= Amount::MAX_MONEY.0)
+            .ok_or_else(|| anyhow!("Amount out of range: {}", s))?;
+        Ok(Amount(if negative { -sat } else { sat }))
+    }
+}
+
+// Accepts the node's JSON numbers as well as the strings written by `Serialize`
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AmountVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for AmountVisitor {
+            type Value = Amount;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a BTC amount as a number or decimal string")
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, btc: f64) -> Result<Amount, E> {
+                Amount::from_btc_exact(btc).map_err(E::custom)
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, btc: i64) -> Result<Amount, E> {
+                self.visit_f64(btc as f64)
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, btc: u64) -> Result<Amount, E> {
+                self.visit_f64(btc as f64)
+            }
+
+            fn visit_str<E: serde::de::Error>(self, btc: &str) -> Result<Amount, E> {
+                btc.parse().map_err(E::custom)
+            }
+
+            // With arbitrary_precision, serde_json hands numbers over as a one-entry map
+            // holding the number's text
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Amount, A::Error> {
+                use serde::de::Error;
+                let (_, number) = map
+                    .next_entry::<String, String>()?
+                    .ok_or_else(|| A::Error::custom("empty map is not an amount"))?;
+                let btc = number.parse::<f64>().map_err(A::Error::custom)?;
+                self.visit_f64(btc)
+            }
+        }
+
+        deserializer.deserialize_any(AmountVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_fixed_eight_decimals() {
+        let cases = [
+            (1, "0.00000001"),
+            (7_000_000, "0.07000000"),
+            (10_000_000, "0.10000000"),
+            (2_099_999_997_690_000, "20999999.97690000"),
+            (Amount::MAX_MONEY.to_sat(), "21000000.00000000"),
+            (-50_000, "-0.00050000"),
+        ];
+        for (sat, json) in cases {
+            assert_eq!(serde_json::to_string(&Amount::from_sat(sat)).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn fee_rates_serialize_with_three_decimals() {
+        let json = |rate: FeeRate| serde_json::to_string(&rate).unwrap();
+        assert_eq!(json(FeeRate::from_sat_per_vb(0.1 + 0.2).unwrap()), "0.300");
+        assert_eq!(json(FeeRate::from_sat_per_vb(1.0).unwrap()), "1.000");
+        assert_eq!(json(FeeRate::from_sat_per_vb(25.1234).unwrap()), "25.123");
+        assert_eq!(json(FeeRate::from_sat_per_kvb(1)), "0.001");
+        assert_eq!(json(FeeRate::ZERO), "0.000");
+        assert_eq!(
+            FeeRate::from_sat_per_vb(2.5).unwrap().to_sat_per_kvb(),
+            2500
+        );
+        assert!(FeeRate::from_sat_per_vb(-1.0).is_err());
+        assert!(FeeRate::from_sat_per_vb(f64::NAN).is_err());
+        assert!(FeeRate::from_sat_per_vb(f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn deserializes_numbers_and_strings() {
+        let from = |json: &str| serde_json::from_str::<Amount>(json).unwrap().to_sat();
+        assert_eq!(from("0.00000001"), 1);
+        assert_eq!(from("0.07"), 7_000_000);
+        assert_eq!(from("20999999.9769"), 2_099_999_997_690_000);
+        assert_eq!(from("21000000"), Amount::MAX_MONEY.to_sat());
+        assert_eq!(from("\"0.00000001\""), 1);
+        assert_eq!(from("\"-0.0005\""), -50_000);
+        assert!(serde_json::from_str::<Amount>("0.000000001").is_err());
+        assert!(serde_json::from_str::<Amount>("\"0.000000001\"").is_err());
+        assert!(serde_json::from_str::<Amount>("\"21000000.00000001\"").is_err());
+        assert!(serde_json::from_str::<Amount>("\"1e-8\"").is_err());
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        for sat in [
+            0,
+            1,
+            546,
+            7_000_000,
+            123_456_789,
+            Amount::MAX_MONEY.to_sat(),
+        ] {
+            let amount = Amount::from_sat(sat);
+            let json = serde_json::to_value(amount).unwrap();
+            assert_eq!(serde_json::from_value::<Amount>(json).unwrap(), amount);
+        }
+    }
+
+    // Every satoshi value at the ends of the range, and a seeded sample across the whole
+    // 0-21M BTC range, survives f64 and back exactly
+    #[test]
+    fn strict_conversion_round_trips_across_the_money_range() {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let max = Amount::MAX_MONEY.to_sat();
+        let mut rng = StdRng::seed_from_u64(21_000_000);
+        let sampled = (0..200_000).map(|_| rng.gen_range(0..=max));
+        for sat in (0..100_000).chain(max - 100_000..=max).chain(sampled) {
+            let btc = Amount::from_sat(sat).to_btc();
+            assert_eq!(btc_f64_to_sat(btc).unwrap(), sat as u64, "{}", btc);
+            let lenient = btc_f64_to_sat_lenient(btc).unwrap();
+            assert_eq!(lenient.sat, sat as u64);
+            assert!(lenient.is_exact());
+            let from_json: Amount = serde_json::from_value(serde_json::json!(btc)).unwrap();
+            assert_eq!(from_json.to_sat(), sat);
+        }
+    }
+
+    #[test]
+    fn lenient_conversion_rounds_ties_to_even() {
+        let cases = [
+            // (btc, sat, adjustment in satoshis)
+            (0.000000005, 0, -0.5),
+            (0.000000015, 2, 0.5),
+            (0.000000025, 2, -0.5),
+            (1.000000035, 100_000_004, 0.5),
+            (0.000000014, 1, -0.4),
+            (0.000000016, 2, 0.4),
+            (0.123456789, 12_345_679, 0.1),
+        ];
+        for (btc, sat, adjustment) in cases {
+            let rounded = btc_f64_to_sat_lenient(btc).unwrap();
+            assert_eq!(rounded.sat, sat, "{}", btc);
+            assert!((rounded.adjustment - adjustment).abs() < 1e-9, "{}", btc);
+            assert!(!rounded.is_exact());
+        }
+        assert_eq!(Amount::from_btc(-0.000000015).unwrap().to_sat(), -2);
+    }
+
+    #[test]
+    fn strict_conversion_rejects_what_the_node_never_sends() {
+        for btc in [
+            0.000000001,
+            0.000000015,
+            1.123456789,
+            -0.00000001,
+            21_000_000.00000001,
+            f64::NAN,
+            f64::INFINITY,
+        ] {
+            assert!(btc_f64_to_sat(btc).is_err(), "{}", btc);
+        }
+        assert!(btc_f64_to_sat_lenient(-1.0).is_err());
+        assert!(btc_f64_to_sat_lenient(f64::NAN).is_err());
+        assert!(Amount::from_btc_exact(0.000000001).is_err());
+    }
+}
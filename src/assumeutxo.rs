@@ -0,0 +1,208 @@
+use anyhow::Result;
+use serde_json::json;
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::BitcoinClient;
+use crate::error::BitcoinRpcError;
+use crate::node_version::NodeVersion;
+use crate::types::{BlockRef, DumpTxOutSetResult, LoadTxOutSetResult};
+
+// Writing or loading a mainnet UTXO set takes many minutes
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(2 * 60 * 60);
+
+// Why `load_tx_out_set` could not load a snapshot. Returned inside anyhow.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SnapshotLoadError {
+    // The node could not open the file (RPC_INVALID_PARAMETER)
+    #[error("Snapshot file not readable: {0}")]
+    FileNotReadable(String),
+    // The file does not start with valid snapshot metadata (RPC_DESERIALIZATION_ERROR)
+    #[error("Snapshot metadata invalid: {0}")]
+    InvalidMetadata(String),
+    // The snapshot's base block is not one of the node's hard-coded assumeutxo heights
+    #[error("Snapshot base block not recognized: {0}")]
+    UnrecognizedBaseBlock(String),
+    // The node has not synced the base block's header yet; retry once it has
+    #[error("Snapshot base block header not in the headers chain: {0}")]
+    MissingBaseHeader(String),
+    #[error("A snapshot is already loaded: {0}")]
+    AlreadyLoaded(String),
+    // The coins do not hash to the value the node expects for that height
+    #[error("Snapshot content does not match: {0}")]
+    ContentMismatch(String),
+    #[error("Unable to load UTXO snapshot: {0}")]
+    Failed(String),
+}
+
+type Variant = fn(String) -> SnapshotLoadError;
+
+// Core reports every activation failure as RPC_INTERNAL_ERROR, distinguished only by
+// the reason it prefixes with "Unable to load UTXO snapshot"
+const ACTIVATION_FAILURES: &[(&str, Variant)] = &[
+    (
+        "snapshot metadata not recognized",
+        SnapshotLoadError::UnrecognizedBaseBlock,
+    ),
+    (
+        "must appear in the headers chain",
+        SnapshotLoadError::MissingBaseHeader,
+    ),
+    (
+        "snapshot-based chainstate more than once",
+        SnapshotLoadError::AlreadyLoaded,
+    ),
+    (
+        "Bad snapshot content hash",
+        SnapshotLoadError::ContentMismatch,
+    ),
+];
+
+impl SnapshotLoadError {
+    fn classify(code: i32, message: &str) -> Option<Self> {
+        let message = message.to_string();
+        match code {
+            -8 => Some(SnapshotLoadError::FileNotReadable(message)),
+            -22 => Some(SnapshotLoadError::InvalidMetadata(message)),
+            -32603 => Some(
+                ACTIVATION_FAILURES
+                    .iter()
+                    .find(|(reason, _)| message.contains(reason))
+                    .map_or_else(
+                        || SnapshotLoadError::Failed(message.clone()),
+                        |(_, variant)| variant(message.clone()),
+                    ),
+            ),
+            _ => None,
+        }
+    }
+}
+
+// What `dump_tx_out_set` writes. Nodes before 28.0 only write the current UTXO set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotType {
+    // The UTXO set at the current tip
+    Latest,
+    // Roll the chainstate back to a block, the latest assumeutxo height when None, dump
+    // it, and replay to the tip again. The node does not serve other requests meanwhile.
+    Rollback(Option<BlockRef>),
+}
+
+impl BitcoinClient {
+    // Write a UTXO set snapshot to `path` on the node's filesystem. `snapshot` defaults
+    // to `SnapshotType::Latest`; 28.0 and later need the type spelled out, so it is sent
+    // there, while older nodes take only the path.
+    pub async fn dump_tx_out_set(
+        &self,
+        path: &str,
+        snapshot: Option<SnapshotType>,
+    ) -> Result<DumpTxOutSetResult> {
+        let params = match snapshot {
+            Some(SnapshotType::Rollback(target)) => {
+                self.require_version(NodeVersion::SNAPSHOT_TYPE, "dumptxoutset rollback")
+                    .await?;
+                match target {
+                    Some(target) => json!([path, "rollback", {"rollback": target}]),
+                    None => json!([path, "rollback"]),
+                }
+            }
+            _ if self.detect_node_version().await? >= NodeVersion::SNAPSHOT_TYPE => {
+                json!([path, "latest"])
+            }
+            _ => json!([path]),
+        };
+        self.with_timeout(SNAPSHOT_TIMEOUT)
+            .call("dumptxoutset", params)
+            .await
+    }
+
+    // Load an assumeutxo snapshot from `path` on the node's filesystem. Node-side
+    // failures come back as `SnapshotLoadError`.
+    pub async fn load_tx_out_set(&self, path: &str) -> Result<LoadTxOutSetResult> {
+        self.require_version(NodeVersion::ASSUME_UTXO, "loadtxoutset")
+            .await?;
+        self.with_timeout(SNAPSHOT_TIMEOUT)
+            .call("loadtxoutset", json!([path]))
+            .await
+            .map_err(|e| {
+                let classified = e
+                    .downcast_ref::<BitcoinRpcError>()
+                    .and_then(|rpc| SnapshotLoadError::classify(rpc.code()?, rpc.message()));
+                match classified {
+                    Some(error) => error.into(),
+                    None => e,
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_by_code_then_core_reason() {
+        let cases = [
+            (
+                -8,
+                "Couldn't open file /tmp/utxo.dat for reading.",
+                SnapshotLoadError::FileNotReadable as Variant,
+            ),
+            (
+                -22,
+                "Unable to parse metadata: Invalid UTXO set snapshot magic bytes.",
+                SnapshotLoadError::InvalidMetadata,
+            ),
+            (
+                -32603,
+                "Unable to load UTXO snapshot: assumeutxo block hash in snapshot metadata not \
+                 recognized (hash: 0000abcd, height: 840000). The following snapshot heights \
+                 are available: 840000.",
+                SnapshotLoadError::UnrecognizedBaseBlock,
+            ),
+            (
+                -32603,
+                "Unable to load UTXO snapshot: The base block header (0000abcd) must appear in \
+                 the headers chain. Make sure all headers are syncing, and call loadtxoutset \
+                 again.",
+                SnapshotLoadError::MissingBaseHeader,
+            ),
+            (
+                -32603,
+                "Unable to load UTXO snapshot: Can't activate a snapshot-based chainstate more \
+                 than once",
+                SnapshotLoadError::AlreadyLoaded,
+            ),
+            (
+                -32603,
+                "Unable to load UTXO snapshot: Population failed: Bad snapshot content hash: \
+                 expected aa, got bb.",
+                SnapshotLoadError::ContentMismatch,
+            ),
+            (
+                -32603,
+                "Unable to load UTXO snapshot: Work does not exceed active chainstate.",
+                SnapshotLoadError::Failed,
+            ),
+        ];
+        for (code, message, variant) in cases {
+            assert_eq!(
+                SnapshotLoadError::classify(code, message),
+                Some(variant(message.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn leaves_unrelated_codes_alone() {
+        // A header mention under another code is not a snapshot failure
+        assert_eq!(
+            SnapshotLoadError::classify(-1, "header already known"),
+            None
+        );
+        assert_eq!(
+            SnapshotLoadError::classify(-28, "Loading block index..."),
+            None
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::BitcoinClient;
+use crate::cancel::RESCAN_TIMEOUT;
+use crate::error::BitcoinRpcError;
+use crate::types::{DumpWalletResult, RestoreWalletResult};
+
+// Why the wallet would not export a private key. Returned inside anyhow.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum KeyExportError {
+    // Watch-only wallet
+    #[error("Private keys are disabled for this wallet: {0}")]
+    PrivateKeysDisabled(String),
+    // The address is valid but no key for it is in the wallet
+    #[error("Private key not known: {0}")]
+    KeyNotKnown(String),
+    // Descriptor wallets export keys through `list_descriptors(true)` instead
+    #[error("Only legacy wallets support this: {0}")]
+    LegacyOnly(String),
+}
+
+impl KeyExportError {
+    fn classify(message: &str) -> Option<Self> {
+        let message = message.to_string();
+        if message.contains("Private keys are disabled") {
+            Some(KeyExportError::PrivateKeysDisabled(message))
+        } else if message.contains("is not known") {
+            Some(KeyExportError::KeyNotKnown(message))
+        } else if message.contains("Only legacy wallets") {
+            Some(KeyExportError::LegacyOnly(message))
+        } else {
+            None
+        }
+    }
+}
+
+fn key_export_error(error: anyhow::Error) -> anyhow::Error {
+    match error
+        .downcast_ref::<BitcoinRpcError>()
+        .filter(|rpc| rpc.code().is_some())
+        .and_then(|rpc| KeyExportError::classify(rpc.message()))
+    {
+        Some(export_error) => export_error.into(),
+        None => error,
+    }
+}
+
+impl BitcoinClient {
+    // WIF private key for an address of a legacy wallet, which must be unlocked
+    pub async fn dump_priv_key(&self, address: &str) -> Result<String> {
+        self.call("dumpprivkey", json!([address]))
+            .await
+            .map_err(key_export_error)
+    }
+
+    // Write every key of a legacy wallet to a new file at `path` on the node's
+    // filesystem; an existing file is never overwritten
+    pub async fn dump_wallet(&self, path: &str) -> Result<DumpWalletResult> {
+        self.call("dumpwallet", json!([path]))
+            .await
+            .map_err(key_export_error)
+    }
+
+    // Import a `dump_wallet` file into a legacy wallet and rescan for its keys
+    pub async fn import_wallet(&self, path: &str) -> Result<()> {
+        self.with_timeout(RESCAN_TIMEOUT)
+            .call::<Value>("importwallet", json!([path]))
+            .await?;
+        Ok(())
+    }
+
+    // Copy the wallet file to `destination` on the node's filesystem
+    pub async fn backup_wallet(&self, destination: &str) -> Result<()> {
+        self.call::<Value>("backupwallet", json!([destination]))
+            .await?;
+        Ok(())
+    }
+
+    // Load a `backup_wallet` file as a new wallet named `wallet_name`. Needs 23.0 or
+    // later.
+    pub async fn restore_wallet(
+        &self,
+        wallet_name: &str,
+        backup_file: &str,
+        load_on_startup: Option<bool>,
+    ) -> Result<RestoreWalletResult> {
+        self.with_timeout(RESCAN_TIMEOUT)
+            .call(
+                "restorewallet",
+                json!([wallet_name, backup_file, load_on_startup]),
+            )
+            .await
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::BitcoinClient;
+use crate::error::BitcoinRpcError;
+use crate::types::{
+    BlockHeader, BlockRef, BlockStats, BlockchainInfo, MempoolInfo, Transaction, TxOut,
+};
+
+// Source of the ids that tie handles to the batch that issued them
+static NEXT_BATCH_ID: AtomicU64 = AtomicU64::new(0);
+
+// Position of a queued call in its batch, resolving to `T`
+#[derive(Debug)]
+pub struct BatchHandle<T> {
+    batch: u64,
+    index: usize,
+    _result: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for BatchHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BatchHandle<T> {}
+
+impl<T> BatchHandle<T> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+// Calls queued to go out in one JSON-RPC batch; each one succeeds or fails on its own
+pub struct BatchRequest<'a> {
+    client: &'a BitcoinClient,
+    id: u64,
+    requests: Vec<(String, Value)>,
+}
+
+impl<'a> BatchRequest<'a> {
+    pub fn new(client: &'a BitcoinClient) -> Self {
+        BatchRequest {
+            client,
+            id: NEXT_BATCH_ID.fetch_add(1, Ordering::Relaxed),
+            requests: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    // Queue any method; `T` is what the result will be decoded into
+    pub fn call<T>(&mut self, method: &str, params: Value) -> BatchHandle<T> {
+        self.requests.push((method.to_string(), params));
+        BatchHandle {
+            batch: self.id,
+            index: self.requests.len() - 1,
+            _result: PhantomData,
+        }
+    }
+
+    pub fn get_blockchain_info(&mut self) -> BatchHandle<BlockchainInfo> {
+        self.call("getblockchaininfo", Value::Null)
+    }
+
+    pub fn get_block_count(&mut self) -> BatchHandle<u64> {
+        self.call("getblockcount", Value::Null)
+    }
+
+    pub fn get_best_block_hash(&mut self) -> BatchHandle<String> {
+        self.call("getbestblockhash", Value::Null)
+    }
+
+    pub fn get_block_hash(&mut self, height: u64) -> BatchHandle<String> {
+        self.call("getblockhash", json!([height]))
+    }
+
+    pub fn get_block_header(&mut self, block_hash: &str) -> BatchHandle<BlockHeader> {
+        self.call("getblockheader", json!([block_hash, true]))
+    }
+
+    pub fn get_block_stats(&mut self, block: impl Into<BlockRef>) -> BatchHandle<BlockStats> {
+        self.call("getblockstats", json!([block.into()]))
+    }
+
+    // Decoded transaction; `get_raw_transaction_hex` for the serialized form
+    pub fn get_raw_transaction(&mut self, txid: &str) -> BatchHandle<Transaction> {
+        self.call("getrawtransaction", json!([txid, true]))
+    }
+
+    pub fn get_raw_transaction_hex(&mut self, txid: &str) -> BatchHandle<String> {
+        self.call("getrawtransaction", json!([txid, false]))
+    }
+
+    pub fn get_tx_out(
+        &mut self,
+        txid: &str,
+        vout: u32,
+        include_mempool: bool,
+    ) -> BatchHandle<Option<TxOut>> {
+        self.call("gettxout", json!([txid, vout, include_mempool]))
+    }
+
+    pub fn get_mempool_info(&mut self) -> BatchHandle<MempoolInfo> {
+        self.call("getmempoolinfo", Value::Null)
+    }
+
+    pub fn get_difficulty(&mut self) -> BatchHandle<f64> {
+        self.call("getdifficulty", Value::Null)
+    }
+
+    // Send every queued call at once. Only a failure of the batch as a whole is an
+    // error here; sub-request failures are kept in the response.
+    pub async fn send(self) -> Result<BatchResponse> {
+        if self.requests.is_empty() {
+            return Err(anyhow!("Cannot send an empty batch"));
+        }
+        let responses = self.client.batch_send(&self.requests).await?;
+        let results = self
+            .requests
+            .iter()
+            .zip(responses)
+            .map(|((_method, _), (result, error))| match error {
+                Some(error) => Err(BitcoinRpcError::from(error)),
+                None => {
+                    let result = result.unwrap_or(Value::Null);
+                    #[cfg(feature = "validate-responses")]
+                    crate::validation::log_response_warnings(_method, &result);
+                    Ok(result)
+                }
+            })
+            .collect();
+        Ok(BatchResponse {
+            batch: self.id,
+            results,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchResponse {
+    batch: u64,
+    results: Vec<Result<Value, BitcoinRpcError>>,
+}
+
+impl BatchResponse {
+    // Decode the result of one queued call
+    pub fn get<T: for<'de> Deserialize<'de>>(
+        &self,
+        handle: BatchHandle<T>,
+    ) -> Result<T, BitcoinRpcError> {
+        let result = self
+            .results
+            .get(handle.index)
+            .filter(|_| handle.batch == self.batch)
+            .ok_or_else(|| BitcoinRpcError::Decode("Handle from another batch".to_string()))?;
+        match result {
+            Ok(value) => Ok(serde_json::from_value(value.clone())?),
+            Err(error) => Err(error.clone()),
+        }
+    }
+
+    pub fn results(&self) -> &[Result<Value, BitcoinRpcError>] {
+        &self.results
+    }
+
+    pub fn into_results(self) -> Vec<Result<Value, BitcoinRpcError>> {
+        self.results
+    }
+}
+
+impl BitcoinClient {
+    pub fn batch(&self) -> BatchRequest<'_> {
+        BatchRequest::new(self)
+    }
+}
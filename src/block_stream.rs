@@ -0,0 +1,129 @@
+use anyhow::{Result, anyhow};
+use futures_util::stream::{self, Stream};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use crate::BitcoinClient;
+use crate::types::Block;
+
+// Recent blocks remembered to find where a reorg reconnects
+const TRACKED_BLOCKS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub enum BlockEvent {
+    Connected(Box<Block>),
+    // Blocks no longer on the best chain, newest first. Followed by `Connected` events
+    // for the replacing branch.
+    Reorg { disconnected: Vec<String> },
+}
+
+struct BlockFollower<'a> {
+    client: &'a BitcoinClient,
+    poll_interval: Duration,
+    // Height to hash of the recent best chain as last seen
+    known: BTreeMap<u64, String>,
+    pending: VecDeque<BlockEvent>,
+}
+
+impl BlockFollower<'_> {
+    // Seed the window with the tip and its ancestors, so a reorg right after the start
+    // still finds where it reconnects
+    async fn start(&mut self) -> Result<()> {
+        let mut cursor = Some(self.client.get_best_block_hash().await?);
+        while let Some(hash) = cursor
+            && self.known.len() < TRACKED_BLOCKS
+        {
+            let header = self.client.get_block_header(&hash).await?;
+            self.known.insert(header.height, header.hash);
+            cursor = header.previousblockhash;
+        }
+        Ok(())
+    }
+
+    // Queue events for everything between the known chain and the node's tip
+    async fn catch_up(&mut self, tip: String) -> Result<()> {
+        let mut new_hashes = Vec::new();
+        let mut cursor = tip;
+        let fork_height = loop {
+            let header = self.client.get_block_header(&cursor).await?;
+            if self.known.get(&header.height) == Some(&header.hash) {
+                break header.height;
+            }
+            if self
+                .known
+                .first_key_value()
+                .is_some_and(|(lowest, _)| header.height <= *lowest)
+            {
+                return Err(anyhow!(
+                    "Reorg deeper than the {} tracked blocks",
+                    TRACKED_BLOCKS
+                ));
+            }
+            new_hashes.push(header.hash);
+            cursor = header
+                .previousblockhash
+                .ok_or_else(|| anyhow!("Walked back to genesis without reconnecting"))?;
+        };
+        let disconnected: Vec<String> = self
+            .known
+            .split_off(&(fork_height + 1))
+            .into_values()
+            .rev()
+            .collect();
+        if !disconnected.is_empty() {
+            self.pending.push_back(BlockEvent::Reorg { disconnected });
+        }
+        for hash in new_hashes.into_iter().rev() {
+            let block = self.client.get_block(&hash).await?;
+            self.known.insert(block.height, block.hash.clone());
+            self.pending
+                .push_back(BlockEvent::Connected(Box::new(block)));
+        }
+        while self.known.len() > TRACKED_BLOCKS {
+            self.known.pop_first();
+        }
+        Ok(())
+    }
+
+    async fn next_event(&mut self) -> Result<BlockEvent> {
+        if self.known.is_empty() {
+            self.start().await?;
+        }
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+            let tip = self.client.get_best_block_hash().await?;
+            if self.known.last_key_value().map(|(_, hash)| hash) != Some(&tip) {
+                self.catch_up(tip).await?;
+                continue;
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+impl BitcoinClient {
+    // Blocks connected after the call, in chain order, found by polling the tip every
+    // `poll_interval`. A reorg yields `BlockEvent::Reorg` with the dropped hashes before
+    // the new branch. Errors are yielded and polling carries on.
+    pub fn subscribe_blocks(
+        &self,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<BlockEvent>> + '_ {
+        let follower = BlockFollower {
+            client: self,
+            poll_interval,
+            known: BTreeMap::new(),
+            pending: VecDeque::new(),
+        };
+        stream::unfold(follower, |mut follower| async move {
+            let event = follower.next_event().await;
+            if event.is_err() {
+                // Back off before the next attempt instead of spinning on a failing node
+                tokio::time::sleep(follower.poll_interval).await;
+            }
+            Some((event, follower))
+        })
+    }
+}
@@ -0,0 +1,423 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::BitcoinClient;
+use crate::error::BitcoinRpcError;
+use crate::send_raw::TxRejection;
+use crate::serialization::Serialization;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BroadcastPolicy {
+    pub target_confirmations: u32,
+    // Unix time after which an unconfirmed transaction is abandoned
+    pub deadline: Option<u64>,
+    pub rebroadcast_interval_secs: u64,
+    // Ask the wallet to bump the fee when the node rejects the fee as too low
+    pub bump_on_low_fee: bool,
+}
+
+impl Default for BroadcastPolicy {
+    fn default() -> Self {
+        BroadcastPolicy {
+            target_confirmations: 1,
+            deadline: None,
+            rebroadcast_interval_secs: 600,
+            bump_on_low_fee: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BroadcastStatus {
+    Pending,
+    WaitingForParent,
+    FeeTooLow,
+    InMempool,
+    Confirmed(u32),
+    Replaced(String),
+    Abandoned,
+    Failed(String),
+}
+
+impl BroadcastStatus {
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            BroadcastStatus::Replaced(_) | BroadcastStatus::Abandoned | BroadcastStatus::Failed(_)
+        )
+    }
+}
+
+// How the node rejected a broadcast attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BroadcastFailure {
+    MissingInputs,
+    FeeTooLow,
+    AlreadyKnown,
+    AlreadyConfirmed,
+    Conflict,
+    Rejected,
+}
+
+impl BroadcastFailure {
+    // Map a sendrawtransaction rejection onto a failure class
+    pub fn classify(error: &BitcoinRpcError) -> Self {
+        match TxRejection::classify(error) {
+            Some(TxRejection::AlreadyInMempool | TxRejection::SameNonWitnessData) => {
+                BroadcastFailure::AlreadyKnown
+            }
+            Some(TxRejection::AlreadyInChain) => BroadcastFailure::AlreadyConfirmed,
+            Some(TxRejection::MissingInputs) => BroadcastFailure::MissingInputs,
+            Some(TxRejection::FeeTooLow | TxRejection::ReplacementFeeTooLow) => {
+                BroadcastFailure::FeeTooLow
+            }
+            Some(TxRejection::MempoolConflict) => BroadcastFailure::Conflict,
+            Some(TxRejection::MaxFeeExceeded | TxRejection::MaxBurnExceeded) | None => {
+                BroadcastFailure::Rejected
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTransaction {
+    pub txid: String,
+    pub hex: String,
+    pub policy: BroadcastPolicy,
+    pub status: BroadcastStatus,
+    pub attempts: u32,
+    pub enqueued_at: u64,
+    pub last_attempt_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BroadcastSummary {
+    pub pending: usize,
+    pub in_mempool: usize,
+    pub confirmed: usize,
+    pub failed: usize,
+}
+
+// Durable storage for the broadcast queue
+pub trait BroadcastStore: Send + Sync {
+    fn load(&self) -> Result<Vec<QueuedTransaction>>;
+    fn save(&self, queue: &[QueuedTransaction]) -> Result<()>;
+}
+
+// Stores the whole queue as a JSON document, replaced atomically on every save
+pub struct FileBroadcastStore {
+    path: PathBuf,
+}
+
+impl FileBroadcastStore {
+    pub fn new(path: &Path) -> Self {
+        FileBroadcastStore {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl BroadcastStore for FileBroadcastStore {
+    fn load(&self) -> Result<Vec<QueuedTransaction>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_slice(&std::fs::read(&self.path)?)?)
+    }
+
+    fn save(&self, queue: &[QueuedTransaction]) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(queue)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+type SummaryHook = Arc<dyn Fn(&BroadcastSummary) + Send + Sync>;
+
+// Persistent outbound queue that broadcasts, rebroadcasts and tracks transactions to a target depth
+pub struct Broadcaster {
+    client: BitcoinClient,
+    store: Box<dyn BroadcastStore>,
+    queue: Mutex<BTreeMap<String, QueuedTransaction>>,
+    on_summary: Option<SummaryHook>,
+}
+
+impl Broadcaster {
+    // Create a broadcaster, resuming any queue left in the store
+    pub fn new(client: BitcoinClient, store: Box<dyn BroadcastStore>) -> Result<Self> {
+        let queue = store
+            .load()?
+            .into_iter()
+            .map(|tx| (tx.txid.clone(), tx))
+            .collect();
+        Ok(Broadcaster {
+            client,
+            store,
+            queue: Mutex::new(queue),
+            on_summary: None,
+        })
+    }
+
+    pub fn with_summary_hook(
+        mut self,
+        hook: impl Fn(&BroadcastSummary) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_summary = Some(Arc::new(hook));
+        self
+    }
+
+    // Persist the transaction and attempt a first broadcast, returning its txid
+    pub async fn enqueue(&self, tx_hex: &str, policy: BroadcastPolicy) -> Result<String> {
+        let txid = Serialization::deserialize_transaction(tx_hex)?.txid();
+        {
+            let mut queue = self.queue.lock().unwrap();
+            queue.entry(txid.clone()).or_insert(QueuedTransaction {
+                txid: txid.clone(),
+                hex: tx_hex.to_string(),
+                policy,
+                status: BroadcastStatus::Pending,
+                attempts: 0,
+                enqueued_at: now(),
+                last_attempt_at: None,
+                last_error: None,
+            });
+            self.persist(&queue)?;
+        }
+        self.process_one(&txid).await?;
+        Ok(txid)
+    }
+
+    pub fn status(&self, txid: &str) -> Option<BroadcastStatus> {
+        self.queue
+            .lock()
+            .unwrap()
+            .get(txid)
+            .map(|tx| tx.status.clone())
+    }
+
+    pub fn transactions(&self) -> Vec<QueuedTransaction> {
+        self.queue.lock().unwrap().values().cloned().collect()
+    }
+
+    pub fn summary(&self) -> BroadcastSummary {
+        let queue = self.queue.lock().unwrap();
+        let mut summary = BroadcastSummary::default();
+        for tx in queue.values() {
+            match &tx.status {
+                BroadcastStatus::InMempool => summary.in_mempool += 1,
+                BroadcastStatus::Confirmed(_) => summary.confirmed += 1,
+                status if status.is_final() => summary.failed += 1,
+                _ => summary.pending += 1,
+            }
+        }
+        summary
+    }
+
+    // Drop confirmed and finished entries from the queue
+    pub fn prune(&self) -> Result<()> {
+        let mut queue = self.queue.lock().unwrap();
+        queue.retain(|_, tx| match tx.status {
+            BroadcastStatus::Confirmed(depth) => depth < tx.policy.target_confirmations,
+            ref status => !status.is_final(),
+        });
+        self.persist(&queue)
+    }
+
+    // Run one pass over the queue: broadcast, rebroadcast, check depth and expire
+    pub async fn process(&self) -> Result<BroadcastSummary> {
+        let txids: Vec<String> = self.queue.lock().unwrap().keys().cloned().collect();
+        for txid in txids {
+            self.process_one(&txid).await?;
+        }
+        let summary = self.summary();
+        if let Some(hook) = &self.on_summary {
+            hook(&summary);
+        }
+        Ok(summary)
+    }
+
+    // Process the queue forever at the given interval
+    pub async fn run(&self, interval: Duration) -> Result<()> {
+        loop {
+            self.process().await?;
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    async fn process_one(&self, txid: &str) -> Result<()> {
+        let Some(tx) = self.queue.lock().unwrap().get(txid).cloned() else {
+            return Ok(());
+        };
+        if tx.status.is_final() {
+            return Ok(());
+        }
+        if let Some(depth) = self.confirmations(&tx, false).await {
+            return self.update(txid, |entry| {
+                entry.status = BroadcastStatus::Confirmed(depth)
+            });
+        }
+        if tx.policy.deadline.is_some_and(|deadline| now() > deadline) {
+            return self.update(txid, |entry| entry.status = BroadcastStatus::Abandoned);
+        }
+        let due = match tx.last_attempt_at {
+            None => true,
+            Some(last) => now() >= last + tx.policy.rebroadcast_interval_secs,
+        };
+        if !due && tx.status != BroadcastStatus::Pending {
+            return Ok(());
+        }
+        let attempted_at = now();
+        let mut replacement = None;
+        let (status, last_error) = match self.client.send_raw_transaction(&tx.hex).await {
+            Ok(_) => (BroadcastStatus::InMempool, None),
+            Err(e) => {
+                let message = e.to_string();
+                let failure = e
+                    .downcast_ref::<BitcoinRpcError>()
+                    .map_or(BroadcastFailure::Rejected, BroadcastFailure::classify);
+                let status = match failure {
+                    BroadcastFailure::AlreadyKnown => BroadcastStatus::InMempool,
+                    // The node only says the transaction is in a block; when no lookup can
+                    // find it (outputs spent, not a wallet transaction, no -txindex) that
+                    // still means a depth of at least one
+                    BroadcastFailure::AlreadyConfirmed => {
+                        BroadcastStatus::Confirmed(self.confirmations(&tx, true).await.unwrap_or(1))
+                    }
+                    BroadcastFailure::MissingInputs => BroadcastStatus::WaitingForParent,
+                    BroadcastFailure::FeeTooLow if tx.policy.bump_on_low_fee => {
+                        match self.bump(&tx.txid).await {
+                            Ok((txid, hex)) => {
+                                replacement = Some((txid.clone(), hex));
+                                BroadcastStatus::Replaced(txid)
+                            }
+                            Err(_) => BroadcastStatus::FeeTooLow,
+                        }
+                    }
+                    BroadcastFailure::FeeTooLow => BroadcastStatus::FeeTooLow,
+                    BroadcastFailure::Conflict | BroadcastFailure::Rejected => {
+                        BroadcastStatus::Failed(message.clone())
+                    }
+                };
+                (status, Some(message))
+            }
+        };
+        self.update(txid, |entry| {
+            entry.attempts += 1;
+            entry.last_attempt_at = Some(attempted_at);
+            entry.status = status;
+            entry.last_error = last_error;
+        })?;
+        match replacement {
+            Some((txid, hex)) => self.track_replacement(&tx, txid, hex, attempted_at),
+            None => Ok(()),
+        }
+    }
+
+    // Depth of a confirmed transaction, or None while it is unconfirmed or unknown. A
+    // transaction in the mempool costs one call; otherwise ask the wallet, then -txindex,
+    // then the unspent set. Only the first output is probed there, unless the node has
+    // already said the transaction is in a block and every output is worth a call.
+    async fn confirmations(&self, tx: &QueuedTransaction, every_output: bool) -> Option<u32> {
+        // Only presence matters, so the entry is not decoded
+        if self
+            .client
+            .call::<serde_json::Value>("getmempoolentry", json!([tx.txid]))
+            .await
+            .is_ok()
+        {
+            return None;
+        }
+        if let Ok(wallet_tx) = self.client.get_transaction(&tx.txid, true, false).await
+            && wallet_tx.confirmations > 0
+        {
+            return u32::try_from(wallet_tx.confirmations).ok();
+        }
+        if let Ok(raw) = self.client.get_raw_transaction(&tx.txid).await
+            && let Some(depth) = raw.confirmations.filter(|c| *c > 0)
+        {
+            return Some(depth);
+        }
+        let outputs = if every_output {
+            Serialization::deserialize_transaction(&tx.hex)
+                .map(|raw| raw.outputs.len())
+                .unwrap_or_default()
+        } else {
+            1
+        };
+        for vout in 0..outputs as u32 {
+            if let Ok(Some(out)) = self.client.get_tx_out(&tx.txid, vout, false).await {
+                return Some(out.confirmations).filter(|c| *c > 0);
+            }
+        }
+        None
+    }
+
+    // Bump the fee through the wallet, returning the replacement's txid and hex
+    async fn bump(&self, txid: &str) -> Result<(String, String)> {
+        let result: serde_json::Value = self.client.call("bumpfee", json!([txid])).await?;
+        let replacement = result["txid"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| anyhow!("bumpfee returned no txid"))?;
+        let hex = self
+            .client
+            .get_transaction(&replacement, true, false)
+            .await?
+            .hex;
+        Ok((replacement, hex))
+    }
+
+    // Queue a fee bump under the policy of the transaction it replaces, so it is still
+    // driven to the target depth and abandoned at the same deadline. The wallet has
+    // already broadcast it.
+    fn track_replacement(
+        &self,
+        replaced: &QueuedTransaction,
+        txid: String,
+        hex: String,
+        broadcast_at: u64,
+    ) -> Result<()> {
+        let mut queue = self.queue.lock().unwrap();
+        queue.entry(txid.clone()).or_insert(QueuedTransaction {
+            txid,
+            hex,
+            policy: replaced.policy.clone(),
+            status: BroadcastStatus::InMempool,
+            attempts: 1,
+            enqueued_at: broadcast_at,
+            last_attempt_at: Some(broadcast_at),
+            last_error: None,
+        });
+        self.persist(&queue)
+    }
+
+    // Apply `change` to the entry as it is now rather than to the copy taken before the
+    // calls, so a concurrent enqueue or prune is not overwritten. A pruned entry stays gone.
+    fn update(&self, txid: &str, change: impl FnOnce(&mut QueuedTransaction)) -> Result<()> {
+        let mut queue = self.queue.lock().unwrap();
+        let Some(entry) = queue.get_mut(txid) else {
+            return Ok(());
+        };
+        change(entry);
+        self.persist(&queue)
+    }
+
+    fn persist(&self, queue: &BTreeMap<String, QueuedTransaction>) -> Result<()> {
+        let entries: Vec<QueuedTransaction> = queue.values().cloned().collect();
+        self.store.save(&entries)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
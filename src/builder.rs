@@ -0,0 +1,350 @@
+use anyhow::{Result, anyhow};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use reqwest::{Certificate, Client, Identity, Proxy, Url};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use crate::BitcoinClient;
+use crate::cache::ResponseCache;
+use crate::cookie::read_cookie_auth;
+use crate::error::BitcoinRpcError;
+use crate::middleware::{MiddlewareStack, RpcMiddleware};
+use crate::transport::Transport;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+enum Credentials {
+    UserPass(String, String),
+    Cookie(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+struct ProxyConfig {
+    url: String,
+    auth: Option<(String, String)>,
+    remote_dns: bool,
+}
+
+impl ProxyConfig {
+    // The proxy URL reqwest should use. SOCKS credentials travel in the URL, HTTP proxy
+    // credentials as a Proxy-Authorization header.
+    fn to_proxy(&self) -> Result<Proxy> {
+        let mut url = Url::parse(&self.url).map_err(|e| anyhow!("Invalid proxy URL: {}", e))?;
+        if self.remote_dns && url.scheme() == "socks5" {
+            // socks5h hands host names to the proxy, which .onion addresses require
+            url = Url::parse(&url.as_str().replacen("socks5://", "socks5h://", 1))?;
+        }
+        let socks = url.scheme().starts_with("socks");
+        if let Some((username, password)) = &self.auth
+            && socks
+        {
+            url.set_username(username)
+                .and_then(|_| url.set_password(Some(password)))
+                .map_err(|_| anyhow!("Proxy URL cannot carry credentials"))?;
+        }
+        let mut proxy = Proxy::all(url.as_str()).map_err(BitcoinRpcError::from)?;
+        if let Some((username, password)) = &self.auth
+            && !socks
+        {
+            proxy = proxy.basic_auth(username, password);
+        }
+        Ok(proxy)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BitcoinClientBuilder {
+    url: String,
+    credentials: Credentials,
+    timeout: Duration,
+    connect_timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+    tcp_keepalive: Option<Duration>,
+    user_agent: Option<String>,
+    accept_invalid_certs: bool,
+    proxy: Option<String>,
+    // Kept apart from the URL so they apply whichever order the setters are called in
+    proxy_auth: Option<(String, String)>,
+    proxy_remote_dns: bool,
+    log_bodies: bool,
+    max_concurrent_requests: Option<usize>,
+    cache_capacity: Option<usize>,
+    keepalive: Option<Duration>,
+    unix_socket: Option<PathBuf>,
+    middleware: MiddlewareStack,
+    // PEM bundles, parsed in `build` so a bad one is reported there
+    root_certificates: Vec<Vec<u8>>,
+    // PKCS#12 archive and its password
+    identity: Option<(Vec<u8>, String)>,
+}
+
+impl BitcoinClientBuilder {
+    pub fn new(url: &str) -> Self {
+        BitcoinClientBuilder {
+            url: url.to_string(),
+            credentials: Credentials::UserPass(String::new(), String::new()),
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: None,
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            user_agent: None,
+            accept_invalid_certs: false,
+            proxy: None,
+            proxy_auth: None,
+            proxy_remote_dns: false,
+            log_bodies: false,
+            max_concurrent_requests: None,
+            cache_capacity: None,
+            keepalive: None,
+            unix_socket: None,
+            middleware: MiddlewareStack::default(),
+            root_certificates: Vec::new(),
+            identity: None,
+        }
+    }
+
+    pub fn auth(mut self, username: &str, password: &str) -> Self {
+        self.credentials = Credentials::UserPass(username.to_string(), password.to_string());
+        self
+    }
+
+    // Authenticate with bitcoind's cookie file, re-read when the node rotates it
+    pub fn cookie_file(mut self, cookie_path: &Path) -> Self {
+        self.credentials = Credentials::Cookie(cookie_path.to_path_buf());
+        self
+    }
+
+    // Whole-request timeout; slow RPCs like scantxoutset need more than the default 30s
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    // Send TCP keep-alive probes every `interval` so NATs and firewalls keep idle pooled
+    // connections open. See `keepalive` for pinging the node itself.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    // Skip TLS certificate checks, for nodes behind a self-signed proxy
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    // Trust an extra CA, e.g. the private CA of a TLS terminator in front of the node
+    pub fn add_root_certificate(mut self, pem: &[u8]) -> Self {
+        self.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    // Client certificate for mutual TLS, as a PKCS#12 archive
+    pub fn identity(mut self, pkcs12_der: &[u8], password: &str) -> Self {
+        self.identity = Some((pkcs12_der.to_vec(), password.to_string()));
+        self
+    }
+
+    // Route every request through a proxy, e.g. "socks5://127.0.0.1:9050" for Tor
+    pub fn proxy(mut self, url: &str) -> Self {
+        self.proxy = Some(url.to_string());
+        self
+    }
+
+    // Credentials for the proxy set with `proxy`
+    pub fn proxy_auth(mut self, username: &str, password: &str) -> Self {
+        self.proxy_auth = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    // Let a SOCKS5 proxy resolve host names (socks5h), needed for .onion nodes
+    pub fn proxy_remote_dns(mut self, remote_dns: bool) -> Self {
+        self.proxy_remote_dns = remote_dns;
+        self
+    }
+
+    // Log complete request and response bodies at debug level, unredacted. Only takes
+    // effect with the `tracing` feature; meant for regtest debugging.
+    pub fn log_bodies(mut self, log_bodies: bool) -> Self {
+        self.log_bodies = log_bodies;
+        self
+    }
+
+    // Keep at most `max` requests in flight, shared by all clones and wallet handles, so
+    // spawning many calls at once queues them here instead of overflowing the node's
+    // -rpcworkqueue. A batch counts as one request.
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = Some(max);
+        self
+    }
+
+    // Cache up to `entries` results of getblock, getblockheader and getrawtransaction by
+    // hash and verbosity, evicting the least recently used. Blocks and headers are only
+    // cached in hex, and confirmed transactions without `confirmations`, so no cached
+    // result reports a confirmation count frozen at its first fetch.
+    pub fn cache_capacity(mut self, entries: usize) -> Self {
+        self.cache_capacity = Some(entries);
+        self
+    }
+
+    // Ping the node every `interval` so the pooled connection is not dropped while idle.
+    // `build` spawns the pinger, so it must then run inside a tokio runtime; the task
+    // ends once every clone of the built client has been dropped.
+    pub fn keepalive(mut self, interval: Duration) -> Self {
+        self.keepalive = Some(interval);
+        self
+    }
+
+    // Run `middleware` around every single RPC. Layers run in the order added, the first
+    // one outermost, with retries inside the innermost.
+    pub fn with_middleware(mut self, middleware: Arc<dyn RpcMiddleware>) -> Self {
+        self.middleware.0.push(middleware);
+        self
+    }
+
+    // Speak HTTP over a Unix domain socket instead of TCP. The URL passed to `new` is
+    // then only used for its path, so any "http://localhost" will do. Proxy and TLS
+    // settings do not apply.
+    #[cfg(unix)]
+    pub fn unix_socket(mut self, path: &Path) -> Self {
+        self.unix_socket = Some(path.to_path_buf());
+        self
+    }
+
+    fn proxy_config(&self) -> Result<Option<ProxyConfig>> {
+        let Some(url) = &self.proxy else {
+            if self.proxy_auth.is_some() || self.proxy_remote_dns {
+                return Err(anyhow!("Proxy options were set without a proxy URL"));
+            }
+            return Ok(None);
+        };
+        Ok(Some(ProxyConfig {
+            url: url.clone(),
+            auth: self.proxy_auth.clone(),
+            remote_dns: self.proxy_remote_dns,
+        }))
+    }
+
+    fn http_client(&self) -> Result<Client> {
+        let mut http = Client::builder()
+            .timeout(self.timeout)
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+        if let Some(timeout) = self.connect_timeout {
+            http = http.connect_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            http = http.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            http = http.tcp_keepalive(interval);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            http = http.user_agent(user_agent);
+        }
+        if let Some(proxy) = self.proxy_config()? {
+            http = http.proxy(proxy.to_proxy()?);
+        }
+        for (index, pem) in self.root_certificates.iter().enumerate() {
+            let certificate = Certificate::from_pem(pem)
+                .map_err(|e| anyhow!("Root certificate {} is not valid PEM: {}", index, e))?;
+            http = http.add_root_certificate(certificate);
+        }
+        if let Some((der, password)) = &self.identity {
+            let identity = Identity::from_pkcs12_der(der, password)
+                .map_err(|e| anyhow!("Client identity is not a usable PKCS#12 archive: {}", e))?;
+            http = http.identity(identity);
+        }
+        Ok(http.build().map_err(BitcoinRpcError::from)?)
+    }
+
+    pub fn build(self) -> Result<BitcoinClient> {
+        let transport = match &self.unix_socket {
+            #[cfg(unix)]
+            Some(path) => {
+                if self.proxy_config()?.is_some() {
+                    return Err(anyhow!("A proxy cannot be used with a Unix socket"));
+                }
+                Transport::Unix {
+                    path: path.clone(),
+                    timeout: self.timeout,
+                }
+            }
+            _ => Transport::Tcp {
+                client: self.http_client()?,
+                proxy: self.proxy.clone(),
+            },
+        };
+        let limiter = match self.max_concurrent_requests {
+            Some(0) => return Err(anyhow!("max_concurrent_requests must be at least 1")),
+            Some(max) => Some(Arc::new(Semaphore::new(max))),
+            None => None,
+        };
+        let (auth, cookie_path) = match self.credentials {
+            Credentials::UserPass(username, password) => {
+                let auth = format!("{}:{}", username, password);
+                (format!("Basic {}", BASE64_STANDARD.encode(&auth)), None)
+            }
+            Credentials::Cookie(path) => (read_cookie_auth(&path)?, Some(path)),
+        };
+        let keepalive = self.keepalive;
+        let mut client = BitcoinClient {
+            transport,
+            url: self.url,
+            log_bodies: self.log_bodies,
+            auth: Arc::new(RwLock::new(auth)),
+            cookie_path,
+            wallet_name: None,
+            cassette: None,
+            cache: self
+                .cache_capacity
+                .filter(|entries| *entries > 0)
+                .map(|entries| Arc::new(ResponseCache::new(entries))),
+            retry: None,
+            next_id: Arc::new(AtomicU64::new(1)),
+            node_version: Arc::new(AtomicU32::new(0)),
+            limiter,
+            request_timeout: None,
+            middleware: self.middleware,
+            extra_headers: None,
+            keepalive: None,
+        };
+        if let Some(interval) = keepalive {
+            client.start_keepalive(interval)?;
+        }
+        Ok(client)
+    }
+}
+
+impl BitcoinClient {
+    pub fn builder(url: &str) -> BitcoinClientBuilder {
+        BitcoinClientBuilder::new(url)
+    }
+
+    // Node behind a Unix domain socket, e.g. one forwarded with `ssh -L /path:host:8332`
+    #[cfg(unix)]
+    pub fn new_unix(path: &Path, username: &str, password: &str) -> Result<Self> {
+        BitcoinClientBuilder::new("http://localhost")
+            .unix_socket(path)
+            .auth(username, password)
+            .build()
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::BitcoinClient;
+
+// Lookups by hash whose answer cannot change. Only the serialized forms are cached as
+// they are: verbose blocks and headers report `confirmations` and `nextblockhash`, which
+// change with every block and on a reorg.
+const CACHEABLE_METHODS: &[&str] = &["getblock", "getblockheader", "getrawtransaction"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct LruState {
+    // Key to result and the tick it was last used at
+    entries: HashMap<String, (Value, u64)>,
+    // Tick to key, oldest first
+    order: BTreeMap<u64, String>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+// Least-recently-used cache of RPC results, shared by clones of a client
+#[derive(Debug)]
+pub(crate) struct ResponseCache {
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        ResponseCache {
+            capacity,
+            state: Mutex::new(LruState::default()),
+        }
+    }
+
+    // Key for a cacheable call, the method plus its hash and verbosity parameters
+    pub(crate) fn key(method: &str, params: &Value) -> Option<String> {
+        CACHEABLE_METHODS
+            .contains(&method)
+            .then(|| format!("{}:{}", method, params))
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Result<Option<Value>> {
+        let mut state = self.lock()?;
+        let state = &mut *state;
+        state.tick += 1;
+        match state.entries.get_mut(key) {
+            Some((value, used)) => {
+                state.order.remove(used);
+                *used = state.tick;
+                state.order.insert(state.tick, key.to_string());
+                state.hits += 1;
+                Ok(Some(value.clone()))
+            }
+            None => {
+                state.misses += 1;
+                Ok(None)
+            }
+        }
+    }
+
+    pub(crate) fn insert(&self, key: String, value: &Value) -> Result<()> {
+        let value = match value {
+            Value::String(_) => value.clone(),
+            // A confirmed transaction is kept without its confirmation count, which
+            // deserializes as None; one without a block hash is still in the mempool
+            Value::Object(tx)
+                if key.starts_with("getrawtransaction:")
+                    && tx.get("blockhash").is_some_and(Value::is_string) =>
+            {
+                let mut tx = tx.clone();
+                tx.remove("confirmations");
+                Value::Object(tx)
+            }
+            _ => return Ok(()),
+        };
+        let mut state = self.lock()?;
+        let state = &mut *state;
+        state.tick += 1;
+        if let Some((_, used)) = state.entries.insert(key.clone(), (value, state.tick)) {
+            state.order.remove(&used);
+        }
+        state.order.insert(state.tick, key);
+        while state.entries.len() > self.capacity {
+            let Some((_, oldest)) = state.order.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, LruState>> {
+        self.state
+            .lock()
+            .map_err(|_| anyhow!("Cache lock poisoned"))
+    }
+}
+
+impl BitcoinClient {
+    // Hit and miss counts since the client was built or last cleared; all zero without
+    // `cache_capacity`
+    pub fn cache_stats(&self) -> CacheStats {
+        let Some(cache) = &self.cache else {
+            return CacheStats::default();
+        };
+        match cache.lock() {
+            Ok(state) => CacheStats {
+                hits: state.hits,
+                misses: state.misses,
+                entries: state.entries.len(),
+                capacity: cache.capacity,
+            },
+            Err(_) => CacheStats::default(),
+        }
+    }
+
+    // Drop every cached result and reset the counters
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache
+            && let Ok(mut state) = cache.lock()
+        {
+            *state = LruState::default();
+        }
+    }
+}
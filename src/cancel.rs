@@ -0,0 +1,107 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+use crate::BitcoinClient;
+use crate::error::BitcoinRpcError;
+use crate::types::ActiveCommand;
+
+// A wallet rescan can take hours on mainnet
+pub(crate) const RESCAN_TIMEOUT: Duration = Duration::from_secs(12 * 60 * 60);
+
+// Sends a cleanup RPC from a background task if dropped while armed, e.g. an abort when
+// the future running a long command is cancelled before the node answers
+pub(crate) struct AbortOnDrop {
+    client: BitcoinClient,
+    method: &'static str,
+    params: Value,
+    armed: bool,
+}
+
+impl AbortOnDrop {
+    pub(crate) fn new(client: &BitcoinClient, method: &'static str, params: Value) -> Self {
+        AbortOnDrop {
+            client: client.clone(),
+            method,
+            params,
+            armed: true,
+        }
+    }
+
+    pub(crate) fn disarm(mut self) {
+        self.armed = false;
+    }
+
+    // Disarm once the node has answered the command, with a result or an RPC error.
+    // After a timeout or a lost connection it may still be running, so the cleanup is
+    // sent as the guard drops.
+    pub(crate) fn finish<T>(self, result: &Result<T>) {
+        let answered = match result {
+            Ok(_) => true,
+            Err(e) => e
+                .downcast_ref::<BitcoinRpcError>()
+                .is_some_and(|rpc| rpc.code().is_some()),
+        };
+        if answered {
+            self.disarm();
+        }
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        // Outside a runtime there is nothing to spawn on; the command runs to completion
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let client = self.client.clone();
+        let method = self.method;
+        let params = self.params.take();
+        runtime.spawn(async move {
+            if let Err(e) = client.call::<Value>(method, params).await {
+                log::warn!("{} after cancellation failed: {}", method, e);
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RescanResult {
+    pub start_height: u64,
+    pub stop_height: u64,
+}
+
+impl BitcoinClient {
+    // Rescan the wallet from `start_height` to `stop_height` (the tip when None).
+    // Dropping the future before it resolves sends `abortrescan`. A start below the
+    // prune height fails with `BlockPruned` before anything is sent.
+    pub async fn rescan_blockchain(
+        &self,
+        start_height: u64,
+        stop_height: Option<u64>,
+    ) -> Result<RescanResult> {
+        self.prune_status().await?.check(start_height)?;
+        let guard = AbortOnDrop::new(self, "abortrescan", Value::Null);
+        let result = self
+            .with_timeout(RESCAN_TIMEOUT)
+            .call("rescanblockchain", json!([start_height, stop_height]))
+            .await;
+        guard.finish(&result);
+        result
+    }
+
+    // Commands the node is executing right now, from every client. The entry for the
+    // `getrpcinfo` call itself is left out.
+    pub async fn active_commands(&self) -> Result<Vec<ActiveCommand>> {
+        let info = self.get_rpc_info().await?;
+        Ok(info
+            .active_commands
+            .into_iter()
+            .filter(|command| command.method != "getrpcinfo")
+            .collect())
+    }
+}
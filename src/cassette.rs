@@ -0,0 +1,237 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::RpcError;
+
+const REDACTED: &str = "<redacted>";
+
+// Parameters carrying secrets, per method, by position and by name
+const SECRET_PARAMS: &[(&str, usize, &str)] = &[
+    ("createwallet", 3, "passphrase"),
+    ("encryptwallet", 0, "passphrase"),
+    ("importprivkey", 0, "privkey"),
+    ("migratewallet", 1, "passphrase"),
+    ("sethdseed", 1, "seed"),
+    ("signmessagewithprivkey", 0, "privkey"),
+    ("signrawtransactionwithkey", 1, "privkeys"),
+    ("walletpassphrase", 0, "passphrase"),
+    ("walletpassphrasechange", 0, "oldpassphrase"),
+    ("walletpassphrasechange", 1, "newpassphrase"),
+];
+
+// Methods taking a `requests` array whose descriptors or keys may be private
+const IMPORT_METHODS: &[&str] = &["importdescriptors", "importmulti"];
+
+// Fields of an import request that can carry xprv or WIF keys
+const IMPORT_SECRET_FIELDS: &[&str] = &["desc", "keys"];
+
+// Methods whose results are secrets
+const SECRET_RESULTS: &[&str] = &["dumpprivkey"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    pub method: String,
+    pub params: Value,
+    pub result: Option<Value>,
+    pub error: Option<RpcError>,
+}
+
+#[derive(Debug)]
+enum CassetteMode {
+    Record(Mutex<File>),
+    Replay(Mutex<Vec<(CassetteEntry, bool)>>),
+}
+
+// A JSON-lines file of recorded RPC exchanges, used to record a session or serve it back
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+}
+
+impl Cassette {
+    // Append every exchange made by the client to the cassette file
+    pub fn record(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Cassette {
+            path: path.to_path_buf(),
+            mode: CassetteMode::Record(Mutex::new(file)),
+        })
+    }
+
+    // Serve responses from the cassette file instead of the network
+    pub fn replay(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let mut entries = Vec::new();
+        for (line_no, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: CassetteEntry = serde_json::from_str(line).map_err(|e| {
+                anyhow!(
+                    "Invalid cassette entry at {}:{}: {}",
+                    path.display(),
+                    line_no + 1,
+                    e
+                )
+            })?;
+            entries.push((entry, false));
+        }
+        Ok(Cassette {
+            path: path.to_path_buf(),
+            mode: CassetteMode::Replay(Mutex::new(entries)),
+        })
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self.mode, CassetteMode::Replay(_))
+    }
+
+    pub(crate) fn write(
+        &self,
+        method: &str,
+        params: &Value,
+        result: &Option<Value>,
+        error: &Option<RpcError>,
+    ) -> Result<()> {
+        let CassetteMode::Record(file) = &self.mode else {
+            return Ok(());
+        };
+        let entry = CassetteEntry {
+            method: method.to_string(),
+            params: normalize_params(method, params),
+            result: result
+                .as_ref()
+                .map(|result| redact_result(method, params, result)),
+            error: error.clone(),
+        };
+        let mut file = file.lock().unwrap();
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+
+    // Find the recorded response for a request, preferring entries not yet served
+    pub(crate) fn lookup(
+        &self,
+        method: &str,
+        params: &Value,
+    ) -> Result<(Option<Value>, Option<RpcError>)> {
+        let CassetteMode::Replay(entries) = &self.mode else {
+            return Err(anyhow!("Cassette is not in replay mode"));
+        };
+        let params = normalize_params(method, params);
+        let mut entries = entries.lock().unwrap();
+        let matches = |entry: &CassetteEntry| entry.method == method && entry.params == params;
+        let index = entries
+            .iter()
+            .position(|(entry, used)| !used && matches(entry))
+            .or_else(|| entries.iter().rposition(|(entry, _)| matches(entry)));
+        match index {
+            Some(index) => {
+                entries[index].1 = true;
+                let entry = &entries[index].0;
+                Ok((entry.result.clone(), entry.error.clone()))
+            }
+            None => {
+                let request = format!("{} {}", method, params);
+                let nearest = entries
+                    .iter()
+                    .map(|(entry, _)| format!("{} {}", entry.method, entry.params))
+                    .min_by_key(|recorded| edit_distance(recorded, &request));
+                Err(anyhow!(
+                    "No recorded response in {} for {}{}",
+                    self.path.display(),
+                    request,
+                    nearest
+                        .map(|n| format!(" (nearest recorded call: {})", n))
+                        .unwrap_or_default()
+                ))
+            }
+        }
+    }
+}
+
+// Treat absent and empty params alike and mask secret arguments, positional or named
+fn normalize_params(method: &str, params: &Value) -> Value {
+    let mut params = match params {
+        Value::Null => Value::Array(Vec::new()),
+        other => other.clone(),
+    };
+    for &(_, position, name) in SECRET_PARAMS.iter().filter(|(m, _, _)| *m == method) {
+        if let Some(item) = param_mut(&mut params, position, name) {
+            *item = Value::String(REDACTED.to_string());
+        }
+    }
+    if IMPORT_METHODS.contains(&method)
+        && let Some(Value::Array(requests)) = param_mut(&mut params, 0, "requests")
+    {
+        for request in requests.iter_mut().filter_map(Value::as_object_mut) {
+            for field in IMPORT_SECRET_FIELDS {
+                if let Some(value) = request.get_mut(*field) {
+                    *value = Value::String(REDACTED.to_string());
+                }
+            }
+        }
+    }
+    params
+}
+
+fn param<'a>(params: &'a Value, position: usize, name: &str) -> Option<&'a Value> {
+    match params {
+        Value::Array(items) => items.get(position),
+        Value::Object(named) => named.get(name),
+        _ => None,
+    }
+}
+
+fn param_mut<'a>(params: &'a mut Value, position: usize, name: &str) -> Option<&'a mut Value> {
+    match params {
+        Value::Array(items) => items.get_mut(position),
+        Value::Object(named) => named.get_mut(name),
+        _ => None,
+    }
+}
+
+// Mask secret results. Private `listdescriptors` keeps its shape so replay still
+// decodes it, with every descriptor masked.
+fn redact_result(method: &str, params: &Value, result: &Value) -> Value {
+    if SECRET_RESULTS.contains(&method) {
+        return Value::String(REDACTED.to_string());
+    }
+    let mut result = result.clone();
+    if method == "listdescriptors"
+        && param(params, 0, "private") == Some(&Value::Bool(true))
+        && let Some(Value::Array(descriptors)) = result.get_mut("descriptors")
+    {
+        for descriptor in descriptors.iter_mut() {
+            if let Some(desc) = descriptor.get_mut("desc") {
+                *desc = Value::String(REDACTED.to_string());
+            }
+        }
+    }
+    result
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
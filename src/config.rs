@@ -0,0 +1,383 @@
+use anyhow::{Result, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::builder::BitcoinClientBuilder;
+use crate::cookie::{default_datadir, network_dir};
+use crate::{BitcoinClient, BitcoinClientType, default_rpc_port};
+
+// Options bitcoind only applies to mainnet when set outside a network section
+const NETWORK_ONLY: &[&str] = &["rpcbind", "rpcport"];
+
+// Section name bitcoind uses for each network in bitcoin.conf
+fn section_name(network: BitcoinClientType) -> &'static str {
+    match network {
+        BitcoinClientType::Mainnet => "main",
+        BitcoinClientType::Testnet => "test",
+        BitcoinClientType::Signet => "signet",
+        BitcoinClientType::Regtest => "regtest",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ConfEntry {
+    // None for the top-level section
+    section: Option<String>,
+    key: String,
+    value: String,
+}
+
+// A parsed bitcoin.conf, looked up the way bitcoind does: a network's own section wins
+// over the top level, and the first of repeated keys wins within a section
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitcoinConf {
+    entries: Vec<ConfEntry>,
+}
+
+impl BitcoinConf {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Cannot read {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| anyhow!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut entries = Vec::new();
+        let mut section: Option<String> = None;
+        for (number, line) in text.lines().enumerate() {
+            let line = match line.split_once('#') {
+                Some((before, _)) => before,
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = Some(name.trim().to_string());
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Line {} is not key=value: {}", number + 1, line))?;
+            let key = key.trim();
+            // `regtest.rpcport=...` is shorthand for a key in the [regtest] section
+            let (entry_section, key) = match key.split_once('.') {
+                Some((prefix, key)) => (Some(prefix.to_string()), key),
+                None => (section.clone(), key),
+            };
+            entries.push(ConfEntry {
+                section: entry_section,
+                key: key.to_string(),
+                value: value.trim().to_string(),
+            });
+        }
+        Ok(BitcoinConf { entries })
+    }
+
+    fn first_in(&self, section: Option<&str>, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|e| e.section.as_deref() == section && e.key == key)
+            .map(|e| e.value.as_str())
+    }
+
+    // Value of `key` for `network`, or None when unset or negated with `no<key>`
+    pub fn get(&self, network: BitcoinClientType, key: &str) -> Option<&str> {
+        let mut sections = vec![Some(section_name(network))];
+        if network == BitcoinClientType::Mainnet || !NETWORK_ONLY.contains(&key) {
+            sections.push(None);
+        }
+        let negated = format!("no{}", key);
+        for section in sections {
+            if self.first_in(section, &negated).is_some_and(|v| v != "0") {
+                return None;
+            }
+            if let Some(value) = self.first_in(section, key) {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    // Every value of a repeatable key such as `rpcbind`, network section first
+    pub fn get_all(&self, network: BitcoinClientType, key: &str) -> Vec<&str> {
+        let section = section_name(network);
+        let own: Vec<&str> = self
+            .entries
+            .iter()
+            .filter(|e| e.section.as_deref() == Some(section) && e.key == key)
+            .map(|e| e.value.as_str())
+            .collect();
+        if !own.is_empty() || (network != BitcoinClientType::Mainnet && NETWORK_ONLY.contains(&key))
+        {
+            return own;
+        }
+        self.entries
+            .iter()
+            .filter(|e| e.section.is_none() && e.key == key)
+            .map(|e| e.value.as_str())
+            .collect()
+    }
+
+    // Network chosen in the file with `chain=` or `regtest=1` and friends, if any
+    pub fn chain(&self) -> Result<Option<BitcoinClientType>> {
+        if let Some(chain) = self.first_in(None, "chain") {
+            return match chain {
+                "main" => Ok(Some(BitcoinClientType::Mainnet)),
+                "test" => Ok(Some(BitcoinClientType::Testnet)),
+                "signet" => Ok(Some(BitcoinClientType::Signet)),
+                "regtest" => Ok(Some(BitcoinClientType::Regtest)),
+                other => Err(anyhow!("Unsupported chain={}", other)),
+            };
+        }
+        let flags = [
+            ("testnet", BitcoinClientType::Testnet),
+            ("signet", BitcoinClientType::Signet),
+            ("regtest", BitcoinClientType::Regtest),
+        ];
+        let mut chosen = None;
+        for (key, network) in flags {
+            if self.first_in(None, key).is_some_and(|v| v != "0") {
+                if chosen.is_some() {
+                    return Err(anyhow!(
+                        "More than one of testnet, signet and regtest is set"
+                    ));
+                }
+                chosen = Some(network);
+            }
+        }
+        Ok(chosen)
+    }
+
+    // RPC URL for `network`: `rpcconnect`, else the first `rpcbind` (wildcards mean this
+    // host), with `rpcport` or the network's default port
+    pub fn rpc_url(&self, network: BitcoinClientType) -> Result<String> {
+        let address = self
+            .get(network, "rpcconnect")
+            .or_else(|| self.get_all(network, "rpcbind").first().copied());
+        let (host, address_port) = match address {
+            Some(address) => split_host_port(address)?,
+            None => ("127.0.0.1".to_string(), None),
+        };
+        let host = match host.as_str() {
+            "" | "0.0.0.0" => "127.0.0.1".to_string(),
+            "[::]" => "[::1]".to_string(),
+            _ => host,
+        };
+        let port = match self.get(network, "rpcport") {
+            Some(port) => port
+                .parse::<u16>()
+                .map_err(|_| anyhow!("Invalid rpcport={}", port))?,
+            None => address_port.unwrap_or(default_rpc_port(network)),
+        };
+        Ok(format!("http://{}:{}", host, port))
+    }
+
+    // Cookie file bitcoind writes for `network`, honouring `datadir` and `rpccookiefile`
+    pub fn cookie_path(&self, network: BitcoinClientType) -> Result<PathBuf> {
+        let datadir = match self.get(network, "datadir") {
+            Some(datadir) => PathBuf::from(datadir),
+            None => default_datadir()
+                .ok_or_else(|| anyhow!("Cannot locate the default Bitcoin data directory"))?,
+        };
+        let dir = network_dir(&datadir, network);
+        Ok(match self.get(network, "rpccookiefile") {
+            Some(file) => dir.join(file),
+            None => dir.join(".cookie"),
+        })
+    }
+
+    // Client for `network`, with `rpcuser`/`rpcpassword` when a password is set and
+    // the node's cookie otherwise. A node started with this file runs on the network it
+    // selects, so asking for another one is an error.
+    pub fn builder(&self, network: BitcoinClientType) -> Result<BitcoinClientBuilder> {
+        if let Some(chain) = self.chain()?
+            && chain != network
+        {
+            return Err(anyhow!(
+                "File selects chain={}, not {}",
+                section_name(chain),
+                section_name(network)
+            ));
+        }
+        let builder = BitcoinClientBuilder::new(&self.rpc_url(network)?);
+        Ok(match self.get(network, "rpcpassword") {
+            Some(password) => {
+                builder.auth(self.get(network, "rpcuser").unwrap_or_default(), password)
+            }
+            None => builder.cookie_file(&self.cookie_path(network)?),
+        })
+    }
+}
+
+// "host", "host:port", "[v6]" or "[v6]:port"
+fn split_host_port(address: &str) -> Result<(String, Option<u16>)> {
+    let parse_port = |port: &str| {
+        port.parse::<u16>()
+            .map_err(|_| anyhow!("Invalid port in {}", address))
+    };
+    if address.starts_with('[') {
+        let end = address
+            .find(']')
+            .ok_or_else(|| anyhow!("Unclosed bracket in {}", address))?;
+        let port = match address[end + 1..].strip_prefix(':') {
+            Some(port) => Some(parse_port(port)?),
+            None => None,
+        };
+        return Ok((address[..=end].to_string(), port));
+    }
+    match address.split_once(':') {
+        // More than one colon is a bare IPv6 address
+        Some((host, port)) if !port.contains(':') => {
+            Ok((host.to_string(), Some(parse_port(port)?)))
+        }
+        Some(_) => Ok((format!("[{}]", address), None)),
+        None => Ok((address.to_string(), None)),
+    }
+}
+
+impl BitcoinClient {
+    // Connect to the node a bitcoin.conf describes, using its settings for `network`
+    pub fn from_bitcoin_conf(path: &Path, network: BitcoinClientType) -> Result<Self> {
+        BitcoinConf::load(path)?.builder(network)?.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use BitcoinClientType::{Mainnet, Regtest, Signet, Testnet};
+
+    fn conf(text: &str) -> BitcoinConf {
+        BitcoinConf::parse(text).unwrap()
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let conf = conf("# node settings\n\n  rpcuser = alice # inline\nrpcconnect=10.0.0.1#old\n");
+        assert_eq!(conf.get(Mainnet, "rpcuser"), Some("alice"));
+        // Everything after '#' is a comment, as in bitcoind
+        assert_eq!(conf.get(Mainnet, "rpcconnect"), Some("10.0.0.1"));
+        assert!(BitcoinConf::parse("rpcuser alice").is_err());
+    }
+
+    #[test]
+    fn the_first_of_repeated_keys_wins() {
+        let conf = conf("rpcuser=first\nrpcuser=second\nrpcbind=10.0.0.1\nrpcbind=10.0.0.2\n");
+        assert_eq!(conf.get(Mainnet, "rpcuser"), Some("first"));
+        assert_eq!(conf.get_all(Mainnet, "rpcbind"), ["10.0.0.1", "10.0.0.2"]);
+        assert_eq!(conf.rpc_url(Mainnet).unwrap(), "http://10.0.0.1:8332");
+    }
+
+    #[test]
+    fn network_sections_win_over_the_top_level() {
+        let conf = conf("rpcuser=top\n[test]\nrpcuser=testuser\n[regtest]\nrpcpassword=rt\n");
+        assert_eq!(conf.get(Testnet, "rpcuser"), Some("testuser"));
+        assert_eq!(conf.get(Regtest, "rpcuser"), Some("top"));
+        assert_eq!(conf.get(Mainnet, "rpcuser"), Some("top"));
+        assert_eq!(conf.get(Regtest, "rpcpassword"), Some("rt"));
+        assert_eq!(conf.get(Signet, "rpcpassword"), None);
+    }
+
+    #[test]
+    fn dotted_keys_are_section_shorthand() {
+        let conf = conf("regtest.rpcport=19000\nrpcport=9000\n[regtest]\nrpcport=19001\n");
+        assert_eq!(conf.get(Regtest, "rpcport"), Some("19000"));
+        assert_eq!(conf.rpc_url(Regtest).unwrap(), "http://127.0.0.1:19000");
+        assert_eq!(conf.rpc_url(Mainnet).unwrap(), "http://127.0.0.1:9000");
+    }
+
+    #[test]
+    fn negated_keys_unset_the_option() {
+        let conf = conf("rpcconnect=10.0.0.5\n[regtest]\nnorpcconnect=1\n[test]\nnorpcconnect=0\n");
+        assert_eq!(conf.get(Regtest, "rpcconnect"), None);
+        assert_eq!(conf.get(Testnet, "rpcconnect"), Some("10.0.0.5"));
+        assert_eq!(conf.get(Mainnet, "rpcconnect"), Some("10.0.0.5"));
+        assert_eq!(conf.rpc_url(Regtest).unwrap(), "http://127.0.0.1:18443");
+    }
+
+    #[test]
+    fn top_level_port_and_bind_apply_to_mainnet_only() {
+        let conf = conf("rpcport=9000\nrpcbind=10.0.0.1\nrpcuser=alice\n");
+        assert_eq!(conf.rpc_url(Mainnet).unwrap(), "http://10.0.0.1:9000");
+        assert_eq!(conf.rpc_url(Regtest).unwrap(), "http://127.0.0.1:18443");
+        assert_eq!(conf.rpc_url(Testnet).unwrap(), "http://127.0.0.1:18332");
+        assert!(conf.get_all(Signet, "rpcbind").is_empty());
+        // Other keys still fall through
+        assert_eq!(conf.get(Regtest, "rpcuser"), Some("alice"));
+    }
+
+    #[test]
+    fn ipv6_binds_become_bracketed_hosts() {
+        let url = |text: &str| conf(text).rpc_url(Mainnet).unwrap();
+        assert_eq!(url("rpcbind=[::1]:8400"), "http://[::1]:8400");
+        assert_eq!(url("rpcbind=::1"), "http://[::1]:8332");
+        assert_eq!(url("rpcbind=[::]"), "http://[::1]:8332");
+        assert_eq!(
+            url("rpcbind=0.0.0.0:8400\nrpcport=8500"),
+            "http://127.0.0.1:8500"
+        );
+        assert!(conf("rpcbind=[::1").rpc_url(Mainnet).is_err());
+        assert!(conf("rpcbind=host:port").rpc_url(Mainnet).is_err());
+    }
+
+    #[test]
+    fn chain_comes_from_chain_or_a_network_flag() {
+        assert_eq!(conf("chain=regtest").chain().unwrap(), Some(Regtest));
+        assert_eq!(conf("testnet=1").chain().unwrap(), Some(Testnet));
+        assert_eq!(conf("regtest=0\nsignet=1").chain().unwrap(), Some(Signet));
+        assert_eq!(conf("[regtest]\nrpcport=1").chain().unwrap(), None);
+        assert!(conf("chain=testnet4").chain().is_err());
+        assert!(conf("testnet=1\nregtest=1").chain().is_err());
+    }
+
+    #[test]
+    fn another_network_than_the_file_selects_is_refused() {
+        let regtest = conf("chain=regtest\nrpcpassword=pass\n");
+        assert!(regtest.builder(Regtest).is_ok());
+        assert_eq!(
+            regtest.builder(Mainnet).unwrap_err().to_string(),
+            "File selects chain=regtest, not main"
+        );
+        let testnet = conf("testnet=1\nrpcpassword=pass\n");
+        assert_eq!(
+            testnet.builder(Regtest).unwrap_err().to_string(),
+            "File selects chain=test, not regtest"
+        );
+    }
+
+    #[test]
+    fn the_cookie_is_used_without_a_password() {
+        let datadir = std::env::temp_dir().join(format!("bitcoin-sdk-conf-{}", std::process::id()));
+        let regtest = conf(&format!("datadir={}\nregtest=1\n", datadir.display()));
+        let cookie = datadir.join("regtest").join(".cookie");
+        assert_eq!(regtest.cookie_path(Regtest).unwrap(), cookie);
+        assert_eq!(
+            regtest.cookie_path(Mainnet).unwrap(),
+            datadir.join(".cookie")
+        );
+        let custom = conf(&format!(
+            "datadir={}\n[regtest]\nrpccookiefile=node.cookie\n",
+            datadir.display()
+        ));
+        assert_eq!(
+            custom.cookie_path(Regtest).unwrap(),
+            datadir.join("regtest").join("node.cookie")
+        );
+
+        let error = regtest.builder(Regtest).unwrap().build().unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with(&format!("Cannot read cookie {}", cookie.display())),
+            "{}",
+            error
+        );
+        fs::create_dir_all(cookie.parent().unwrap()).unwrap();
+        fs::write(&cookie, "__cookie__:0123abcd").unwrap();
+        let built = regtest.builder(Regtest).unwrap().build();
+        fs::remove_dir_all(&datadir).unwrap();
+        built.unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use crate::error::BitcoinRpcError;
+use crate::node_version::NodeVersion;
+use crate::types::{ImportMempoolOptions, MemoryInfo, RpcInfo, SaveMempoolResult};
+use crate::{BitcoinClient, BitcoinNetWorkRequest, BitcoinNetWorkResponse};
+
+// Reply to `stop`, unchanged since 0.x
+const STOPPING: &str = "Bitcoin Core stopping";
+// Level 4 over the whole mainnet chain runs for many hours
+const VERIFY_CHAIN_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+impl BitcoinClient {
+    // Seconds since the node started
+    pub async fn uptime(&self) -> Result<u64> {
+        self.call("uptime", Value::Null).await
+    }
+
+    // Ask the node to shut down. bitcoind may close the socket while replying, so a
+    // connection dropped after a 2xx status counts as success.
+    pub async fn stop(&self) -> Result<()> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = BitcoinNetWorkRequest {
+            jsonrpc: "2.0".to_string(),
+            id,
+            method: "stop".to_string(),
+            params: Value::Null,
+        };
+        let _permit = self.acquire_slot().await?;
+        let reply = self
+            .post(&self.endpoint(std::iter::once("stop")), &request)
+            .await?;
+        if reply.status.is_success() {
+            // The body may be cut off; a reset here still means the node is going down
+            if let Ok(body) = &reply.body
+                && !String::from_utf8_lossy(body).contains(STOPPING)
+            {
+                log::debug!(
+                    "stop answered without {:?}: {}",
+                    STOPPING,
+                    String::from_utf8_lossy(body)
+                );
+            }
+            return Ok(());
+        }
+        // An error reply, e.g. a -28 warm-up rejection or failed auth
+        self.read_response::<BitcoinNetWorkResponse<Value>>(reply)
+            .and_then(|reply| match reply.error {
+                Some(error) => Err(BitcoinRpcError::from(error).into()),
+                None => Ok(()),
+            })
+    }
+
+    // Locked memory pool statistics (`getmemoryinfo "stats"`)
+    pub async fn get_memory_info(&self) -> Result<MemoryInfo> {
+        self.call("getmemoryinfo", json!(["stats"])).await
+    }
+
+    // glibc's malloc_info XML (`getmemoryinfo "mallocinfo"`), only on glibc builds
+    pub async fn get_malloc_info(&self) -> Result<String> {
+        self.call("getmemoryinfo", json!(["mallocinfo"])).await
+    }
+
+    // Check the last `nblocks` blocks (default 6, 0 for all) at `checklevel` 0-4
+    // (default 3). Sent with a timeout long enough for high levels over many blocks.
+    pub async fn verify_chain(&self, checklevel: Option<u8>, nblocks: Option<u32>) -> Result<bool> {
+        self.with_timeout(VERIFY_CHAIN_TIMEOUT)
+            .call("verifychain", json!([checklevel, nblocks]))
+            .await
+    }
+
+    // Write the mempool to disk now rather than at shutdown
+    pub async fn save_mempool(&self) -> Result<SaveMempoolResult> {
+        let result: Option<SaveMempoolResult> = self.call("savemempool", Value::Null).await?;
+        Ok(result.unwrap_or_default())
+    }
+
+    // Load a mempool.dat from `path` on the node's filesystem, e.g. one written by
+    // `save_mempool` on another node. Needs Bitcoin Core 25.0 or later.
+    pub async fn import_mempool(&self, path: &str, options: ImportMempoolOptions) -> Result<()> {
+        self.require_version(NodeVersion::IMPORT_MEMPOOL, "importmempool")
+            .await?;
+        let params = if options.is_empty() {
+            json!([path])
+        } else {
+            json!([path, options])
+        };
+        self.call::<Value>("importmempool", params).await?;
+        Ok(())
+    }
+
+    pub async fn get_rpc_info(&self) -> Result<RpcInfo> {
+        self.call("getrpcinfo", Value::Null).await
+    }
+}
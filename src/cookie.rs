@@ -0,0 +1,78 @@
+use anyhow::{Result, anyhow};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::builder::BitcoinClientBuilder;
+use crate::{BitcoinClient, BitcoinClientType, local_url};
+
+// Read a `.cookie` file and build the Basic auth header from its `user:token` pair
+pub(crate) fn read_cookie_auth(cookie_path: &Path) -> Result<String> {
+    let contents = fs::read_to_string(cookie_path)
+        .map_err(|e| anyhow!("Cannot read cookie {}: {}", cookie_path.display(), e))?;
+    let cookie = contents.trim();
+    match cookie.split_once(':') {
+        Some((user, token)) if !user.is_empty() && !token.is_empty() => {
+            Ok(format!("Basic {}", BASE64_STANDARD.encode(cookie)))
+        }
+        _ => Err(anyhow!("Malformed cookie file {}", cookie_path.display())),
+    }
+}
+
+// Bitcoin Core's default data directory for this platform
+pub fn default_datadir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("Bitcoin"))
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME")
+            .map(|dir| PathBuf::from(dir).join("Library/Application Support/Bitcoin"))
+    } else {
+        std::env::var_os("HOME").map(|dir| PathBuf::from(dir).join(".bitcoin"))
+    }
+}
+
+// The per-network subdirectory bitcoind uses inside a data directory
+pub(crate) fn network_dir(datadir: &Path, network: BitcoinClientType) -> PathBuf {
+    match network {
+        BitcoinClientType::Mainnet => datadir.to_path_buf(),
+        BitcoinClientType::Testnet => datadir.join("testnet3"),
+        BitcoinClientType::Signet => datadir.join("signet"),
+        BitcoinClientType::Regtest => datadir.join("regtest"),
+    }
+}
+
+// Where bitcoind writes its cookie for a network under the default data directory
+pub fn default_cookie_path(network: BitcoinClientType) -> Option<PathBuf> {
+    Some(network_dir(&default_datadir()?, network).join(".cookie"))
+}
+
+impl BitcoinClient {
+    // Authenticate with the cookie bitcoind writes when no rpcuser/rpcpassword is set.
+    // The cookie is read again and the request retried once on HTTP 401, since it
+    // changes every time the node restarts.
+    pub fn new_with_cookie(url: &str, cookie_path: &Path) -> Result<Self> {
+        BitcoinClientBuilder::new(url)
+            .cookie_file(cookie_path)
+            .build()
+    }
+
+    // Local node on the default port, authenticated with the cookie in the default datadir
+    pub fn new_local_with_cookie(network: BitcoinClientType) -> Result<Self> {
+        let cookie_path = default_cookie_path(network)
+            .ok_or_else(|| anyhow!("Cannot locate the default Bitcoin data directory"))?;
+        Self::new_with_cookie(&local_url(network), &cookie_path)
+    }
+
+    // Pick up a rotated cookie; false when this client does not use one
+    pub(crate) fn reload_cookie(&self) -> Result<bool> {
+        let Some(cookie_path) = &self.cookie_path else {
+            return Ok(false);
+        };
+        let auth = read_cookie_auth(cookie_path)?;
+        *self
+            .auth
+            .write()
+            .map_err(|_| anyhow!("Auth lock poisoned"))? = auth;
+        Ok(true)
+    }
+}
@@ -1,11 +1,15 @@
 use anyhow::Result;
+use base64::{Engine, prelude::BASE64_STANDARD};
 use bech32::{ToBase32, Variant};
-use bs58;
 use ripemd::Ripemd160;
-use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
 use sha2::{Digest, Sha256};
 
 use crate::BitcoinClientType;
+use crate::serialization::Serialization;
+
+const SIGNED_MESSAGE_MAGIC: &str = "Bitcoin Signed Message:\n";
 
 pub struct BitcoinCrypto;
 
@@ -167,17 +171,23 @@ impl BitcoinCrypto {
             BitcoinClientType::Regtest => "bcrt",
         };
 
-        let data = hash160.to_base32();
+        // Witness version 0 followed by the program
+        let mut data = vec![bech32::u5::try_from_u8(0).expect("0 is a valid u5")];
+        data.extend(hash160.to_base32());
         bech32::encode(hrp, data, Variant::Bech32)
             .map_err(|e| anyhow::anyhow!("Bech32 encode error: {}", e))
     }
 
-    // Decoding Bech32 addresses
+    // Decoding Bech32 addresses into the HRP and witness program
     pub fn decode_bech32_address(address: &str) -> Result<(String, Vec<u8>)> {
         let decoded =
             bech32::decode(address).map_err(|e| anyhow::anyhow!("Bech32 decode error: {}", e))?;
-        // to bytes
-        let bytes = bech32::FromBase32::from_base32(&decoded.1)
+        // Skip the witness version, then convert the program to bytes
+        let program = decoded
+            .1
+            .get(1..)
+            .ok_or_else(|| anyhow::anyhow!("Empty witness address"))?;
+        let bytes = bech32::FromBase32::from_base32(program)
             .map_err(|e| anyhow::anyhow!("Bech32 from_base32 error: {}", e))?;
         Ok((decoded.0, bytes))
     }
@@ -193,6 +203,50 @@ impl BitcoinCrypto {
         Ok((version, hash))
     }
 
+    // Output script paying to an address
+    pub fn address_to_script_pubkey(address: &str) -> Result<Vec<u8>> {
+        if let Ok((hrp, data, variant)) = bech32::decode(address) {
+            if !matches!(hrp.as_str(), "bc" | "tb" | "bcrt") {
+                return Err(anyhow::anyhow!("Unknown address prefix: {}", hrp));
+            }
+            let (version, program) = data
+                .split_first()
+                .ok_or_else(|| anyhow::anyhow!("Empty witness address"))?;
+            let program: Vec<u8> = bech32::FromBase32::from_base32(program)
+                .map_err(|e| anyhow::anyhow!("Bech32 from_base32 error: {}", e))?;
+            let version = version.to_u8();
+            // BIP350: version 0 uses bech32, later versions bech32m
+            let expected = if version == 0 {
+                Variant::Bech32
+            } else {
+                Variant::Bech32m
+            };
+            if variant != expected {
+                return Err(anyhow::anyhow!("Wrong checksum variant for {}", address));
+            }
+            // BIP141: version 0 programs are a 20-byte key hash or a 32-byte script hash
+            if version > 16
+                || !(2..=40).contains(&program.len())
+                || (version == 0 && program.len() != 20 && program.len() != 32)
+            {
+                return Err(anyhow::anyhow!("Invalid witness program in {}", address));
+            }
+            let mut script = vec![if version == 0 { 0x00 } else { 0x50 + version }];
+            script.push(program.len() as u8);
+            script.extend_from_slice(&program);
+            return Ok(script);
+        }
+        let (version, hash) = Self::decode_address(address)?;
+        if hash.len() != 20 {
+            return Err(anyhow::anyhow!("Invalid hash length in {}", address));
+        }
+        match version {
+            0x00 | 0x6f => Ok([&[0x76, 0xa9, 0x14][..], &hash, &[0x88, 0xac]].concat()),
+            0x05 | 0xc4 => Ok([&[0xa9, 0x14][..], &hash, &[0x87]].concat()),
+            _ => Err(anyhow::anyhow!("Unknown address prefix: 0x{:02x}", version)),
+        }
+    }
+
     // Create a private key in WIF format
     pub fn private_key_to_wif(
         private_key: &[u8; 32],
@@ -243,6 +297,65 @@ impl BitcoinCrypto {
         Ok((private_key, compressed, network))
     }
 
+    // Digest signed by `signmessage`: double SHA256 of the magic prefix and the message,
+    // each preceded by its varint length
+    pub fn signed_message_hash(message: &str) -> [u8; 32] {
+        let mut data = Serialization::serialize_varint(SIGNED_MESSAGE_MAGIC.len() as u64);
+        data.extend_from_slice(SIGNED_MESSAGE_MAGIC.as_bytes());
+        data.extend(Serialization::serialize_varint(message.len() as u64));
+        data.extend_from_slice(message.as_bytes());
+        Self::double_sha256(&data)
+    }
+
+    // Checks a base64 `signmessage` signature without a node. Only P2PKH addresses can
+    // sign this way; a malformed signature is an error, a wrong one is `false`
+    pub fn verify_message(address: &str, signature: &str, message: &str) -> Result<bool> {
+        if bech32::decode(address).is_ok() {
+            return Err(anyhow::anyhow!(
+                "Message signing only supports P2PKH addresses, {} is segwit",
+                address
+            ));
+        }
+        let (version, hash) = Self::decode_address(address)?;
+        if !matches!(version, 0x00 | 0x6f) || hash.len() != 20 {
+            return Err(anyhow::anyhow!(
+                "Message signing only supports P2PKH addresses, got {}",
+                address
+            ));
+        }
+        let signature = BASE64_STANDARD
+            .decode(signature)
+            .map_err(|e| anyhow::anyhow!("Malformed base64 signature: {}", e))?;
+        if signature.len() != 65 {
+            return Err(anyhow::anyhow!(
+                "Signature must be 65 bytes, got {}",
+                signature.len()
+            ));
+        }
+        // Header byte: 27 + recovery id, plus 4 when the key is compressed
+        let header = signature[0];
+        if !(27..=34).contains(&header) {
+            return Err(anyhow::anyhow!(
+                "Invalid signature header: 0x{:02x}",
+                header
+            ));
+        }
+        let compressed = header >= 31;
+        let recovery_id = RecoveryId::from_i32(((header - 27) & 3) as i32)?;
+        let signature = RecoverableSignature::from_compact(&signature[1..], recovery_id)?;
+        let digest = Message::from_slice(&Self::signed_message_hash(message))?;
+        let Ok(public_key) = Secp256k1::verification_only().recover_ecdsa(&digest, &signature)
+        else {
+            return Ok(false);
+        };
+        let public_key = if compressed {
+            public_key.serialize().to_vec()
+        } else {
+            public_key.serialize_uncompressed().to_vec()
+        };
+        Ok(Self::hash160(&public_key)[..] == hash[..])
+    }
+
     // Base58 encoding (without checksum)
     pub fn base58_encode(data: &[u8]) -> String {
         bs58::encode(data).into_string()
@@ -0,0 +1,214 @@
+use thiserror::Error;
+
+use crate::RpcError;
+
+// Codes without a variant of their own, for errors matched on code alone
+pub(crate) const RPC_MISC_ERROR: i32 = -1;
+pub(crate) const RPC_WALLET_INVALID_LABEL_NAME: i32 = -11;
+pub(crate) const RPC_INTERNAL_ERROR: i32 = -32603;
+
+// Failure of a single RPC. Node-side rejections carry Core's error code; transport and
+// decode failures are separate so retry logic can tell them apart. Returned inside
+// anyhow, so callers match with `err.downcast_ref::<BitcoinRpcError>()`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BitcoinRpcError {
+    #[error("RPC error -5: {0}")]
+    InvalidAddressOrKey(String),
+    // -5 from a mempool lookup: the transaction was confirmed, evicted or never seen
+    #[error("RPC error -5: {0}")]
+    NotInMempool(String),
+    // -5 from `gettransaction`: the txid is not a transaction of this wallet
+    #[error("RPC error -5: {0}")]
+    NotInWallet(String),
+    #[error("RPC error -6: {0}")]
+    InsufficientFunds(String),
+    #[error("RPC error -8: {0}")]
+    InvalidParameter(String),
+    // The wallet is encrypted and locked; unlock it with `walletpassphrase` first
+    #[error("RPC error -13: {0}")]
+    WalletUnlockNeeded(String),
+    #[error("RPC error -18: {0}")]
+    WalletNotFound(String),
+    #[error("RPC error -19: {0}")]
+    WalletNotSpecified(String),
+    #[error("RPC error -25: {0}")]
+    VerifyError(String),
+    #[error("RPC error -26: {0}")]
+    VerifyRejected(String),
+    #[error("RPC error -27: {0}")]
+    VerifyAlreadyInChain(String),
+    #[error("RPC error -28: {0}")]
+    InWarmup(String),
+    #[error("RPC error -32601: {0}")]
+    MethodNotFound(String),
+    // Any other code the node returns
+    #[error("RPC error {code}: {message}")]
+    Rpc { code: i32, message: String },
+    // HTTP 401, the credentials or cookie were not accepted
+    #[error("Authentication failed: check rpcuser/rpcpassword or the cookie file")]
+    AuthFailed,
+    // Non-success HTTP status without a JSON-RPC error body
+    #[error("HTTP error {status}: {body}")]
+    Http { status: u16, body: String },
+    // The node's RPC work queue overflowed; raise -rpcworkqueue or send fewer requests at once
+    #[error("Node RPC work queue is full: {0}")]
+    WorkQueueFull(String),
+    // The node could not be reached, e.g. connection refused
+    #[error("Connection error: {0}")]
+    Connect(String),
+    // The configured proxy could not be reached or refused the connection
+    #[error("Proxy error: {0}")]
+    Proxy(String),
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    // Any other failure before a response arrived
+    #[error("Transport error: {0}")]
+    Transport(String),
+    // The response arrived but did not have the expected shape
+    #[error("Decode error: {0}")]
+    Decode(String),
+}
+
+impl BitcoinRpcError {
+    pub fn from_code(code: i32, message: String) -> Self {
+        match code {
+            -5 => BitcoinRpcError::InvalidAddressOrKey(message),
+            -6 => BitcoinRpcError::InsufficientFunds(message),
+            -8 => BitcoinRpcError::InvalidParameter(message),
+            -13 => BitcoinRpcError::WalletUnlockNeeded(message),
+            -18 => BitcoinRpcError::WalletNotFound(message),
+            -19 => BitcoinRpcError::WalletNotSpecified(message),
+            -25 => BitcoinRpcError::VerifyError(message),
+            -26 => BitcoinRpcError::VerifyRejected(message),
+            -27 => BitcoinRpcError::VerifyAlreadyInChain(message),
+            -28 => BitcoinRpcError::InWarmup(message),
+            -32601 => BitcoinRpcError::MethodNotFound(message),
+            code => BitcoinRpcError::Rpc { code, message },
+        }
+    }
+
+    // Core's error code, None for transport and decode failures
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            BitcoinRpcError::InvalidAddressOrKey(_)
+            | BitcoinRpcError::NotInMempool(_)
+            | BitcoinRpcError::NotInWallet(_) => Some(-5),
+            BitcoinRpcError::InsufficientFunds(_) => Some(-6),
+            BitcoinRpcError::InvalidParameter(_) => Some(-8),
+            BitcoinRpcError::WalletUnlockNeeded(_) => Some(-13),
+            BitcoinRpcError::WalletNotFound(_) => Some(-18),
+            BitcoinRpcError::WalletNotSpecified(_) => Some(-19),
+            BitcoinRpcError::VerifyError(_) => Some(-25),
+            BitcoinRpcError::VerifyRejected(_) => Some(-26),
+            BitcoinRpcError::VerifyAlreadyInChain(_) => Some(-27),
+            BitcoinRpcError::InWarmup(_) => Some(-28),
+            BitcoinRpcError::MethodNotFound(_) => Some(-32601),
+            BitcoinRpcError::Rpc { code, .. } => Some(*code),
+            BitcoinRpcError::AuthFailed
+            | BitcoinRpcError::Http { .. }
+            | BitcoinRpcError::WorkQueueFull(_)
+            | BitcoinRpcError::Connect(_)
+            | BitcoinRpcError::Proxy(_)
+            | BitcoinRpcError::Timeout(_)
+            | BitcoinRpcError::Transport(_)
+            | BitcoinRpcError::Decode(_) => None,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            BitcoinRpcError::InvalidAddressOrKey(message)
+            | BitcoinRpcError::NotInMempool(message)
+            | BitcoinRpcError::NotInWallet(message)
+            | BitcoinRpcError::InsufficientFunds(message)
+            | BitcoinRpcError::InvalidParameter(message)
+            | BitcoinRpcError::WalletUnlockNeeded(message)
+            | BitcoinRpcError::WalletNotFound(message)
+            | BitcoinRpcError::WalletNotSpecified(message)
+            | BitcoinRpcError::VerifyError(message)
+            | BitcoinRpcError::VerifyRejected(message)
+            | BitcoinRpcError::VerifyAlreadyInChain(message)
+            | BitcoinRpcError::InWarmup(message)
+            | BitcoinRpcError::MethodNotFound(message)
+            | BitcoinRpcError::Rpc { message, .. }
+            | BitcoinRpcError::Connect(message)
+            | BitcoinRpcError::Proxy(message)
+            | BitcoinRpcError::Timeout(message)
+            | BitcoinRpcError::Transport(message)
+            | BitcoinRpcError::WorkQueueFull(message)
+            | BitcoinRpcError::Decode(message) => message,
+            BitcoinRpcError::Http { body, .. } => body,
+            BitcoinRpcError::AuthFailed => "Authentication failed",
+        }
+    }
+
+    // The request never got a JSON-RPC answer, so sending it again may succeed
+    pub fn is_transport(&self) -> bool {
+        matches!(
+            self,
+            BitcoinRpcError::Http { .. }
+                | BitcoinRpcError::WorkQueueFull(_)
+                | BitcoinRpcError::Connect(_)
+                | BitcoinRpcError::Proxy(_)
+                | BitcoinRpcError::Timeout(_)
+                | BitcoinRpcError::Transport(_)
+        )
+    }
+}
+
+impl From<RpcError> for BitcoinRpcError {
+    fn from(error: RpcError) -> Self {
+        BitcoinRpcError::from_code(error.code, error.message)
+    }
+}
+
+impl From<reqwest::Error> for BitcoinRpcError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_decode() {
+            BitcoinRpcError::Decode(error.to_string())
+        } else if error.is_timeout() {
+            BitcoinRpcError::Timeout(error.to_string())
+        } else if error.is_connect() {
+            BitcoinRpcError::Connect(error.to_string())
+        } else {
+            BitcoinRpcError::Transport(error.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for BitcoinRpcError {
+    fn from(error: serde_json::Error) -> Self {
+        BitcoinRpcError::Decode(error.to_string())
+    }
+}
+
+// Turn the -5 of a lookup by txid in the mempool into `NotInMempool`
+pub(crate) fn not_in_mempool(error: anyhow::Error) -> anyhow::Error {
+    match error.downcast::<BitcoinRpcError>() {
+        Ok(BitcoinRpcError::InvalidAddressOrKey(message)) => {
+            BitcoinRpcError::NotInMempool(message).into()
+        }
+        Ok(other) => other.into(),
+        Err(error) => error,
+    }
+}
+
+// Turn the -5 of a wallet lookup by txid into `NotInWallet`
+pub(crate) fn not_in_wallet(error: anyhow::Error) -> anyhow::Error {
+    match error.downcast::<BitcoinRpcError>() {
+        Ok(BitcoinRpcError::InvalidAddressOrKey(message)) => {
+            BitcoinRpcError::NotInWallet(message).into()
+        }
+        Ok(other) => other.into(),
+        Err(error) => error,
+    }
+}
+
+// A response whose id does not answer the request it was read for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("Response id mismatch: expected {expected}, got {}", actual.map_or("none".to_string(), |id| id.to_string()))]
+pub struct ResponseIdMismatch {
+    pub expected: u64,
+    // None when a batch came back without any response for `expected`
+    pub actual: Option<u64>,
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::BitcoinClient;
+use crate::error::BitcoinRpcError;
+use crate::types::{BumpFeeOptions, BumpFeeResult, PsbtBumpFeeResult};
+
+// Why the node would not replace a transaction. Returned inside anyhow.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BumpFeeError {
+    // The original does not signal BIP125 and the node does not do full RBF for it
+    #[error("Transaction is not replaceable: {0}")]
+    NotReplaceable(String),
+    // The requested fee or rate is below what the replacement rules require; retry
+    // with a higher one
+    #[error("Replacement fee too low: {0}")]
+    InsufficientFee(String),
+    // The transaction was already replaced, confirmed, or has wallet descendants
+    #[error("Transaction cannot be bumped: {0}")]
+    AlreadyBumped(String),
+}
+
+impl BumpFeeError {
+    // None for failures with other causes, e.g. a locked wallet or unknown txid
+    fn classify(message: &str) -> Option<Self> {
+        let message = message.to_string();
+        let lower = message.to_lowercase();
+        if lower.contains("not bip 125 replaceable") {
+            Some(BumpFeeError::NotReplaceable(message))
+        } else if lower.contains("insufficient total fee")
+            || (lower.contains("fee rate")
+                && (lower.contains("too low") || lower.contains("lower than the minimum")))
+        {
+            Some(BumpFeeError::InsufficientFee(message))
+        } else if lower.contains("descendants")
+            || lower.contains("already bumped")
+            || lower.contains("has been mined")
+            || lower.contains("already been mined")
+            || lower.contains("conflicts with")
+        {
+            Some(BumpFeeError::AlreadyBumped(message))
+        } else {
+            None
+        }
+    }
+}
+
+// Recognized node-side rejections become `BumpFeeError`; anything else passes through
+fn bump_fee_error(error: anyhow::Error) -> anyhow::Error {
+    match error
+        .downcast_ref::<BitcoinRpcError>()
+        .filter(|rpc| rpc.code().is_some())
+        .and_then(|rpc| BumpFeeError::classify(rpc.message()))
+    {
+        Some(bump_error) => bump_error.into(),
+        None => error,
+    }
+}
+
+impl BitcoinClient {
+    // Replace an unconfirmed wallet transaction with one paying a higher fee, signed
+    // and broadcast by the wallet
+    pub async fn bump_fee(&self, txid: &str, options: BumpFeeOptions) -> Result<BumpFeeResult> {
+        self.call("bumpfee", json!([txid, options]))
+            .await
+            .map_err(bump_fee_error)
+    }
+
+    // Like `bump_fee`, returning the replacement as an unsigned PSBT, for watch-only
+    // wallets. Needs 21.0 or later.
+    pub async fn psbt_bump_fee(
+        &self,
+        txid: &str,
+        options: BumpFeeOptions,
+    ) -> Result<PsbtBumpFeeResult> {
+        self.call("psbtbumpfee", json!([txid, options]))
+            .await
+            .map_err(bump_fee_error)
+    }
+}
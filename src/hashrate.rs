@@ -0,0 +1,220 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::BitcoinClient;
+
+pub const RETARGET_INTERVAL: u64 = 2016;
+pub const TARGET_SPACING_SECS: u64 = 600;
+// Compact form of the mainnet proof-of-work limit, the easiest target a header may carry
+pub const MAINNET_POW_LIMIT_BITS: u32 = 0x1d00_ffff;
+// Window size reported for the hashrate implied by the current difficulty
+pub const DIFFICULTY_IMPLIED_WINDOW: u32 = 0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HashrateSample {
+    pub window_blocks: u32,
+    pub hashps: f64,
+    pub as_of_height: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyAdjustment {
+    pub current_height: u64,
+    pub period_start_height: u64,
+    pub next_retarget_height: u64,
+    pub blocks_remaining: u64,
+    pub average_block_secs: f64,
+    // Projected difficulty change in percent, e.g. 3.5 for +3.5%
+    pub projected_change_pct: f64,
+    pub expected_activation_time: u64,
+}
+
+// Hashes per second implied by a difficulty at the target block spacing
+pub fn hashrate_from_difficulty(difficulty: f64) -> f64 {
+    difficulty * 2f64.powi(32) / TARGET_SPACING_SECS as f64
+}
+
+// Expand compact `bits` into the 256-bit target, big-endian. Negative or overflowing
+// encodings, which no valid header has, give an all-zero target.
+pub fn target_from_bits(bits: u32) -> [u8; 32] {
+    let mut target = [0u8; 32];
+    let exponent = (bits >> 24) as usize;
+    let mantissa = bits & 0x007f_ffff;
+    if bits & 0x0080_0000 != 0 && mantissa != 0 {
+        return target;
+    }
+    if exponent <= 3 {
+        let value = mantissa >> (8 * (3 - exponent));
+        target[28..].copy_from_slice(&value.to_be_bytes());
+        return target;
+    }
+    let bytes = mantissa.to_be_bytes();
+    for (i, byte) in bytes[1..].iter().enumerate() {
+        // Byte i of the 3-byte mantissa lands at 32 - exponent + i
+        match (32 + i).checked_sub(exponent) {
+            Some(pos) if pos < 32 => target[pos] = *byte,
+            _ if *byte != 0 => return [0u8; 32],
+            _ => {}
+        }
+    }
+    target
+}
+
+// Compact `bits` for a big-endian target, as Core's `GetCompact`: the top three bytes,
+// shifted down a byte when the first would set the sign bit
+pub fn bits_from_target(target: &[u8; 32]) -> u32 {
+    let Some(first) = target.iter().position(|byte| *byte != 0) else {
+        return 0;
+    };
+    let mut size = (32 - first) as u32;
+    let mut mantissa = target[first..]
+        .iter()
+        .take(3)
+        .fold(0u32, |acc, byte| (acc << 8) | *byte as u32);
+    if size < 3 {
+        mantissa <<= 8 * (3 - size);
+    }
+    if mantissa & 0x0080_0000 != 0 {
+        mantissa >>= 8;
+        size += 1;
+    }
+    (size << 24) | mantissa
+}
+
+// Bits Core sets after a period whose first and last blocks carry these timestamps:
+// the target scaled by the period's timespan over two weeks, the timespan clamped to
+// 4x either way and the result capped at `pow_limit_bits`
+pub fn retarget_bits(
+    bits: u32,
+    first_block_time: u64,
+    last_block_time: u64,
+    pow_limit_bits: u32,
+) -> u32 {
+    let target_timespan = RETARGET_INTERVAL * TARGET_SPACING_SECS;
+    let actual = last_block_time
+        .saturating_sub(first_block_time)
+        .clamp(target_timespan / 4, target_timespan * 4);
+
+    // Big-endian 64-bit limbs, with a spare one on top for the multiplication
+    let mut limbs = [0u64; 5];
+    for (limb, chunk) in limbs[1..].iter_mut().zip(target_from_bits(bits).chunks(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    let mut carry = 0u128;
+    for limb in limbs.iter_mut().rev() {
+        let product = *limb as u128 * actual as u128 + carry;
+        *limb = product as u64;
+        carry = product >> 64;
+    }
+    let mut remainder = 0u128;
+    for limb in limbs.iter_mut() {
+        let dividend = (remainder << 64) | *limb as u128;
+        *limb = (dividend / target_timespan as u128) as u64;
+        remainder = dividend % target_timespan as u128;
+    }
+    if limbs[0] != 0 {
+        return pow_limit_bits;
+    }
+
+    let mut target = [0u8; 32];
+    for (chunk, limb) in target.chunks_mut(8).zip(&limbs[1..]) {
+        chunk.copy_from_slice(&limb.to_be_bytes());
+    }
+    if target > target_from_bits(pow_limit_bits) {
+        return pow_limit_bits;
+    }
+    bits_from_target(&target)
+}
+
+// Difficulty for compact `bits`, computed the way Core's `getdifficulty` does
+pub fn difficulty_from_bits(bits: u32) -> f64 {
+    let mantissa = bits & 0x00ff_ffff;
+    if mantissa == 0 {
+        return 0.0;
+    }
+    let mut shift = (bits >> 24) & 0xff;
+    let mut difficulty = 0x0000_ffff as f64 / mantissa as f64;
+    while shift < 29 {
+        difficulty *= 256.0;
+        shift += 1;
+    }
+    while shift > 29 {
+        difficulty /= 256.0;
+        shift -= 1;
+    }
+    difficulty
+}
+
+// Difficulty multiplier for a period whose blocks average `average_block_secs`.
+// Core measures 2015 intervals against 2016 * 600 seconds and clamps the timespan to 4x
+// either way.
+pub fn project_retarget(average_block_secs: f64) -> f64 {
+    let target = (RETARGET_INTERVAL * TARGET_SPACING_SECS) as f64;
+    let actual =
+        (average_block_secs * (RETARGET_INTERVAL - 1) as f64).clamp(target / 4.0, target * 4.0);
+    target / actual
+}
+
+impl BitcoinClient {
+    // Network hashrate over each window of blocks, plus the hashrate implied by the
+    // current difficulty under `DIFFICULTY_IMPLIED_WINDOW`, all from one batch
+    pub async fn hashrate_trend(&self, windows: &[u32]) -> Result<Vec<HashrateSample>> {
+        let mut requests = vec![
+            ("getblockcount".to_string(), Value::Null),
+            ("getdifficulty".to_string(), Value::Null),
+        ];
+        requests.extend(
+            windows
+                .iter()
+                .map(|w| ("getnetworkhashps".to_string(), json!([w, -1]))),
+        );
+        let results = self.batch_call(requests).await?;
+        let as_of_height: u64 = serde_json::from_value(results[0].clone())?;
+        let difficulty: f64 = serde_json::from_value(results[1].clone())?;
+        let mut samples = vec![HashrateSample {
+            window_blocks: DIFFICULTY_IMPLIED_WINDOW,
+            hashps: hashrate_from_difficulty(difficulty),
+            as_of_height,
+        }];
+        for (window, result) in windows.iter().zip(&results[2..]) {
+            samples.push(HashrateSample {
+                window_blocks: *window,
+                hashps: serde_json::from_value(result.clone())?,
+                as_of_height,
+            });
+        }
+        Ok(samples)
+    }
+
+    // Project the next retarget from the pace of the current period so far
+    pub async fn difficulty_adjustment_estimate(&self) -> Result<DifficultyAdjustment> {
+        let current_height = self.get_block_count().await?;
+        let period_start_height = current_height - current_height % RETARGET_INTERVAL;
+        let headers = self
+            .headers_at(&[period_start_height, current_height])
+            .await?;
+        let (start, tip) = match headers.as_slice() {
+            [start, tip] => (start, tip),
+            _ => return Err(anyhow!("Expected two headers")),
+        };
+        let intervals = current_height - period_start_height;
+        let average_block_secs = if intervals == 0 {
+            TARGET_SPACING_SECS as f64
+        } else {
+            tip.time.saturating_sub(start.time) as f64 / intervals as f64
+        };
+        let next_retarget_height = period_start_height + RETARGET_INTERVAL;
+        let blocks_remaining = next_retarget_height - current_height;
+        Ok(DifficultyAdjustment {
+            current_height,
+            period_start_height,
+            next_retarget_height,
+            blocks_remaining,
+            average_block_secs,
+            projected_change_pct: (project_retarget(average_block_secs) - 1.0) * 100.0,
+            expected_activation_time: tip.time
+                + (blocks_remaining as f64 * average_block_secs).round() as u64,
+        })
+    }
+}
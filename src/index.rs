@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::script::{ScriptTemplateRegistry, ScriptType, TemplateMatch};
+use crate::types::{BlockVerbose, OutPoint};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpentOutput {
+    pub outpoint: OutPoint,
+    pub spent_by_txid: String,
+    pub spent_at_height: u64,
+    pub input_index: u32,
+    pub template: Option<TemplateMatch>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpentOutputRef<'a> {
+    pub txid: &'a str,
+    pub vout: u32,
+    pub spent_by_txid: &'a str,
+    pub spent_at_height: u64,
+    pub input_index: u32,
+    // Hex scriptPubKey of the spent output, only in blocks fetched with prevouts
+    pub script_hex: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreatedOutput {
+    pub outpoint: OutPoint,
+    pub script_type: ScriptType,
+    pub amount_sat: u64,
+    pub address: Option<String>,
+    pub created_at_height: u64,
+    pub template: Option<TemplateMatch>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreatedOutputRef<'a> {
+    pub txid: &'a str,
+    pub vout: u32,
+    pub script_type: ScriptType,
+    pub amount_sat: u64,
+    pub address: Option<&'a str>,
+    pub created_at_height: u64,
+    pub script_hex: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockDelta {
+    pub height: u64,
+    pub hash: String,
+    pub spent: Vec<SpentOutput>,
+    pub created: Vec<CreatedOutput>,
+}
+
+impl From<SpentOutputRef<'_>> for SpentOutput {
+    fn from(spent: SpentOutputRef<'_>) -> Self {
+        SpentOutput {
+            outpoint: OutPoint {
+                txid: spent.txid.to_string(),
+                vout: spent.vout,
+            },
+            spent_by_txid: spent.spent_by_txid.to_string(),
+            spent_at_height: spent.spent_at_height,
+            input_index: spent.input_index,
+            template: None,
+        }
+    }
+}
+
+impl From<CreatedOutputRef<'_>> for CreatedOutput {
+    fn from(created: CreatedOutputRef<'_>) -> Self {
+        CreatedOutput {
+            outpoint: OutPoint {
+                txid: created.txid.to_string(),
+                vout: created.vout,
+            },
+            script_type: created.script_type,
+            amount_sat: created.amount_sat,
+            address: created.address.map(|a| a.to_string()),
+            created_at_height: created.created_at_height,
+            template: None,
+        }
+    }
+}
+
+impl BlockDelta {
+    pub fn from_block(block: &BlockVerbose) -> Result<Self> {
+        Ok(BlockDelta {
+            height: block.height,
+            hash: block.hash.clone(),
+            spent: index_block_spends(block),
+            created: created_outputs(block)?,
+        })
+    }
+
+    // Like `from_block`, with outputs on both sides annotated with the template their
+    // script matches. Spent scripts are only known in blocks from `get_block_with_prevouts`.
+    pub fn from_block_with_templates(
+        block: &BlockVerbose,
+        registry: &ScriptTemplateRegistry,
+    ) -> Result<Self> {
+        Ok(BlockDelta {
+            height: block.height,
+            hash: block.hash.clone(),
+            spent: spent_outputs_with_templates(block, registry),
+            created: created_outputs_with_templates(block, registry)?,
+        })
+    }
+}
+
+// Iterate over every outpoint spent by the block, skipping coinbase inputs
+pub fn iter_block_spends(block: &BlockVerbose) -> impl Iterator<Item = SpentOutputRef<'_>> {
+    block.tx.iter().flat_map(move |tx| {
+        tx.vin
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, vin)| match (&vin.txid, vin.vout) {
+                (Some(txid), Some(vout)) if vin.coinbase.is_none() => Some(SpentOutputRef {
+                    txid,
+                    vout,
+                    spent_by_txid: &tx.txid,
+                    spent_at_height: block.height,
+                    input_index: index as u32,
+                    script_hex: vin
+                        .prevout
+                        .as_ref()
+                        .map(|prevout| prevout.script_pub_key.hex.as_str()),
+                }),
+                _ => None,
+            })
+    })
+}
+
+// Iterate over every output created by the block. Values that are not amounts already
+// fail when the block is decoded; a negative one yields an error here rather than being
+// indexed.
+pub fn iter_created_outputs(
+    block: &BlockVerbose,
+) -> impl Iterator<Item = Result<CreatedOutputRef<'_>>> {
+    block.tx.iter().flat_map(move |tx| {
+        tx.vout.iter().map(move |vout| {
+            let script = &vout.script_pub_key;
+            let amount_sat = vout
+                .value
+                .to_unsigned_sat()
+                .with_context(|| format!("Output {}:{}", tx.txid, vout.n))?;
+            Ok(CreatedOutputRef {
+                txid: &tx.txid,
+                vout: vout.n,
+                script_type: script.r#type,
+                amount_sat,
+                address: script
+                    .address
+                    .as_deref()
+                    .or_else(|| script.addresses.as_ref()?.first().map(|a| a.as_str())),
+                created_at_height: block.height,
+                script_hex: &script.hex,
+            })
+        })
+    })
+}
+
+pub fn index_block_spends(block: &BlockVerbose) -> Vec<SpentOutput> {
+    let mut spends = Vec::with_capacity(block.tx.iter().map(|tx| tx.vin.len()).sum());
+    spends.extend(iter_block_spends(block).map(SpentOutput::from));
+    spends
+}
+
+pub fn created_outputs(block: &BlockVerbose) -> Result<Vec<CreatedOutput>> {
+    let mut outputs = Vec::with_capacity(block.tx.iter().map(|tx| tx.vout.len()).sum());
+    for created in iter_created_outputs(block) {
+        outputs.push(CreatedOutput::from(created?));
+    }
+    Ok(outputs)
+}
+
+// Spent outputs annotated with the protocol template each spent script matches, if any
+pub fn spent_outputs_with_templates(
+    block: &BlockVerbose,
+    registry: &ScriptTemplateRegistry,
+) -> Vec<SpentOutput> {
+    iter_block_spends(block)
+        .map(|spent| SpentOutput {
+            template: spent
+                .script_hex
+                .and_then(|hex| match_script_hex(registry, hex)),
+            ..SpentOutput::from(spent)
+        })
+        .collect()
+}
+
+// Created outputs annotated with the protocol template each script matches, if any
+pub fn created_outputs_with_templates(
+    block: &BlockVerbose,
+    registry: &ScriptTemplateRegistry,
+) -> Result<Vec<CreatedOutput>> {
+    iter_created_outputs(block)
+        .map(|created| {
+            let created = created?;
+            Ok(CreatedOutput {
+                template: match_script_hex(registry, created.script_hex),
+                ..CreatedOutput::from(created)
+            })
+        })
+        .collect()
+}
+
+fn match_script_hex(registry: &ScriptTemplateRegistry, hex: &str) -> Option<TemplateMatch> {
+    registry.match_template(&hex::decode(hex).ok()?)
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::time::Instant;
+use tracing::{Span, field};
+
+use crate::RpcError;
+
+// Methods whose parameters carry passphrases or private keys; none of them are logged
+const SECRET_METHODS: &[&str] = &[
+    "createwallet",
+    "encryptwallet",
+    "importmulti",
+    "importprivkey",
+    "importwallet",
+    "migratewallet",
+    "sethdseed",
+    "signmessagewithprivkey",
+    "signrawtransactionwithkey",
+    "walletpassphrase",
+    "walletpassphrasechange",
+];
+
+// Longest string parameter shown in full, enough for a txid or block hash
+const MAX_SHOWN_LEN: usize = 64;
+
+fn looks_secret(s: &str) -> bool {
+    s.contains("prv")
+        || ((s.len() == 51 || s.len() == 52) && s.starts_with(['5', 'K', 'L', 'c', '9']))
+}
+
+fn summarize(value: &Value) -> String {
+    match value {
+        Value::String(s) if looks_secret(s) => "<redacted>".to_string(),
+        Value::String(s) if s.len() > MAX_SHOWN_LEN => format!("<{} chars>", s.len()),
+        Value::Array(items) if items.len() > 4 => format!("[{} items]", items.len()),
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(summarize).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Object(map) => format!("{{{} keys}}", map.len()),
+        other => other.to_string(),
+    }
+}
+
+// Short, redacted form of the parameters for span fields
+pub(crate) fn summarize_params(method: &str, params: &Value) -> String {
+    if SECRET_METHODS.contains(&method) {
+        return "<redacted>".to_string();
+    }
+    summarize(params)
+}
+
+pub(crate) fn call_span(method: &str, params: &Value) -> Span {
+    tracing::debug_span!(
+        "rpc",
+        method,
+        params = %summarize_params(method, params),
+        status = field::Empty,
+        rpc_code = field::Empty,
+        latency_ms = field::Empty,
+    )
+}
+
+pub(crate) fn batch_span(len: usize) -> Span {
+    tracing::debug_span!(
+        "rpc_batch",
+        len,
+        status = field::Empty,
+        latency_ms = field::Empty,
+    )
+}
+
+// Called from inside the request future, so it lands on the call or batch span
+pub(crate) fn record_status(status: u16) {
+    Span::current().record("status", status);
+}
+
+pub(crate) fn log_body(direction: &str, body: &str) {
+    tracing::debug!(direction, body, "rpc body");
+}
+
+pub(crate) fn finish_call(
+    span: &Span,
+    started: Instant,
+    outcome: &Result<(Option<Value>, Option<RpcError>)>,
+) {
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+    match outcome {
+        Ok((_, Some(error))) => {
+            span.record("rpc_code", error.code);
+            span.in_scope(
+                || tracing::debug!(code = error.code, message = %error.message, "rpc error"),
+            );
+        }
+        Err(e) => span.in_scope(|| tracing::warn!(error = %e, "rpc failed")),
+        Ok(_) => {}
+    }
+}
+
+pub(crate) fn finish_batch(
+    span: &Span,
+    started: Instant,
+    requests: &[(String, Value)],
+    outcome: &Result<Vec<(Option<Value>, Option<RpcError>)>>,
+) {
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+    let _entered = span.enter();
+    match outcome {
+        Ok(responses) => {
+            for (index, ((method, params), (_, error))) in
+                requests.iter().zip(responses).enumerate()
+            {
+                let params = summarize_params(method, params);
+                match error {
+                    Some(error) => tracing::debug!(
+                        index,
+                        method = %method,
+                        params = %params,
+                        rpc_code = error.code,
+                        message = %error.message,
+                        "batch entry failed"
+                    ),
+                    None => {
+                        tracing::debug!(index, method = %method, params = %params, "batch entry")
+                    }
+                }
+            }
+        }
+        Err(e) => tracing::warn!(error = %e, "rpc batch failed"),
+    }
+}
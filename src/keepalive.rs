@@ -0,0 +1,85 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::runtime::Handle;
+
+use crate::BitcoinClient;
+
+// Shared between the client clones and the background pinger, which only holds it weakly
+#[derive(Debug)]
+pub(crate) struct KeepAlive {
+    interval: Duration,
+    // Unix time in milliseconds of the last successful ping, 0 before the first
+    last_success_ms: AtomicU64,
+}
+
+impl KeepAlive {
+    fn record_success(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        self.last_success_ms.store(now, Ordering::Relaxed);
+    }
+}
+
+impl BitcoinClient {
+    // Spawn the pinger set up by `BitcoinClientBuilder::keepalive`. The task ends once
+    // every clone of this client has been dropped.
+    pub(crate) fn start_keepalive(&mut self, interval: Duration) -> Result<()> {
+        let runtime = Handle::try_current()
+            .map_err(|_| anyhow!("A keepalive can only be started inside a tokio runtime"))?;
+        let state = Arc::new(KeepAlive {
+            interval,
+            last_success_ms: AtomicU64::new(0),
+        });
+        let mut pinger = self.clone();
+        // Pings are housekeeping and stay out of any recording
+        pinger.cassette = None;
+        pinger.retry = None;
+        runtime.spawn(run_keepalive(pinger, Arc::downgrade(&state)));
+        self.keepalive = Some(state);
+        Ok(())
+    }
+
+    // Open the connection ahead of the first real call
+    pub async fn warm_up(&self) -> Result<()> {
+        self.call::<Value>("getblockcount", Value::Null).await?;
+        if let Some(state) = &self.keepalive {
+            state.record_success();
+        }
+        Ok(())
+    }
+
+    // When the keepalive last reached the node, for health checks
+    pub fn last_keepalive(&self) -> Option<SystemTime> {
+        let state = self.keepalive.as_ref()?;
+        match state.last_success_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(UNIX_EPOCH + Duration::from_millis(ms)),
+        }
+    }
+}
+
+async fn run_keepalive(pinger: BitcoinClient, state: Weak<KeepAlive>) {
+    let Some(interval) = state.upgrade().map(|s| s.interval) else {
+        return;
+    };
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let Some(state) = state.upgrade() else {
+            return;
+        };
+        if pinger
+            .call::<Value>("getblockcount", Value::Null)
+            .await
+            .is_ok()
+        {
+            state.record_success();
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::BitcoinClient;
+use crate::amount::Amount;
+use crate::error::{BitcoinRpcError, RPC_WALLET_INVALID_LABEL_NAME};
+use crate::types::{SendManyOptions, Utxo, WalletTransaction};
+
+// A view over the wallet restricted to the addresses carrying a single label
+#[derive(Debug)]
+pub struct LabeledClient {
+    client: BitcoinClient,
+    label: String,
+    addresses: Mutex<Option<HashSet<String>>>,
+}
+
+impl BitcoinClient {
+    pub fn labeled(&self, label: &str) -> LabeledClient {
+        LabeledClient {
+            client: self.clone(),
+            label: label.to_string(),
+            addresses: Mutex::new(None),
+        }
+    }
+}
+
+impl LabeledClient {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn client(&self) -> &BitcoinClient {
+        &self.client
+    }
+
+    // Addresses under the label, fetched once and cached until invalidated
+    pub async fn addresses(&self) -> Result<HashSet<String>> {
+        if let Some(cached) = self.addresses.lock().unwrap().as_ref() {
+            return Ok(cached.clone());
+        }
+        let addresses: HashSet<String> = match self.client.get_addresses_by_label(&self.label).await
+        {
+            Ok(map) => map.into_keys().collect(),
+            // The node reports an unused label as -11 rather than an empty map
+            Err(e)
+                if e.downcast_ref::<BitcoinRpcError>()
+                    .is_some_and(|rpc| rpc.code() == Some(RPC_WALLET_INVALID_LABEL_NAME)) =>
+            {
+                HashSet::new()
+            }
+            Err(e) => return Err(e),
+        };
+        *self.addresses.lock().unwrap() = Some(addresses.clone());
+        Ok(addresses)
+    }
+
+    pub fn invalidate(&self) {
+        *self.addresses.lock().unwrap() = None;
+    }
+
+    pub async fn get_new_address(&self, address_type: Option<&str>) -> Result<String> {
+        let address = self
+            .client
+            .get_new_address(Some(&self.label), address_type)
+            .await?;
+        if let Some(cached) = self.addresses.lock().unwrap().as_mut() {
+            cached.insert(address.clone());
+        }
+        Ok(address)
+    }
+
+    // A fresh change address put under the label, so change sent there stays in this
+    // view. Labelled, the node counts it as a receive address rather than change.
+    pub async fn get_raw_change_address(&self, address_type: Option<&str>) -> Result<String> {
+        let params = match address_type {
+            Some(address_type) => json!([address_type]),
+            None => Value::Null,
+        };
+        let address: String = self.client.call("getrawchangeaddress", params).await?;
+        self.client.set_label(&address, &self.label).await?;
+        if let Some(cached) = self.addresses.lock().unwrap().as_mut() {
+            cached.insert(address.clone());
+        }
+        Ok(address)
+    }
+
+    // Up to `count` entries after skipping the `skip` most recent, oldest first. The node
+    // attributes only incoming payments to a label, so sends are not listed.
+    pub async fn list_transactions(
+        &self,
+        count: u32,
+        skip: u32,
+        include_watchonly: bool,
+    ) -> Result<Vec<WalletTransaction>> {
+        let transactions = self
+            .client
+            .list_transactions(Some(&self.label), count, skip, include_watchonly)
+            .await?;
+        let addresses = self.addresses().await?;
+        // Same guard as `list_unspent`
+        Ok(transactions
+            .into_iter()
+            .filter(|t| t.address.as_ref().is_some_and(|a| addresses.contains(a)))
+            .collect())
+    }
+
+    pub async fn list_unspent(&self, min_conf: i32, max_conf: i32) -> Result<Vec<Utxo>> {
+        let addresses = self.addresses().await?;
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+        let addresses: Vec<&str> = addresses.iter().map(|a| a.as_str()).collect();
+        let utxos = self
+            .client
+            .list_unspent(min_conf, max_conf, Some(addresses.clone()))
+            .await?;
+        // Guard against nodes that ignore the address filter
+        Ok(utxos
+            .into_iter()
+            .filter(|u| u.address.as_deref().is_some_and(|a| addresses.contains(&a)))
+            .collect())
+    }
+
+    // Send with the label recorded as the transaction comment
+    pub async fn send_to_address(&self, address: &str, amount: Amount) -> Result<String> {
+        self.client
+            .call("sendtoaddress", json!([address, amount, self.label]))
+            .await
+    }
+
+    pub async fn send_many(&self, amounts: HashMap<String, Amount>) -> Result<String> {
+        self.client
+            .send_many(
+                amounts,
+                SendManyOptions::default().minconf(1).comment(&self.label),
+            )
+            .await
+    }
+}
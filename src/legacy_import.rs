@@ -0,0 +1,78 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+
+use crate::BitcoinClient;
+use crate::cancel::RESCAN_TIMEOUT;
+use crate::error::BitcoinRpcError;
+use crate::prune::RescanDisabledWhenPruned;
+use crate::types::{ImportMultiOptions, ImportMultiRequest, ImportResult};
+
+// Turn the pruned-node rescan refusal into `RescanDisabledWhenPruned`
+fn rescan_error(error: anyhow::Error) -> anyhow::Error {
+    match error.downcast_ref::<BitcoinRpcError>() {
+        Some(rpc) if rpc.message().contains("Rescan is disabled") => {
+            RescanDisabledWhenPruned.into()
+        }
+        _ => error,
+    }
+}
+
+// The legacy (non-descriptor) wallet import RPCs. A rescan runs inside the call, so
+// calls that rescan get `RESCAN_TIMEOUT` instead of the client's timeout.
+impl BitcoinClient {
+    fn import_client(&self, rescan: bool) -> BitcoinClient {
+        if rescan {
+            self.with_timeout(RESCAN_TIMEOUT)
+        } else {
+            self.clone()
+        }
+    }
+
+    pub async fn import_priv_key(&self, wif: &str, label: &str, rescan: bool) -> Result<()> {
+        self.import_client(rescan)
+            .call::<Value>("importprivkey", json!([wif, label, rescan]))
+            .await
+            .map_err(rescan_error)?;
+        Ok(())
+    }
+
+    // Watch an address or a hex script. With `p2sh`, a script is also watched as the
+    // redeem script of its P2SH address.
+    pub async fn import_address(
+        &self,
+        address_or_script: &str,
+        label: &str,
+        rescan: bool,
+        p2sh: bool,
+    ) -> Result<()> {
+        self.import_client(rescan)
+            .call::<Value>(
+                "importaddress",
+                json!([address_or_script, label, rescan, p2sh]),
+            )
+            .await
+            .map_err(rescan_error)?;
+        Ok(())
+    }
+
+    pub async fn import_pubkey(&self, pubkey_hex: &str, label: &str, rescan: bool) -> Result<()> {
+        self.import_client(rescan)
+            .call::<Value>("importpubkey", json!([pubkey_hex, label, rescan]))
+            .await
+            .map_err(rescan_error)?;
+        Ok(())
+    }
+
+    // Import several keys, scripts or descriptors with a single rescan. Check each
+    // result, since entries succeed or fail independently.
+    pub async fn import_multi(
+        &self,
+        requests: Vec<ImportMultiRequest>,
+        options: ImportMultiOptions,
+    ) -> Result<Vec<ImportResult>> {
+        self.import_client(options.rescan != Some(false))
+            .call("importmulti", json!([requests, options]))
+            .await
+            .map_err(rescan_error)
+    }
+}
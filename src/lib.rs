@@ -1,22 +1,176 @@
+mod abandon;
+mod addresses;
+mod alerts;
+mod amount;
+mod assumeutxo;
+mod backup;
+mod batch;
+mod block_stream;
+mod broadcast;
+mod builder;
+mod cache;
+mod cancel;
+mod cassette;
+mod compact_filter;
+mod config;
+mod control;
+mod cookie;
 mod crypto;
+mod error;
+mod fee_bump;
+mod hashrate;
+mod index;
+#[cfg(feature = "tracing")]
+mod instrument;
+mod keepalive;
+mod labeled;
+mod legacy_import;
+mod mempool;
+mod mempool_mirror;
+mod middleware;
+mod mining;
+mod multisig;
+mod node_snapshot;
+mod node_status;
+mod node_version;
+mod payment;
+mod payout;
+mod prune;
+mod psbt;
+mod reorg;
+mod rest;
+mod retry;
+mod rpc;
+mod scan;
+mod script;
+mod send_raw;
 mod serialization;
+mod signet;
+mod snapshot;
+mod streaming;
+pub mod testing;
+mod timing;
+mod transport;
 mod types;
+mod utxo_lock;
+#[cfg(feature = "validate-responses")]
+mod validation;
+mod vectors;
+mod wait;
+mod wallet;
+mod watch;
+#[cfg(feature = "zmq")]
+mod zmq;
 
-use base64::{Engine, prelude::BASE64_STANDARD};
+pub use abandon::*;
+pub use addresses::*;
+pub use alerts::*;
+pub use amount::*;
+pub use assumeutxo::*;
+pub use backup::*;
+pub use batch::*;
+pub use block_stream::*;
+pub use broadcast::*;
+pub use builder::*;
+pub use cache::*;
+pub use cancel::*;
+pub use cassette::*;
+pub use compact_filter::*;
+pub use config::*;
+pub use cookie::*;
 pub use crypto::*;
+pub use error::*;
+pub use fee_bump::*;
+pub use hashrate::*;
+pub use index::*;
+pub use labeled::*;
+pub use mempool::*;
+pub use mempool_mirror::*;
+pub use middleware::*;
+pub use multisig::*;
+pub use node_snapshot::*;
+pub use node_status::*;
+pub use node_version::*;
+pub use payment::*;
+pub use payout::*;
+pub use prune::*;
+pub use psbt::*;
+pub use reorg::*;
+pub use rest::*;
+pub use retry::*;
+pub use rpc::*;
+pub use script::*;
+pub use send_raw::*;
+pub use serialization::*;
+pub use signet::*;
+pub use snapshot::*;
+pub use timing::*;
 pub use types::*;
+pub use utxo_lock::*;
+#[cfg(feature = "validate-responses")]
+pub use validation::*;
+pub use vectors::*;
+pub use wallet::*;
+pub use watch::*;
+#[cfg(feature = "zmq")]
+pub use zmq::*;
 
 use anyhow::{Result, anyhow};
-use reqwest::{Client, Response};
+use reqwest::StatusCode;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::transport::HttpReply;
 
 #[derive(Debug, Clone)]
 pub struct BitcoinClient {
-    client: Client,
+    transport: transport::Transport,
     url: String,
-    auth: String,
+    // Shared with clones so a reloaded cookie reaches all of them
+    auth: Arc<RwLock<String>>,
+    cookie_path: Option<PathBuf>,
+    // Log full request and response bodies, with the `tracing` feature
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    log_bodies: bool,
+    // Set on handles from `wallet`, routing wallet RPCs to /wallet/<name>
+    wallet_name: Option<String>,
+    cassette: Option<Arc<Cassette>>,
+    // Results of immutable lookups by hash, from `cache_capacity`
+    cache: Option<Arc<cache::ResponseCache>>,
+    retry: Option<Arc<RetryPolicy>>,
+    // Next JSON-RPC request id, shared with clones so ids stay unique across handles
+    next_id: Arc<AtomicU64>,
+    // Version last reported by the node, 0 until known
+    node_version: Arc<AtomicU32>,
+    // Caps requests in flight across clones, from `max_concurrent_requests`
+    limiter: Option<Arc<Semaphore>>,
+    // Replaces the builder's timeout on handles made for long-polling RPCs
+    request_timeout: Option<Duration>,
+    middleware: middleware::MiddlewareStack,
+    // Headers set by middleware, sent after and in place of the client's own
+    extra_headers: Option<Arc<HeaderMap>>,
+    keepalive: Option<Arc<keepalive::KeepAlive>>,
+}
+
+pub(crate) fn default_rpc_port(network: BitcoinClientType) -> u16 {
+    match network {
+        BitcoinClientType::Mainnet => 8332,
+        BitcoinClientType::Testnet => 18332,
+        BitcoinClientType::Signet => 38332,
+        BitcoinClientType::Regtest => 18443,
+    }
+}
+
+// RPC endpoint of a node on this machine using the network's default port
+pub(crate) fn local_url(network: BitcoinClientType) -> String {
+    format!("http://127.0.0.1:{}", default_rpc_port(network))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,69 +190,203 @@ struct BitcoinNetWorkResponse<T> {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct RpcError {
-    code: i32,
-    message: String,
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
 }
 
 impl BitcoinClient {
     pub fn new(url: &str, username: &str, password: &str) -> Self {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
+        BitcoinClientBuilder::new(url)
+            .auth(username, password)
             .build()
-            .unwrap();
-        let auth = format!("{}:{}", username, password);
-        let auth = format!("Basic {}", BASE64_STANDARD.encode(&auth));
-        BitcoinClient {
-            client,
-            url: url.to_string(),
-            auth,
-        }
+            .expect("default HTTP client")
+    }
+
+    // Handle sharing this client's connection and state but with its own whole-request
+    // timeout, for slow calls such as a UTXO set scan
+    pub fn with_timeout(&self, timeout: Duration) -> BitcoinClient {
+        let mut client = self.clone();
+        client.request_timeout = Some(timeout);
+        client
+    }
+
+    // Record every exchange to, or serve every response from, a cassette file
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(Arc::new(cassette));
+        self
     }
 
     pub fn new_local(network: BitcoinClientType) -> Self {
-        let (url, port) = match network {
-            BitcoinClientType::Mainnet => ("http://127.0.0.1", 8332),
-            BitcoinClientType::Testnet => ("http://127.0.0.1", 18332),
-            BitcoinClientType::Signet => ("http://127.0.0.1", 38332),
-            BitcoinClientType::Regtest => ("http://127.0.0.1", 18443),
-        };
-        Self::new(&format!("{}:{}", url, port), "bitcoin", "password")
+        Self::new(&local_url(network), "bitcoin", "password")
     }
 
     async fn call<T: for<'de> Deserialize<'de>>(&self, method: &str, params: Value) -> Result<T> {
+        let cached = match &self.cache {
+            Some(cache) => cache::ResponseCache::key(method, &params).map(|key| (cache, key)),
+            None => None,
+        };
+        if let Some((cache, key)) = &cached
+            && let Some(result) = cache.get(key)?
+        {
+            return Ok(serde_json::from_value(result).map_err(BitcoinRpcError::from)?);
+        }
+        let (result, error) = match &self.cassette {
+            Some(cassette) if cassette.is_replay() => cassette.lookup(method, &params)?,
+            _ => self.dispatch(method, params).await?,
+        };
+        if let Some(error) = error {
+            return Err(BitcoinRpcError::from(error).into());
+        }
+        #[cfg(feature = "validate-responses")]
+        if let Some(result) = &result {
+            validation::log_response_warnings(method, result);
+        }
+        if let (Some((cache, key)), Some(result)) = (cached, &result) {
+            cache.insert(key, result)?;
+        }
+        Ok(serde_json::from_value(result.unwrap_or(Value::Null)).map_err(BitcoinRpcError::from)?)
+    }
+
+    async fn send_request(
+        &self,
+        method: &str,
+        params: &Value,
+    ) -> Result<(Option<Value>, Option<RpcError>)> {
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+            let span = instrument::call_span(method, params);
+            let started = std::time::Instant::now();
+            let outcome = self
+                .send_once(method, params)
+                .instrument(span.clone())
+                .await;
+            instrument::finish_call(&span, started, &outcome);
+            outcome
+        }
+        #[cfg(not(feature = "tracing"))]
+        self.send_once(method, params).await
+    }
+
+    async fn send_once(
+        &self,
+        method: &str,
+        params: &Value,
+    ) -> Result<(Option<Value>, Option<RpcError>)> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
         let request = BitcoinNetWorkRequest {
             jsonrpc: "2.0".to_string(),
-            id: 1,
+            id,
             method: method.to_string(),
-            params,
+            params: params.clone(),
         };
-        let response: Response = self
-            .client
-            .post(&self.url)
-            .header("Authorization", &self.auth)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(anyhow!("HTTP error {}: {}", status, text));
+        let url = self.endpoint(std::iter::once(method));
+        let _permit = self.acquire_slot().await?;
+        let response = self.post(&url, &request).await?;
+        let rpc_response: BitcoinNetWorkResponse<Value> = self.read_response(response)?;
+        if rpc_response.id != id {
+            return Err(ResponseIdMismatch {
+                expected: id,
+                actual: Some(rpc_response.id),
+            }
+            .into());
+        }
+        if let Some(cassette) = &self.cassette {
+            cassette.write(method, params, &rpc_response.result, &rpc_response.error)?;
         }
-        let rpc_response: BitcoinNetWorkResponse<T> = response.json().await?;
-        if let Some(error) = rpc_response.error {
-            return Err(anyhow!("RPC error {}: {}", error.code, error.message));
+        Ok((rpc_response.result, rpc_response.error))
+    }
+
+    // Wait for a free slot when a concurrency limit is set; held until the response is read
+    async fn acquire_slot(&self) -> Result<Option<OwnedSemaphorePermit>> {
+        match &self.limiter {
+            Some(limiter) => Ok(Some(
+                limiter
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|_| anyhow!("Request limiter closed"))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    // POST a JSON body, re-reading the cookie and retrying once on HTTP 401
+    async fn post<B: Serialize>(&self, url: &str, body: &B) -> Result<HttpReply> {
+        let body = serde_json::to_vec(body).map_err(BitcoinRpcError::from)?;
+        #[cfg(feature = "tracing")]
+        if self.log_bodies {
+            instrument::log_body("request", &String::from_utf8_lossy(&body));
+        }
+        let mut retried = false;
+        loop {
+            let auth = self
+                .auth
+                .read()
+                .map_err(|_| anyhow!("Auth lock poisoned"))?
+                .clone();
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&auth).map_err(|_| anyhow!("Invalid auth header"))?,
+            );
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            if let Some(extra) = &self.extra_headers {
+                for (name, value) in extra.iter() {
+                    headers.insert(name, value.clone());
+                }
+            }
+            let reply = self
+                .transport
+                .send(url, headers, body.clone(), self.request_timeout)
+                .await?;
+            #[cfg(feature = "tracing")]
+            instrument::record_status(reply.status.as_u16());
+            if reply.status == StatusCode::UNAUTHORIZED && !retried && self.reload_cookie()? {
+                retried = true;
+                continue;
+            }
+            return Ok(reply);
+        }
+    }
+
+    // Decode a JSON-RPC reply. Core answers rejected single requests with HTTP 500 and
+    // the error in the body, so only a body that is not a reply counts as an HTTP error.
+    fn read_response<T: for<'de> Deserialize<'de>>(&self, reply: HttpReply) -> Result<T> {
+        let status = reply.status;
+        let text = String::from_utf8_lossy(&reply.body?).into_owned();
+        #[cfg(feature = "tracing")]
+        if self.log_bodies {
+            instrument::log_body("response", &text);
+        }
+        if status == StatusCode::UNAUTHORIZED {
+            return Err(BitcoinRpcError::AuthFailed.into());
+        }
+        match serde_json::from_str(&text) {
+            Ok(reply) => Ok(reply),
+            Err(e) if status.is_success() => Err(BitcoinRpcError::from(e).into()),
+            // Plain-text reply from the HTTP server, not the RPC handler
+            Err(_) if text.contains("Work queue depth exceeded") => {
+                Err(BitcoinRpcError::WorkQueueFull(text.trim().to_string()).into())
+            }
+            Err(_) => Err(BitcoinRpcError::Http {
+                status: status.as_u16(),
+                body: text,
+            }
+            .into()),
         }
-        rpc_response
-            .result
-            .ok_or_else(|| anyhow!("No result in response"))
     }
 
     pub async fn get_blockchain_info(&self) -> Result<BlockchainInfo> {
         self.call("getblockchaininfo", Value::Null).await
     }
 
+    // Soft fork deployment status as of `block_hash` (default the tip), on 23.0 and later
+    pub async fn get_deployment_info(&self, block_hash: Option<&str>) -> Result<DeploymentInfo> {
+        self.call("getdeploymentinfo", json!([block_hash])).await
+    }
+
     pub async fn get_block_count(&self) -> Result<u64> {
         self.call("getblockcount", Value::Null).await
     }
@@ -107,29 +395,131 @@ impl BitcoinClient {
         self.call("getbestblockhash", Value::Null).await
     }
 
-    pub async fn get_block(&self, block_hash: &str, verbosity: u8) -> Result<Block> {
-        self.call("getblock", json!([block_hash, verbosity])).await
+    // Serialized block, `getblock` at verbosity 0
+    pub async fn get_block_hex(&self, block_hash: &str) -> Result<String> {
+        self.get_block_at(block_hash, 0).await
+    }
+
+    // Block with txids only, `getblock` at verbosity 1
+    pub async fn get_block(&self, block_hash: &str) -> Result<Block> {
+        self.get_block_at(block_hash, 1).await
+    }
+
+    // Block with decoded transactions, `getblock` at verbosity 2
+    pub async fn get_block_verbose(&self, block_hash: &str) -> Result<BlockVerbose> {
+        self.get_block_at(block_hash, 2).await
+    }
+
+    // Like `get_block_verbose`, with `prevout` set on inputs. Needs v23 or newer.
+    pub async fn get_block_with_prevouts(&self, block_hash: &str) -> Result<BlockVerbose> {
+        self.get_block_at(block_hash, 3).await
+    }
+
+    async fn get_block_at<T: for<'de> Deserialize<'de>>(
+        &self,
+        block_hash: &str,
+        verbosity: u8,
+    ) -> Result<T> {
+        match self.call("getblock", json!([block_hash, verbosity])).await {
+            Err(e) => {
+                let block = BlockRef::from(block_hash);
+                Err(self.explain_pruned(&block, e).await)
+            }
+            result => result,
+        }
     }
 
     pub async fn get_block_hash(&self, height: u64) -> Result<String> {
         self.call("getblockhash", json!([height])).await
     }
 
-    pub async fn get_block_header(&self, block_hash: &str, verbose: bool) -> Result<BlockHeader> {
-        self.call("getblockheader", json!([block_hash, verbose]))
+    pub async fn get_block_header(&self, block_hash: &str) -> Result<BlockHeader> {
+        self.call("getblockheader", json!([block_hash, true])).await
+    }
+
+    // The 80-byte serialized header, see `Serialization::parse_block_header`
+    pub async fn get_block_header_hex(&self, block_hash: &str) -> Result<String> {
+        self.call("getblockheader", json!([block_hash, false]))
             .await
     }
 
+    // Headers for every hash in one batch request, in the same order
+    pub async fn get_block_headers_batch(&self, block_hashes: &[&str]) -> Result<Vec<BlockHeader>> {
+        let requests = block_hashes
+            .iter()
+            .map(|hash| ("getblockheader".to_string(), json!([hash, true])))
+            .collect();
+        self.batch_call(requests)
+            .await?
+            .into_iter()
+            .map(|header| Ok(serde_json::from_value(header).map_err(BitcoinRpcError::from)?))
+            .collect()
+    }
+
     pub async fn get_chain_tips(&self) -> Result<Vec<ChainTip>> {
         self.call("getchaintips", Value::Null).await
     }
 
+    // Mark a block and its descendants invalid, reorging away from it if it is active
+    pub async fn invalidate_block(&self, block_hash: &str) -> Result<()> {
+        self.call::<Value>("invalidateblock", json!([block_hash]))
+            .await?;
+        Ok(())
+    }
+
+    // Undo `invalidate_block` for a block and its descendants
+    pub async fn reconsider_block(&self, block_hash: &str) -> Result<()> {
+        self.call::<Value>("reconsiderblock", json!([block_hash]))
+            .await?;
+        Ok(())
+    }
+
+    // Prefer this block's branch over another of equal work
+    pub async fn precious_block(&self, block_hash: &str) -> Result<()> {
+        self.call::<Value>("preciousblock", json!([block_hash]))
+            .await?;
+        Ok(())
+    }
+
+    // Transaction throughput over the `nblocks` blocks (default about a month) ending at
+    // `block_hash` (default the tip)
+    pub async fn get_chain_tx_stats(
+        &self,
+        nblocks: Option<u32>,
+        block_hash: Option<&str>,
+    ) -> Result<ChainTxStats> {
+        self.call("getchaintxstats", json!([nblocks, block_hash]))
+            .await
+    }
+
     pub async fn get_difficulty(&self) -> Result<f64> {
         self.call("getdifficulty", Value::Null).await
     }
 
-    pub async fn get_raw_transaction(&self, txid: &str, verbose: bool) -> Result<Transaction> {
-        self.call("getrawtransaction", json!([txid, verbose])).await
+    // Decoded transaction; `get_raw_transaction_hex` gives the serialized form.
+    // Transactions outside the mempool need -txindex.
+    pub async fn get_raw_transaction(&self, txid: &str) -> Result<Transaction> {
+        self.call("getrawtransaction", json!([txid, true])).await
+    }
+
+    // Serialized transaction. Transactions outside the mempool need -txindex.
+    pub async fn get_raw_transaction_hex(&self, txid: &str) -> Result<String> {
+        self.call("getrawtransaction", json!([txid, false])).await
+    }
+
+    // Decoded transaction with the fee and the outputs its inputs spend, saving a
+    // lookup per input. `blockhash` finds confirmed transactions without -txindex. Needs
+    // 25.0 or later; older nodes answer at verbosity 1, without the extra fields.
+    pub async fn get_raw_transaction_verbose2(
+        &self,
+        txid: &str,
+        blockhash: Option<&str>,
+    ) -> Result<TransactionWithPrevouts> {
+        let params = match blockhash {
+            Some(blockhash) => json!([txid, 2, blockhash]),
+            None => json!([txid, 2]),
+        };
+        self.call("getrawtransaction", params).await
     }
 
     pub async fn decode_raw_transaction(&self, tx_hex: &str) -> Result<DecodedTransaction> {
@@ -150,6 +540,27 @@ impl BitcoinClient {
             .await
     }
 
+    // Hex merkleblock proving the txids are in a block. Without `block_hash` the node
+    // needs -txindex or an unspent output of the transactions to find the block. A
+    // `block_hash` below the prune height fails with `BlockPruned`.
+    pub async fn get_tx_out_proof(
+        &self,
+        txids: &[&str],
+        block_hash: Option<&str>,
+    ) -> Result<String> {
+        let result = self.call("gettxoutproof", json!([txids, block_hash])).await;
+        match (result, block_hash) {
+            (Err(e), Some(hash)) => Err(self.explain_pruned(&BlockRef::from(hash), e).await),
+            (result, _) => result,
+        }
+    }
+
+    // Txids the proof commits to, checked by the node against its own chain. Use
+    // `Serialization::verify_tx_out_proof` to check without trusting the node.
+    pub async fn verify_tx_out_proof(&self, proof_hex: &str) -> Result<Vec<String>> {
+        self.call("verifytxoutproof", json!([proof_hex])).await
+    }
+
     pub async fn get_tx_out_set_info(&self) -> Result<TxOutSetInfo> {
         self.call("gettxoutsetinfo", Value::Null).await
     }
@@ -158,12 +569,93 @@ impl BitcoinClient {
         self.call("getwalletinfo", Value::Null).await
     }
 
+    // A transaction of this wallet, with the decoded transaction too when `verbose`. Fails
+    // with `BitcoinRpcError::NotInWallet` for a txid the wallet does not know.
+    pub async fn get_transaction(
+        &self,
+        txid: &str,
+        include_watchonly: bool,
+        verbose: bool,
+    ) -> Result<GetTransactionResult> {
+        self.call("gettransaction", json!([txid, include_watchonly, verbose]))
+            .await
+            .map_err(error::not_in_wallet)
+    }
+
+    // Up to `count` entries after skipping the `skip` most recent, returned oldest first.
+    // `label` None lists every label.
+    pub async fn list_transactions(
+        &self,
+        label: Option<&str>,
+        count: u32,
+        skip: u32,
+        include_watchonly: bool,
+    ) -> Result<Vec<WalletTransaction>> {
+        self.call(
+            "listtransactions",
+            json!([label.unwrap_or("*"), count, skip, include_watchonly]),
+        )
+        .await
+    }
+
+    // Wallet entries in blocks after `block_hash`, or every entry when None, plus
+    // the entries reorged out since then when `include_removed` is set
+    pub async fn list_since_block(
+        &self,
+        block_hash: Option<&str>,
+        target_confirmations: u32,
+        include_watchonly: bool,
+        include_removed: bool,
+    ) -> Result<ListSinceBlockResult> {
+        self.call(
+            "listsinceblock",
+            json!([
+                block_hash.unwrap_or(""),
+                target_confirmations,
+                include_watchonly,
+                include_removed
+            ]),
+        )
+        .await
+    }
+
+    // Balances by trust and maturity; prefer this over `get_balance`. Needs 0.19 or later.
+    pub async fn get_balances(&self) -> Result<Balances> {
+        self.call("getbalances", Value::Null).await
+    }
+
+    // Trusted balance with the node's default minimum confirmations
+    pub async fn get_balance_simple(&self) -> Result<Amount> {
+        self.call("getbalance", Value::Null).await
+    }
+
+    // Fee rate per kvB for this wallet's transactions, overriding estimation; zero
+    // restores estimation. Returns true once set.
+    pub async fn set_tx_fee(&self, fee_per_kb: Amount) -> Result<bool> {
+        self.call("settxfee", json!([fee_per_kb])).await
+    }
+
+    // How broadcasting `rawtxs` would change the wallet's balance, change outputs
+    // included. Later transactions may spend earlier ones, as in a package. Needs 24.0
+    // or later.
+    pub async fn simulate_raw_transaction(
+        &self,
+        rawtxs: &[&str],
+        options: Option<SimulateOptions>,
+    ) -> Result<SimulateResult> {
+        let params = match options {
+            Some(options) => json!([rawtxs, options]),
+            None => json!([rawtxs]),
+        };
+        self.call("simulaterawtransaction", params).await
+    }
+
     pub async fn get_balance(
         &self,
         dummy: &str,
         min_conf: i32,
         include_watchonly: bool,
-    ) -> Result<f64> {
+    ) -> Result<Amount> {
         self.call("getbalance", json!([dummy, min_conf, include_watchonly]))
             .await
     }
@@ -188,11 +680,98 @@ impl BitcoinClient {
         self.call("getaddressesbylabel", json!([label])).await
     }
 
+    // Labels in use, optionally only those of "send" or "receive" addresses
+    pub async fn list_labels(&self, purpose: Option<&str>) -> Result<Vec<String>> {
+        let params = match purpose {
+            Some(purpose) => json!([purpose]),
+            None => Value::Null,
+        };
+        self.call("listlabels", params).await
+    }
+
+    pub async fn set_label(&self, address: &str, label: &str) -> Result<()> {
+        self.call::<Value>("setlabel", json!([address, label]))
+            .await?;
+        Ok(())
+    }
+
+    // Total received by `address` in transactions with at least `minconf` confirmations.
+    // `include_immature_coinbase` needs 24.0 or later.
+    pub async fn get_received_by_address(
+        &self,
+        address: &str,
+        minconf: u32,
+        include_immature_coinbase: Option<bool>,
+    ) -> Result<Amount> {
+        let params = match include_immature_coinbase {
+            Some(include) => json!([address, minconf, include]),
+            None => json!([address, minconf]),
+        };
+        self.call("getreceivedbyaddress", params).await
+    }
+
+    // Total received by the label's addresses in transactions with at least `minconf`
+    // confirmations
+    pub async fn get_received_by_label(&self, label: &str, minconf: u32) -> Result<Amount> {
+        self.call("getreceivedbylabel", json!([label, minconf]))
+            .await
+    }
+
+    pub async fn list_received_by_label(
+        &self,
+        minconf: u32,
+        include_empty: bool,
+        include_watchonly: bool,
+    ) -> Result<Vec<ReceivedByLabel>> {
+        self.call(
+            "listreceivedbylabel",
+            json!([minconf, include_empty, include_watchonly]),
+        )
+        .await
+    }
+
+    // Per-address totals with the paying txids; `address_filter` limits the list to one
+    // address and needs 0.20 or later
+    pub async fn list_received_by_address(
+        &self,
+        minconf: u32,
+        include_empty: bool,
+        include_watchonly: bool,
+        address_filter: Option<&str>,
+    ) -> Result<Vec<ReceivedByAddress>> {
+        let params = match address_filter {
+            Some(address) => json!([minconf, include_empty, include_watchonly, address]),
+            None => json!([minconf, include_empty, include_watchonly]),
+        };
+        self.call("listreceivedbyaddress", params).await
+    }
+
+    // Wallet view of an address: ownership, keys, derivation path and labels
+    pub async fn get_address_info(&self, address: &str) -> Result<AddressDetails> {
+        self.call("getaddressinfo", json!([address])).await
+    }
+
     pub async fn validate_address(&self, address: &str) -> Result<ValidateAddress> {
         self.call("validateaddress", json!([address])).await
     }
 
-    pub async fn send_to_address(&self, address: &str, amount: f64) -> Result<String> {
+    // Base64 signature proving control of a P2PKH address
+    pub async fn sign_message(&self, address: &str, message: &str) -> Result<String> {
+        self.call("signmessage", json!([address, message])).await
+    }
+
+    // Node-side check; `BitcoinCrypto::verify_message` does the same locally
+    pub async fn verify_message(
+        &self,
+        address: &str,
+        signature: &str,
+        message: &str,
+    ) -> Result<bool> {
+        self.call("verifymessage", json!([address, signature, message]))
+            .await
+    }
+
+    pub async fn send_to_address(&self, address: &str, amount: Amount) -> Result<String> {
         self.call("sendtoaddress", json!([address, amount])).await
     }
 
@@ -217,6 +796,106 @@ impl BitcoinClient {
         self.call("getpeerinfo", Value::Null).await
     }
 
+    // Connect to, stop connecting to or try once `node` ("host:port"). `v2transport`
+    // asks for a BIP324 encrypted connection and needs 26.0 or later.
+    pub async fn add_node(
+        &self,
+        node: &str,
+        command: AddNodeCommand,
+        v2transport: Option<bool>,
+    ) -> Result<()> {
+        let params = match v2transport {
+            Some(v2transport) => {
+                self.require_version(NodeVersion::V2_TRANSPORT, "addnode v2transport")
+                    .await?;
+                json!([node, command, v2transport])
+            }
+            None => json!([node, command]),
+        };
+        self.call::<Value>("addnode", params).await?;
+        Ok(())
+    }
+
+    pub async fn disconnect_node(&self, target: DisconnectTarget) -> Result<()> {
+        let params = match target {
+            DisconnectTarget::Address(address) => json!([address]),
+            DisconnectTarget::NodeId(id) => json!(["", id]),
+        };
+        self.call::<Value>("disconnectnode", params).await?;
+        Ok(())
+    }
+
+    pub async fn get_connection_count(&self) -> Result<u64> {
+        self.call("getconnectioncount", Value::Null).await
+    }
+
+    // Bytes sent and received since startup, and the upload target
+    pub async fn get_net_totals(&self) -> Result<NetTotals> {
+        self.call("getnettotals", Value::Null).await
+    }
+
+    // Up to `count` known addresses, 0 for all, optionally from one `network` such as
+    // "ipv4" or "onion". Filtering by network needs 22.0 or later.
+    pub async fn get_node_addresses(
+        &self,
+        count: u32,
+        network: Option<&str>,
+    ) -> Result<Vec<NodeAddress>> {
+        let params = match network {
+            Some(network) => json!([count, network]),
+            None => json!([count]),
+        };
+        self.call("getnodeaddresses", params).await
+    }
+
+    // Put an address straight into the address manager, in the "tried" table when
+    // `tried` is set. Regtest-only test hook; `tried` needs 22.0 or later.
+    pub async fn add_peer_address(
+        &self,
+        address: &str,
+        port: u16,
+        tried: bool,
+    ) -> Result<AddPeerAddressResult> {
+        let params = if tried {
+            self.require_version(NodeVersion::ADD_PEER_ADDRESS_TRIED, "addpeeraddress tried")
+                .await?;
+            json!([address, port, true])
+        } else {
+            self.require_version(NodeVersion::ADD_PEER_ADDRESS, "addpeeraddress")
+                .await?;
+            json!([address, port])
+        };
+        self.call("addpeeraddress", params).await
+    }
+
+    // Address manager table sizes keyed by network name, plus "all_networks"
+    pub async fn get_addr_man_info(&self) -> Result<HashMap<String, AddrManNetworkCounts>> {
+        self.require_version(NodeVersion::ADDRMAN_INFO, "getaddrmaninfo")
+            .await?;
+        self.call("getaddrmaninfo", Value::Null).await
+    }
+
+    // Turn all P2P activity on or off; returns the new state
+    pub async fn set_network_active(&self, active: bool) -> Result<bool> {
+        self.call("setnetworkactive", json!([active])).await
+    }
+
+    // Queue a ping to every peer. Results show up later in `PeerInfo::pingtime`, and
+    // `PeerInfo::pingwait` while outstanding.
+    pub async fn ping(&self) -> Result<()> {
+        self.call::<Value>("ping", Value::Null).await?;
+        Ok(())
+    }
+
+    // Nodes added with `add_node`, or just `node`, and whether they are connected
+    pub async fn get_added_node_info(&self, node: Option<&str>) -> Result<Vec<AddedNodeInfo>> {
+        let params = match node {
+            Some(node) => json!([node]),
+            None => Value::Null,
+        };
+        self.call("getaddednodeinfo", params).await
+    }
+
     pub async fn get_network_hash_ps(
         &self,
         nblocks: Option<i32>,
@@ -234,10 +913,24 @@ impl BitcoinClient {
         self.call("getmempoolinfo", Value::Null).await
     }
 
+    // Fails with `BitcoinRpcError::NotInMempool` when the transaction is not in the mempool
+    pub async fn get_mempool_entry(&self, txid: &str) -> Result<MempoolEntry> {
+        self.call("getmempoolentry", json!([txid]))
+            .await
+            .map_err(error::not_in_mempool)
+    }
+
     pub async fn get_raw_mempool(&self, verbose: bool) -> Result<Vec<String>> {
         self.call("getrawmempool", json!([verbose])).await
     }
 
+    // Mempool txids together with the mempool sequence they are consistent with
+    pub async fn get_raw_mempool_with_sequence(&self) -> Result<(Vec<String>, u64)> {
+        let snapshot: MempoolSequenceSnapshot =
+            self.call("getrawmempool", json!([false, true])).await?;
+        Ok((snapshot.txids, snapshot.mempool_sequence))
+    }
+
     pub async fn estimate_smart_fee(&self, conf_target: i32) -> Result<FeeEstimate> {
         self.call("estimatesmartfee", json!([conf_target])).await
     }
@@ -254,12 +947,43 @@ impl BitcoinClient {
     pub async fn create_raw_transaction(
         &self,
         inputs: Vec<CreateTxInput>,
-        outputs: HashMap<String, f64>,
+        outputs: HashMap<String, Amount>,
     ) -> Result<String> {
         self.call("createrawtransaction", json!([inputs, outputs]))
             .await
     }
 
+    // Merge the signatures of partially signed copies of one transaction, e.g. from the
+    // signers of a legacy multisig. Copies of different transactions are refused before
+    // anything is sent.
+    pub async fn combine_raw_transaction(&self, hex_txs: &[&str]) -> Result<String> {
+        let mut skeleton = None;
+        for (index, hex) in hex_txs.iter().enumerate() {
+            let unsigned = Serialization::deserialize_transaction(hex)
+                .map_err(|e| anyhow!("Transaction {} could not be decoded: {}", index, e))?
+                .unsigned();
+            match &skeleton {
+                None => skeleton = Some(unsigned),
+                Some(first) if *first != unsigned => {
+                    return Err(anyhow!(
+                        "Transaction {} differs from the first in more than its signatures",
+                        index
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        if skeleton.is_none() {
+            return Err(anyhow!("No transactions to combine"));
+        }
+        self.call("combinerawtransaction", json!([hex_txs])).await
+    }
+
+    // Type, addresses and segwit form of a script given in hex, e.g. a redeem script
+    pub async fn decode_script(&self, script_hex: &str) -> Result<DecodedScript> {
+        self.call("decodescript", json!([script_hex])).await
+    }
+
     pub async fn sign_raw_transaction_with_wallet(
         &self,
         tx_hex: &str,
@@ -268,41 +992,161 @@ impl BitcoinClient {
             .await
     }
 
-    pub async fn get_block_stats(&self, height: u64) -> Result<BlockStats> {
-        self.call("getblockstats", json!([height])).await
+    // Sign with the given WIF keys only, leaving the node's wallet out of it. `prevtxs`
+    // describes inputs that are not in the UTXO set, e.g. parents not yet broadcast;
+    // segwit entries need an amount and are refused without one before anything is sent.
+    pub async fn sign_raw_transaction_with_key(
+        &self,
+        tx_hex: &str,
+        privkeys_wif: &[&str],
+        prevtxs: Option<Vec<PrevTx>>,
+        sighashtype: Option<SighashType>,
+    ) -> Result<SignedTransaction> {
+        if let Some(prev) = prevtxs
+            .iter()
+            .flatten()
+            .find(|prev| prev.amount.is_none() && prev.is_witness())
+        {
+            return Err(anyhow!(
+                "Previous output {}:{} is segwit and needs an amount",
+                prev.txid,
+                prev.vout
+            ));
+        }
+        let params = match (prevtxs, sighashtype) {
+            (prevtxs, Some(sighashtype)) => {
+                json!([
+                    tx_hex,
+                    privkeys_wif,
+                    prevtxs.unwrap_or_default(),
+                    sighashtype
+                ])
+            }
+            (Some(prevtxs), None) => json!([tx_hex, privkeys_wif, prevtxs]),
+            (None, None) => json!([tx_hex, privkeys_wif]),
+        };
+        self.call("signrawtransactionwithkey", params).await
+    }
+
+    pub async fn get_block_stats(&self, block: impl Into<BlockRef>) -> Result<BlockStats> {
+        self.get_block_stats_filtered(block, &[]).await
+    }
+
+    // Only the named stats, e.g. `&["feerate_percentiles", "totalfee"]`; the rest are None.
+    // Cheaper for the node when none of the selected stats need the undo data.
+    pub async fn get_block_stats_filtered(
+        &self,
+        block: impl Into<BlockRef>,
+        stats: &[&str],
+    ) -> Result<BlockStats> {
+        let block = block.into();
+        let params = if stats.is_empty() {
+            json!([block])
+        } else {
+            json!([block, stats])
+        };
+        match self.call("getblockstats", params).await {
+            Err(e) => Err(self.explain_pruned(&block, e).await),
+            result => result,
+        }
     }
 
     pub async fn batch_call(&self, requests: Vec<(String, Value)>) -> Result<Vec<Value>> {
+        let mut results = Vec::new();
+        for ((_method, _), (result, error)) in
+            requests.iter().zip(self.batch_send(&requests).await?)
+        {
+            if let Some(error) = error {
+                return Err(BitcoinRpcError::from(error).into());
+            }
+            #[cfg(feature = "validate-responses")]
+            if let Some(result) = &result {
+                validation::log_response_warnings(_method, result);
+            }
+            results.push(result.unwrap_or(Value::Null));
+        }
+        Ok(results)
+    }
+
+    // Send a batch and return every sub-response, errors included, in request order
+    async fn batch_send(
+        &self,
+        requests: &[(String, Value)],
+    ) -> Result<Vec<(Option<Value>, Option<RpcError>)>> {
+        if let Some(cassette) = self.cassette.as_ref().filter(|c| c.is_replay()) {
+            return requests
+                .iter()
+                .map(|(method, params)| cassette.lookup(method, params))
+                .collect();
+        }
+        // Middleware handles one RPC at a time, so with any installed the requests go
+        // out individually, each through the chain
+        if !self.middleware.0.is_empty() {
+            return futures_util::future::try_join_all(
+                requests
+                    .iter()
+                    .map(|(method, params)| self.dispatch(method, params.clone())),
+            )
+            .await;
+        }
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+            let span = instrument::batch_span(requests.len());
+            let started = std::time::Instant::now();
+            let outcome = self
+                .batch_send_once(requests)
+                .instrument(span.clone())
+                .await;
+            instrument::finish_batch(&span, started, requests, &outcome);
+            outcome
+        }
+        #[cfg(not(feature = "tracing"))]
+        self.batch_send_once(requests).await
+    }
+
+    async fn batch_send_once(
+        &self,
+        requests: &[(String, Value)],
+    ) -> Result<Vec<(Option<Value>, Option<RpcError>)>> {
+        let first_id = self
+            .next_id
+            .fetch_add(requests.len() as u64, Ordering::Relaxed);
         let batch_requests: Vec<BitcoinNetWorkRequest> = requests
-            .into_iter()
+            .iter()
             .enumerate()
             .map(|(i, (method, params))| BitcoinNetWorkRequest {
                 jsonrpc: "2.0".to_string(),
-                id: i as u64,
-                method,
-                params,
+                id: first_id + i as u64,
+                method: method.clone(),
+                params: params.clone(),
             })
             .collect();
-        let response: Response = self
-            .client
-            .post(&self.url)
-            .header("Authorization", &self.auth)
-            .header("Content-Type", "application/json")
-            .json(&batch_requests)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(anyhow!("HTTP error: {}", response.status()));
-        }
-        let responses: Vec<BitcoinNetWorkResponse<Value>> = response.json().await?;
-        let mut results = Vec::new();
-        for response in responses {
-            if let Some(error) = response.error {
-                return Err(anyhow!("RPC error {}: {}", error.code, error.message));
+        // A batch with any wallet RPC goes to the wallet path, which serves chain RPCs too
+        let url = self.endpoint(requests.iter().map(|(method, _)| method.as_str()));
+        let _permit = self.acquire_slot().await?;
+        let response = self.post(&url, &batch_requests).await?;
+        let responses: Vec<BitcoinNetWorkResponse<Value>> = self.read_response(response)?;
+        // Servers may answer a batch in any order; put the responses back in request order
+        let mut by_id: HashMap<u64, BitcoinNetWorkResponse<Value>> =
+            responses.into_iter().map(|r| (r.id, r)).collect();
+        let responses = batch_requests
+            .iter()
+            .map(|request| {
+                by_id.remove(&request.id).ok_or(ResponseIdMismatch {
+                    expected: request.id,
+                    actual: None,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(cassette) = &self.cassette {
+            for (response, (method, params)) in responses.iter().zip(requests) {
+                cassette.write(method, params, &response.result, &response.error)?;
             }
-            results.push(response.result.unwrap_or(Value::Null));
         }
-        Ok(results)
+        Ok(responses
+            .into_iter()
+            .map(|response| (response.result, response.error))
+            .collect())
     }
 }
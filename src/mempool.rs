@@ -0,0 +1,140 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use thiserror::Error;
+
+use crate::BitcoinClient;
+use crate::amount::Amount;
+use crate::error::not_in_mempool;
+use crate::serialization::Serialization;
+use crate::types::{MempoolEntry, OutPoint, SpendingPrevout, SubmitPackageResult};
+
+// Most transactions the node accepts in one package
+pub const MAX_PACKAGE_COUNT: usize = 25;
+
+// Why `submit_package` refused a package before sending it
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PackageError {
+    #[error("Package must have 1 to {MAX_PACKAGE_COUNT} transactions, got {0}")]
+    InvalidCount(usize),
+    #[error("Transaction {index} could not be decoded: {message}")]
+    Malformed { index: usize, message: String },
+    // A transaction spends one that comes after it
+    #[error("Transaction {child} spends {parent}, which comes after it in the package")]
+    ParentAfterChild { child: String, parent: String },
+}
+
+// A mempool transaction together with its unconfirmed ancestors, as miners weigh it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageFee {
+    pub fee: Amount,
+    pub vsize: u64,
+}
+
+impl PackageFee {
+    pub fn sat_per_vb(&self) -> f64 {
+        if self.vsize == 0 {
+            return 0.0;
+        }
+        self.fee.to_sat() as f64 / self.vsize as f64
+    }
+}
+
+impl BitcoinClient {
+    // Txids of the transaction's in-mempool ancestors, excluding itself
+    pub async fn get_mempool_ancestors(&self, txid: &str) -> Result<Vec<String>> {
+        self.call("getmempoolancestors", json!([txid, false]))
+            .await
+            .map_err(not_in_mempool)
+    }
+
+    pub async fn get_mempool_ancestors_verbose(
+        &self,
+        txid: &str,
+    ) -> Result<HashMap<String, MempoolEntry>> {
+        self.call("getmempoolancestors", json!([txid, true]))
+            .await
+            .map_err(not_in_mempool)
+    }
+
+    // Txids of the transaction's in-mempool descendants, excluding itself
+    pub async fn get_mempool_descendants(&self, txid: &str) -> Result<Vec<String>> {
+        self.call("getmempooldescendants", json!([txid, false]))
+            .await
+            .map_err(not_in_mempool)
+    }
+
+    pub async fn get_mempool_descendants_verbose(
+        &self,
+        txid: &str,
+    ) -> Result<HashMap<String, MempoolEntry>> {
+        self.call("getmempooldescendants", json!([txid, true]))
+            .await
+            .map_err(not_in_mempool)
+    }
+
+    // Which mempool transactions spend the outpoints, in request order. Needs 24.0 or
+    // later; older nodes fail with `BitcoinRpcError::MethodNotFound`.
+    pub async fn get_tx_spending_prevout(
+        &self,
+        outpoints: &[OutPoint],
+    ) -> Result<Vec<SpendingPrevout>> {
+        self.call("gettxspendingprevout", json!([outpoints])).await
+    }
+
+    // Fee and vsize of the transaction plus all its unconfirmed ancestors, using
+    // modified fees, e.g. to work out how much a CPFP child must pay
+    pub async fn get_package_fee_and_vsize(&self, txid: &str) -> Result<PackageFee> {
+        let entry = self.get_mempool_entry(txid).await?;
+        Ok(PackageFee {
+            fee: entry.fees.ancestor,
+            vsize: entry.ancestorsize,
+        })
+    }
+
+    // Relay transactions together so a parent below the mempool minimum fee can ride on
+    // its child's fee. `rawtxs` must list parents before children. `maxfeerate` is per
+    // kvB. Needs 28.0 or later outside regtest.
+    pub async fn submit_package(
+        &self,
+        rawtxs: &[&str],
+        maxfeerate: Option<Amount>,
+        maxburnamount: Option<Amount>,
+    ) -> Result<SubmitPackageResult> {
+        check_package(rawtxs)?;
+        let mut params = vec![json!(rawtxs), json!(maxfeerate), json!(maxburnamount)];
+        while params.last().is_some_and(Value::is_null) {
+            params.pop();
+        }
+        self.call("submitpackage", Value::Array(params)).await
+    }
+}
+
+fn check_package(rawtxs: &[&str]) -> Result<(), PackageError> {
+    if rawtxs.is_empty() || rawtxs.len() > MAX_PACKAGE_COUNT {
+        return Err(PackageError::InvalidCount(rawtxs.len()));
+    }
+    let txs = rawtxs
+        .iter()
+        .enumerate()
+        .map(|(index, hex)| {
+            Serialization::deserialize_transaction(hex).map_err(|e| PackageError::Malformed {
+                index,
+                message: e.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let txids: Vec<[u8; 32]> = txs.iter().map(|tx| tx.txid_bytes()).collect();
+    for (index, tx) in txs.iter().enumerate() {
+        for input in &tx.inputs {
+            if txids[index + 1..].contains(&input.prev_txid) {
+                return Err(PackageError::ParentAfterChild {
+                    child: tx.txid(),
+                    parent: Serialization::hash_to_hex(&input.prev_txid),
+                });
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use anyhow::{Result, anyhow};
+use std::collections::HashSet;
+use tokio::sync::broadcast;
+
+use crate::BitcoinClient;
+
+const CHANGE_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceKind {
+    BlockConnected,
+    BlockDisconnected,
+    TxAdded { mempool_sequence: u64 },
+    TxRemoved { mempool_sequence: u64 },
+}
+
+// One message from the ZMQ `sequence` topic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceEvent {
+    // Block hash or txid in display order
+    pub hash: String,
+    pub kind: SequenceKind,
+    // Per-topic message counter from the last ZMQ frame, used for gap detection
+    pub zmq_sequence: Option<u32>,
+}
+
+impl SequenceEvent {
+    // Body layout: 32-byte hash, one label byte ('C', 'D', 'A' or 'R'),
+    // then an 8-byte little-endian mempool sequence for 'A' and 'R'
+    pub fn parse(body: &[u8], zmq_sequence: Option<u32>) -> Result<Self> {
+        if body.len() < 33 {
+            return Err(anyhow!("Sequence message too short: {} bytes", body.len()));
+        }
+        let hash = hex::encode(&body[..32]);
+        let mempool_sequence = || -> Result<u64> {
+            let bytes: [u8; 8] = body
+                .get(33..41)
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| anyhow!("Sequence message missing mempool sequence"))?;
+            Ok(u64::from_le_bytes(bytes))
+        };
+        let kind = match body[32] {
+            b'C' => SequenceKind::BlockConnected,
+            b'D' => SequenceKind::BlockDisconnected,
+            b'A' => SequenceKind::TxAdded {
+                mempool_sequence: mempool_sequence()?,
+            },
+            b'R' => SequenceKind::TxRemoved {
+                mempool_sequence: mempool_sequence()?,
+            },
+            label => return Err(anyhow!("Unknown sequence label 0x{:02x}", label)),
+        };
+        Ok(SequenceEvent {
+            hash,
+            kind,
+            zmq_sequence,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MempoolChange {
+    Added(String),
+    Removed(String),
+    // Block transactions that left the mempool by confirming
+    Confirmed {
+        block_hash: String,
+        txids: Vec<String>,
+    },
+    BlockDisconnected(String),
+    // A gap was detected and the mirror rebuilt from a fresh snapshot
+    Resynced,
+}
+
+// Exact copy of a node's mempool txids, kept current from `sequence` events
+#[derive(Debug)]
+pub struct MempoolMirror {
+    client: BitcoinClient,
+    txids: HashSet<String>,
+    // Mempool sequence the next 'A' or 'R' event should carry. Events below it are already
+    // reflected; events above it mean some were missed.
+    next_mempool_sequence: u64,
+    last_zmq_sequence: Option<u32>,
+    changes: broadcast::Sender<MempoolChange>,
+}
+
+impl MempoolMirror {
+    pub async fn new(client: BitcoinClient) -> Result<Self> {
+        // The snapshot's sequence is the next one the node will use
+        let (txids, next_mempool_sequence) = client.get_raw_mempool_with_sequence().await?;
+        let (changes, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+        Ok(MempoolMirror {
+            client,
+            txids: txids.into_iter().collect(),
+            next_mempool_sequence,
+            last_zmq_sequence: None,
+            changes,
+        })
+    }
+
+    pub fn txids(&self) -> &HashSet<String> {
+        &self.txids
+    }
+
+    pub fn contains(&self, txid: &str) -> bool {
+        self.txids.contains(txid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.txids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.txids.is_empty()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<MempoolChange> {
+        self.changes.subscribe()
+    }
+
+    // Apply events in the order they arrived. Subscribe to ZMQ before calling `new` so no
+    // event between the snapshot and the first `apply` is lost.
+    pub async fn apply(&mut self, event: SequenceEvent) -> Result<Vec<MempoolChange>> {
+        if let Some(seq) = event.zmq_sequence {
+            let gap = self
+                .last_zmq_sequence
+                .is_some_and(|last| seq != last.wrapping_add(1));
+            self.last_zmq_sequence = Some(seq);
+            if gap {
+                return self.resync().await;
+            }
+        }
+        if let SequenceKind::TxAdded { mempool_sequence }
+        | SequenceKind::TxRemoved { mempool_sequence } = event.kind
+        {
+            // Already reflected in the snapshot
+            if mempool_sequence < self.next_mempool_sequence {
+                return Ok(Vec::new());
+            }
+            // Events were missed even though the ZMQ counter ran on, e.g. across a reconnect
+            if mempool_sequence > self.next_mempool_sequence {
+                return self.resync().await;
+            }
+            self.next_mempool_sequence = mempool_sequence + 1;
+        }
+        let changes = match event.kind {
+            SequenceKind::TxAdded { .. } => {
+                if self.txids.insert(event.hash.clone()) {
+                    vec![MempoolChange::Added(event.hash)]
+                } else {
+                    Vec::new()
+                }
+            }
+            SequenceKind::TxRemoved { .. } => {
+                if self.txids.remove(&event.hash) {
+                    vec![MempoolChange::Removed(event.hash)]
+                } else {
+                    Vec::new()
+                }
+            }
+            SequenceKind::BlockConnected => {
+                // Confirmed transactions leave without their own 'R' events, but each still
+                // uses up a mempool sequence
+                let block = self.client.get_block(&event.hash).await?;
+                let txids: Vec<String> = block
+                    .tx
+                    .into_iter()
+                    .filter(|txid| self.txids.remove(txid))
+                    .collect();
+                self.next_mempool_sequence += txids.len() as u64;
+                vec![MempoolChange::Confirmed {
+                    block_hash: event.hash,
+                    txids,
+                }]
+            }
+            // Transactions returned to the mempool arrive as their own 'A' events
+            SequenceKind::BlockDisconnected => vec![MempoolChange::BlockDisconnected(event.hash)],
+        };
+        self.publish(&changes);
+        Ok(changes)
+    }
+
+    // Rebuild from a fresh snapshot, reporting what changed while events were missing
+    pub async fn resync(&mut self) -> Result<Vec<MempoolChange>> {
+        let (txids, next_mempool_sequence) = self.client.get_raw_mempool_with_sequence().await?;
+        let fresh: HashSet<String> = txids.into_iter().collect();
+        let mut changes: Vec<MempoolChange> = self
+            .txids
+            .difference(&fresh)
+            .map(|txid| MempoolChange::Removed(txid.clone()))
+            .collect();
+        changes.extend(
+            fresh
+                .difference(&self.txids)
+                .map(|txid| MempoolChange::Added(txid.clone())),
+        );
+        changes.push(MempoolChange::Resynced);
+        self.txids = fresh;
+        self.next_mempool_sequence = next_mempool_sequence;
+        self.publish(&changes);
+        Ok(changes)
+    }
+
+    fn publish(&self, changes: &[MempoolChange]) {
+        for change in changes {
+            // No subscribers is not an error
+            let _ = self.changes.send(change.clone());
+        }
+    }
+}
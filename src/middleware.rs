@@ -0,0 +1,110 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{BitcoinClient, RpcError};
+
+// One RPC as it passes through the middleware chain. Headers set here are sent with the
+// HTTP request and replace the client's own, e.g. `Authorization`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcRequest {
+    pub method: String,
+    pub params: Value,
+    pub headers: Vec<(String, String)>,
+}
+
+// The node's answer: a result, or the JSON-RPC error it returned
+#[derive(Debug, Clone, Default)]
+pub struct RpcResponse {
+    pub result: Option<Value>,
+    pub error: Option<RpcError>,
+}
+
+// Code run around every RPC sent by a client. Call `next.run(req)` to pass the request
+// on, or return a response without it to short-circuit. Cache hits do not pass through
+// middleware. A client with middleware sends the entries of a batch as separate
+// requests, so each of them does.
+#[async_trait]
+pub trait RpcMiddleware: Send + Sync {
+    async fn handle(&self, req: RpcRequest, next: Next<'_>) -> Result<RpcResponse>;
+}
+
+// The rest of the chain after the current middleware, ending in the HTTP request
+pub struct Next<'a> {
+    client: &'a BitcoinClient,
+    remaining: &'a [Arc<dyn RpcMiddleware>],
+}
+
+impl Next<'_> {
+    pub async fn run(self, req: RpcRequest) -> Result<RpcResponse> {
+        match self.remaining.split_first() {
+            Some((middleware, remaining)) => {
+                let next = Next {
+                    client: self.client,
+                    remaining,
+                };
+                middleware.handle(req, next).await
+            }
+            None => {
+                let client = if req.headers.is_empty() {
+                    self.client
+                } else {
+                    &self.client.with_headers(&req.headers)?
+                };
+                let (result, error) = client.send_with_retry(&req.method, &req.params).await?;
+                Ok(RpcResponse { result, error })
+            }
+        }
+    }
+}
+
+// Middleware added to the builder, outermost first
+#[derive(Clone, Default)]
+pub(crate) struct MiddlewareStack(pub(crate) Vec<Arc<dyn RpcMiddleware>>);
+
+impl fmt::Debug for MiddlewareStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MiddlewareStack({} layers)", self.0.len())
+    }
+}
+
+impl BitcoinClient {
+    // Send one request through the middleware chain
+    pub(crate) async fn dispatch(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<(Option<Value>, Option<RpcError>)> {
+        if self.middleware.0.is_empty() {
+            return self.send_with_retry(method, &params).await;
+        }
+        let next = Next {
+            client: self,
+            remaining: &self.middleware.0,
+        };
+        let request = RpcRequest {
+            method: method.to_string(),
+            params,
+            headers: Vec::new(),
+        };
+        let response = next.run(request).await?;
+        Ok((response.result, response.error))
+    }
+
+    fn with_headers(&self, headers: &[(String, String)]) -> Result<BitcoinClient> {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| anyhow!("Invalid header name {}", name))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| anyhow!("Invalid value for header {}", name))?;
+            map.insert(name, value);
+        }
+        let mut client = self.clone();
+        client.extra_headers = Some(Arc::new(map));
+        Ok(client)
+    }
+}
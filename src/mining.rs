@@ -0,0 +1,78 @@
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+
+use crate::BitcoinClient;
+use crate::node_version::NodeVersion;
+use crate::types::{
+    BlockTemplate, BlockTemplateRequest, GenerateBlockResult, GenerateOutput, TemplateMode,
+};
+
+impl BitcoinClient {
+    // Template for the next block. With a `longpollid` the node holds the request until
+    // the template changes, so no client timeout applies.
+    pub async fn get_block_template(&self, request: BlockTemplateRequest) -> Result<BlockTemplate> {
+        if request.mode == Some(TemplateMode::Proposal) {
+            return Err(anyhow!("Proposal mode has no template, use propose_block"));
+        }
+        let client = match request.longpollid {
+            Some(_) => self.for_wait(0),
+            None => self.clone(),
+        };
+        client.call("getblocktemplate", json!([request])).await
+    }
+
+    // Check a block against the node's rules without submitting it. None when it is
+    // valid, otherwise the rejection reason.
+    pub async fn propose_block(&self, block_hex: &str) -> Result<Option<String>> {
+        let request = BlockTemplateRequest {
+            mode: Some(TemplateMode::Proposal),
+            data: Some(block_hex.to_string()),
+            ..BlockTemplateRequest::new()
+        };
+        let result: Value = self.call("getblocktemplate", json!([request])).await?;
+        Ok(result.as_str().map(str::to_string))
+    }
+
+    // Submit a solved block. None when accepted, otherwise the node's reason, e.g.
+    // "duplicate", "inconclusive" or "bad-txnmrklroot".
+    pub async fn submit_block(&self, block_hex: &str) -> Result<Option<String>> {
+        hex::decode(block_hex).map_err(|e| anyhow!("Block is not valid hex: {}", e))?;
+        let result: Value = self.call("submitblock", json!([block_hex])).await?;
+        Ok(result.as_str().map(str::to_string))
+    }
+
+    // Submit a header so the node can sync towards it before the block arrives. Invalid
+    // headers fail with `BitcoinRpcError::VerifyError`.
+    pub async fn submit_header(&self, header_hex: &str) -> Result<()> {
+        let header =
+            hex::decode(header_hex).map_err(|e| anyhow!("Header is not valid hex: {}", e))?;
+        if header.len() != 80 {
+            return Err(anyhow!("Header must be 80 bytes, got {}", header.len()));
+        }
+        self.call::<Value>("submitheader", json!([header_hex]))
+            .await?;
+        Ok(())
+    }
+
+    // Mine a block holding exactly `txs`, raw hex or txids of mempool transactions, in
+    // that order. Without `submit` the block is returned as hex instead of being added
+    // to the chain, which needs 25.0 or later. Regtest only in practice.
+    pub async fn generate_block(
+        &self,
+        output: impl Into<GenerateOutput>,
+        txs: &[&str],
+        submit: bool,
+    ) -> Result<GenerateBlockResult> {
+        let params = if submit {
+            json!([output.into(), txs])
+        } else {
+            self.require_version(
+                NodeVersion::GENERATE_BLOCK_NO_SUBMIT,
+                "generateblock submit",
+            )
+            .await?;
+            json!([output.into(), txs, false])
+        };
+        self.call("generateblock", params).await
+    }
+}
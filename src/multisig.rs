@@ -0,0 +1,74 @@
+use anyhow::Result;
+use secp256k1::PublicKey;
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::BitcoinClient;
+use crate::types::MultisigResult;
+
+// Consensus limit on keys in a CHECKMULTISIG script
+const MAX_MULTISIG_KEYS: usize = 20;
+
+// Rejected before the request is sent
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MultisigError {
+    #[error("{required} signatures required from {keys} keys")]
+    InvalidThreshold { required: u8, keys: usize },
+    #[error("Invalid public key: {0}")]
+    InvalidPublicKey(String),
+}
+
+impl BitcoinClient {
+    // m-of-n address from hex public keys; `address_type` is "legacy", "p2sh-segwit" or
+    // "bech32"
+    pub async fn create_multisig(
+        &self,
+        nrequired: u8,
+        keys: &[&str],
+        address_type: Option<&str>,
+    ) -> Result<MultisigResult> {
+        check_multisig(nrequired, keys)?;
+        let mut params = vec![json!(nrequired), json!(keys)];
+        if let Some(address_type) = address_type {
+            params.push(json!(address_type));
+        }
+        self.call("createmultisig", Value::Array(params)).await
+    }
+
+    // Same as `create_multisig`, also adding the script to the wallet (legacy wallets only)
+    pub async fn add_multisig_address(
+        &self,
+        nrequired: u8,
+        keys: &[&str],
+        label: Option<&str>,
+        address_type: Option<&str>,
+    ) -> Result<MultisigResult> {
+        check_multisig(nrequired, keys)?;
+        let mut params = vec![json!(nrequired), json!(keys)];
+        if label.is_some() || address_type.is_some() {
+            params.push(json!(label.unwrap_or("")));
+        }
+        if let Some(address_type) = address_type {
+            params.push(json!(address_type));
+        }
+        self.call("addmultisigaddress", Value::Array(params)).await
+    }
+}
+
+fn check_multisig(nrequired: u8, keys: &[&str]) -> Result<(), MultisigError> {
+    if nrequired == 0 || nrequired as usize > keys.len() || keys.len() > MAX_MULTISIG_KEYS {
+        return Err(MultisigError::InvalidThreshold {
+            required: nrequired,
+            keys: keys.len(),
+        });
+    }
+    for key in keys {
+        let valid = hex::decode(key)
+            .ok()
+            .is_some_and(|bytes| PublicKey::from_slice(&bytes).is_ok());
+        if !valid {
+            return Err(MultisigError::InvalidPublicKey(key.to_string()));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,364 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::BitcoinClient;
+use crate::amount::Amount;
+
+// Normalized view of a node and its wallet, stable across captures so two snapshots
+// taken around an upgrade or migration can be compared field by field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    pub block_count: u64,
+    pub best_block_hash: String,
+    pub loaded_wallets: Vec<String>,
+    // Every captured wallet by name, "" for the default wallet
+    pub wallets: BTreeMap<String, WalletSnapshot>,
+    pub network: NetworkEssentials,
+    pub mempool_size: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WalletSnapshot {
+    // Confirmed and unconfirmed UTXO value per label, "" for unlabelled outputs
+    pub label_balances: BTreeMap<String, Amount>,
+    pub utxos: Vec<SnapshotUtxo>,
+}
+
+// Confirmations are left out on purpose: they change with every block
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SnapshotUtxo {
+    pub txid: String,
+    pub vout: u32,
+    pub amount: Amount,
+    pub address: Option<String>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkEssentials {
+    pub version: u64,
+    pub subversion: String,
+    pub protocolversion: u64,
+    pub localservices: String,
+    pub networkactive: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SnapshotUnspent {
+    txid: String,
+    vout: u32,
+    amount: Amount,
+    address: Option<String>,
+    label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+// One difference, addressed by a path such as `wallets/<wallet>/utxos/<txid>:<vout>` or
+// `wallets/<wallet>/label_balances/<label>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeSnapshotDiff {
+    pub changes: Vec<SnapshotChange>,
+}
+
+// Which drift `NodeSnapshot::diff_with` treats as expected
+#[derive(Debug, Clone)]
+pub struct DiffRules {
+    // Ignore tip changes as long as the block count went up
+    pub allow_tip_advance: bool,
+    pub ignore_mempool: bool,
+    // Path prefixes to leave out, e.g. "network/subversion"
+    pub ignore_paths: Vec<String>,
+}
+
+impl Default for DiffRules {
+    fn default() -> Self {
+        DiffRules {
+            allow_tip_advance: true,
+            ignore_mempool: true,
+            ignore_paths: Vec::new(),
+        }
+    }
+}
+
+impl DiffRules {
+    // Report every difference
+    pub fn strict() -> Self {
+        DiffRules {
+            allow_tip_advance: false,
+            ignore_mempool: false,
+            ignore_paths: Vec::new(),
+        }
+    }
+
+    pub fn ignore(mut self, path_prefix: &str) -> Self {
+        self.ignore_paths.push(path_prefix.to_string());
+        self
+    }
+
+    fn ignores(&self, path: &str) -> bool {
+        self.ignore_paths
+            .iter()
+            .any(|p| path.starts_with(p.as_str()))
+    }
+}
+
+impl NodeSnapshot {
+    pub async fn capture(client: &BitcoinClient) -> Result<Self> {
+        let block_count = client.get_block_count().await?;
+        let best_block_hash = client.get_best_block_hash().await?;
+        let mut loaded_wallets = client.list_wallets().await?;
+        loaded_wallets.sort();
+        let network: NetworkEssentials = client.call("getnetworkinfo", Value::Null).await?;
+        let mempool_size = client.get_mempool_info().await?.size;
+
+        let mut wallets = BTreeMap::new();
+        for name in snapshot_wallets(client, &loaded_wallets) {
+            let wallet = WalletSnapshot::capture(&client.wallet(&name)).await?;
+            wallets.insert(name, wallet);
+        }
+
+        Ok(NodeSnapshot {
+            block_count,
+            best_block_hash,
+            loaded_wallets,
+            wallets,
+            network,
+            mempool_size,
+        })
+    }
+
+    // Canonical form: maps and lists are sorted, so equal snapshots serialize identically
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let mut snapshot: NodeSnapshot = serde_json::from_str(json)?;
+        snapshot.loaded_wallets.sort();
+        for wallet in snapshot.wallets.values_mut() {
+            wallet.utxos.sort();
+        }
+        Ok(snapshot)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn diff(&self, other: &NodeSnapshot) -> NodeSnapshotDiff {
+        self.diff_with(other, &DiffRules::default())
+    }
+
+    pub fn diff_with(&self, other: &NodeSnapshot, rules: &DiffRules) -> NodeSnapshotDiff {
+        let mut diff = NodeSnapshotDiff::default();
+        // A different hash at the same height is a reorg, never a tip advance
+        let tip_advanced = other.block_count > self.block_count;
+        if !(rules.allow_tip_advance && tip_advanced) {
+            diff.compare("block_count", &self.block_count, &other.block_count);
+            diff.compare(
+                "best_block_hash",
+                &self.best_block_hash,
+                &other.best_block_hash,
+            );
+        }
+        if !rules.ignore_mempool {
+            diff.compare("mempool_size", &self.mempool_size, &other.mempool_size);
+        }
+
+        let before: BTreeSet<&String> = self.loaded_wallets.iter().collect();
+        let after: BTreeSet<&String> = other.loaded_wallets.iter().collect();
+        for wallet in before.union(&after) {
+            let path = format!("loaded_wallets/{}", wallet);
+            diff.compare_option(
+                &path,
+                before.contains(wallet).then_some(wallet),
+                after.contains(wallet).then_some(wallet),
+            );
+        }
+
+        let empty = WalletSnapshot::default();
+        let names: BTreeSet<&String> = self.wallets.keys().chain(other.wallets.keys()).collect();
+        for name in names {
+            // A wallet missing from one side diffs as if it held nothing, so every
+            // UTXO it had shows up as removed
+            diff.compare_wallet(
+                &format!("wallets/{}", name),
+                self.wallets.get(name).unwrap_or(&empty),
+                other.wallets.get(name).unwrap_or(&empty),
+            );
+        }
+
+        let (a, b) = (&self.network, &other.network);
+        diff.compare("network/version", &a.version, &b.version);
+        diff.compare("network/subversion", &a.subversion, &b.subversion);
+        diff.compare(
+            "network/protocolversion",
+            &a.protocolversion,
+            &b.protocolversion,
+        );
+        diff.compare("network/localservices", &a.localservices, &b.localservices);
+        diff.compare("network/networkactive", &a.networkactive, &b.networkactive);
+
+        diff.changes.retain(|c| !rules.ignores(&c.path));
+        diff
+    }
+}
+
+impl WalletSnapshot {
+    async fn capture(wallet: &BitcoinClient) -> Result<Self> {
+        let unspent: Vec<SnapshotUnspent> = wallet
+            .call("listunspent", serde_json::json!([0, 9_999_999]))
+            .await?;
+        let mut snapshot = WalletSnapshot::default();
+        for u in unspent {
+            let label = u.label.clone().unwrap_or_default();
+            *snapshot.label_balances.entry(label).or_insert(Amount::ZERO) += u.amount;
+            snapshot.utxos.push(SnapshotUtxo {
+                txid: u.txid,
+                vout: u.vout,
+                amount: u.amount,
+                address: u.address,
+                label: u.label,
+            });
+        }
+        snapshot.utxos.sort();
+        Ok(snapshot)
+    }
+}
+
+// The wallets whose UTXOs go into a snapshot: the client's own when it is scoped to
+// one, otherwise every loaded wallet
+fn snapshot_wallets(client: &BitcoinClient, loaded: &[String]) -> Vec<String> {
+    match client.wallet_name() {
+        Some(name) => vec![name.to_string()],
+        None => loaded.to_vec(),
+    }
+}
+
+struct UtxoDisplay<'a>(&'a SnapshotUtxo);
+
+impl PartialEq for UtxoDisplay<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl fmt::Display for UtxoDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} BTC to {} (label {:?})",
+            self.0.amount,
+            self.0.address.as_deref().unwrap_or("?"),
+            self.0.label.as_deref().unwrap_or("")
+        )
+    }
+}
+
+impl NodeSnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn compare_wallet(&mut self, path: &str, before: &WalletSnapshot, after: &WalletSnapshot) {
+        let labels: BTreeSet<&String> = before
+            .label_balances
+            .keys()
+            .chain(after.label_balances.keys())
+            .collect();
+        for label in labels {
+            self.compare_option(
+                &format!("{}/label_balances/{}", path, label),
+                before.label_balances.get(label),
+                after.label_balances.get(label),
+            );
+        }
+
+        let utxos_before: BTreeMap<String, &SnapshotUtxo> = before
+            .utxos
+            .iter()
+            .map(|u| (format!("{}:{}", u.txid, u.vout), u))
+            .collect();
+        let utxos_after: BTreeMap<String, &SnapshotUtxo> = after
+            .utxos
+            .iter()
+            .map(|u| (format!("{}:{}", u.txid, u.vout), u))
+            .collect();
+        let outpoints: BTreeSet<&String> = utxos_before.keys().chain(utxos_after.keys()).collect();
+        for outpoint in outpoints {
+            self.compare_option(
+                &format!("{}/utxos/{}", path, outpoint),
+                utxos_before.get(outpoint).map(|u| UtxoDisplay(u)),
+                utxos_after.get(outpoint).map(|u| UtxoDisplay(u)),
+            );
+        }
+    }
+
+    fn compare<T: PartialEq + fmt::Display>(&mut self, path: &str, before: &T, after: &T) {
+        self.compare_option(path, Some(before), Some(after));
+    }
+
+    fn compare_option<T: PartialEq + fmt::Display>(
+        &mut self,
+        path: &str,
+        before: Option<T>,
+        after: Option<T>,
+    ) {
+        let kind = match (&before, &after) {
+            (Some(b), Some(a)) if b == a => return,
+            (None, None) => return,
+            (Some(_), Some(_)) => ChangeKind::Changed,
+            (None, Some(_)) => ChangeKind::Added,
+            (Some(_), None) => ChangeKind::Removed,
+        };
+        self.changes.push(SnapshotChange {
+            path: path.to_string(),
+            kind,
+            before: before.map(|b| b.to_string()),
+            after: after.map(|a| a.to_string()),
+        });
+    }
+}
+
+impl fmt::Display for NodeSnapshotDiff {
+    // One line per change: "+ path: after", "- path: before" or "~ path: before -> after"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        for change in &self.changes {
+            let before = change.before.as_deref().unwrap_or("");
+            let after = change.after.as_deref().unwrap_or("");
+            match change.kind {
+                ChangeKind::Added => writeln!(f, "+ {}: {}", change.path, after)?,
+                ChangeKind::Removed => writeln!(f, "- {}: {}", change.path, before)?,
+                ChangeKind::Changed => writeln!(f, "~ {}: {} -> {}", change.path, before, after)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::{BlockchainInfo, NetworkInfo};
+use crate::{BitcoinClient, BitcoinClientType};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeStatus {
+    // Core's numeric version, e.g. 270100 for 27.1.0
+    pub version: u32,
+    pub subversion: String,
+    pub uptime_secs: u64,
+    // "main", "test", "testnet4", "signet" or "regtest"
+    pub chain: String,
+    pub in_ibd: bool,
+}
+
+impl NodeStatus {
+    pub fn is_chain(&self, network: BitcoinClientType) -> bool {
+        serde_json::to_value(network).is_ok_and(|chain| chain == self.chain.as_str())
+    }
+
+    // Fail with a message naming what is wrong when the node is on another chain or
+    // older than `min_version`
+    pub fn require(&self, network: BitcoinClientType, min_version: u32) -> Result<()> {
+        if !self.is_chain(network) {
+            return Err(anyhow!(
+                "Node is on chain {}, expected {:?}",
+                self.chain,
+                network
+            ));
+        }
+        if self.version < min_version {
+            return Err(anyhow!(
+                "Node version {} ({}) is older than the required {}",
+                self.version,
+                self.subversion,
+                min_version
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl BitcoinClient {
+    // One round trip checking the node is reachable and the credentials work. Wrong
+    // credentials fail with `BitcoinRpcError::AuthFailed`, an unreachable node with
+    // `BitcoinRpcError::Connect`. The version is cached for `node_version`.
+    pub async fn ping_node(&self) -> Result<NodeStatus> {
+        let results = self
+            .batch_call(vec![
+                ("uptime".to_string(), Value::Null),
+                ("getnetworkinfo".to_string(), Value::Null),
+                ("getblockchaininfo".to_string(), Value::Null),
+            ])
+            .await?;
+        let uptime_secs: u64 = serde_json::from_value(results[0].clone())?;
+        let network: NetworkInfo = serde_json::from_value(results[1].clone())?;
+        let chain: BlockchainInfo = serde_json::from_value(results[2].clone())?;
+        self.remember_node_version(network.version);
+        Ok(NodeStatus {
+            version: network.version,
+            subversion: network.subversion,
+            uptime_secs,
+            chain: chain.chain,
+            in_ibd: chain.initialblockdownload,
+        })
+    }
+}
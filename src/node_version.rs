@@ -0,0 +1,151 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::Ordering;
+
+use crate::BitcoinClient;
+use crate::error::BitcoinRpcError;
+use crate::types::{BlockchainInfo, NetworkInfo, SoftFork};
+
+// Core's numeric version as reported by `getnetworkinfo`. Up to 0.21 it encodes
+// 0.MINOR.PATCH as MINOR * 10000 + PATCH * 100; from 22.0 on it is
+// MAJOR * 10000 + MINOR * 100 + PATCH, so the numbers stay ordered across the switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct NodeVersion(pub u32);
+
+impl NodeVersion {
+    // `getdeploymentinfo` replaced `getblockchaininfo.softforks`
+    pub const DEPLOYMENT_INFO: NodeVersion = NodeVersion(230000);
+    // `loadtxoutset` and assumeutxo snapshots
+    pub const ASSUME_UTXO: NodeVersion = NodeVersion(260000);
+    // `dumptxoutset` type argument, "latest" or "rollback"
+    pub const SNAPSHOT_TYPE: NodeVersion = NodeVersion(280000);
+    // `importmempool`
+    pub const IMPORT_MEMPOOL: NodeVersion = NodeVersion(250000);
+    // `generateblock` with `submit` false
+    pub const GENERATE_BLOCK_NO_SUBMIT: NodeVersion = NodeVersion(250000);
+    // `migratewallet`
+    pub const MIGRATE_WALLET: NodeVersion = NodeVersion(250000);
+    // `addpeeraddress`, and its `tried` argument from 22.0
+    pub const ADD_PEER_ADDRESS: NodeVersion = NodeVersion(210000);
+    pub const ADD_PEER_ADDRESS_TRIED: NodeVersion = NodeVersion(220000);
+    // `getaddrmaninfo`
+    pub const ADDRMAN_INFO: NodeVersion = NodeVersion(260000);
+    // BIP324 v2 transport
+    pub const V2_TRANSPORT: NodeVersion = NodeVersion(260000);
+    // External signers: `enumeratesigners`, `walletdisplayaddress` and signer wallets
+    pub const EXTERNAL_SIGNER: NodeVersion = NodeVersion(220000);
+    // `sendrawtransaction` took `allowhighfees` before `maxfeerate`
+    pub const MAX_FEE_RATE: NodeVersion = NodeVersion(190000);
+
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        if major == 0 {
+            NodeVersion(minor * 10000 + patch * 100)
+        } else {
+            NodeVersion(major * 10000 + minor * 100 + patch)
+        }
+    }
+
+    fn is_legacy(&self) -> bool {
+        self.0 < 220000
+    }
+
+    pub fn major(&self) -> u32 {
+        if self.is_legacy() { 0 } else { self.0 / 10000 }
+    }
+
+    pub fn minor(&self) -> u32 {
+        if self.is_legacy() {
+            self.0 / 10000
+        } else {
+            self.0 / 100 % 100
+        }
+    }
+
+    pub fn patch(&self) -> u32 {
+        if self.is_legacy() {
+            self.0 / 100 % 100
+        } else {
+            self.0 % 100
+        }
+    }
+
+    pub fn at_least(&self, major: u32, minor: u32) -> bool {
+        *self >= NodeVersion::new(major, minor, 0)
+    }
+}
+
+impl fmt::Display for NodeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major(), self.minor(), self.patch())
+    }
+}
+
+impl BitcoinClient {
+    // Version seen on this client or its clones, without asking the node
+    pub fn node_version(&self) -> Option<NodeVersion> {
+        match self.node_version.load(Ordering::Relaxed) {
+            0 => None,
+            version => Some(NodeVersion(version)),
+        }
+    }
+
+    pub(crate) fn remember_node_version(&self, version: u32) {
+        self.node_version.store(version, Ordering::Relaxed);
+    }
+
+    // The node's version, asking it once and caching the answer
+    pub async fn detect_node_version(&self) -> Result<NodeVersion> {
+        if let Some(version) = self.node_version() {
+            return Ok(version);
+        }
+        let info: NetworkInfo = self.call("getnetworkinfo", Value::Null).await?;
+        self.remember_node_version(info.version);
+        Ok(NodeVersion(info.version))
+    }
+
+    // Fail with `BitcoinRpcError::MethodNotFound` before sending `method` to a node older
+    // than `minimum`
+    pub(crate) async fn require_version(&self, minimum: NodeVersion, method: &str) -> Result<()> {
+        let version = self.detect_node_version().await?;
+        if version < minimum {
+            return Err(BitcoinRpcError::MethodNotFound(format!(
+                "{} needs Bitcoin Core {}.{} or later, node is {}",
+                method,
+                minimum.major(),
+                minimum.minor(),
+                version
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    // Soft fork status from `getdeploymentinfo` on 23.0 and later, from
+    // `getblockchaininfo.softforks` before that
+    pub async fn softforks(&self) -> Result<HashMap<String, SoftFork>> {
+        if self.detect_node_version().await? >= NodeVersion::DEPLOYMENT_INFO {
+            Ok(self.get_deployment_info(None).await?.deployments)
+        } else {
+            let info: BlockchainInfo = self.call("getblockchaininfo", Value::Null).await?;
+            Ok(info.softforks)
+        }
+    }
+
+    // Broadcast with a fee rate cap in BTC/kvB, 0 for no cap. Before 0.19 only a
+    // yes/no `allowhighfees` exists, so any cap there means the node's default one.
+    pub async fn send_raw_transaction_with_max_fee_rate(
+        &self,
+        tx_hex: &str,
+        max_fee_rate: f64,
+    ) -> Result<String> {
+        let params = if self.detect_node_version().await? >= NodeVersion::MAX_FEE_RATE {
+            json!([tx_hex, max_fee_rate])
+        } else {
+            json!([tx_hex, max_fee_rate == 0.0])
+        };
+        self.call("sendrawtransaction", params).await
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::BitcoinClient;
+use crate::amount::Amount;
+
+// Where `wait_for_payment` stood when it returned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaymentStatus {
+    // The expected amount arrived but is not yet buried deep enough; `txid` is the most
+    // recent payment to the address
+    Detected { txid: String, confirmations: i64 },
+    // The expected amount has the required confirmations
+    Confirmed,
+    // The expected amount had not arrived by the deadline
+    TimedOut,
+}
+
+impl BitcoinClient {
+    // Poll until `address` has received `expected` with `min_confirmations`, or until
+    // `deadline` has passed. The address must belong to, or be watched by, this wallet.
+    // Payments seen but not confirmed by the deadline come back as `Detected`.
+    pub async fn wait_for_payment(
+        &self,
+        address: &str,
+        expected: Amount,
+        min_confirmations: u32,
+        poll_interval: Duration,
+        deadline: Duration,
+    ) -> Result<PaymentStatus> {
+        let deadline = Instant::now() + deadline;
+        loop {
+            let status = self
+                .payment_status(address, expected, min_confirmations)
+                .await?;
+            if status == PaymentStatus::Confirmed || Instant::now() >= deadline {
+                return Ok(status);
+            }
+            tokio::time::sleep(
+                poll_interval.min(deadline.saturating_duration_since(Instant::now())),
+            )
+            .await;
+        }
+    }
+
+    async fn payment_status(
+        &self,
+        address: &str,
+        expected: Amount,
+        min_confirmations: u32,
+    ) -> Result<PaymentStatus> {
+        let received = self
+            .list_received_by_address(0, false, true, Some(address))
+            .await?;
+        let Some(entry) = received.into_iter().find(|e| e.address == address) else {
+            return Ok(PaymentStatus::TimedOut);
+        };
+        if entry.amount < expected {
+            return Ok(PaymentStatus::TimedOut);
+        }
+        let confirmed = self
+            .get_received_by_address(address, min_confirmations, None)
+            .await?;
+        if confirmed >= expected {
+            return Ok(PaymentStatus::Confirmed);
+        }
+        match entry.txids.last() {
+            Some(txid) => Ok(PaymentStatus::Detected {
+                txid: txid.clone(),
+                confirmations: entry.confirmations,
+            }),
+            None => Ok(PaymentStatus::TimedOut),
+        }
+    }
+}
@@ -0,0 +1,506 @@
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::BitcoinClient;
+use crate::amount::{Amount, FeeRate};
+use crate::crypto::BitcoinCrypto;
+use crate::serialization::RawTxOutput;
+use crate::types::{
+    FundedPsbtOptions, OutPoint, SendManyOptions, SendManyVerboseResult, SendOptions, SendOutput,
+    SendResult, SendToAddressOptions,
+};
+
+// Standard dust threshold for the largest common output type (P2PKH at 3 sat/vB)
+pub const DEFAULT_DUST_LIMIT: Amount = Amount::from_sat(546);
+
+// Who bears the transaction fee
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeePolicy {
+    SenderPays,
+    // Every recipient gives up a share proportional to its amount
+    SplitProportionally,
+    // Only these addresses give up a share, proportional among them
+    FromOutputs(Vec<String>),
+}
+
+// What to do when an address appears more than once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    Merge,
+    Reject,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PayoutProblem {
+    EmptyBatch,
+    InvalidAddress(String),
+    NonPositiveAmount(String),
+    DuplicateAddress(String),
+    UnknownFeeOutput(String),
+    BelowDust { address: String, amount: Amount },
+    // The fee is larger than the outputs meant to cover it
+    FeeExceedsOutputs { fee: Amount, covered: Amount },
+    InsufficientFunds { required: Amount, available: Amount },
+}
+
+impl fmt::Display for PayoutProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayoutProblem::EmptyBatch => write!(f, "no recipients"),
+            PayoutProblem::InvalidAddress(a) => write!(f, "invalid address {}", a),
+            PayoutProblem::NonPositiveAmount(a) => write!(f, "non-positive amount for {}", a),
+            PayoutProblem::DuplicateAddress(a) => write!(f, "duplicate address {}", a),
+            PayoutProblem::UnknownFeeOutput(a) => {
+                write!(f, "fee output {} is not a recipient", a)
+            }
+            PayoutProblem::BelowDust { address, amount } => {
+                write!(f, "{} would receive {} BTC, below dust", address, amount)
+            }
+            PayoutProblem::FeeExceedsOutputs { fee, covered } => {
+                write!(f, "fee {} BTC exceeds the {} BTC paying it", fee, covered)
+            }
+            PayoutProblem::InsufficientFunds {
+                required,
+                available,
+            } => write!(f, "{} BTC required, {} BTC available", required, available),
+        }
+    }
+}
+
+// Every problem found in a batch, returned (inside anyhow) so callers can fix them all at once
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayoutError {
+    pub problems: Vec<PayoutProblem>,
+}
+
+impl fmt::Display for PayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problems: Vec<String> = self.problems.iter().map(|p| p.to_string()).collect();
+        write!(f, "Invalid payout batch: {}", problems.join("; "))
+    }
+}
+
+impl std::error::Error for PayoutError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayoutOutput {
+    pub address: String,
+    pub requested: Amount,
+    pub fee_share: Amount,
+    // What the recipient actually receives
+    pub amount: Amount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayoutPlan {
+    pub outputs: Vec<PayoutOutput>,
+    pub fee: Amount,
+}
+
+impl PayoutPlan {
+    pub fn total_out(&self) -> Amount {
+        self.outputs.iter().map(|o| o.amount).sum()
+    }
+
+    // Outputs for assembling the transaction locally
+    pub fn to_raw_outputs(&self) -> Result<Vec<RawTxOutput>> {
+        self.outputs
+            .iter()
+            .map(|o| {
+                Ok(RawTxOutput {
+                    value: o.amount.to_sat() as u64,
+                    script_pubkey: BitcoinCrypto::address_to_script_pubkey(&o.address)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PayoutBatch {
+    pub recipients: Vec<(String, Amount)>,
+    pub fee_policy: FeePolicy,
+    pub duplicate_policy: DuplicatePolicy,
+    pub dust_limit: Amount,
+}
+
+impl PayoutBatch {
+    pub fn new(recipients: Vec<(String, Amount)>) -> Self {
+        PayoutBatch {
+            recipients,
+            fee_policy: FeePolicy::SenderPays,
+            duplicate_policy: DuplicatePolicy::Reject,
+            dust_limit: DEFAULT_DUST_LIMIT,
+        }
+    }
+
+    pub fn with_fee_policy(mut self, fee_policy: FeePolicy) -> Self {
+        self.fee_policy = fee_policy;
+        self
+    }
+
+    pub fn with_duplicate_policy(mut self, duplicate_policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = duplicate_policy;
+        self
+    }
+
+    pub fn with_dust_limit(mut self, dust_limit: Amount) -> Self {
+        self.dust_limit = dust_limit;
+        self
+    }
+
+    // Recipients after duplicate handling, in first-seen order
+    fn merged(&self, problems: &mut Vec<PayoutProblem>) -> Vec<(String, Amount)> {
+        let mut merged: Vec<(String, Amount)> = Vec::new();
+        for (address, amount) in &self.recipients {
+            if !BitcoinCrypto::validate_address(address) {
+                problems.push(PayoutProblem::InvalidAddress(address.clone()));
+            }
+            if amount.to_sat() <= 0 {
+                problems.push(PayoutProblem::NonPositiveAmount(address.clone()));
+            }
+            match merged.iter_mut().find(|(a, _)| a == address) {
+                Some((_, total)) if self.duplicate_policy == DuplicatePolicy::Merge => {
+                    *total += *amount
+                }
+                Some(_) => {
+                    let problem = PayoutProblem::DuplicateAddress(address.clone());
+                    if !problems.contains(&problem) {
+                        problems.push(problem);
+                    }
+                }
+                None => merged.push((address.clone(), *amount)),
+            }
+        }
+        if merged.is_empty() {
+            problems.push(PayoutProblem::EmptyBatch);
+        }
+        merged
+    }
+
+    // Whether each merged output helps pay the fee
+    fn fee_payers(
+        &self,
+        merged: &[(String, Amount)],
+        problems: &mut Vec<PayoutProblem>,
+    ) -> Vec<bool> {
+        match &self.fee_policy {
+            FeePolicy::SenderPays => vec![false; merged.len()],
+            FeePolicy::SplitProportionally => vec![true; merged.len()],
+            FeePolicy::FromOutputs(addresses) => {
+                for address in addresses {
+                    if !merged.iter().any(|(a, _)| a == address) {
+                        problems.push(PayoutProblem::UnknownFeeOutput(address.clone()));
+                    }
+                }
+                merged.iter().map(|(a, _)| addresses.contains(a)).collect()
+            }
+        }
+    }
+
+    // Check the batch and share `fee` out according to the fee policy. Shares are
+    // floor(fee * amount / covered) with the leftover satoshis going one each to the
+    // largest remainders, earlier recipients first on ties, so they always add up to
+    // exactly `fee`.
+    pub fn plan(&self, fee: Amount, available: Option<Amount>) -> Result<PayoutPlan> {
+        let mut problems = Vec::new();
+        let merged = self.merged(&mut problems);
+        let payers = self.fee_payers(&merged, &mut problems);
+
+        let covered: i128 = merged
+            .iter()
+            .zip(&payers)
+            .filter(|(_, payer)| **payer)
+            .map(|((_, amount), _)| amount.to_sat() as i128)
+            .sum();
+        let fee_sat = fee.to_sat() as i128;
+        let mut shares = vec![0i128; merged.len()];
+        if self.fee_policy != FeePolicy::SenderPays && fee_sat > 0 {
+            if covered < fee_sat {
+                problems.push(PayoutProblem::FeeExceedsOutputs {
+                    fee,
+                    covered: Amount::from_sat(covered as i64),
+                });
+            } else {
+                let mut remainders = Vec::new();
+                for (i, ((_, amount), payer)) in merged.iter().zip(&payers).enumerate() {
+                    if *payer {
+                        let scaled = fee_sat * amount.to_sat() as i128;
+                        shares[i] = scaled / covered;
+                        remainders.push((scaled % covered, i));
+                    }
+                }
+                let leftover = fee_sat - shares.iter().sum::<i128>();
+                remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+                for (_, i) in remainders.into_iter().take(leftover as usize) {
+                    shares[i] += 1;
+                }
+            }
+        }
+
+        let outputs: Vec<PayoutOutput> = merged
+            .into_iter()
+            .zip(shares)
+            .map(|((address, requested), share)| {
+                let fee_share = Amount::from_sat(share as i64);
+                PayoutOutput {
+                    address,
+                    requested,
+                    fee_share,
+                    amount: requested - fee_share,
+                }
+            })
+            .collect();
+        for output in &outputs {
+            if output.requested.to_sat() > 0 && output.amount < self.dust_limit {
+                problems.push(PayoutProblem::BelowDust {
+                    address: output.address.clone(),
+                    amount: output.amount,
+                });
+            }
+        }
+
+        let requested: Amount = outputs.iter().map(|o| o.requested).sum();
+        let required = match self.fee_policy {
+            FeePolicy::SenderPays => requested + fee,
+            _ => requested,
+        };
+        if let Some(available) = available
+            && required > available
+        {
+            problems.push(PayoutProblem::InsufficientFunds {
+                required,
+                available,
+            });
+        }
+
+        if !problems.is_empty() {
+            return Err(PayoutError { problems }.into());
+        }
+        Ok(PayoutPlan { outputs, fee })
+    }
+
+    // Fund, sign and broadcast through the node wallet, optionally at a fixed fee rate.
+    // When recipients share the fee, the wallet first funds the original amounts to learn
+    // the fee, then the same inputs are funded again with the shared-out amounts.
+    pub async fn send(&self, client: &BitcoinClient, fee_rate: Option<FeeRate>) -> Result<String> {
+        let available: Amount = client.call("getbalance", Value::Null).await?;
+        let options = FundedPsbtOptions {
+            fee_rate,
+            ..Default::default()
+        };
+
+        if self.fee_policy == FeePolicy::SenderPays {
+            let plan = self.plan(Amount::ZERO, Some(available))?;
+            let amounts: HashMap<String, Amount> = plan
+                .outputs
+                .into_iter()
+                .map(|o| (o.address, o.amount))
+                .collect();
+            let send_options = SendManyOptions {
+                fee_rate,
+                ..Default::default()
+            };
+            return client.send_many(amounts, send_options).await;
+        }
+
+        // Validate before touching the wallet so every problem is reported up front
+        let unfunded = self.plan(Amount::ZERO, Some(available))?;
+        let payers: Vec<u32> = unfunded
+            .outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| match &self.fee_policy {
+                FeePolicy::FromOutputs(addresses) => addresses.contains(&o.address),
+                _ => true,
+            })
+            .map(|(i, _)| i as u32)
+            .collect();
+        let requested: Vec<SendOutput> = unfunded
+            .outputs
+            .iter()
+            .map(|o| SendOutput::address(&o.address, o.requested))
+            .collect();
+        let first_options = FundedPsbtOptions {
+            subtract_fee_from_outputs: payers,
+            ..options.clone()
+        };
+        let first = client
+            .wallet_create_funded_psbt(&[], &requested, 0, first_options, true)
+            .await?;
+
+        let plan = self.plan(first.fee, Some(available))?;
+        let decoded = client.decode_psbt(&first.psbt).await?;
+        let inputs = decoded
+            .tx
+            .vin
+            .iter()
+            .map(|vin| match (&vin.txid, vin.vout) {
+                (Some(txid), Some(vout)) => Ok(OutPoint {
+                    txid: txid.clone(),
+                    vout,
+                }),
+                _ => Err(anyhow!("Funded PSBT has an input without an outpoint")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let planned: Vec<SendOutput> = plan
+            .outputs
+            .iter()
+            .map(|o| SendOutput::address(&o.address, o.amount))
+            .collect();
+        let second = client
+            .wallet_create_funded_psbt(&inputs, &planned, 0, options.add_inputs(false), true)
+            .await?;
+        if second.fee != first.fee {
+            return Err(anyhow!(
+                "Fee changed between funding passes: {} then {} BTC",
+                first.fee,
+                second.fee
+            ));
+        }
+
+        let signed = client
+            .wallet_process_psbt(&second.psbt, true, None, true, false)
+            .await?;
+        let finalized = client.finalize_psbt(&signed.psbt, true).await?;
+        match (finalized.complete, finalized.hex) {
+            (true, Some(hex)) => client.send_raw_transaction(&hex).await,
+            _ => Err(anyhow!("Wallet could not sign every input")),
+        }
+    }
+}
+
+// `sendmany` parameters, without the trailing unset ones so nodes that predate them
+// still accept the call. Amount and fee-output problems come back as `PayoutError`.
+fn send_many_params(
+    amounts: &HashMap<String, Amount>,
+    options: &SendManyOptions,
+    verbose: bool,
+) -> Result<Value> {
+    let mut problems = Vec::new();
+    if amounts.is_empty() {
+        problems.push(PayoutProblem::EmptyBatch);
+    }
+    let mut addresses: Vec<&String> = amounts.keys().collect();
+    addresses.sort();
+    for address in addresses {
+        if amounts[address].to_sat() <= 0 {
+            problems.push(PayoutProblem::NonPositiveAmount(address.clone()));
+        }
+    }
+    for address in &options.subtract_fee_from {
+        if !amounts.contains_key(address) {
+            problems.push(PayoutProblem::UnknownFeeOutput(address.clone()));
+        }
+    }
+    if !problems.is_empty() {
+        return Err(PayoutError { problems }.into());
+    }
+
+    let mut params = vec![
+        json!(""),
+        json!(amounts),
+        json!(options.minconf),
+        json!(options.comment),
+        if options.subtract_fee_from.is_empty() {
+            Value::Null
+        } else {
+            json!(options.subtract_fee_from)
+        },
+        json!(options.replaceable),
+        json!(options.conf_target),
+        json!(options.estimate_mode),
+        json!(options.fee_rate),
+        if verbose { json!(true) } else { Value::Null },
+    ];
+    while params.last().is_some_and(Value::is_null) {
+        params.pop();
+    }
+    Ok(Value::Array(params))
+}
+
+// Named `sendtoaddress` parameters, checking the amount before the round trip
+fn send_to_address_params(
+    address: &str,
+    amount: Amount,
+    options: &SendToAddressOptions,
+    verbose: bool,
+) -> Result<Value> {
+    if amount.to_sat() <= 0 {
+        return Err(PayoutError {
+            problems: vec![PayoutProblem::NonPositiveAmount(address.to_string())],
+        }
+        .into());
+    }
+    let mut params = serde_json::to_value(options)?;
+    params["address"] = json!(address);
+    params["amount"] = json!(amount);
+    if verbose {
+        params["verbose"] = json!(true);
+    }
+    Ok(params)
+}
+
+impl BitcoinClient {
+    // Pay one address with fee, RBF and comment options, returning the txid
+    pub async fn send_to_address_with_options(
+        &self,
+        address: &str,
+        amount: Amount,
+        options: SendToAddressOptions,
+    ) -> Result<String> {
+        let params = send_to_address_params(address, amount, &options, false)?;
+        self.call("sendtoaddress", params).await
+    }
+
+    // Like `send_to_address_with_options`, also reporting how the fee was chosen.
+    // Needs 21.0 or later.
+    pub async fn send_to_address_verbose(
+        &self,
+        address: &str,
+        amount: Amount,
+        options: SendToAddressOptions,
+    ) -> Result<SendManyVerboseResult> {
+        let params = send_to_address_params(address, amount, &options, true)?;
+        self.call("sendtoaddress", params).await
+    }
+
+    // Pay several addresses in one transaction, returning its txid
+    pub async fn send_many(
+        &self,
+        amounts: HashMap<String, Amount>,
+        options: SendManyOptions,
+    ) -> Result<String> {
+        let params = send_many_params(&amounts, &options, false)?;
+        self.call("sendmany", params).await
+    }
+
+    // Like `send_many`, also reporting how the fee was chosen. Needs 21.0 or later.
+    pub async fn send_many_verbose(
+        &self,
+        amounts: HashMap<String, Amount>,
+        options: SendManyOptions,
+    ) -> Result<SendManyVerboseResult> {
+        let params = send_many_params(&amounts, &options, true)?;
+        self.call("sendmany", params).await
+    }
+
+    // Fund, sign and by default broadcast a transaction paying `outputs`. When the wallet
+    // cannot sign every input, or `add_to_wallet` is false, the result carries a PSBT
+    // or hex for signing or broadcasting elsewhere. Needs 21.0 or later.
+    pub async fn send(&self, outputs: Vec<SendOutput>, options: SendOptions) -> Result<SendResult> {
+        if outputs.is_empty() {
+            return Err(PayoutError {
+                problems: vec![PayoutProblem::EmptyBatch],
+            }
+            .into());
+        }
+        self.call(
+            "send",
+            json!([outputs, Value::Null, Value::Null, Value::Null, options]),
+        )
+        .await
+    }
+}
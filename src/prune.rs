@@ -0,0 +1,279 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::BitcoinClient;
+use crate::error::{BitcoinRpcError, RPC_INTERNAL_ERROR, RPC_MISC_ERROR};
+use crate::index::{BlockDelta, CreatedOutput, SpentOutput, iter_created_outputs};
+use crate::script::ScriptTemplateRegistry;
+use crate::types::{BlockRef, BlockStats, BlockVerbose};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PruneStatus {
+    pub pruned: bool,
+    // Lowest height whose block data is still stored
+    pub prune_height: Option<u64>,
+    pub automatic: Option<bool>,
+    pub target_size: Option<u64>,
+}
+
+impl PruneStatus {
+    // Lowest height with block data, 0 on an unpruned node
+    pub fn first_available(&self) -> u64 {
+        if self.pruned {
+            self.prune_height.unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    pub fn is_available(&self, height: u64) -> bool {
+        height >= self.first_available()
+    }
+
+    // Fail with `BlockPruned` when the block at `height` is gone
+    pub fn check(&self, height: u64) -> Result<()> {
+        if self.is_available(height) {
+            Ok(())
+        } else {
+            Err(BlockPruned {
+                height,
+                prune_height: self.first_available(),
+            }
+            .into())
+        }
+    }
+
+    // Move the start of a range up to the prune height, noting when that happened
+    pub fn clamp(
+        &self,
+        range: RangeInclusive<u64>,
+    ) -> (RangeInclusive<u64>, Option<ClampedToPruneHeight>) {
+        let first = self.first_available();
+        if *range.start() >= first {
+            return (range, None);
+        }
+        let notice = ClampedToPruneHeight {
+            requested_start: *range.start(),
+            clamped_start: first,
+        };
+        (first..=*range.end(), Some(notice))
+    }
+}
+
+// Returned (inside anyhow) in place of Core's -1 "Block not available (pruned data)"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockPruned {
+    pub height: u64,
+    pub prune_height: u64,
+}
+
+impl fmt::Display for BlockPruned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Block {} is pruned, data is kept from height {}",
+            self.height, self.prune_height
+        )
+    }
+}
+
+impl std::error::Error for BlockPruned {}
+
+// Returned (inside anyhow) by `prune_blockchain` on a node started without -prune
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotPruneMode;
+
+impl fmt::Display for NotPruneMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cannot prune blocks because node is not in prune mode")
+    }
+}
+
+impl std::error::Error for NotPruneMode {}
+
+// Returned (inside anyhow) by imports asked to rescan on a pruned node; import with
+// `rescan` off and call `rescan_blockchain` over the heights still stored instead
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RescanDisabledWhenPruned;
+
+impl fmt::Display for RescanDisabledWhenPruned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rescan is disabled when blocks are pruned")
+    }
+}
+
+impl std::error::Error for RescanDisabledWhenPruned {}
+
+// Attached to results whose range was shortened because older blocks are pruned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClampedToPruneHeight {
+    pub requested_start: u64,
+    pub clamped_start: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockStatsRange {
+    pub stats: Vec<BlockStats>,
+    pub clamped: Option<ClampedToPruneHeight>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockRange {
+    pub blocks: Vec<BlockVerbose>,
+    pub clamped: Option<ClampedToPruneHeight>,
+}
+
+// Outputs paying the scanned addresses, in chain order
+#[derive(Debug, Clone)]
+pub struct AddressScan {
+    pub outputs: Vec<CreatedOutput>,
+    pub clamped: Option<ClampedToPruneHeight>,
+}
+
+// Outputs created or spent in the scanned range whose script matched a template, in
+// chain order
+#[derive(Debug, Clone)]
+pub struct TemplateScan {
+    pub created: Vec<CreatedOutput>,
+    pub spent: Vec<SpentOutput>,
+    pub clamped: Option<ClampedToPruneHeight>,
+}
+
+impl BitcoinClient {
+    pub async fn prune_status(&self) -> Result<PruneStatus> {
+        let info = self.get_blockchain_info().await?;
+        Ok(PruneStatus {
+            pruned: info.pruned,
+            prune_height: info.pruneheight,
+            automatic: info.automatic_pruning,
+            target_size: info.prune_target_size,
+        })
+    }
+
+    // Prune block data up to `height` (a height, or a unix time to prune blocks older
+    // than), returning the height of the last block pruned. Needs -prune=1 for
+    // manual pruning; on an unpruned node fails with `NotPruneMode` without calling.
+    pub async fn prune_blockchain(&self, height: u64) -> Result<u64> {
+        if !self.prune_status().await?.pruned {
+            return Err(NotPruneMode.into());
+        }
+        self.call("pruneblockchain", json!([height]))
+            .await
+            .map_err(|e| match e.downcast_ref::<BitcoinRpcError>() {
+                // -1 is the only misc error `pruneblockchain` raises
+                Some(error) if error.code() == Some(RPC_MISC_ERROR) => NotPruneMode.into(),
+                _ => e,
+            })
+    }
+
+    // The part of `range` whose blocks are still stored
+    pub async fn available_block_range(
+        &self,
+        range: RangeInclusive<u64>,
+    ) -> Result<(RangeInclusive<u64>, Option<ClampedToPruneHeight>)> {
+        Ok(self.prune_status().await?.clamp(range))
+    }
+
+    // Stats for every stored block in the range, skipping pruned heights
+    pub async fn get_block_stats_range(
+        &self,
+        range: RangeInclusive<u64>,
+    ) -> Result<BlockStatsRange> {
+        let (range, clamped) = self.available_block_range(range).await?;
+        let mut stats = Vec::new();
+        for height in range {
+            stats.push(self.get_block_stats(height).await?);
+        }
+        Ok(BlockStatsRange { stats, clamped })
+    }
+
+    // Every stored block in the range with decoded transactions, skipping pruned heights
+    pub async fn get_blocks_range(&self, range: RangeInclusive<u64>) -> Result<BlockRange> {
+        let (range, clamped) = self.available_block_range(range).await?;
+        let mut blocks = Vec::new();
+        for height in range {
+            let hash = self.get_block_hash(height).await?;
+            blocks.push(self.get_block_verbose(&hash).await?);
+        }
+        Ok(BlockRange { blocks, clamped })
+    }
+
+    // Outputs created in the range that pay any of `addresses`, read block by block so
+    // it works without a wallet or -txindex. Pruned heights are skipped and noted.
+    pub async fn scan_blocks_for_addresses(
+        &self,
+        addresses: &[&str],
+        range: RangeInclusive<u64>,
+    ) -> Result<AddressScan> {
+        let (range, clamped) = self.available_block_range(range).await?;
+        let mut outputs = Vec::new();
+        for height in range {
+            let hash = self.get_block_hash(height).await?;
+            let block = self.get_block_verbose(&hash).await?;
+            for created in iter_created_outputs(&block) {
+                let created = created?;
+                if created.address.is_some_and(|a| addresses.contains(&a)) {
+                    outputs.push(CreatedOutput::from(created));
+                }
+            }
+        }
+        Ok(AddressScan { outputs, clamped })
+    }
+
+    // Outputs created or spent in the range whose script matches a template in
+    // `registry`, attributed as each block is read. Blocks are fetched with prevouts so
+    // spent scripts are known, which needs v23 or newer. Pruned heights are skipped and
+    // noted.
+    pub async fn scan_blocks_for_templates(
+        &self,
+        registry: &ScriptTemplateRegistry,
+        range: RangeInclusive<u64>,
+    ) -> Result<TemplateScan> {
+        let (range, clamped) = self.available_block_range(range).await?;
+        let mut created = Vec::new();
+        let mut spent = Vec::new();
+        for height in range {
+            let hash = self.get_block_hash(height).await?;
+            let block = self.get_block_with_prevouts(&hash).await?;
+            let delta = BlockDelta::from_block_with_templates(&block, registry)?;
+            created.extend(delta.created.into_iter().filter(|o| o.template.is_some()));
+            spent.extend(delta.spent.into_iter().filter(|o| o.template.is_some()));
+        }
+        Ok(TemplateScan {
+            created,
+            spent,
+            clamped,
+        })
+    }
+
+    // Turn Core's pruned-data error into `BlockPruned`, leaving other errors alone. Core
+    // reports missing block data as -1, or -32603 from `gettxoutproof`, and uses both for
+    // other failures too, so the block must also be below the prune height.
+    pub(crate) async fn explain_pruned(
+        &self,
+        block: &BlockRef,
+        err: anyhow::Error,
+    ) -> anyhow::Error {
+        let unreadable = err
+            .downcast_ref::<BitcoinRpcError>()
+            .is_some_and(|rpc| matches!(rpc.code(), Some(RPC_MISC_ERROR | RPC_INTERNAL_ERROR)));
+        if !unreadable {
+            return err;
+        }
+        let height = match block {
+            BlockRef::Height(height) => Some(*height),
+            BlockRef::Hash(hash) => self.get_block_header(hash).await.ok().map(|h| h.height),
+        };
+        match (height, self.prune_status().await) {
+            (Some(height), Ok(status)) if !status.is_available(height) => BlockPruned {
+                height,
+                prune_height: status.first_available(),
+            }
+            .into(),
+            _ => err,
+        }
+    }
+}
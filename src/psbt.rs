@@ -0,0 +1,175 @@
+use anyhow::Result;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::BitcoinClient;
+use crate::error::BitcoinRpcError;
+use crate::types::{
+    CreateTxInput, DecodedPsbt, FinalizePsbtResult, FundedPsbtOptions, OutPoint, PsbtAnalysis,
+    ScanObject, SendOutput, SighashType, WalletCreateFundedPsbtResult, WalletProcessPsbtResult,
+};
+
+// Why the node refused to combine, join or convert PSBTs. Returned inside anyhow.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PsbtError {
+    // The PSBTs describe different transactions, or share inputs they may not
+    #[error("PSBTs not compatible: {0}")]
+    Incompatible(String),
+    // A PSBT or transaction could not be decoded
+    #[error("Malformed PSBT or transaction: {0}")]
+    Malformed(String),
+}
+
+impl PsbtError {
+    // None for failures with other causes
+    fn classify(code: i32, message: &str) -> Option<Self> {
+        let message = message.to_string();
+        let lower = message.to_lowercase();
+        // RPC_DESERIALIZATION_ERROR
+        if code == -22 {
+            Some(PsbtError::Malformed(message))
+        } else if lower.contains("not compatible") || lower.contains("exists in multiple psbts") {
+            Some(PsbtError::Incompatible(message))
+        } else {
+            None
+        }
+    }
+}
+
+// Recognized node-side rejections become `PsbtError`; anything else passes through
+fn psbt_error(error: anyhow::Error) -> anyhow::Error {
+    match error
+        .downcast_ref::<BitcoinRpcError>()
+        .and_then(|rpc| PsbtError::classify(rpc.code()?, rpc.message()))
+    {
+        Some(psbt_error) => psbt_error.into(),
+        None => error,
+    }
+}
+
+impl BitcoinClient {
+    // Build a PSBT paying `outputs`, with the wallet adding inputs and change as needed
+    // unless `options.add_inputs` is false. `bip32derivs` includes key origins for
+    // external signers.
+    pub async fn wallet_create_funded_psbt(
+        &self,
+        inputs: &[OutPoint],
+        outputs: &[SendOutput],
+        locktime: u32,
+        options: FundedPsbtOptions,
+        bip32derivs: bool,
+    ) -> Result<WalletCreateFundedPsbtResult> {
+        self.call(
+            "walletcreatefundedpsbt",
+            json!([inputs, outputs, locktime, options, bip32derivs]),
+        )
+        .await
+    }
+
+    // Add the wallet's UTXO data and key origins to a PSBT and, when `sign` is set, sign
+    // the inputs it can. `sighash_type` None uses the node's default.
+    pub async fn wallet_process_psbt(
+        &self,
+        psbt: &str,
+        sign: bool,
+        sighash_type: Option<SighashType>,
+        bip32derivs: bool,
+        finalize: bool,
+    ) -> Result<WalletProcessPsbtResult> {
+        self.call(
+            "walletprocesspsbt",
+            json!([psbt, sign, sighash_type, bip32derivs, finalize]),
+        )
+        .await
+    }
+
+    // Unsigned PSBT spending exactly `inputs`; nothing is added or checked against a
+    // wallet. `replaceable` signals BIP125 on inputs without an explicit sequence.
+    pub async fn create_psbt(
+        &self,
+        inputs: &[CreateTxInput],
+        outputs: &[SendOutput],
+        locktime: u32,
+        replaceable: bool,
+    ) -> Result<String> {
+        self.call(
+            "createpsbt",
+            json!([inputs, outputs, locktime, replaceable]),
+        )
+        .await
+    }
+
+    pub async fn decode_psbt(&self, psbt: &str) -> Result<DecodedPsbt> {
+        self.call("decodepsbt", json!([psbt])).await
+    }
+
+    // Per-input state of a PSBT and who has to act on it next
+    pub async fn analyze_psbt(&self, psbt: &str) -> Result<PsbtAnalysis> {
+        self.call("analyzepsbt", json!([psbt])).await
+    }
+
+    // Finalize the inputs that are fully signed. With `extract` and every input final,
+    // the result carries the network transaction instead of the PSBT.
+    pub async fn finalize_psbt(&self, psbt: &str, extract: bool) -> Result<FinalizePsbtResult> {
+        self.call("finalizepsbt", json!([psbt, extract])).await
+    }
+
+    // Merge the signatures and other data of PSBTs for the same transaction
+    pub async fn combine_psbt(&self, psbts: &[&str]) -> Result<String> {
+        self.call("combinepsbt", json!([psbts]))
+            .await
+            .map_err(psbt_error)
+    }
+
+    // One PSBT with the inputs and outputs of all of `psbts`, e.g. for a coinjoin
+    pub async fn join_psbts(&self, psbts: &[&str]) -> Result<String> {
+        self.call("joinpsbts", json!([psbts]))
+            .await
+            .map_err(psbt_error)
+    }
+
+    // Fill in UTXOs from the node's UTXO set, and from `descriptors` the scripts and key
+    // origins needed to sign
+    pub async fn utxo_update_psbt(
+        &self,
+        psbt: &str,
+        descriptors: Option<Vec<ScanObject>>,
+    ) -> Result<String> {
+        let params = match descriptors {
+            Some(descriptors) => json!([psbt, descriptors]),
+            None => json!([psbt]),
+        };
+        self.call("utxoupdatepsbt", params)
+            .await
+            .map_err(psbt_error)
+    }
+
+    // PSBT from a raw transaction. Any signatures are dropped when `permitsigdata` is
+    // set and refused otherwise; `iswitness` None lets the node guess the encoding.
+    pub async fn convert_to_psbt(
+        &self,
+        tx_hex: &str,
+        permitsigdata: bool,
+        iswitness: Option<bool>,
+    ) -> Result<String> {
+        let params = match iswitness {
+            Some(iswitness) => json!([tx_hex, permitsigdata, iswitness]),
+            None => json!([tx_hex, permitsigdata]),
+        };
+        self.call("converttopsbt", params).await.map_err(psbt_error)
+    }
+
+    // Combine the copies each signer returned with the original and finalize, extracting
+    // the transaction once enough signatures are in
+    pub async fn collect_signatures(
+        &self,
+        base_psbt: &str,
+        signed_psbts: Vec<String>,
+    ) -> Result<FinalizePsbtResult> {
+        let psbts: Vec<&str> = std::iter::once(base_psbt)
+            .chain(signed_psbts.iter().map(String::as_str))
+            .collect();
+        let combined = self.combine_psbt(&psbts).await?;
+        self.finalize_psbt(&combined, true).await
+    }
+}
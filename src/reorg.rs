@@ -0,0 +1,287 @@
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+
+use crate::BitcoinClient;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScenarioNode {
+    A,
+    B,
+}
+
+#[derive(Debug, Clone)]
+pub enum ScenarioStep {
+    // Stop both nodes from relaying to each other
+    Partition,
+    // Re-enable networking on both nodes
+    Reconnect,
+    // Mine blocks with no transactions besides the coinbase on a node, leaving its
+    // mempool as it was
+    Mine {
+        node: ScenarioNode,
+        blocks: u32,
+    },
+    // Mine one block containing exactly these raw transactions or txids
+    MineWith {
+        node: ScenarioNode,
+        txs: Vec<String>,
+    },
+    // Submit the blocks of one node's chain that the other node has not seen
+    Release {
+        from: ScenarioNode,
+    },
+    // Invalidate the tip of a node
+    InvalidateTip {
+        node: ScenarioNode,
+    },
+    Checkpoint(String),
+}
+
+// State observed on node A, the node the application under test is watching
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub name: String,
+    pub tip_hash: String,
+    pub height: u64,
+}
+
+// Two linked regtest nodes driven by scenarios
+#[derive(Debug, Clone)]
+pub struct ReorgHarness {
+    pub node_a: BitcoinClient,
+    pub node_b: BitcoinClient,
+    pub mining_address: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReorgScenario {
+    pub name: String,
+    pub steps: Vec<ScenarioStep>,
+}
+
+impl ReorgScenario {
+    pub fn new(name: &str) -> Self {
+        ReorgScenario {
+            name: name.to_string(),
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn step(mut self, step: ScenarioStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn partition(self) -> Self {
+        self.step(ScenarioStep::Partition)
+    }
+
+    pub fn reconnect(self) -> Self {
+        self.step(ScenarioStep::Reconnect)
+    }
+
+    pub fn mine(self, node: ScenarioNode, blocks: u32) -> Self {
+        self.step(ScenarioStep::Mine { node, blocks })
+    }
+
+    pub fn mine_with(self, node: ScenarioNode, txs: &[&str]) -> Self {
+        self.step(ScenarioStep::MineWith {
+            node,
+            txs: txs.iter().map(|t| t.to_string()).collect(),
+        })
+    }
+
+    pub fn release(self, from: ScenarioNode) -> Self {
+        self.step(ScenarioStep::Release { from })
+    }
+
+    pub fn invalidate_tip(self, node: ScenarioNode) -> Self {
+        self.step(ScenarioStep::InvalidateTip { node })
+    }
+
+    pub fn checkpoint(self, name: &str) -> Self {
+        self.step(ScenarioStep::Checkpoint(name.to_string()))
+    }
+
+    // A deposit confirms to depth 2 on A, then B's longer branch with a conflicting spend wins
+    pub fn deposit_reorged_by_conflict(deposit_tx: &str, conflict_tx: &str) -> Self {
+        Self::new("deposit-reorged-by-conflict")
+            .partition()
+            .mine_with(ScenarioNode::A, &[deposit_tx])
+            .mine(ScenarioNode::A, 1)
+            .checkpoint("deposit-confirmed")
+            .mine_with(ScenarioNode::B, &[conflict_tx])
+            .mine(ScenarioNode::B, 2)
+            .release(ScenarioNode::B)
+            .checkpoint("conflict-confirmed")
+            .reconnect()
+    }
+
+    // The original confirms on A while its fee bump confirms on B's longer branch
+    pub fn fee_bump_split(original_tx: &str, bumped_tx: &str) -> Self {
+        Self::new("fee-bump-split")
+            .partition()
+            .mine_with(ScenarioNode::A, &[original_tx])
+            .checkpoint("original-confirmed")
+            .mine_with(ScenarioNode::B, &[bumped_tx])
+            .mine(ScenarioNode::B, 1)
+            .release(ScenarioNode::B)
+            .checkpoint("bump-confirmed")
+            .reconnect()
+    }
+
+    // B withholds a longer chain, then releases it to reorg `depth` blocks on A
+    pub fn withheld_blocks(depth: u32) -> Self {
+        Self::new("withheld-blocks")
+            .partition()
+            .mine(ScenarioNode::A, depth)
+            .checkpoint("before-release")
+            .mine(ScenarioNode::B, depth + 1)
+            .release(ScenarioNode::B)
+            .checkpoint("after-reorg")
+            .reconnect()
+    }
+
+    pub fn start<'a>(&'a self, harness: &'a ReorgHarness) -> ScenarioRun<'a> {
+        ScenarioRun {
+            scenario: self,
+            harness,
+            position: 0,
+        }
+    }
+
+    // Run every step, collecting the checkpoints
+    pub async fn run(&self, harness: &ReorgHarness) -> Result<Vec<Checkpoint>> {
+        let mut run = self.start(harness);
+        let mut checkpoints = Vec::new();
+        while let Some(checkpoint) = run.next_checkpoint().await? {
+            checkpoints.push(checkpoint);
+        }
+        Ok(checkpoints)
+    }
+}
+
+// Step-by-step execution so tests can assert between checkpoints
+pub struct ScenarioRun<'a> {
+    scenario: &'a ReorgScenario,
+    harness: &'a ReorgHarness,
+    position: usize,
+}
+
+impl ScenarioRun<'_> {
+    // Execute steps up to and including the next checkpoint
+    pub async fn next_checkpoint(&mut self) -> Result<Option<Checkpoint>> {
+        while let Some(step) = self.scenario.steps.get(self.position) {
+            self.position += 1;
+            if let Some(checkpoint) = self.execute(step).await.map_err(|e| {
+                anyhow!(
+                    "Scenario {} failed at step {} ({:?}): {}",
+                    self.scenario.name,
+                    self.position,
+                    step,
+                    e
+                )
+            })? {
+                return Ok(Some(checkpoint));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn execute(&self, step: &ScenarioStep) -> Result<Option<Checkpoint>> {
+        let harness = self.harness;
+        match step {
+            ScenarioStep::Partition => {
+                for node in [&harness.node_a, &harness.node_b] {
+                    node.call::<Value>("setnetworkactive", json!([false]))
+                        .await?;
+                }
+            }
+            ScenarioStep::Reconnect => {
+                for node in [&harness.node_a, &harness.node_b] {
+                    node.call::<Value>("setnetworkactive", json!([true]))
+                        .await?;
+                }
+            }
+            // `generatetoaddress` would sweep in whatever the mempool holds, e.g. the
+            // conflicting spend meant for the other branch
+            ScenarioStep::Mine { node, blocks } => {
+                for _ in 0..*blocks {
+                    harness
+                        .node(*node)
+                        .generate_block(harness.mining_address.as_str(), &[], true)
+                        .await?;
+                }
+            }
+            ScenarioStep::MineWith { node, txs } => {
+                let txs: Vec<&str> = txs.iter().map(String::as_str).collect();
+                harness
+                    .node(*node)
+                    .generate_block(harness.mining_address.as_str(), &txs, true)
+                    .await?;
+            }
+            ScenarioStep::Release { from } => {
+                let to = match from {
+                    ScenarioNode::A => ScenarioNode::B,
+                    ScenarioNode::B => ScenarioNode::A,
+                };
+                harness.release(*from, to).await?;
+            }
+            ScenarioStep::InvalidateTip { node } => {
+                let client = harness.node(*node);
+                let tip = client.get_best_block_hash().await?;
+                client.invalidate_block(&tip).await?;
+            }
+            ScenarioStep::Checkpoint(name) => {
+                let tip_hash = harness.node_a.get_best_block_hash().await?;
+                let height = harness.node_a.get_block_count().await?;
+                return Ok(Some(Checkpoint {
+                    name: name.clone(),
+                    tip_hash,
+                    height,
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl ReorgHarness {
+    pub fn new(node_a: BitcoinClient, node_b: BitcoinClient, mining_address: &str) -> Self {
+        ReorgHarness {
+            node_a,
+            node_b,
+            mining_address: mining_address.to_string(),
+        }
+    }
+
+    pub fn node(&self, node: ScenarioNode) -> &BitcoinClient {
+        match node {
+            ScenarioNode::A => &self.node_a,
+            ScenarioNode::B => &self.node_b,
+        }
+    }
+
+    // Copy the blocks `from` has beyond its fork point with `to`, oldest first
+    async fn release(&self, from: ScenarioNode, to: ScenarioNode) -> Result<()> {
+        let source = self.node(from);
+        let target = self.node(to);
+        let mut missing = Vec::new();
+        let mut hash = source.get_best_block_hash().await?;
+        while target.get_block_header(&hash).await.is_err() {
+            let header = source.get_block_header(&hash).await?;
+            missing.push(hash);
+            hash = header
+                .previousblockhash
+                .ok_or_else(|| anyhow!("No common ancestor between the nodes"))?;
+        }
+        for hash in missing.iter().rev() {
+            let block_hex = source.get_block_hex(hash).await?;
+            let rejection = target.submit_block(&block_hex).await?;
+            if let Some(reason) = rejection.filter(|r| r != "duplicate" && r != "inconclusive") {
+                return Err(anyhow!("Block {} rejected: {}", hash, reason));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use anyhow::{Result, anyhow};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::amount::Amount;
+use crate::error::BitcoinRpcError;
+use crate::types::{
+    BitcoinClientType, Block, BlockHeader, BlockchainInfo, OutPoint, ScriptPubKey, Transaction,
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+// The node refuses larger header and outpoint requests
+const MAX_HEADERS: u32 = 2000;
+const MAX_OUTPOINTS: usize = 15;
+
+// Response encoding, chosen by the suffix of the REST path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestFormat {
+    Bin,
+    Hex,
+    Json,
+}
+
+impl RestFormat {
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            RestFormat::Bin => "bin",
+            RestFormat::Hex => "hex",
+            RestFormat::Json => "json",
+        }
+    }
+}
+
+// Failure status from the REST interface. Returned inside anyhow like `BitcoinRpcError`,
+// which still covers transport failures.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RestError {
+    // 404, the block, transaction or path is unknown, or -rest is off
+    #[error("REST resource not found: {0}")]
+    NotFound(String),
+    // 400, the node rejected the hash, count or outpoints
+    #[error("REST bad request: {0}")]
+    BadRequest(String),
+    #[error("REST HTTP error {status}: {body}")]
+    Http { status: u16, body: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestUtxo {
+    pub height: u64,
+    pub value: Amount,
+    #[serde(alias = "scriptPubKey")]
+    pub script_pub_key: ScriptPubKey,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestUtxos {
+    #[serde(alias = "chainHeight")]
+    pub chain_height: u64,
+    #[serde(alias = "chaintipHash")]
+    pub chaintip_hash: String,
+    // One '0' or '1' per requested outpoint, in request order
+    pub bitmap: String,
+    // Only the unspent outpoints, in request order
+    pub utxos: Vec<RestUtxo>,
+}
+
+impl RestUtxos {
+    // Whether the outpoint at `index` in the request is unspent
+    pub fn is_unspent(&self, index: usize) -> bool {
+        self.bitmap.as_bytes().get(index) == Some(&b'1')
+    }
+}
+
+// Client for bitcoind's unauthenticated `-rest` interface, served on the RPC port
+#[derive(Debug, Clone)]
+pub struct BitcoinRestClient {
+    client: Client,
+    url: String,
+}
+
+impl BitcoinRestClient {
+    pub fn new(url: &str) -> Self {
+        let client = Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .build()
+            .expect("default HTTP client");
+        BitcoinRestClient::with_client(url, client)
+    }
+
+    pub fn new_local(network: BitcoinClientType) -> Self {
+        BitcoinRestClient::new(&crate::local_url(network))
+    }
+
+    // Use a preconfigured HTTP client, e.g. one with a proxy or custom TLS roots
+    pub fn with_client(url: &str, client: Client) -> Self {
+        BitcoinRestClient {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // GET /rest/<path>.<suffix>, mapping 404 and 400 to `RestError`
+    pub async fn fetch(&self, path: &str, format: RestFormat) -> Result<Vec<u8>> {
+        let url = format!("{}/rest/{}.{}", self.url, path, format.suffix());
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(BitcoinRpcError::from)?;
+        let status = response.status();
+        let body = response.bytes().await.map_err(BitcoinRpcError::from)?;
+        if status.is_success() {
+            return Ok(body.to_vec());
+        }
+        let text = String::from_utf8_lossy(&body).trim().to_string();
+        Err(match status {
+            StatusCode::NOT_FOUND => RestError::NotFound(text),
+            StatusCode::BAD_REQUEST => RestError::BadRequest(text),
+            _ => RestError::Http {
+                status: status.as_u16(),
+                body: text,
+            },
+        }
+        .into())
+    }
+
+    async fn fetch_hex(&self, path: &str) -> Result<String> {
+        let body = self.fetch(path, RestFormat::Hex).await?;
+        Ok(String::from_utf8(body)
+            .map_err(|e| BitcoinRpcError::Decode(e.to_string()))?
+            .trim()
+            .to_string())
+    }
+
+    async fn fetch_json<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let body = self.fetch(path, RestFormat::Json).await?;
+        Ok(serde_json::from_slice(&body).map_err(BitcoinRpcError::from)?)
+    }
+
+    // Serialized block, the fastest form for bulk sync
+    pub async fn get_block_bin(&self, block_hash: &str) -> Result<Vec<u8>> {
+        self.fetch(&format!("block/{}", block_hash), RestFormat::Bin)
+            .await
+    }
+
+    pub async fn get_block_hex(&self, block_hash: &str) -> Result<String> {
+        self.fetch_hex(&format!("block/{}", block_hash)).await
+    }
+
+    // Block with txids only, like `getblock` at verbosity 1
+    pub async fn get_block(&self, block_hash: &str) -> Result<Block> {
+        self.fetch_json(&format!("block/notxdetails/{}", block_hash))
+            .await
+    }
+
+    // Up to `count` headers starting at `start_hash`, fewer when the chain ends first
+    pub async fn get_headers(&self, count: u32, start_hash: &str) -> Result<Vec<BlockHeader>> {
+        self.fetch_json(&headers_path(count, start_hash)?).await
+    }
+
+    // Concatenated 80-byte serialized headers
+    pub async fn get_headers_bin(&self, count: u32, start_hash: &str) -> Result<Vec<u8>> {
+        self.fetch(&headers_path(count, start_hash)?, RestFormat::Bin)
+            .await
+    }
+
+    // Needs -txindex unless the transaction is in the mempool
+    pub async fn get_tx_bin(&self, txid: &str) -> Result<Vec<u8>> {
+        self.fetch(&format!("tx/{}", txid), RestFormat::Bin).await
+    }
+
+    pub async fn get_tx_hex(&self, txid: &str) -> Result<String> {
+        self.fetch_hex(&format!("tx/{}", txid)).await
+    }
+
+    pub async fn get_tx(&self, txid: &str) -> Result<Transaction> {
+        self.fetch_json(&format!("tx/{}", txid)).await
+    }
+
+    // Unspent state of up to 15 outpoints, including mempool spends and outputs when
+    // `check_mempool` is set
+    pub async fn get_utxo(&self, outpoints: &[OutPoint], check_mempool: bool) -> Result<RestUtxos> {
+        if outpoints.is_empty() || outpoints.len() > MAX_OUTPOINTS {
+            return Err(anyhow!(
+                "getutxos takes 1 to {} outpoints, got {}",
+                MAX_OUTPOINTS,
+                outpoints.len()
+            ));
+        }
+        let mut path = String::from("getutxos");
+        if check_mempool {
+            path.push_str("/checkmempool");
+        }
+        for outpoint in outpoints {
+            path.push_str(&format!("/{}-{}", outpoint.txid, outpoint.vout));
+        }
+        self.fetch_json(&path).await
+    }
+
+    pub async fn get_chaininfo(&self) -> Result<BlockchainInfo> {
+        self.fetch_json("chaininfo").await
+    }
+}
+
+// The count/hash path form, which every release with REST headers accepts
+fn headers_path(count: u32, start_hash: &str) -> Result<String> {
+    if count == 0 || count > MAX_HEADERS {
+        return Err(anyhow!(
+            "Header count must be 1 to {}, got {}",
+            MAX_HEADERS,
+            count
+        ));
+    }
+    Ok(format!("headers/{}/{}", count, start_hash))
+}
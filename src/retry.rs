@@ -0,0 +1,191 @@
+use anyhow::Result;
+use rand::Rng;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::BitcoinRpcError;
+use crate::{BitcoinClient, RpcError};
+
+// Calls with side effects that a retry could repeat, e.g. broadcasting or mining twice
+const NON_IDEMPOTENT_METHODS: &[&str] = &[
+    "abandontransaction",
+    "bumpfee",
+    "createwallet",
+    // Fails once the first attempt has written the file
+    "dumptxoutset",
+    // Likewise refuses to overwrite an existing file
+    "dumpwallet",
+    // A second attempt fails with -15 once the first has encrypted the wallet
+    "encryptwallet",
+    "generateblock",
+    "generatetoaddress",
+    "generatetodescriptor",
+    "getnewaddress",
+    "getrawchangeaddress",
+    "importmempool",
+    // Fails with -35 "already loaded" if the first attempt got through; the other wallet
+    // lifecycle calls likewise fail once the first has changed the wallet
+    "loadwallet",
+    "migratewallet",
+    "newkeypool",
+    "psbtbumpfee",
+    "restorewallet",
+    // A repeated `start` fails with "Scan already in progress" while the first runs
+    "scantxoutset",
+    "send",
+    "sendall",
+    "sendmany",
+    "sendrawtransaction",
+    "sendtoaddress",
+    // Without a seed argument each attempt installs another random seed
+    "sethdseed",
+    "submitblock",
+    "submitpackage",
+    "unloadwallet",
+    // The old passphrase no longer unlocks the wallet after the first attempt
+    "walletpassphrasechange",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryOn {
+    // -28, the node is still loading the block index or wallet
+    InWarmup,
+    // HTTP 503, e.g. the node is shutting down
+    ServiceUnavailable,
+    // "Work queue depth exceeded", the node has more requests than -rpcworkqueue allows
+    WorkQueueFull,
+    ConnectionRefused,
+    Timeout,
+}
+
+impl RetryOn {
+    pub fn all() -> Vec<RetryOn> {
+        vec![
+            RetryOn::InWarmup,
+            RetryOn::ServiceUnavailable,
+            RetryOn::WorkQueueFull,
+            RetryOn::ConnectionRefused,
+            RetryOn::Timeout,
+        ]
+    }
+
+    fn matches(&self, error: &BitcoinRpcError) -> bool {
+        match self {
+            RetryOn::InWarmup => matches!(error, BitcoinRpcError::InWarmup(_)),
+            RetryOn::ServiceUnavailable => {
+                matches!(error, BitcoinRpcError::Http { status: 503, .. })
+            }
+            RetryOn::WorkQueueFull => matches!(error, BitcoinRpcError::WorkQueueFull(_)),
+            RetryOn::ConnectionRefused => matches!(error, BitcoinRpcError::Connect(_)),
+            RetryOn::Timeout => matches!(error, BitcoinRpcError::Timeout(_)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: f64,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_millis(250),
+            max: Duration::from_secs(10),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl Backoff {
+    // Delay before retry number `retry` (1-based), with up to half of it taken off as jitter
+    fn delay(&self, retry: u32) -> Duration {
+        let base = self.initial.as_secs_f64() * self.multiplier.powi(retry as i32 - 1);
+        let base = base.min(self.max.as_secs_f64());
+        Duration::from_secs_f64(base * rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    // Attempts in total, including the first
+    pub max_attempts: u32,
+    pub backoff: Backoff,
+    pub retry_on: Vec<RetryOn>,
+    // Non-idempotent methods that may be retried anyway
+    pub allow_methods: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            backoff: Backoff::default(),
+            retry_on: RetryOn::all(),
+            allow_methods: Vec::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn allow_method(mut self, method: &str) -> Self {
+        self.allow_methods.push(method.to_string());
+        self
+    }
+
+    pub fn may_retry(&self, method: &str) -> bool {
+        !NON_IDEMPOTENT_METHODS.contains(&method) || self.allow_methods.iter().any(|m| m == method)
+    }
+
+    fn retries(&self, error: &BitcoinRpcError) -> bool {
+        self.retry_on.iter().any(|on| on.matches(error))
+    }
+}
+
+impl BitcoinClient {
+    // Retry single calls that fail transiently; batches are sent once
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(Arc::new(policy));
+        self
+    }
+
+    // With a policy installed, a failure comes back as an error naming the attempts
+    // made, whether retries ran out or the failure was not one to retry. The node's
+    // error stays reachable with `downcast_ref::<BitcoinRpcError>()`.
+    pub(crate) async fn send_with_retry(
+        &self,
+        method: &str,
+        params: &Value,
+    ) -> Result<(Option<Value>, Option<RpcError>)> {
+        let Some(policy) = &self.retry else {
+            return self.send_request(method, params).await;
+        };
+        let may_retry = policy.may_retry(method);
+        let mut attempt = 1;
+        loop {
+            let error = match self.send_request(method, params).await {
+                Ok((result, None)) => return Ok((result, None)),
+                Ok((_, Some(error))) => anyhow::Error::from(BitcoinRpcError::from(error)),
+                Err(e) => e,
+            };
+            let transient = error
+                .downcast_ref::<BitcoinRpcError>()
+                .is_some_and(|e| policy.retries(e));
+            if !may_retry || !transient || attempt >= policy.max_attempts {
+                let attempts = if attempt == 1 { "attempt" } else { "attempts" };
+                let summary = format!(
+                    "{} failed after {} {}: {}",
+                    method, attempt, attempts, error
+                );
+                return Err(error.context(summary));
+            }
+            let delay = policy.backoff.delay(attempt);
+            log::debug!("{} failed ({}), retrying in {:?}", method, error, delay);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::BitcoinClient;
+use crate::amount::Amount;
+use crate::error::BitcoinRpcError;
+use crate::types::{
+    Block, BlockHeader, BlockRef, BlockStats, BlockchainInfo, DecodedTransaction, FeeEstimate,
+    MempoolInfo, NetworkInfo, Transaction, TxOut, Utxo, WalletInfo,
+};
+
+fn decode<T: for<'de> Deserialize<'de>>(value: Value) -> Result<T> {
+    Ok(serde_json::from_value(value).map_err(BitcoinRpcError::from)?)
+}
+
+// The RPC surface as a trait, so code can take `impl BitcoinRpc` and be tested against
+// `testing::MockBitcoinRpc`. Implementors only provide `call_value`; every typed method
+// is built on it.
+#[async_trait]
+pub trait BitcoinRpc: Send + Sync {
+    // Send one request and return its raw result
+    async fn call_value(&self, method: &str, params: Value) -> Result<Value>;
+
+    async fn get_blockchain_info(&self) -> Result<BlockchainInfo> {
+        decode(self.call_value("getblockchaininfo", Value::Null).await?)
+    }
+
+    async fn get_block_count(&self) -> Result<u64> {
+        decode(self.call_value("getblockcount", Value::Null).await?)
+    }
+
+    async fn get_best_block_hash(&self) -> Result<String> {
+        decode(self.call_value("getbestblockhash", Value::Null).await?)
+    }
+
+    async fn get_block_hash(&self, height: u64) -> Result<String> {
+        decode(self.call_value("getblockhash", json!([height])).await?)
+    }
+
+    async fn get_block(&self, block_hash: &str) -> Result<Block> {
+        decode(self.call_value("getblock", json!([block_hash, 1])).await?)
+    }
+
+    async fn get_block_header(&self, block_hash: &str) -> Result<BlockHeader> {
+        decode(
+            self.call_value("getblockheader", json!([block_hash, true]))
+                .await?,
+        )
+    }
+
+    async fn get_block_stats(&self, block: BlockRef) -> Result<BlockStats> {
+        decode(self.call_value("getblockstats", json!([block])).await?)
+    }
+
+    async fn get_raw_transaction(&self, txid: &str) -> Result<Transaction> {
+        decode(
+            self.call_value("getrawtransaction", json!([txid, true]))
+                .await?,
+        )
+    }
+
+    async fn get_raw_transaction_hex(&self, txid: &str) -> Result<String> {
+        decode(
+            self.call_value("getrawtransaction", json!([txid, false]))
+                .await?,
+        )
+    }
+
+    async fn decode_raw_transaction(&self, tx_hex: &str) -> Result<DecodedTransaction> {
+        decode(
+            self.call_value("decoderawtransaction", json!([tx_hex]))
+                .await?,
+        )
+    }
+
+    async fn send_raw_transaction(&self, tx_hex: &str) -> Result<String> {
+        decode(
+            self.call_value("sendrawtransaction", json!([tx_hex]))
+                .await?,
+        )
+    }
+
+    async fn get_tx_out(
+        &self,
+        txid: &str,
+        vout: u32,
+        include_mempool: bool,
+    ) -> Result<Option<TxOut>> {
+        decode(
+            self.call_value("gettxout", json!([txid, vout, include_mempool]))
+                .await?,
+        )
+    }
+
+    async fn get_mempool_info(&self) -> Result<MempoolInfo> {
+        decode(self.call_value("getmempoolinfo", Value::Null).await?)
+    }
+
+    async fn get_raw_mempool(&self) -> Result<Vec<String>> {
+        decode(self.call_value("getrawmempool", json!([false])).await?)
+    }
+
+    async fn estimate_smart_fee(&self, conf_target: i32) -> Result<FeeEstimate> {
+        decode(
+            self.call_value("estimatesmartfee", json!([conf_target]))
+                .await?,
+        )
+    }
+
+    async fn get_network_info(&self) -> Result<NetworkInfo> {
+        decode(self.call_value("getnetworkinfo", Value::Null).await?)
+    }
+
+    async fn get_wallet_info(&self) -> Result<WalletInfo> {
+        decode(self.call_value("getwalletinfo", Value::Null).await?)
+    }
+
+    async fn get_new_address(&self, label: Option<&str>) -> Result<String> {
+        let params = match label {
+            Some(label) => json!([label]),
+            None => Value::Null,
+        };
+        decode(self.call_value("getnewaddress", params).await?)
+    }
+
+    async fn list_unspent(
+        &self,
+        min_conf: i32,
+        max_conf: i32,
+        addresses: Option<Vec<&str>>,
+    ) -> Result<Vec<Utxo>> {
+        let params = match addresses {
+            Some(addrs) => json!([min_conf, max_conf, addrs]),
+            None => json!([min_conf, max_conf]),
+        };
+        decode(self.call_value("listunspent", params).await?)
+    }
+
+    async fn send_to_address(&self, address: &str, amount: Amount) -> Result<String> {
+        decode(
+            self.call_value("sendtoaddress", json!([address, amount]))
+                .await?,
+        )
+    }
+}
+
+#[async_trait]
+impl BitcoinRpc for BitcoinClient {
+    async fn call_value(&self, method: &str, params: Value) -> Result<Value> {
+        self.call(method, params).await
+    }
+
+    // The inherent versions turn pruned-data errors into `BlockPruned`
+    async fn get_block(&self, block_hash: &str) -> Result<Block> {
+        BitcoinClient::get_block(self, block_hash).await
+    }
+
+    async fn get_block_stats(&self, block: BlockRef) -> Result<BlockStats> {
+        BitcoinClient::get_block_stats(self, block).await
+    }
+}
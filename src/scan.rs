@@ -0,0 +1,35 @@
+use anyhow::Result;
+use serde_json::json;
+
+use crate::BitcoinClient;
+use crate::cancel::AbortOnDrop;
+use crate::types::{ScanObject, ScanProgress, ScanTxOutResult, ScannedUtxo};
+
+impl BitcoinClient {
+    // Scan the UTXO set for `objects`. Scans can take minutes, more than the default
+    // timeout, so call this on `client.with_timeout(..)` for large sets. Dropping the
+    // future before it resolves sends `scantxoutset abort`, so the node does not keep
+    // scanning for nobody. Only one scan runs on a node at a time.
+    pub async fn scan_tx_out_set(&self, objects: &[ScanObject]) -> Result<ScanTxOutResult> {
+        let guard = AbortOnDrop::new(self, "scantxoutset", json!(["abort"]));
+        let result = self.call("scantxoutset", json!(["start", objects])).await;
+        guard.finish(&result);
+        result
+    }
+
+    // Stop the running scan; false when none was running
+    pub async fn scan_tx_out_set_abort(&self) -> Result<bool> {
+        self.call("scantxoutset", json!(["abort"])).await
+    }
+
+    // Progress of the running scan, None when none is running
+    pub async fn scan_tx_out_set_status(&self) -> Result<Option<ScanProgress>> {
+        self.call("scantxoutset", json!(["status"])).await
+    }
+
+    // Unspent outputs paying `address`, found without a wallet
+    pub async fn get_address_utxos(&self, address: &str) -> Result<Vec<ScannedUtxo>> {
+        let object = ScanObject::from(format!("addr({})", address).as_str());
+        Ok(self.scan_tx_out_set(&[object]).await?.unspents)
+    }
+}
@@ -0,0 +1,304 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScriptType {
+    #[serde(rename = "pubkey")]
+    P2PK,
+    #[serde(rename = "pubkeyhash")]
+    P2PKH,
+    #[serde(rename = "scripthash")]
+    P2SH,
+    #[serde(rename = "witness_v0_keyhash")]
+    P2WPKH,
+    #[serde(rename = "witness_v0_scripthash")]
+    P2WSH,
+    #[serde(rename = "witness_v1_taproot")]
+    P2TR,
+    #[serde(rename = "witness_unknown")]
+    WitnessUnknown,
+    #[serde(rename = "anchor")]
+    Anchor,
+    #[serde(rename = "multisig")]
+    Multisig,
+    #[serde(rename = "nulldata")]
+    NullData,
+    // Also any type name this version does not know
+    #[serde(rename = "nonstandard", other)]
+    NonStandard,
+}
+
+impl ScriptType {
+    // Compact numeric tag used by the binary snapshot format
+    pub fn to_u8(self) -> u8 {
+        match self {
+            ScriptType::P2PK => 0,
+            ScriptType::P2PKH => 1,
+            ScriptType::P2SH => 2,
+            ScriptType::P2WPKH => 3,
+            ScriptType::P2WSH => 4,
+            ScriptType::P2TR => 5,
+            ScriptType::WitnessUnknown => 6,
+            ScriptType::Multisig => 7,
+            ScriptType::NullData => 8,
+            ScriptType::NonStandard => 9,
+            ScriptType::Anchor => 10,
+        }
+    }
+
+    // Spent through the witness, so signatures commit to the amount
+    pub fn is_witness(self) -> bool {
+        matches!(
+            self,
+            ScriptType::P2WPKH
+                | ScriptType::P2WSH
+                | ScriptType::P2TR
+                | ScriptType::WitnessUnknown
+                | ScriptType::Anchor
+        )
+    }
+
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(ScriptType::P2PK),
+            1 => Some(ScriptType::P2PKH),
+            2 => Some(ScriptType::P2SH),
+            3 => Some(ScriptType::P2WPKH),
+            4 => Some(ScriptType::P2WSH),
+            5 => Some(ScriptType::P2TR),
+            6 => Some(ScriptType::WitnessUnknown),
+            7 => Some(ScriptType::Multisig),
+            8 => Some(ScriptType::NullData),
+            9 => Some(ScriptType::NonStandard),
+            10 => Some(ScriptType::Anchor),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ScriptType {
+    type Err = anyhow::Error;
+
+    // Parse the `type` name reported by the node in scriptPubKey objects
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pubkey" => Ok(ScriptType::P2PK),
+            "pubkeyhash" => Ok(ScriptType::P2PKH),
+            "scripthash" => Ok(ScriptType::P2SH),
+            "witness_v0_keyhash" => Ok(ScriptType::P2WPKH),
+            "witness_v0_scripthash" => Ok(ScriptType::P2WSH),
+            "witness_v1_taproot" => Ok(ScriptType::P2TR),
+            "witness_unknown" => Ok(ScriptType::WitnessUnknown),
+            "multisig" => Ok(ScriptType::Multisig),
+            "nulldata" => Ok(ScriptType::NullData),
+            "nonstandard" => Ok(ScriptType::NonStandard),
+            "anchor" => Ok(ScriptType::Anchor),
+            _ => Err(anyhow::anyhow!("Unknown script type: {}", s)),
+        }
+    }
+}
+
+// Classify a scriptPubKey into one of the standard output types
+pub fn classify_script(script: &[u8]) -> ScriptType {
+    const OP_0: u8 = 0x00;
+    const OP_1: u8 = 0x51;
+    const OP_16: u8 = 0x60;
+    const OP_RETURN: u8 = 0x6a;
+    const OP_DUP: u8 = 0x76;
+    const OP_EQUAL: u8 = 0x87;
+    const OP_EQUALVERIFY: u8 = 0x88;
+    const OP_HASH160: u8 = 0xa9;
+    const OP_CHECKSIG: u8 = 0xac;
+    const OP_CHECKMULTISIG: u8 = 0xae;
+
+    match script {
+        [
+            OP_DUP,
+            OP_HASH160,
+            0x14,
+            hash @ ..,
+            OP_EQUALVERIFY,
+            OP_CHECKSIG,
+        ] if hash.len() == 20 => ScriptType::P2PKH,
+        [OP_HASH160, 0x14, hash @ .., OP_EQUAL] if hash.len() == 20 => ScriptType::P2SH,
+        [OP_0, 0x14, program @ ..] if program.len() == 20 => ScriptType::P2WPKH,
+        [OP_0, 0x20, program @ ..] if program.len() == 32 => ScriptType::P2WSH,
+        [OP_1, 0x20, program @ ..] if program.len() == 32 => ScriptType::P2TR,
+        [OP_1, 0x02, 0x4e, 0x73] => ScriptType::Anchor,
+        [version, len, program @ ..]
+            if (OP_1..=OP_16).contains(version)
+                && (2..=40).contains(len)
+                && program.len() == *len as usize =>
+        {
+            ScriptType::WitnessUnknown
+        }
+        [0x21, key @ .., OP_CHECKSIG] if key.len() == 33 => ScriptType::P2PK,
+        [0x41, key @ .., OP_CHECKSIG] if key.len() == 65 => ScriptType::P2PK,
+        [OP_RETURN, ..] => ScriptType::NullData,
+        [m, .., n, OP_CHECKMULTISIG] if is_multisig(script, *m, *n) => ScriptType::Multisig,
+        _ => ScriptType::NonStandard,
+    }
+}
+
+// Check the `OP_m <pubkey>... OP_n OP_CHECKMULTISIG` layout
+fn is_multisig(script: &[u8], m: u8, n: u8) -> bool {
+    if !(0x51..=0x60).contains(&m) || !(0x51..=0x60).contains(&n) || m > n {
+        return false;
+    }
+    let mut keys = 0usize;
+    let mut pos = 1;
+    let end = script.len() - 2;
+    while pos < end {
+        let len = script[pos] as usize;
+        if len != 33 && len != 65 {
+            return false;
+        }
+        pos += 1 + len;
+        keys += 1;
+    }
+    pos == end && keys == (n - 0x50) as usize
+}
+
+// Minimal push of arbitrary data onto a script
+pub(crate) fn push_data(data: &[u8]) -> Vec<u8> {
+    let mut script = Vec::with_capacity(data.len() + 3);
+    match data.len() {
+        len @ 0..=75 => script.push(len as u8),
+        len @ 76..=255 => script.extend_from_slice(&[0x4c, len as u8]),
+        len => {
+            script.push(0x4d);
+            script.extend_from_slice(&(len as u16).to_le_bytes());
+        }
+    }
+    script.extend_from_slice(data);
+    script
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateMatch {
+    pub name: String,
+    pub payload: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptClassification {
+    pub script_type: ScriptType,
+    pub template: Option<TemplateMatch>,
+}
+
+// Recognizer for a protocol-specific output script
+pub trait ScriptTemplate: Send + Sync {
+    fn name(&self) -> &str;
+
+    // Return the extracted payload when the script matches the template
+    fn recognize(&self, script: &[u8]) -> Option<Vec<u8>>;
+}
+
+// OP_RETURN outputs whose pushed data starts with a protocol prefix (e.g. "omni")
+pub struct OpReturnPrefixTemplate {
+    name: String,
+    prefix: Vec<u8>,
+}
+
+impl OpReturnPrefixTemplate {
+    pub fn new(name: &str, prefix: &[u8]) -> Self {
+        OpReturnPrefixTemplate {
+            name: name.to_string(),
+            prefix: prefix.to_vec(),
+        }
+    }
+}
+
+impl ScriptTemplate for OpReturnPrefixTemplate {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn recognize(&self, script: &[u8]) -> Option<Vec<u8>> {
+        let data = op_return_data(script)?;
+        data.strip_prefix(self.prefix.as_slice())
+            .map(|p| p.to_vec())
+    }
+}
+
+// Pay-to-anchor outputs (`OP_1 <0x4e73>`)
+pub struct AnchorTemplate;
+
+impl ScriptTemplate for AnchorTemplate {
+    fn name(&self) -> &str {
+        "p2a"
+    }
+
+    fn recognize(&self, script: &[u8]) -> Option<Vec<u8>> {
+        (script == [0x51, 0x02, 0x4e, 0x73]).then(Vec::new)
+    }
+}
+
+// Ordered set of templates consulted before falling back to the standard types
+#[derive(Clone, Default)]
+pub struct ScriptTemplateRegistry {
+    templates: Vec<Arc<dyn ScriptTemplate>>,
+}
+
+impl ScriptTemplateRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Registry preloaded with the pay-to-anchor recognizer
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(AnchorTemplate);
+        registry
+    }
+
+    pub fn register(&mut self, template: impl ScriptTemplate + 'static) -> &mut Self {
+        self.templates.push(Arc::new(template));
+        self
+    }
+
+    pub fn match_template(&self, script: &[u8]) -> Option<TemplateMatch> {
+        self.templates.iter().find_map(|template| {
+            template.recognize(script).map(|payload| TemplateMatch {
+                name: template.name().to_string(),
+                payload: hex::encode(payload),
+            })
+        })
+    }
+
+    pub fn classify(&self, script: &[u8]) -> ScriptClassification {
+        ScriptClassification {
+            script_type: classify_script(script),
+            template: self.match_template(script),
+        }
+    }
+}
+
+// Concatenated push data following OP_RETURN, or None if the script is not a data carrier
+pub fn op_return_data(script: &[u8]) -> Option<Vec<u8>> {
+    let (&first, mut rest) = script.split_first()?;
+    if first != 0x6a {
+        return None;
+    }
+    let mut data = Vec::new();
+    while let Some((&opcode, tail)) = rest.split_first() {
+        let (len, tail) = match opcode {
+            0x01..=0x4b => (opcode as usize, tail),
+            0x4c => (*tail.first()? as usize, &tail[1..]),
+            0x4d => (
+                u16::from_le_bytes([*tail.first()?, *tail.get(1)?]) as usize,
+                &tail[2..],
+            ),
+            // Small-integer opcodes such as a protocol marker carry no push data
+            0x00 | 0x4f..=0x60 => (0, tail),
+            _ => return None,
+        };
+        if tail.len() < len {
+            return None;
+        }
+        data.extend_from_slice(&tail[..len]);
+        rest = &tail[len..];
+    }
+    Some(data)
+}
@@ -0,0 +1,236 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::BitcoinClient;
+use crate::error::BitcoinRpcError;
+use crate::types::SendRawTxOptions;
+
+// Rejections of `send_raw_transaction_with_options` worth branching on. Returned inside
+// anyhow; other rejections stay `BitcoinRpcError`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SendRawError {
+    // Above `max_fee_rate`; raise it, or pass 0, if the fee is intended
+    #[error("Fee rate above the maximum: {0}")]
+    MaxFeeExceeded(String),
+    // More than `max_burn_amount` goes to unspendable outputs
+    #[error("Burn amount above the maximum: {0}")]
+    MaxBurnExceeded(String),
+    // Already in the node's mempool, so nothing more to do
+    #[error("Transaction already in mempool: {0}")]
+    AlreadyInMempool(String),
+    // Already confirmed, or a copy with a different witness is in the mempool
+    #[error("Transaction already known: {0}")]
+    AlreadyKnown(String),
+    // Replaces mempool transactions without paying enough more than them
+    #[error("Replacement fee too low: {0}")]
+    ReplacementFeeTooLow(String),
+}
+
+impl SendRawError {
+    // None for failures with other causes
+    fn classify(error: &BitcoinRpcError) -> Option<Self> {
+        let message = error.message().to_string();
+        match TxRejection::classify(error)? {
+            TxRejection::MaxFeeExceeded => Some(SendRawError::MaxFeeExceeded(message)),
+            TxRejection::MaxBurnExceeded => Some(SendRawError::MaxBurnExceeded(message)),
+            TxRejection::AlreadyInMempool => Some(SendRawError::AlreadyInMempool(message)),
+            TxRejection::AlreadyInChain | TxRejection::SameNonWitnessData => {
+                Some(SendRawError::AlreadyKnown(message))
+            }
+            TxRejection::ReplacementFeeTooLow => Some(SendRawError::ReplacementFeeTooLow(message)),
+            TxRejection::MissingInputs | TxRejection::FeeTooLow | TxRejection::MempoolConflict => {
+                None
+            }
+        }
+    }
+}
+
+// Why the node refused a transaction. The code decides where it can; -25 and -26 each
+// cover several causes, told apart by Core's fixed TransactionError text (-25) or the
+// reject reason that starts the message (-26).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TxRejection {
+    AlreadyInChain,
+    AlreadyInMempool,
+    // A copy with a different witness is in the mempool
+    SameNonWitnessData,
+    MissingInputs,
+    MaxFeeExceeded,
+    MaxBurnExceeded,
+    FeeTooLow,
+    ReplacementFeeTooLow,
+    MempoolConflict,
+}
+
+impl TxRejection {
+    // None for errors that are not a rejection, or a reject reason not listed here
+    pub(crate) fn classify(error: &BitcoinRpcError) -> Option<Self> {
+        match error {
+            BitcoinRpcError::VerifyAlreadyInChain(_) => Some(TxRejection::AlreadyInChain),
+            BitcoinRpcError::VerifyError(message) => match message.as_str() {
+                // "Missing inputs" before 22.0
+                "Inputs missing or spent" | "Missing inputs" => Some(TxRejection::MissingInputs),
+                m if m.starts_with("Fee exceeds maximum configured by user") => {
+                    Some(TxRejection::MaxFeeExceeded)
+                }
+                m if m.starts_with("Unspendable output exceeds maximum configured by user") => {
+                    Some(TxRejection::MaxBurnExceeded)
+                }
+                _ => None,
+            },
+            BitcoinRpcError::VerifyRejected(message) => match reject_reason(message) {
+                // The outputs are already in the UTXO set
+                "txn-already-known" => Some(TxRejection::AlreadyInChain),
+                "txn-already-in-mempool" => Some(TxRejection::AlreadyInMempool),
+                "txn-same-nonwitness-data-in-mempool" => Some(TxRejection::SameNonWitnessData),
+                "bad-txns-inputs-missingorspent" | "missing-inputs" => {
+                    Some(TxRejection::MissingInputs)
+                }
+                // "absurdly-high-fee" before 0.20
+                "max-fee-exceeded" | "absurdly-high-fee" => Some(TxRejection::MaxFeeExceeded),
+                "min relay fee not met" | "mempool min fee not met" => Some(TxRejection::FeeTooLow),
+                "insufficient fee" => Some(TxRejection::ReplacementFeeTooLow),
+                "txn-mempool-conflict" => Some(TxRejection::MempoolConflict),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+// The reject reason of a -26 message: "min relay fee not met, 100 < 141" and, before
+// 0.19, "min relay fee not met (code 66)" or "66: min relay fee not met"
+fn reject_reason(message: &str) -> &str {
+    let reason = match message.split_once(": ") {
+        Some((code, rest)) if code.bytes().all(|b| b.is_ascii_digit()) => rest,
+        _ => message,
+    };
+    let end = reason.find([',', '(']).unwrap_or(reason.len());
+    reason[..end].trim_end()
+}
+
+// Recognized node-side rejections become `SendRawError`; anything else passes through
+fn send_raw_error(error: anyhow::Error) -> anyhow::Error {
+    match error
+        .downcast_ref::<BitcoinRpcError>()
+        .and_then(SendRawError::classify)
+    {
+        Some(send_error) => send_error.into(),
+        None => error,
+    }
+}
+
+impl BitcoinClient {
+    // `send_raw_transaction` with the fee rate and burn caps overridden. Needs 0.19 or
+    // later; see `send_raw_transaction_with_max_fee_rate` for older nodes.
+    pub async fn send_raw_transaction_with_options(
+        &self,
+        tx_hex: &str,
+        options: SendRawTxOptions,
+    ) -> Result<String> {
+        let mut params = vec![
+            json!(tx_hex),
+            json!(options.max_fee_rate),
+            json!(options.max_burn_amount),
+        ];
+        while params.last().is_some_and(Value::is_null) {
+            params.pop();
+        }
+        self.call("sendrawtransaction", Value::Array(params))
+            .await
+            .map_err(send_raw_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(code: i32, message: &str) -> Option<TxRejection> {
+        TxRejection::classify(&BitcoinRpcError::from_code(code, message.to_string()))
+    }
+
+    #[test]
+    fn classifies_messages_from_several_core_versions() {
+        let cases = [
+            (
+                -27,
+                "Transaction already in block chain",
+                TxRejection::AlreadyInChain,
+            ),
+            (
+                -27,
+                "Transaction outputs already in utxo set",
+                TxRejection::AlreadyInChain,
+            ),
+            (-26, "txn-already-known", TxRejection::AlreadyInChain),
+            (-26, "txn-already-in-mempool", TxRejection::AlreadyInMempool),
+            (
+                -26,
+                "txn-same-nonwitness-data-in-mempool",
+                TxRejection::SameNonWitnessData,
+            ),
+            (-25, "Inputs missing or spent", TxRejection::MissingInputs),
+            (-25, "Missing inputs", TxRejection::MissingInputs),
+            (
+                -26,
+                "bad-txns-inputs-missingorspent",
+                TxRejection::MissingInputs,
+            ),
+            (
+                -25,
+                "Fee exceeds maximum configured by user (e.g. -maxtxfee, maxfeerate)",
+                TxRejection::MaxFeeExceeded,
+            ),
+            (
+                -26,
+                "absurdly-high-fee, 1000000 > 100000 (code 256)",
+                TxRejection::MaxFeeExceeded,
+            ),
+            (
+                -25,
+                "Unspendable output exceeds maximum configured by user (maxburnamount)",
+                TxRejection::MaxBurnExceeded,
+            ),
+            (
+                -26,
+                "min relay fee not met, 100 < 141",
+                TxRejection::FeeTooLow,
+            ),
+            (
+                -26,
+                "mempool min fee not met, 110 < 2000",
+                TxRejection::FeeTooLow,
+            ),
+            (-26, "66: min relay fee not met", TxRejection::FeeTooLow),
+            (
+                -26,
+                "min relay fee not met (code 66)",
+                TxRejection::FeeTooLow,
+            ),
+            (
+                -26,
+                "insufficient fee, rejecting replacement 1234, less fees than conflicting txs; 0.0001 < 0.0002",
+                TxRejection::ReplacementFeeTooLow,
+            ),
+            (-26, "txn-mempool-conflict", TxRejection::MempoolConflict),
+        ];
+        for (code, message, expected) in cases {
+            assert_eq!(classify(code, message), Some(expected), "{}", message);
+        }
+    }
+
+    #[test]
+    fn leaves_other_errors_unclassified() {
+        assert_eq!(classify(-26, "non-final"), None);
+        assert_eq!(
+            classify(-25, "Transaction rejected by AcceptToMemoryPool"),
+            None
+        );
+        // The same text under another code is not a rejection
+        assert_eq!(classify(-8, "txn-already-in-mempool"), None);
+        let timeout = BitcoinRpcError::Timeout("min relay fee not met".to_string());
+        assert_eq!(TxRejection::classify(&timeout), None);
+    }
+}
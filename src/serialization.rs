@@ -1,6 +1,12 @@
 use crate::crypto::BitcoinCrypto;
+use crate::types::BlockHeader;
 use anyhow::Result;
 
+pub const SIGHASH_ALL: u32 = 0x01;
+pub const SIGHASH_NONE: u32 = 0x02;
+pub const SIGHASH_SINGLE: u32 = 0x03;
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
 pub struct Serialization;
 
 impl Serialization {
@@ -108,3 +114,652 @@ impl Serialization {
         hex::encode(reversed) == merkle_root
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTxInput {
+    pub prev_txid: [u8; 32],
+    pub prev_vout: u32,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    pub witness: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTxOutput {
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTransaction {
+    pub version: i32,
+    pub inputs: Vec<RawTxInput>,
+    pub outputs: Vec<RawTxOutput>,
+    pub lock_time: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawBlockHeader {
+    pub version: i32,
+    pub prev_blockhash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub time: u32,
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawBlock {
+    pub header: RawBlockHeader,
+    pub transactions: Vec<RawTransaction>,
+}
+
+// A `gettxoutproof` result: a header plus the partial merkle tree proving some of the
+// block's transactions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleBlock {
+    pub header: RawBlockHeader,
+    pub total_transactions: u32,
+    // Internal byte order, in depth-first traversal order
+    pub hashes: Vec<[u8; 32]>,
+    // Traversal bits, least significant bit of each byte first
+    pub flags: Vec<u8>,
+}
+
+impl RawTransaction {
+    pub fn has_witness(&self) -> bool {
+        self.inputs.iter().any(|input| !input.witness.is_empty())
+    }
+
+    // Serialize the transaction, optionally in BIP144 witness format
+    pub fn serialize(&self, include_witness: bool) -> Vec<u8> {
+        let witness = include_witness && self.has_witness();
+        let mut data = self.version.to_le_bytes().to_vec();
+        if witness {
+            data.extend_from_slice(&[0x00, 0x01]);
+        }
+        data.extend(Serialization::serialize_varint(self.inputs.len() as u64));
+        for input in &self.inputs {
+            data.extend_from_slice(&input.prev_txid);
+            data.extend_from_slice(&input.prev_vout.to_le_bytes());
+            data.extend(Serialization::serialize_bytes(&input.script_sig));
+            data.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        data.extend(Serialization::serialize_varint(self.outputs.len() as u64));
+        for output in &self.outputs {
+            data.extend_from_slice(&output.value.to_le_bytes());
+            data.extend(Serialization::serialize_bytes(&output.script_pubkey));
+        }
+        if witness {
+            for input in &self.inputs {
+                data.extend(Serialization::serialize_varint(input.witness.len() as u64));
+                for item in &input.witness {
+                    data.extend(Serialization::serialize_bytes(item));
+                }
+            }
+        }
+        data.extend_from_slice(&self.lock_time.to_le_bytes());
+        data
+    }
+
+    // Transaction hash in internal byte order
+    pub fn txid_bytes(&self) -> [u8; 32] {
+        BitcoinCrypto::double_sha256(&self.serialize(false))
+    }
+
+    pub fn txid(&self) -> String {
+        Serialization::hash_to_hex(&self.txid_bytes())
+    }
+
+    pub fn wtxid(&self) -> String {
+        Serialization::hash_to_hex(&BitcoinCrypto::double_sha256(&self.serialize(true)))
+    }
+
+    // The transaction with every scriptSig and witness removed, which is the same for all
+    // partially signed copies of it
+    pub fn unsigned(&self) -> RawTransaction {
+        let mut tx = self.clone();
+        for input in &mut tx.inputs {
+            input.script_sig.clear();
+            input.witness.clear();
+        }
+        tx
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.inputs.len() == 1
+            && self.inputs[0].prev_txid == [0u8; 32]
+            && self.inputs[0].prev_vout == 0xFFFFFFFF
+    }
+
+    // Pre-segwit signature hash for an input. `script_code` is the script being satisfied
+    // with any OP_CODESEPARATOR handling already applied.
+    pub fn legacy_sighash(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        sighash_type: u32,
+    ) -> [u8; 32] {
+        let base = sighash_type & 0x1f;
+        // Core signs the constant 1 for SIGHASH_SINGLE without a matching output
+        if input_index >= self.inputs.len()
+            || (base == SIGHASH_SINGLE && input_index >= self.outputs.len())
+        {
+            let mut one = [0u8; 32];
+            one[0] = 1;
+            return one;
+        }
+        let mut tx = self.clone();
+        for (i, input) in tx.inputs.iter_mut().enumerate() {
+            input.witness.clear();
+            input.script_sig = if i == input_index {
+                script_code.to_vec()
+            } else {
+                Vec::new()
+            };
+            if i != input_index && (base == SIGHASH_NONE || base == SIGHASH_SINGLE) {
+                input.sequence = 0;
+            }
+        }
+        if base == SIGHASH_NONE {
+            tx.outputs.clear();
+        } else if base == SIGHASH_SINGLE {
+            tx.outputs.truncate(input_index + 1);
+            for output in &mut tx.outputs[..input_index] {
+                output.value = u64::MAX;
+                output.script_pubkey.clear();
+            }
+        }
+        if sighash_type & SIGHASH_ANYONECANPAY != 0 {
+            tx.inputs = vec![tx.inputs.swap_remove(input_index)];
+        }
+        let mut preimage = tx.serialize(false);
+        preimage.extend_from_slice(&sighash_type.to_le_bytes());
+        BitcoinCrypto::double_sha256(&preimage)
+    }
+
+    // BIP143 signature hash for a segwit v0 input spending `amount` satoshis
+    pub fn segwit_v0_sighash(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        amount: u64,
+        sighash_type: u32,
+    ) -> Result<[u8; 32]> {
+        let input = self
+            .inputs
+            .get(input_index)
+            .ok_or_else(|| anyhow::anyhow!("Input {} out of range", input_index))?;
+        let base = sighash_type & 0x1f;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+        let hash_prevouts = if anyone_can_pay {
+            [0u8; 32]
+        } else {
+            let mut data = Vec::new();
+            for i in &self.inputs {
+                data.extend_from_slice(&i.prev_txid);
+                data.extend_from_slice(&i.prev_vout.to_le_bytes());
+            }
+            BitcoinCrypto::double_sha256(&data)
+        };
+        let hash_sequence = if anyone_can_pay || base == SIGHASH_NONE || base == SIGHASH_SINGLE {
+            [0u8; 32]
+        } else {
+            let data: Vec<u8> = self
+                .inputs
+                .iter()
+                .flat_map(|i| i.sequence.to_le_bytes())
+                .collect();
+            BitcoinCrypto::double_sha256(&data)
+        };
+        let serialize_output = |output: &RawTxOutput| {
+            let mut data = output.value.to_le_bytes().to_vec();
+            data.extend(Serialization::serialize_bytes(&output.script_pubkey));
+            data
+        };
+        let hash_outputs = if base != SIGHASH_NONE && base != SIGHASH_SINGLE {
+            BitcoinCrypto::double_sha256(
+                &self
+                    .outputs
+                    .iter()
+                    .flat_map(serialize_output)
+                    .collect::<Vec<u8>>(),
+            )
+        } else if base == SIGHASH_SINGLE && input_index < self.outputs.len() {
+            BitcoinCrypto::double_sha256(&serialize_output(&self.outputs[input_index]))
+        } else {
+            [0u8; 32]
+        };
+        let mut preimage = self.version.to_le_bytes().to_vec();
+        preimage.extend_from_slice(&hash_prevouts);
+        preimage.extend_from_slice(&hash_sequence);
+        preimage.extend_from_slice(&input.prev_txid);
+        preimage.extend_from_slice(&input.prev_vout.to_le_bytes());
+        preimage.extend(Serialization::serialize_bytes(script_code));
+        preimage.extend_from_slice(&amount.to_le_bytes());
+        preimage.extend_from_slice(&input.sequence.to_le_bytes());
+        preimage.extend_from_slice(&hash_outputs);
+        preimage.extend_from_slice(&self.lock_time.to_le_bytes());
+        preimage.extend_from_slice(&sighash_type.to_le_bytes());
+        Ok(BitcoinCrypto::double_sha256(&preimage))
+    }
+}
+
+impl RawBlockHeader {
+    pub fn serialize(&self) -> [u8; 80] {
+        let mut data = [0u8; 80];
+        data[0..4].copy_from_slice(&self.version.to_le_bytes());
+        data[4..36].copy_from_slice(&self.prev_blockhash);
+        data[36..68].copy_from_slice(&self.merkle_root);
+        data[68..72].copy_from_slice(&self.time.to_le_bytes());
+        data[72..76].copy_from_slice(&self.bits.to_le_bytes());
+        data[76..80].copy_from_slice(&self.nonce.to_le_bytes());
+        data
+    }
+
+    pub fn block_hash(&self) -> String {
+        Serialization::hash_to_hex(&BitcoinCrypto::double_sha256(&self.serialize()))
+    }
+
+    // Hash the header and compare it against its own `bits`, without trusting the node
+    pub fn check_proof_of_work(&self) -> bool {
+        let mut hash = BitcoinCrypto::double_sha256(&self.serialize());
+        hash.reverse();
+        hash <= crate::hashrate::target_from_bits(self.bits)
+    }
+}
+
+impl RawBlock {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut data = self.header.serialize().to_vec();
+        data.extend(Serialization::serialize_varint(
+            self.transactions.len() as u64
+        ));
+        for tx in &self.transactions {
+            data.extend(tx.serialize(true));
+        }
+        data
+    }
+
+    // Merkle root over the current transaction list, in internal byte order
+    pub fn compute_merkle_root(&self) -> [u8; 32] {
+        let txids: Vec<[u8; 32]> = self.transactions.iter().map(|tx| tx.txid_bytes()).collect();
+        Serialization::merkle_root(&txids)
+    }
+}
+
+// A transaction proven by a merkle block and its position in the block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleMatch {
+    pub txid: String,
+    pub index: u32,
+}
+
+impl MerkleBlock {
+    fn tree_width(&self, height: u32) -> usize {
+        ((self.total_transactions as usize) + (1 << height) - 1) >> height
+    }
+
+    // Walk the partial tree as Core's CPartialMerkleTree does, returning the merkle root
+    // it commits to and the matched txids with their positions in the block
+    pub fn extract_matches(&self) -> Result<([u8; 32], Vec<MerkleMatch>)> {
+        if self.total_transactions == 0 {
+            return Err(anyhow::anyhow!("Merkle block has no transactions"));
+        }
+        if self.hashes.len() > self.total_transactions as usize {
+            return Err(anyhow::anyhow!("More hashes than transactions"));
+        }
+        if self.flags.len() * 8 < self.hashes.len() {
+            return Err(anyhow::anyhow!("Fewer flag bits than hashes"));
+        }
+        let mut height = 0;
+        while self.tree_width(height) > 1 {
+            height += 1;
+        }
+        let mut walk = MerkleWalk {
+            block: self,
+            bits_used: 0,
+            hashes_used: 0,
+            matches: Vec::new(),
+        };
+        let root = walk.traverse(height, 0)?;
+        if walk.bits_used.div_ceil(8) != self.flags.len() {
+            return Err(anyhow::anyhow!("Unused flag bytes in merkle block"));
+        }
+        if walk.hashes_used != self.hashes.len() {
+            return Err(anyhow::anyhow!("Unused hashes in merkle block"));
+        }
+        Ok((root, walk.matches))
+    }
+}
+
+struct MerkleWalk<'a> {
+    block: &'a MerkleBlock,
+    bits_used: usize,
+    hashes_used: usize,
+    matches: Vec<MerkleMatch>,
+}
+
+impl MerkleWalk<'_> {
+    fn traverse(&mut self, height: u32, pos: usize) -> Result<[u8; 32]> {
+        let byte = self
+            .block
+            .flags
+            .get(self.bits_used / 8)
+            .ok_or_else(|| anyhow::anyhow!("Merkle block ran out of flag bits"))?;
+        let parent_of_match = (byte >> (self.bits_used % 8)) & 1 == 1;
+        self.bits_used += 1;
+        if height == 0 || !parent_of_match {
+            let hash = *self
+                .block
+                .hashes
+                .get(self.hashes_used)
+                .ok_or_else(|| anyhow::anyhow!("Merkle block ran out of hashes"))?;
+            self.hashes_used += 1;
+            if height == 0 && parent_of_match {
+                self.matches.push(MerkleMatch {
+                    txid: Serialization::hash_to_hex(&hash),
+                    index: pos as u32,
+                });
+            }
+            return Ok(hash);
+        }
+        let left = self.traverse(height - 1, pos * 2)?;
+        let right = if pos * 2 + 1 < self.block.tree_width(height - 1) {
+            let right = self.traverse(height - 1, pos * 2 + 1)?;
+            // Identical siblings would let a duplicated transaction forge a match
+            if right == left {
+                return Err(anyhow::anyhow!("Duplicate sibling hashes in merkle block"));
+            }
+            right
+        } else {
+            left
+        };
+        Ok(BitcoinCrypto::double_sha256(
+            &[&left[..], &right[..]].concat(),
+        ))
+    }
+}
+
+impl Serialization {
+    // Serialize a byte string with its varint length prefix
+    pub fn serialize_bytes(bytes: &[u8]) -> Vec<u8> {
+        let mut result = Self::serialize_varint(bytes.len() as u64);
+        result.extend_from_slice(bytes);
+        result
+    }
+
+    // Display a hash in the conventional reversed hex form
+    pub fn hash_to_hex(hash: &[u8; 32]) -> String {
+        let mut reversed = *hash;
+        reversed.reverse();
+        hex::encode(reversed)
+    }
+
+    // Parse a hash from reversed hex back into internal byte order
+    pub fn hex_to_hash(hash: &str) -> Result<[u8; 32]> {
+        let mut bytes: [u8; 32] = hex::decode(hash)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Invalid hash length: {}", hash))?;
+        bytes.reverse();
+        Ok(bytes)
+    }
+
+    // Compute a merkle root from hashes in internal byte order
+    pub fn merkle_root(hashes: &[[u8; 32]]) -> [u8; 32] {
+        if hashes.is_empty() {
+            return [0u8; 32];
+        }
+        let mut level = hashes.to_vec();
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let right = if pair.len() > 1 { pair[1] } else { pair[0] };
+                    BitcoinCrypto::double_sha256(&[&pair[0][..], &right[..]].concat())
+                })
+                .collect();
+        }
+        level[0]
+    }
+
+    // Sibling hashes from the leaf at `index` up to the root, in internal byte order
+    pub fn merkle_branch(hashes: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
+        let mut branch = Vec::new();
+        let mut level = hashes.to_vec();
+        let mut index = index;
+        while level.len() > 1 {
+            let sibling = (index ^ 1).min(level.len() - 1);
+            branch.push(level[sibling]);
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let right = if pair.len() > 1 { pair[1] } else { pair[0] };
+                    BitcoinCrypto::double_sha256(&[&pair[0][..], &right[..]].concat())
+                })
+                .collect();
+            index /= 2;
+        }
+        branch
+    }
+
+    // Fold a merkle branch back up to the root it proves
+    pub fn merkle_root_from_branch(leaf: &[u8; 32], branch: &[[u8; 32]], index: usize) -> [u8; 32] {
+        let mut hash = *leaf;
+        let mut index = index;
+        for sibling in branch {
+            let pair = if index & 1 == 1 {
+                [&sibling[..], &hash[..]].concat()
+            } else {
+                [&hash[..], &sibling[..]].concat()
+            };
+            hash = BitcoinCrypto::double_sha256(&pair);
+            index /= 2;
+        }
+        hash
+    }
+
+    // Parse a transaction from raw bytes, returning it and the number of bytes consumed
+    pub fn parse_transaction(data: &[u8]) -> Result<(RawTransaction, usize)> {
+        let mut reader = ByteReader::new(data);
+        let tx = reader.transaction()?;
+        Ok((tx, reader.position()))
+    }
+
+    pub fn deserialize_transaction(tx_hex: &str) -> Result<RawTransaction> {
+        let bytes = hex::decode(tx_hex)?;
+        let (tx, used) = Self::parse_transaction(&bytes)?;
+        if used != bytes.len() {
+            return Err(anyhow::anyhow!("Trailing data after transaction"));
+        }
+        Ok(tx)
+    }
+
+    pub fn parse_block_header(data: &[u8]) -> Result<RawBlockHeader> {
+        ByteReader::new(data).block_header()
+    }
+
+    pub fn deserialize_merkle_block(proof_hex: &str) -> Result<MerkleBlock> {
+        let bytes = hex::decode(proof_hex)?;
+        let mut reader = ByteReader::new(&bytes);
+        let header = reader.block_header()?;
+        let total_transactions = reader.u32_le()?;
+        let count = reader.varint()?;
+        if count as usize > reader.remaining() / 32 {
+            return Err(anyhow::anyhow!("Merkle block hash count exceeds its size"));
+        }
+        let hashes = (0..count)
+            .map(|_| reader.hash())
+            .collect::<Result<Vec<_>>>()?;
+        let flags = reader.var_bytes()?.to_vec();
+        if reader.remaining() != 0 {
+            return Err(anyhow::anyhow!("Trailing data after merkle block"));
+        }
+        Ok(MerkleBlock {
+            header,
+            total_transactions,
+            hashes,
+            flags,
+        })
+    }
+
+    // Check a `gettxoutproof` result offline against a header obtained elsewhere,
+    // returning the txids it proves are in that block
+    pub fn verify_tx_out_proof(proof_hex: &str, header: &BlockHeader) -> Result<Vec<String>> {
+        let block = Self::deserialize_merkle_block(proof_hex)?;
+        if block.header.block_hash() != header.hash {
+            return Err(anyhow::anyhow!(
+                "Proof is for block {}, expected {}",
+                block.header.block_hash(),
+                header.hash
+            ));
+        }
+        let (root, matches) = block.extract_matches()?;
+        // The proof's own header is what the block hash commits to; `merkleroot` in the
+        // JSON header is not bound to `hash` and is only checked as well
+        if root != block.header.merkle_root {
+            return Err(anyhow::anyhow!(
+                "Proof merkle root {} does not match its header's {}",
+                Self::hash_to_hex(&root),
+                Self::hash_to_hex(&block.header.merkle_root)
+            ));
+        }
+        if Self::hash_to_hex(&root) != header.merkleroot {
+            return Err(anyhow::anyhow!(
+                "Proof merkle root {} does not match {}",
+                Self::hash_to_hex(&root),
+                header.merkleroot
+            ));
+        }
+        Ok(matches.into_iter().map(|m| m.txid).collect())
+    }
+
+    pub fn deserialize_block(block_hex: &str) -> Result<RawBlock> {
+        let bytes = hex::decode(block_hex)?;
+        let mut reader = ByteReader::new(&bytes);
+        let header = reader.block_header()?;
+        let count = reader.varint()?;
+        let mut transactions = Vec::new();
+        for _ in 0..count {
+            transactions.push(reader.transaction()?);
+        }
+        if reader.remaining() != 0 {
+            return Err(anyhow::anyhow!("Trailing data after block"));
+        }
+        Ok(RawBlock {
+            header,
+            transactions,
+        })
+    }
+}
+
+// Cursor over a byte slice used by the binary parsers in this crate
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        ByteReader { data, pos: 0 }
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.remaining() < len {
+            return Err(anyhow::anyhow!("Unexpected end of data"));
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    pub(crate) fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32_le(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    pub(crate) fn u64_le(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    pub(crate) fn hash(&mut self) -> Result<[u8; 32]> {
+        Ok(self.take(32)?.try_into()?)
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<u64> {
+        let (value, used) = Serialization::deserialize_varint(&self.data[self.pos..])?;
+        self.pos += used;
+        Ok(value)
+    }
+
+    pub(crate) fn var_bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.varint()? as usize;
+        self.take(len)
+    }
+
+    pub(crate) fn block_header(&mut self) -> Result<RawBlockHeader> {
+        Ok(RawBlockHeader {
+            version: self.u32_le()? as i32,
+            prev_blockhash: self.hash()?,
+            merkle_root: self.hash()?,
+            time: self.u32_le()?,
+            bits: self.u32_le()?,
+            nonce: self.u32_le()?,
+        })
+    }
+
+    pub(crate) fn transaction(&mut self) -> Result<RawTransaction> {
+        let version = self.u32_le()? as i32;
+        let segwit = self.remaining() >= 2 && self.data[self.pos] == 0x00;
+        if segwit {
+            if self.data[self.pos + 1] != 0x01 {
+                return Err(anyhow::anyhow!("Invalid segwit flag"));
+            }
+            self.pos += 2;
+        }
+        let input_count = self.varint()?;
+        let mut inputs = Vec::new();
+        for _ in 0..input_count {
+            inputs.push(RawTxInput {
+                prev_txid: self.hash()?,
+                prev_vout: self.u32_le()?,
+                script_sig: self.var_bytes()?.to_vec(),
+                sequence: self.u32_le()?,
+                witness: Vec::new(),
+            });
+        }
+        let output_count = self.varint()?;
+        let mut outputs = Vec::new();
+        for _ in 0..output_count {
+            outputs.push(RawTxOutput {
+                value: self.u64_le()?,
+                script_pubkey: self.var_bytes()?.to_vec(),
+            });
+        }
+        if segwit {
+            for input in &mut inputs {
+                let items = self.varint()?;
+                for _ in 0..items {
+                    input.witness.push(self.var_bytes()?.to_vec());
+                }
+            }
+        }
+        Ok(RawTransaction {
+            version,
+            inputs,
+            outputs,
+            lock_time: self.u32_le()?,
+        })
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::{Result, anyhow};
+use secp256k1::{Message, Secp256k1, SecretKey};
+
+use crate::BitcoinClient;
+use crate::crypto::BitcoinCrypto;
+use crate::script::push_data;
+use crate::serialization::{RawTransaction, RawTxInput, RawTxOutput, SIGHASH_ALL, Serialization};
+use crate::types::BlockchainInfo;
+
+// BIP325 commitment section header
+const SIGNET_HEADER: [u8; 4] = [0xec, 0xc7, 0xda, 0xa2];
+const WITNESS_COMMITMENT_PREFIX: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+const OP_0: u8 = 0x00;
+const OP_1: u8 = 0x51;
+const OP_RETURN: u8 = 0x6a;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_CHECKMULTISIG: u8 = 0xae;
+const P2PKH_MAINNET: u8 = 0x00;
+const P2PKH_TESTNET: u8 = 0x6f;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignetParams {
+    pub challenge_script: Vec<u8>,
+}
+
+impl SignetParams {
+    pub fn new(challenge_script: Vec<u8>) -> Self {
+        SignetParams { challenge_script }
+    }
+
+    pub fn from_hex(challenge_hex: &str) -> Result<Self> {
+        Ok(Self::new(hex::decode(challenge_hex)?))
+    }
+
+    pub fn from_blockchain_info(info: &BlockchainInfo) -> Result<Self> {
+        let challenge = info
+            .signet_challenge
+            .as_deref()
+            .ok_or_else(|| anyhow!("Node is not running on a signet chain"))?;
+        Self::from_hex(challenge)
+    }
+
+    // Public key of a single-key challenge (`<pubkey> OP_CHECKSIG` or `OP_1 <pubkey> OP_1 OP_CHECKMULTISIG`)
+    pub fn single_key(&self) -> Option<&[u8]> {
+        let key = match self.challenge_script.as_slice() {
+            [len, key @ .., OP_CHECKSIG] if *len as usize == key.len() => Some(key),
+            [OP_1, len, key @ .., OP_1, OP_CHECKMULTISIG] if *len as usize == key.len() => {
+                Some(key)
+            }
+            _ => None,
+        };
+        key.filter(|key| key.len() == 33 || key.len() == 65)
+    }
+
+    // Check whether the private key satisfies the single-key challenge
+    pub fn can_sign(&self, private_key: &[u8; 32]) -> Result<bool> {
+        let Some(key) = self.single_key() else {
+            return Ok(false);
+        };
+        let compressed = key.len() == 33;
+        Ok(BitcoinCrypto::private_to_public(private_key, compressed)? == key)
+    }
+
+    // Check whether a P2PKH address belongs to the challenge key; other address types never match
+    pub fn matches_address(&self, address: &str) -> Result<bool> {
+        let Some(key) = self.single_key() else {
+            return Ok(false);
+        };
+        let (version, hash) = BitcoinCrypto::decode_address(address)?;
+        if !matches!(version, P2PKH_MAINNET | P2PKH_TESTNET) {
+            return Ok(false);
+        }
+        Ok(hash == BitcoinCrypto::hash160(key))
+    }
+
+    // Add the BIP325 block signature to a block, returning the signed block hex.
+    // The block must carry a witness commitment; proof of work has to be redone afterwards.
+    pub fn sign_signet_block(&self, block_hex: &str, private_key: &[u8; 32]) -> Result<String> {
+        if !self.can_sign(private_key)? {
+            return Err(anyhow!("Private key does not satisfy the signet challenge"));
+        }
+        let mut block = Serialization::deserialize_block(block_hex)?;
+        let coinbase = block
+            .transactions
+            .first_mut()
+            .ok_or_else(|| anyhow!("Block has no coinbase transaction"))?;
+        let commitment_index = coinbase
+            .outputs
+            .iter()
+            .rposition(|out| out.script_pubkey.starts_with(&WITNESS_COMMITMENT_PREFIX))
+            .ok_or_else(|| anyhow!("Block has no witness commitment"))?;
+        let commitment = coinbase.outputs[commitment_index].script_pubkey.clone();
+        if commitment.len() != WITNESS_COMMITMENT_PREFIX.len() + 32 {
+            return Err(anyhow!("Block is already signed"));
+        }
+
+        // The signed merkle root covers the coinbase with an empty signet section
+        let mut unsigned = commitment.clone();
+        unsigned.extend(push_data(&SIGNET_HEADER));
+        coinbase.outputs[commitment_index].script_pubkey = unsigned;
+        let signet_merkle = block.compute_merkle_root();
+
+        let mut block_data = block.header.version.to_le_bytes().to_vec();
+        block_data.extend_from_slice(&block.header.prev_blockhash);
+        block_data.extend_from_slice(&signet_merkle);
+        block_data.extend_from_slice(&block.header.time.to_le_bytes());
+        let mut to_spend_script = vec![OP_0];
+        to_spend_script.extend(push_data(&block_data));
+        let to_spend = RawTransaction {
+            version: 0,
+            inputs: vec![RawTxInput {
+                prev_txid: [0u8; 32],
+                prev_vout: 0xFFFFFFFF,
+                script_sig: to_spend_script,
+                sequence: 0,
+                witness: Vec::new(),
+            }],
+            outputs: vec![RawTxOutput {
+                value: 0,
+                script_pubkey: self.challenge_script.clone(),
+            }],
+            lock_time: 0,
+        };
+        let mut to_sign = RawTransaction {
+            version: 0,
+            inputs: vec![RawTxInput {
+                prev_txid: to_spend.txid_bytes(),
+                prev_vout: 0,
+                script_sig: self.challenge_script.clone(),
+                sequence: 0,
+                witness: Vec::new(),
+            }],
+            outputs: vec![RawTxOutput {
+                value: 0,
+                script_pubkey: vec![OP_RETURN],
+            }],
+            lock_time: 0,
+        };
+
+        // Legacy SIGHASH_ALL digest with the challenge as script code. Core grinds for a
+        // low R, so the signed block matches its signet miner byte for byte.
+        let digest = to_sign.legacy_sighash(0, &self.challenge_script, SIGHASH_ALL);
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(private_key)?;
+        let signature = secp.sign_ecdsa_low_r(&Message::from_slice(&digest)?, &secret_key);
+        let mut signature = signature.serialize_der().to_vec();
+        signature.push(SIGHASH_ALL as u8);
+
+        let mut script_sig = Vec::new();
+        if self.challenge_script.last() == Some(&OP_CHECKMULTISIG) {
+            script_sig.push(OP_0);
+        }
+        script_sig.extend(push_data(&signature));
+        to_sign.inputs[0].script_sig = script_sig;
+
+        // Solution is the scriptSig followed by an empty witness stack
+        let mut section = SIGNET_HEADER.to_vec();
+        section.extend(Serialization::serialize_bytes(
+            &to_sign.inputs[0].script_sig,
+        ));
+        section.extend(Serialization::serialize_varint(0));
+        let mut signed = commitment;
+        signed.extend(push_data(&section));
+        block.transactions[0].outputs[commitment_index].script_pubkey = signed;
+        block.header.merkle_root = block.compute_merkle_root();
+        Ok(hex::encode(block.serialize()))
+    }
+}
+
+impl BitcoinClient {
+    pub async fn get_signet_params(&self) -> Result<SignetParams> {
+        let info = self.get_blockchain_info().await?;
+        SignetParams::from_blockchain_info(&info)
+    }
+}
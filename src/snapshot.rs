@@ -0,0 +1,246 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::crypto::BitcoinCrypto;
+use crate::script::{ScriptType, classify_script};
+use crate::serialization::{ByteReader, Serialization};
+use crate::types::ScanObject;
+use crate::{BitcoinClient, BitcoinClientType};
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"BUTX";
+const SNAPSHOT_VERSION: u8 = 1;
+const MAX_MONEY_SAT: u64 = 21_000_000 * 100_000_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtxoSnapshot {
+    pub network: BitcoinClientType,
+    pub descriptors: Vec<String>,
+    pub height: u64,
+    pub block_hash: String,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub txid: String,
+    pub vout: u32,
+    pub amount_sat: u64,
+    pub script_type: ScriptType,
+    pub script_pub_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub created: Vec<SnapshotEntry>,
+    pub spent: Vec<SnapshotEntry>,
+}
+
+impl UtxoSnapshot {
+    // Encode the snapshot in the versioned binary format, trailed by a sha256 of the body
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut data = SNAPSHOT_MAGIC.to_vec();
+        data.push(SNAPSHOT_VERSION);
+        data.push(network_to_u8(self.network));
+        data.extend(Serialization::serialize_varint(self.height));
+        data.extend_from_slice(&Serialization::hex_to_hash(&self.block_hash)?);
+        data.extend(Serialization::serialize_varint(
+            self.descriptors.len() as u64
+        ));
+        for descriptor in &self.descriptors {
+            data.extend(Serialization::serialize_string(descriptor));
+        }
+        data.extend(Serialization::serialize_varint(self.entries.len() as u64));
+        for entry in &self.entries {
+            let script = hex::decode(&entry.script_pub_key)?;
+            data.extend_from_slice(&Serialization::hex_to_hash(&entry.txid)?);
+            data.extend(Serialization::serialize_varint(entry.vout as u64));
+            data.extend(Serialization::serialize_varint(entry.amount_sat));
+            data.push(entry.script_type.to_u8());
+            data.extend(Serialization::serialize_bytes(&script));
+        }
+        let checksum = BitcoinCrypto::sha256(&data);
+        data.extend_from_slice(&checksum);
+        Ok(data)
+    }
+
+    // Decode a snapshot, verifying the trailing checksum and every entry
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < SNAPSHOT_MAGIC.len() + 2 + 32 {
+            return Err(anyhow!("Snapshot too short"));
+        }
+        let (body, checksum) = bytes.split_at(bytes.len() - 32);
+        if BitcoinCrypto::sha256(body) != checksum {
+            return Err(anyhow!("Snapshot checksum mismatch"));
+        }
+        let mut reader = ByteReader::new(body);
+        if reader.take(4)? != SNAPSHOT_MAGIC {
+            return Err(anyhow!("Not a UTXO snapshot file"));
+        }
+        let version = reader.byte()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(anyhow!("Unsupported snapshot version {}", version));
+        }
+        let network = network_from_u8(reader.byte()?)?;
+        let height = reader.varint()?;
+        let block_hash = Serialization::hash_to_hex(&reader.hash()?);
+        let descriptor_count = reader.varint()?;
+        let mut descriptors = Vec::new();
+        for _ in 0..descriptor_count {
+            let len = reader.varint()? as usize;
+            let descriptor = String::from_utf8(reader.take(len)?.to_vec())
+                .map_err(|_| anyhow!("Descriptor is not valid UTF-8"))?;
+            descriptors.push(descriptor);
+        }
+        let entry_count = reader.varint()?;
+        let mut entries = Vec::new();
+        for index in 0..entry_count {
+            let txid = Serialization::hash_to_hex(&reader.hash()?);
+            let vout = u32::try_from(reader.varint()?)
+                .map_err(|_| anyhow!("Entry {}: vout out of range", index))?;
+            let amount_sat = reader.varint()?;
+            let script_type = ScriptType::from_u8(reader.byte()?)
+                .ok_or_else(|| anyhow!("Entry {}: unknown script type", index))?;
+            let script = reader.var_bytes()?;
+            if amount_sat > MAX_MONEY_SAT {
+                return Err(anyhow!("Entry {}: amount exceeds money supply", index));
+            }
+            if classify_script(script) != script_type {
+                return Err(anyhow!(
+                    "Entry {}: script type does not match script",
+                    index
+                ));
+            }
+            entries.push(SnapshotEntry {
+                txid,
+                vout,
+                amount_sat,
+                script_type,
+                script_pub_key: hex::encode(script),
+            });
+        }
+        if reader.remaining() != 0 {
+            return Err(anyhow!("Trailing data after snapshot entries"));
+        }
+        Ok(UtxoSnapshot {
+            network,
+            descriptors,
+            height,
+            block_hash,
+            entries,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+}
+
+// Read a snapshot previously written by `export_utxo_snapshot`
+pub fn load_utxo_snapshot(path: &Path) -> Result<UtxoSnapshot> {
+    let bytes = std::fs::read(path)?;
+    UtxoSnapshot::from_bytes(&bytes)
+}
+
+// Compare two snapshots of the same descriptor set on the same network
+pub fn diff_snapshots(old: &UtxoSnapshot, new: &UtxoSnapshot) -> Result<SnapshotDiff> {
+    if old.network != new.network {
+        return Err(anyhow!(
+            "Snapshots were taken on different networks ({:?} vs {:?})",
+            old.network,
+            new.network
+        ));
+    }
+    let mut old_descriptors = old.descriptors.clone();
+    let mut new_descriptors = new.descriptors.clone();
+    old_descriptors.sort();
+    new_descriptors.sort();
+    if old_descriptors != new_descriptors {
+        return Err(anyhow!(
+            "Snapshots were taken with different descriptor sets"
+        ));
+    }
+    let old_entries: HashMap<(&str, u32), &SnapshotEntry> = old
+        .entries
+        .iter()
+        .map(|e| ((e.txid.as_str(), e.vout), e))
+        .collect();
+    let new_entries: HashMap<(&str, u32), &SnapshotEntry> = new
+        .entries
+        .iter()
+        .map(|e| ((e.txid.as_str(), e.vout), e))
+        .collect();
+    let created = new
+        .entries
+        .iter()
+        .filter(|e| !old_entries.contains_key(&(e.txid.as_str(), e.vout)))
+        .cloned()
+        .collect();
+    let spent = old
+        .entries
+        .iter()
+        .filter(|e| !new_entries.contains_key(&(e.txid.as_str(), e.vout)))
+        .cloned()
+        .collect();
+    Ok(SnapshotDiff { created, spent })
+}
+
+impl BitcoinClient {
+    // Scan the UTXO set for the descriptors and persist the result as a snapshot file
+    pub async fn export_utxo_snapshot(
+        &self,
+        descriptors: &[&str],
+        path: &Path,
+    ) -> Result<UtxoSnapshot> {
+        let info = self.get_blockchain_info().await?;
+        let network: BitcoinClientType = serde_json::from_value(info.chain.clone().into())
+            .map_err(|_| anyhow!("Unsupported chain: {}", info.chain))?;
+        let objects: Vec<ScanObject> = descriptors.iter().map(|d| ScanObject::from(*d)).collect();
+        let scan = self.scan_tx_out_set(&objects).await?;
+        if !scan.success {
+            return Err(anyhow!("UTXO set scan did not complete"));
+        }
+        let mut entries = Vec::new();
+        for utxo in scan.unspents {
+            let script = hex::decode(&utxo.script_pub_key)?;
+            entries.push(SnapshotEntry {
+                txid: utxo.txid,
+                vout: utxo.vout,
+                amount_sat: utxo.amount.to_unsigned_sat()?,
+                script_type: classify_script(&script),
+                script_pub_key: utxo.script_pub_key,
+            });
+        }
+        entries.sort_by(|a, b| (&a.txid, a.vout).cmp(&(&b.txid, b.vout)));
+        let snapshot = UtxoSnapshot {
+            network,
+            descriptors: descriptors.iter().map(|d| d.to_string()).collect(),
+            height: scan.height,
+            block_hash: scan.bestblock,
+            entries,
+        };
+        snapshot.save(path)?;
+        Ok(snapshot)
+    }
+}
+
+fn network_to_u8(network: BitcoinClientType) -> u8 {
+    match network {
+        BitcoinClientType::Mainnet => 0,
+        BitcoinClientType::Testnet => 1,
+        BitcoinClientType::Signet => 2,
+        BitcoinClientType::Regtest => 3,
+    }
+}
+
+fn network_from_u8(value: u8) -> Result<BitcoinClientType> {
+    match value {
+        0 => Ok(BitcoinClientType::Mainnet),
+        1 => Ok(BitcoinClientType::Testnet),
+        2 => Ok(BitcoinClientType::Signet),
+        3 => Ok(BitcoinClientType::Regtest),
+        _ => Err(anyhow!("Unknown snapshot network: {}", value)),
+    }
+}
@@ -0,0 +1,179 @@
+use anyhow::{Result, anyhow};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde_json::{Value, json};
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+use crate::BitcoinClient;
+use crate::types::{Utxo, WalletTransaction, WalletTxCategory};
+
+// Entries re-read from the previous page to notice the list shifting underneath us
+const PAGE_OVERLAP: usize = 20;
+// Restarts from the newest entry allowed when a page no longer lines up
+const MAX_PAGE_RESTARTS: u32 = 3;
+// Addresses per `listunspent` call when partitioning by address
+const UNSPENT_ADDRESS_CHUNK: usize = 500;
+const MAX_CONFIRMATIONS: i32 = 9_999_999;
+
+type EntryKey = (String, WalletTxCategory, Option<u32>);
+
+struct TransactionPager<'a> {
+    client: &'a BitcoinClient,
+    page_size: usize,
+    // Entries consumed so far, counted from the newest
+    advanced: usize,
+    seen: HashSet<EntryKey>,
+    restarts: u32,
+    done: bool,
+}
+
+impl TransactionPager<'_> {
+    async fn next_page(&mut self) -> Result<Vec<WalletTransaction>> {
+        loop {
+            let overlap = PAGE_OVERLAP.min(self.advanced);
+            let skip = self.advanced - overlap;
+            let count = self.page_size + overlap;
+            let mut page: Vec<WalletTransaction> = self
+                .client
+                .call("listtransactions", json!(["*", count, skip, true]))
+                .await?;
+            // Each page comes oldest first; the stream runs newest first
+            page.reverse();
+            let key = |t: &WalletTransaction| (t.txid.clone(), t.category, t.vout);
+            if overlap > 0 && !page.is_empty() && !page.iter().any(|t| self.seen.contains(&key(t)))
+            {
+                // Entries were removed since the last page, so this one may have jumped
+                // past some; start over and let the seen set drop repeats
+                self.restarts += 1;
+                if self.restarts > MAX_PAGE_RESTARTS {
+                    return Err(anyhow!("Transaction list kept shifting while paging"));
+                }
+                self.advanced = 0;
+                continue;
+            }
+            self.done = page.len() < count;
+            self.advanced = skip + page.len();
+            return Ok(page
+                .into_iter()
+                .filter(|t| self.seen.insert(key(t)))
+                .collect());
+        }
+    }
+}
+
+struct UnspentPager<'a> {
+    client: &'a BitcoinClient,
+    // None until the wallet's addresses have been fetched
+    chunks: Option<VecDeque<Vec<String>>>,
+}
+
+impl UnspentPager<'_> {
+    // Every address the wallet can hold outputs on: receive addresses, including unused
+    // and unlabeled ones, plus the change addresses `listaddressgroupings` reveals
+    async fn wallet_addresses(&self) -> Result<BTreeSet<String>> {
+        let received = self
+            .client
+            .list_received_by_address(0, true, true, None)
+            .await?;
+        let mut addresses: BTreeSet<String> = received.into_iter().map(|r| r.address).collect();
+        let groupings: Vec<Vec<Vec<Value>>> =
+            self.client.call("listaddressgroupings", json!([])).await?;
+        addresses.extend(
+            groupings
+                .iter()
+                .flatten()
+                .filter_map(|entry| entry.first()?.as_str().map(str::to_string)),
+        );
+        Ok(addresses)
+    }
+
+    // The next batch of addresses, or None when every address is done
+    async fn next_chunk(&mut self) -> Result<Option<Vec<String>>> {
+        if self.chunks.is_none() {
+            let addresses: Vec<String> = self.wallet_addresses().await?.into_iter().collect();
+            self.chunks = Some(
+                addresses
+                    .chunks(UNSPENT_ADDRESS_CHUNK)
+                    .map(|c| c.to_vec())
+                    .collect(),
+            );
+        }
+        Ok(self.chunks.as_mut().and_then(|c| c.pop_front()))
+    }
+
+    async fn next_page(&mut self) -> Result<Option<Vec<Utxo>>> {
+        let Some(chunk) = self.next_chunk().await? else {
+            return Ok(None);
+        };
+        let addresses: Vec<&str> = chunk.iter().map(|a| a.as_str()).collect();
+        let utxos = self
+            .client
+            .list_unspent(0, MAX_CONFIRMATIONS, Some(addresses))
+            .await?;
+        Ok(Some(utxos))
+    }
+}
+
+// Flatten pages into items, ending after the first error
+fn flatten_pages<T>(pages: impl Stream<Item = Result<Vec<T>>>) -> impl Stream<Item = Result<T>> {
+    pages.flat_map(|page| {
+        let items: Vec<Result<T>> = match page {
+            Ok(items) => items.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        };
+        stream::iter(items)
+    })
+}
+
+impl BitcoinClient {
+    // Every wallet transaction entry, newest first, fetched `page_size` at a time.
+    // Consecutive pages overlap so entries that shift between requests are de-duplicated
+    // on txid, category and vout rather than returned twice or skipped. Entries that
+    // arrive after the stream starts may or may not be included.
+    pub fn stream_transactions(
+        &self,
+        page_size: usize,
+    ) -> impl Stream<Item = Result<WalletTransaction>> + '_ {
+        let pager = TransactionPager {
+            client: self,
+            page_size: page_size.max(1),
+            advanced: 0,
+            seen: HashSet::new(),
+            restarts: 0,
+            done: false,
+        };
+        let pages = stream::unfold(pager, |mut pager| async move {
+            if pager.done {
+                return None;
+            }
+            let page = pager.next_page().await;
+            pager.done |= page.is_err();
+            Some((page, pager))
+        });
+        flatten_pages(pages)
+    }
+
+    // Every wallet UTXO, including unconfirmed ones. With `chunk_by_address` the set is
+    // fetched in bounded batches over the wallet's receive and change addresses; change
+    // addresses first used after the stream starts are not covered.
+    pub fn stream_unspent(&self, chunk_by_address: bool) -> impl Stream<Item = Result<Utxo>> + '_ {
+        let pager = UnspentPager {
+            client: self,
+            chunks: None,
+        };
+        let pages = stream::unfold((pager, false), move |(mut pager, done)| async move {
+            if done {
+                return None;
+            }
+            if !chunk_by_address {
+                let all = pager.client.list_unspent(0, MAX_CONFIRMATIONS, None).await;
+                return Some((all, (pager, true)));
+            }
+            match pager.next_page().await {
+                Ok(Some(page)) => Some((Ok(page), (pager, false))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), (pager, true))),
+            }
+        });
+        flatten_pages(pages)
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::error::BitcoinRpcError;
+use crate::middleware::{Next, RpcMiddleware, RpcRequest, RpcResponse};
+use crate::rpc::BitcoinRpc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedCall {
+    pub method: String,
+    pub params: Value,
+}
+
+// In-memory `BitcoinRpc` for tests. Responses are queued per method and handed out in
+// order; a call with nothing queued fails. Every call is recorded for later assertions.
+#[derive(Debug, Default)]
+pub struct MockBitcoinRpc {
+    responses: Mutex<HashMap<String, VecDeque<Result<Value, BitcoinRpcError>>>>,
+    calls: Mutex<Vec<RecordedCall>>,
+}
+
+impl MockBitcoinRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Queue the next result for `method`
+    pub fn respond<T: Serialize>(&self, method: &str, result: T) -> &Self {
+        let value = serde_json::to_value(result).expect("mock response must serialize");
+        self.push(method, Ok(value))
+    }
+
+    // Queue an error for the next call to `method`
+    pub fn fail(&self, method: &str, error: BitcoinRpcError) -> &Self {
+        self.push(method, Err(error))
+    }
+
+    fn push(&self, method: &str, response: Result<Value, BitcoinRpcError>) -> &Self {
+        self.responses
+            .lock()
+            .unwrap()
+            .entry(method.to_string())
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    pub fn calls_to(&self, method: &str) -> Vec<Value> {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|call| call.method == method)
+            .map(|call| call.params.clone())
+            .collect()
+    }
+
+    // Panic with the recorded calls when `method` was never called with `params`
+    pub fn assert_called_with(&self, method: &str, params: &Value) {
+        let calls = self.calls();
+        assert!(
+            calls
+                .iter()
+                .any(|c| c.method == method && &c.params == params),
+            "{} was not called with {}; calls: {:?}",
+            method,
+            params,
+            calls
+        );
+    }
+
+    // Panic when queued responses were left unused
+    pub fn assert_drained(&self) {
+        let responses = self.responses.lock().unwrap();
+        let left: Vec<(&String, usize)> = responses
+            .iter()
+            .filter(|(_, queue)| !queue.is_empty())
+            .map(|(method, queue)| (method, queue.len()))
+            .collect();
+        assert!(left.is_empty(), "Unused mock responses: {:?}", left);
+    }
+}
+
+#[async_trait]
+impl BitcoinRpc for MockBitcoinRpc {
+    async fn call_value(&self, method: &str, params: Value) -> Result<Value> {
+        self.calls.lock().unwrap().push(RecordedCall {
+            method: method.to_string(),
+            params,
+        });
+        let response = self
+            .responses
+            .lock()
+            .unwrap()
+            .get_mut(method)
+            .and_then(|queue| queue.pop_front());
+        match response {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(error)) => Err(error.into()),
+            None => Err(anyhow!("No mock response queued for {}", method)),
+        }
+    }
+}
+
+// Middleware counting the calls made per method, e.g. to assert a cache avoided a
+// round trip
+#[derive(Debug, Default)]
+pub struct CallCounter {
+    counts: Mutex<HashMap<String, u64>>,
+}
+
+impl CallCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count(&self, method: &str) -> u64 {
+        self.counts
+            .lock()
+            .unwrap()
+            .get(method)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.lock().unwrap().values().sum()
+    }
+}
+
+#[async_trait]
+impl RpcMiddleware for CallCounter {
+    async fn handle(&self, req: RpcRequest, next: Next<'_>) -> Result<RpcResponse> {
+        *self
+            .counts
+            .lock()
+            .unwrap()
+            .entry(req.method.clone())
+            .or_default() += 1;
+        next.run(req).await
+    }
+}
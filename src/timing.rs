@@ -0,0 +1,122 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+use crate::BitcoinClient;
+use crate::types::BlockHeader;
+
+// Heights probed per round of the search; two batched RPC calls per round
+const PROBES_PER_ROUND: u64 = 15;
+const TARGET_BLOCK_SECS: u64 = 600;
+const MAX_BLOCK_VSIZE: u64 = 1_000_000;
+
+// Which block `find_block_at_time` returns. Both compare against `mediantime`, which unlike
+// `time` never decreases along the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeHint {
+    // The first block whose median time is at or after the timestamp
+    FirstAtOrAfter,
+    // The last block whose median time is before the timestamp
+    LastBefore,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eta {
+    pub expected_blocks: u64,
+    pub expected_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EtaEntry {
+    vsize: u64,
+    ancestorsize: u64,
+    fees: EtaFees,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EtaFees {
+    ancestor: f64,
+}
+
+impl EtaEntry {
+    fn package_feerate(&self) -> f64 {
+        self.fees.ancestor / self.ancestorsize.max(1) as f64
+    }
+}
+
+impl BitcoinClient {
+    pub async fn find_block_at_time(&self, unix_time: u64, hint: TimeHint) -> Result<BlockHeader> {
+        let tip = self.get_block_count().await?;
+        // Invariant: mediantime(h) < unix_time for h < low, >= unix_time for h >= high
+        let (mut low, mut high) = (0u64, tip + 1);
+        while low < high {
+            let step = ((high - low) / (PROBES_PER_ROUND + 1)).max(1);
+            let heights: Vec<u64> = (1..=PROBES_PER_ROUND)
+                .map(|i| low + step * i - 1)
+                .filter(|h| *h < high)
+                .collect();
+            let headers = self.headers_at(&heights).await?;
+            let mut new_low = low;
+            let mut new_high = high;
+            for (height, header) in heights.iter().zip(&headers) {
+                if header.mediantime < unix_time {
+                    new_low = height + 1;
+                } else {
+                    new_high = *height;
+                    break;
+                }
+            }
+            low = new_low;
+            high = new_high;
+        }
+        let height = match hint {
+            TimeHint::FirstAtOrAfter if low > tip => {
+                return Err(anyhow!("No block at or after {}", unix_time));
+            }
+            TimeHint::FirstAtOrAfter => low,
+            TimeHint::LastBefore if low == 0 => {
+                return Err(anyhow!("No block before {}", unix_time));
+            }
+            TimeHint::LastBefore => low - 1,
+        };
+        let hash = self.get_block_hash(height).await?;
+        self.get_block_header(&hash).await
+    }
+
+    // Estimate when a mempool transaction confirms from how much higher-paying
+    // package weight is queued ahead of it
+    pub async fn estimate_confirmation_eta(&self, txid: &str) -> Result<Eta> {
+        let entry: EtaEntry = self.call("getmempoolentry", json!([txid])).await?;
+        let mempool: HashMap<String, EtaEntry> = self.call("getrawmempool", json!([true])).await?;
+        let feerate = entry.package_feerate();
+        let ahead: u64 = mempool
+            .iter()
+            .filter(|(id, other)| id.as_str() != txid && other.package_feerate() > feerate)
+            .map(|(_, other)| other.vsize)
+            .sum();
+        let expected_blocks = (ahead + entry.vsize).div_ceil(MAX_BLOCK_VSIZE).max(1);
+        Ok(Eta {
+            expected_blocks,
+            expected_seconds: expected_blocks * TARGET_BLOCK_SECS,
+        })
+    }
+
+    // Fetch headers for several heights with two batch calls
+    pub(crate) async fn headers_at(&self, heights: &[u64]) -> Result<Vec<BlockHeader>> {
+        let hashes = self
+            .batch_call(
+                heights
+                    .iter()
+                    .map(|h| ("getblockhash".to_string(), json!([h])))
+                    .collect(),
+            )
+            .await?;
+        let hashes: Vec<String> = hashes
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<_, _>>()?;
+        let hashes: Vec<&str> = hashes.iter().map(String::as_str).collect();
+        self.get_block_headers_batch(&hashes).await
+    }
+}
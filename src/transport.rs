@@ -0,0 +1,200 @@
+use reqwest::header::HeaderMap;
+use reqwest::{Client, StatusCode};
+use std::time::Duration;
+
+use crate::error::BitcoinRpcError;
+
+// How requests reach the node. The RPC plumbing only sees `send`, so wallet routing,
+// cookies and retries work the same over every transport.
+#[derive(Debug, Clone)]
+pub(crate) enum Transport {
+    // HTTP(S) over TCP, with the proxy URL kept for error messages
+    Tcp {
+        client: Client,
+        proxy: Option<String>,
+    },
+    // Plain HTTP over a Unix domain socket
+    #[cfg(unix)]
+    Unix {
+        path: std::path::PathBuf,
+        timeout: Duration,
+    },
+}
+
+pub(crate) struct HttpReply {
+    pub(crate) status: StatusCode,
+    // Separate from the status, since bitcoind's `stop` may drop the connection mid-body
+    pub(crate) body: Result<Vec<u8>, BitcoinRpcError>,
+}
+
+impl Transport {
+    // POST `body` to `url`; for a Unix socket only the URL's path is used. `timeout`
+    // replaces the transport's own for this request.
+    pub(crate) async fn send(
+        &self,
+        url: &str,
+        headers: HeaderMap,
+        body: Vec<u8>,
+        timeout: Option<Duration>,
+    ) -> Result<HttpReply, BitcoinRpcError> {
+        match self {
+            Transport::Tcp { client, proxy } => {
+                let mut request = client.post(url).headers(headers).body(body);
+                if let Some(timeout) = timeout {
+                    request = request.timeout(timeout);
+                }
+                let response = request.send().await.map_err(|e| match proxy {
+                    // Through a proxy the first hop is the proxy, not the node
+                    Some(proxy) if e.is_connect() => {
+                        BitcoinRpcError::Proxy(format!("{}: {}", proxy, e))
+                    }
+                    _ => BitcoinRpcError::from(e),
+                })?;
+                let status = response.status();
+                let body = response
+                    .bytes()
+                    .await
+                    .map(|bytes| bytes.to_vec())
+                    .map_err(BitcoinRpcError::from);
+                Ok(HttpReply { status, body })
+            }
+            #[cfg(unix)]
+            Transport::Unix {
+                path,
+                timeout: default_timeout,
+            } => {
+                let timeout = timeout.unwrap_or(*default_timeout);
+                tokio::time::timeout(timeout, unix::send(path, url, &headers, &body))
+                    .await
+                    .map_err(|_| {
+                        BitcoinRpcError::Timeout(format!(
+                            "no reply on {} within {:?}",
+                            path.display(),
+                            timeout
+                        ))
+                    })?
+            }
+        }
+    }
+}
+
+// Just enough HTTP/1.1 for bitcoind: one request per connection, closed by the server
+#[cfg(unix)]
+mod unix {
+    use reqwest::header::HeaderMap;
+    use reqwest::{StatusCode, Url};
+    use std::path::Path;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixStream;
+
+    use super::HttpReply;
+    use crate::error::BitcoinRpcError;
+
+    pub(super) async fn send(
+        path: &Path,
+        url: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<HttpReply, BitcoinRpcError> {
+        let socket_error = |e: std::io::Error| {
+            BitcoinRpcError::Transport(format!("Unix socket {}: {}", path.display(), e))
+        };
+        let target = Url::parse(url)
+            .map_err(|e| BitcoinRpcError::Transport(format!("Invalid URL {}: {}", url, e)))?;
+        let mut stream = UnixStream::connect(path).await.map_err(|e| {
+            BitcoinRpcError::Connect(format!("Unix socket {}: {}", path.display(), e))
+        })?;
+        let mut request = format!(
+            "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n",
+            target.path(),
+            body.len()
+        )
+        .into_bytes();
+        for (name, value) in headers {
+            request.extend_from_slice(name.as_str().as_bytes());
+            request.extend_from_slice(b": ");
+            request.extend_from_slice(value.as_bytes());
+            request.extend_from_slice(b"\r\n");
+        }
+        request.extend_from_slice(b"\r\n");
+        request.extend_from_slice(body);
+        stream.write_all(&request).await.map_err(socket_error)?;
+
+        let mut raw = Vec::new();
+        let read_error = stream.read_to_end(&mut raw).await.err();
+        let Some(head_end) = raw.windows(4).position(|w| w == b"\r\n\r\n") else {
+            return Err(match read_error {
+                Some(e) => socket_error(e),
+                None => BitcoinRpcError::Transport(format!(
+                    "Unix socket {}: connection closed before a complete HTTP reply",
+                    path.display()
+                )),
+            });
+        };
+        let head = String::from_utf8_lossy(&raw[..head_end]).to_string();
+        let mut lines = head.split("\r\n");
+        let status = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse::<u16>().ok())
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .ok_or_else(|| {
+                BitcoinRpcError::Transport(format!(
+                    "Unix socket {}: malformed status line",
+                    path.display()
+                ))
+            })?;
+        let mut content_length = None;
+        let mut chunked = false;
+        for line in lines {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse::<usize>().ok();
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                chunked = value.eq_ignore_ascii_case("chunked");
+            }
+        }
+        let rest = &raw[head_end + 4..];
+        let body = if chunked {
+            decode_chunked(rest)
+        } else {
+            match content_length {
+                Some(length) if rest.len() >= length => Some(rest[..length].to_vec()),
+                Some(_) => None,
+                None => Some(rest.to_vec()),
+            }
+        };
+        let body = body.ok_or_else(|| match read_error {
+            Some(e) => socket_error(e),
+            None => BitcoinRpcError::Transport(format!(
+                "Unix socket {}: connection closed mid-body",
+                path.display()
+            )),
+        });
+        Ok(HttpReply { status, body })
+    }
+
+    // None when the data ends before the final zero-length chunk
+    fn decode_chunked(mut data: &[u8]) -> Option<Vec<u8>> {
+        let mut body = Vec::new();
+        loop {
+            let line_end = data.windows(2).position(|w| w == b"\r\n")?;
+            let size_field = std::str::from_utf8(&data[..line_end]).ok()?;
+            let size_field = size_field.split(';').next()?.trim();
+            let size = usize::from_str_radix(size_field, 16).ok()?;
+            data = &data[line_end + 2..];
+            if size == 0 {
+                return Some(body);
+            }
+            // A size near usize::MAX must not wrap past the end of the data
+            if data.len() < size.checked_add(2)? {
+                return None;
+            }
+            body.extend_from_slice(&data[..size]);
+            data = &data[size + 2..];
+        }
+    }
+}
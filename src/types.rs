@@ -1,8 +1,13 @@
 /// This module contains definitions for all data types.
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+use crate::RpcError;
+use crate::amount::{Amount, FeeRate};
+use crate::script::{ScriptType, classify_script};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BitcoinClientType {
     #[serde(rename = "main")]
     Mainnet,
@@ -28,15 +33,79 @@ pub struct BlockchainInfo {
     pub size_on_disk: u64,
     pub pruned: bool,
     pub pruneheight: Option<u64>,
+    pub automatic_pruning: Option<bool>,
+    pub prune_target_size: Option<u64>,
+    // Removed in 23.0 in favour of `getdeploymentinfo`
+    #[serde(default)]
     pub softforks: HashMap<String, SoftFork>,
-    pub warnings: String,
+    pub signet_challenge: Option<String>,
+    pub warnings: Warnings,
+}
+
+// Node warnings, which older releases report as one string and newer ones as an array
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Warnings(pub Vec<String>);
+
+impl<'de> Deserialize<'de> for Warnings {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Shape {
+            One(String),
+            Many(Vec<String>),
+        }
+        Ok(match Shape::deserialize(deserializer)? {
+            Shape::One(s) if s.is_empty() => Warnings(Vec::new()),
+            Shape::One(s) => Warnings(vec![s]),
+            Shape::Many(v) => Warnings(v.into_iter().filter(|s| !s.is_empty()).collect()),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SoftFork {
+    // "buried" or "bip9"
     pub r#type: String,
     pub active: bool,
+    // Activation height, for buried deployments and active bip9 ones
     pub height: Option<u64>,
+    pub bip9: Option<Bip9Info>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bip9Info {
+    // Version bit to signal with, omitted once the deployment is final
+    pub bit: Option<u8>,
+    pub start_time: i64,
+    pub timeout: i64,
+    pub min_activation_height: Option<u64>,
+    // "defined", "started", "locked_in", "active" or "failed"
+    pub status: String,
+    // Height of the first block to which `status` applies
+    pub since: u64,
+    // Status of the next block, from `getdeploymentinfo`
+    pub status_next: Option<String>,
+    // Signalling in the current period, while started or locked in
+    pub statistics: Option<Bip9Statistics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bip9Statistics {
+    pub period: u32,
+    // Omitted while locked in
+    pub threshold: Option<u32>,
+    // Blocks so far in the period, and how many of them signalled
+    pub elapsed: u32,
+    pub count: u32,
+    // Whether the threshold can still be reached this period
+    pub possible: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentInfo {
+    pub hash: String,
+    pub height: u64,
+    pub deployments: HashMap<String, SoftFork>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +117,7 @@ pub struct Block {
     pub weight: u32,
     pub height: u64,
     pub version: i32,
+    #[serde(alias = "versionHex")]
     pub version_hex: String,
     pub merkleroot: String,
     pub tx: Vec<String>,
@@ -57,6 +127,7 @@ pub struct Block {
     pub bits: String,
     pub difficulty: f64,
     pub chainwork: String,
+    #[serde(alias = "nTx")]
     pub n_tx: u32,
     pub previousblockhash: Option<String>,
     pub nextblockhash: Option<String>,
@@ -68,14 +139,80 @@ pub struct BlockHeader {
     pub confirmations: i32,
     pub height: u64,
     pub version: i32,
+    #[serde(alias = "versionHex")]
+    pub version_hex: String,
+    pub merkleroot: String,
+    pub time: u64,
+    pub mediantime: u64,
+    pub nonce: u64,
+    pub bits: String,
+    pub difficulty: f64,
+    pub chainwork: String,
+    #[serde(alias = "nTx")]
+    pub n_tx: u32,
+    pub previousblockhash: Option<String>,
+    pub nextblockhash: Option<String>,
+}
+
+impl BlockHeader {
+    // Compact target, None if the node sent something other than 8 hex digits
+    pub fn bits_value(&self) -> Option<u32> {
+        u32::from_str_radix(&self.bits, 16).ok()
+    }
+
+    // The proof-of-work target as a big-endian 256-bit number; the block hash, read in
+    // the usual display order, must not exceed it
+    pub fn target(&self) -> [u8; 32] {
+        self.bits_value()
+            .map(crate::hashrate::target_from_bits)
+            .unwrap_or([0u8; 32])
+    }
+
+    // Difficulty recomputed from `bits`, matching the node's `difficulty` field
+    pub fn difficulty_from_bits(&self) -> f64 {
+        self.bits_value()
+            .map(crate::hashrate::difficulty_from_bits)
+            .unwrap_or(0.0)
+    }
+
+    // Whether `hash` meets this header's target
+    pub fn meets_target(&self) -> bool {
+        hex::decode(&self.hash)
+            .ok()
+            .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+            .is_some_and(|hash| hash <= self.target())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockFilter {
+    // Hex of the serialized filter, see `CompactFilter::from_hex`
+    pub filter: String,
+    // Filter header chaining this filter to the previous block's
+    pub header: String,
+}
+
+// `getblock` at verbosity 2, or 3 for `prevout` on every non-coinbase input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockVerbose {
+    pub hash: String,
+    pub confirmations: i32,
+    pub strippedsize: Option<u32>,
+    pub size: u32,
+    pub weight: u32,
+    pub height: u64,
+    pub version: i32,
+    #[serde(alias = "versionHex")]
     pub version_hex: String,
     pub merkleroot: String,
+    pub tx: Vec<Transaction>,
     pub time: u64,
     pub mediantime: u64,
     pub nonce: u64,
     pub bits: String,
     pub difficulty: f64,
     pub chainwork: String,
+    #[serde(alias = "nTx")]
     pub n_tx: u32,
     pub previousblockhash: Option<String>,
     pub nextblockhash: Option<String>,
@@ -86,7 +223,40 @@ pub struct ChainTip {
     pub height: u64,
     pub hash: String,
     pub branchlen: i32,
-    pub status: String,
+    pub status: ChainTipStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChainTipStatus {
+    // The tip of the best chain
+    Active,
+    // Fully validated branch that is not the best chain
+    ValidFork,
+    // All blocks downloaded but not yet validated
+    ValidHeaders,
+    // Headers are valid but some blocks were never downloaded
+    HeadersOnly,
+    // The branch contains an invalid block, including ones marked with invalidateblock
+    Invalid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainTxStats {
+    // Timestamp of the final block in the window
+    pub time: u64,
+    // Transactions in the chain up to the final block; absent on a node still
+    // validating an assumeutxo snapshot's history
+    pub txcount: Option<u64>,
+    pub window_final_block_hash: Option<String>,
+    pub window_final_block_height: Option<u64>,
+    pub window_block_count: Option<u64>,
+    // The rest are omitted when the window is empty
+    pub window_tx_count: Option<u64>,
+    // Seconds between the window's first and final block
+    pub window_interval: Option<u64>,
+    // Transactions per second over the window
+    pub txrate: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,6 +275,18 @@ pub struct Transaction {
     pub confirmations: Option<u32>,
     pub time: Option<u64>,
     pub blocktime: Option<u64>,
+    // Only in verbose blocks and `getrawtransaction` at verbosity 2, and only when the
+    // node has the undo data
+    pub fee: Option<Amount>,
+}
+
+// `getrawtransaction` at verbosity 2, with `fee` and each input's `prevout` filled in
+pub type TransactionWithPrevouts = Transaction;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OutPoint {
+    pub txid: String,
+    pub vout: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,11 +298,24 @@ pub struct Vin {
     pub txinwitness: Option<Vec<String>>,
     pub sequence: u64,
     pub coinbase: Option<String>,
+    // The output being spent, from `getblock` at verbosity 3 and `getrawtransaction` at
+    // verbosity 2
+    pub prevout: Option<PrevOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrevOut {
+    // Whether the spent output was created by a coinbase
+    pub generated: bool,
+    pub height: u64,
+    pub value: Amount,
+    #[serde(alias = "scriptPubKey")]
+    pub script_pub_key: ScriptPubKey,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Vout {
-    pub value: f64,
+    pub value: Amount,
     pub n: u32,
     #[serde(alias = "scriptPubKey")]
     pub script_pub_key: ScriptPubKey,
@@ -137,10 +332,39 @@ pub struct ScriptPubKey {
     pub asm: String,
     pub hex: String,
     pub req_sigs: Option<u32>,
-    pub r#type: String,
+    pub r#type: ScriptType,
+    pub address: Option<String>,
     pub addresses: Option<Vec<String>>,
 }
 
+// Reply to `decodescript`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedScript {
+    pub asm: String,
+    // Inferred descriptor. Needs 22.0 or later.
+    pub desc: Option<String>,
+    pub r#type: ScriptType,
+    // Only for scripts that are themselves a standard output
+    pub address: Option<String>,
+    // The P2SH address wrapping the script, when it can be wrapped
+    pub p2sh: Option<String>,
+    // The script as a segwit output, when it could be one
+    pub segwit: Option<SegwitScript>,
+}
+
+// The segwit projection of a decoded script: P2WPKH for a single key, P2WSH otherwise
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegwitScript {
+    pub asm: String,
+    pub hex: String,
+    pub r#type: ScriptType,
+    pub address: Option<String>,
+    pub desc: Option<String>,
+    // The segwit output nested in P2SH
+    #[serde(rename = "p2sh-segwit")]
+    pub p2sh_segwit: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DecodedTransaction {
     pub txid: String,
@@ -158,7 +382,7 @@ pub struct DecodedTransaction {
 pub struct TxOut {
     pub bestblock: String,
     pub confirmations: u32,
-    pub value: f64,
+    pub value: Amount,
     #[serde(alias = "scriptPubKey")]
     pub script_pub_key: ScriptPubKey,
     pub coinbase: bool,
@@ -173,22 +397,23 @@ pub struct TxOutSetInfo {
     pub bogosize: u64,
     pub hash_serialized_2: String,
     pub disk_size: u64,
-    pub total_amount: f64,
+    pub total_amount: Amount,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletInfo {
     pub walletname: String,
     pub walletversion: u32,
-    pub balance: f64,
-    pub unconfirmed_balance: f64,
-    pub immature_balance: f64,
+    pub balance: Amount,
+    pub unconfirmed_balance: Amount,
+    pub immature_balance: Amount,
     pub txcount: u32,
     pub keypoololdest: u64,
     pub keypoolsize: u32,
     pub keypoolsize_hd_internal: u32,
     pub unlocked_until: Option<u64>,
-    pub paytxfee: f64,
+    // Per kvB, zero when the fee is estimated
+    pub paytxfee: Amount,
     pub hdseedid: Option<String>,
     pub private_keys_enabled: bool,
     pub avoid_reuse: bool,
@@ -201,9 +426,131 @@ pub struct Scanning {
     pub progress: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// An address's entry in `getaddressesbylabel`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AddressInfo {
-    pub purpose: String,
+    pub purpose: AddressPurpose,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressPurpose {
+    // One of the wallet's own addresses
+    Receive,
+    // An address the wallet paid, labeled in the address book
+    Send,
+    // Set by newer nodes on addresses recorded for refunds
+    Refund,
+    #[serde(other)]
+    Unknown,
+}
+
+// `purpose` used to be a plain String; as_str and the string comparisons
+// keep code written against that shape compiling
+impl AddressPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressPurpose::Receive => "receive",
+            AddressPurpose::Send => "send",
+            AddressPurpose::Refund => "refund",
+            AddressPurpose::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for AddressPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for AddressPurpose {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for AddressPurpose {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceivedByLabel {
+    pub involves_watchonly: Option<bool>,
+    pub amount: Amount,
+    // Of the most recent payment counted
+    pub confirmations: i64,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceivedByAddress {
+    pub involves_watchonly: Option<bool>,
+    pub address: String,
+    pub amount: Amount,
+    // Of the most recent payment counted
+    pub confirmations: i64,
+    pub label: String,
+    // Transactions paying the address
+    #[serde(default)]
+    pub txids: Vec<String>,
+}
+
+// `getaddressinfo`: what the wallet knows about an address. Beyond the basics, which
+// fields are present depends on the address type and whether the wallet owns it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressDetails {
+    pub address: String,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: String,
+    pub ismine: bool,
+    pub iswatchonly: bool,
+    // The wallet knows how to spend it, ignoring private keys
+    pub solvable: bool,
+    pub isscript: bool,
+    pub ischange: bool,
+    pub iswitness: bool,
+    pub desc: Option<String>,
+    // The wallet descriptor the address was derived from
+    pub parent_desc: Option<String>,
+    pub witness_version: Option<u8>,
+    pub witness_program: Option<String>,
+    // Script type for P2SH and P2WSH, e.g. "multisig" or "witness_v0_keyhash"
+    pub script: Option<String>,
+    // Redeem or witness script
+    pub hex: Option<String>,
+    pub pubkey: Option<String>,
+    // Keys of a multisig script
+    pub pubkeys: Option<Vec<String>>,
+    pub sigsrequired: Option<u32>,
+    pub iscompressed: Option<bool>,
+    // Details of the script wrapped in a P2SH address, e.g. P2SH-P2WPKH
+    pub embedded: Option<EmbeddedAddress>,
+    pub timestamp: Option<u64>,
+    pub hdkeypath: Option<String>,
+    pub hdseedid: Option<String>,
+    pub hdmasterfingerprint: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedAddress {
+    pub address: String,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: String,
+    pub isscript: bool,
+    pub iswitness: bool,
+    pub witness_version: Option<u8>,
+    pub witness_program: Option<String>,
+    pub script: Option<String>,
+    pub hex: Option<String>,
+    pub pubkey: Option<String>,
+    pub pubkeys: Option<Vec<String>>,
+    pub sigsrequired: Option<u32>,
+    pub iscompressed: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -226,7 +573,7 @@ pub struct Utxo {
     pub label: Option<String>,
     #[serde(alias = "scriptPubKey")]
     pub script_pub_key: String,
-    pub amount: f64,
+    pub amount: Amount,
     pub confirmations: u32,
     #[serde(alias = "redeemScript")]
     pub redeem_script: Option<String>,
@@ -238,6 +585,111 @@ pub struct Utxo {
     pub safe: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WalletTxCategory {
+    Send,
+    Receive,
+    // Mature coinbase output
+    Generate,
+    // Coinbase output with fewer than 100 confirmations
+    Immature,
+    // Coinbase output of a block that is no longer in the active chain
+    Orphan,
+}
+
+// One entry of `listtransactions` or `listsinceblock`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletTransaction {
+    pub involves_watchonly: Option<bool>,
+    pub address: Option<String>,
+    pub category: WalletTxCategory,
+    pub amount: Amount,
+    pub label: Option<String>,
+    pub vout: Option<u32>,
+    pub fee: Option<Amount>,
+    pub confirmations: i64,
+    pub generated: Option<bool>,
+    pub trusted: Option<bool>,
+    pub blockhash: Option<String>,
+    pub blockheight: Option<u64>,
+    pub blockindex: Option<u32>,
+    pub blocktime: Option<u64>,
+    pub txid: String,
+    pub time: u64,
+    pub timereceived: u64,
+    pub wtxid: Option<String>,
+    #[serde(default)]
+    pub walletconflicts: Vec<String>,
+    pub comment: Option<String>,
+    #[serde(rename = "bip125-replaceable")]
+    pub bip125_replaceable: Option<Bip125Replaceable>,
+    pub abandoned: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListSinceBlockResult {
+    pub transactions: Vec<WalletTransaction>,
+    // Entries of transactions reorged out of the chain since the given block; only
+    // filled in when `include_removed` is set
+    #[serde(default)]
+    pub removed: Vec<WalletTransaction>,
+    // Hash of the block `target_confirmations` deep, to pass to the next call
+    pub lastblock: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bip125Replaceable {
+    Yes,
+    No,
+    // Unconfirmed with an unconfirmed ancestor the wallet cannot see
+    Unknown,
+}
+
+// `gettransaction`: one wallet transaction with its per-output details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetTransactionResult {
+    // Net effect on the wallet balance, negative for payments out
+    pub amount: Amount,
+    // Only for transactions the wallet sent, and negative
+    pub fee: Option<Amount>,
+    // Negative when the transaction conflicts with one that many blocks deep
+    pub confirmations: i64,
+    pub generated: Option<bool>,
+    pub trusted: Option<bool>,
+    pub blockhash: Option<String>,
+    pub blockheight: Option<u64>,
+    pub blockindex: Option<u32>,
+    pub blocktime: Option<u64>,
+    pub txid: String,
+    pub wtxid: Option<String>,
+    #[serde(default)]
+    pub walletconflicts: Vec<String>,
+    pub time: u64,
+    pub timereceived: u64,
+    pub comment: Option<String>,
+    #[serde(rename = "bip125-replaceable")]
+    pub bip125_replaceable: Bip125Replaceable,
+    pub details: Vec<WalletTxDetail>,
+    pub hex: String,
+    // Only when requested with `verbose`
+    pub decoded: Option<DecodedTransaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletTxDetail {
+    pub involves_watchonly: Option<bool>,
+    pub address: Option<String>,
+    pub category: WalletTxCategory,
+    pub amount: Amount,
+    pub label: Option<String>,
+    pub vout: u32,
+    pub fee: Option<Amount>,
+    // Only set on send entries
+    pub abandoned: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkInfo {
     pub version: u32,
@@ -252,7 +704,7 @@ pub struct NetworkInfo {
     pub relayfee: f64,
     pub incrementalfee: f64,
     pub localaddresses: Vec<LocalAddress>,
-    pub warnings: String,
+    pub warnings: Warnings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -287,20 +739,119 @@ pub struct PeerInfo {
     pub timeoffset: i32,
     pub pingtime: f64,
     pub minping: Option<f64>,
+    // Seconds an outstanding ping has been waiting, e.g. after `ping`
+    pub pingwait: Option<f64>,
     pub version: u32,
     pub subver: String,
     pub inbound: bool,
-    pub addnode: bool,
+    // `addnode`, `banscore` and `whitelisted` were removed in 22.0
+    #[serde(default)]
+    pub addnode: Option<bool>,
     pub startingheight: u32,
-    pub banscore: u32,
+    #[serde(default)]
+    pub banscore: Option<u32>,
     pub synced_headers: i32,
     pub synced_blocks: i32,
     pub inflight: Vec<u32>,
-    pub whitelisted: bool,
+    #[serde(default)]
+    pub whitelisted: Option<bool>,
     pub permissions: Vec<String>,
     pub minfeefilter: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetTotals {
+    pub totalbytesrecv: u64,
+    pub totalbytessent: u64,
+    // Unix time in milliseconds
+    pub timemillis: u64,
+    pub uploadtarget: UploadTarget,
+}
+
+// State of the -maxuploadtarget limit; all zero when none is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadTarget {
+    // Length of the measuring cycle in seconds
+    pub timeframe: u64,
+    // Bytes allowed per cycle
+    pub target: u64,
+    pub target_reached: bool,
+    pub serve_historical_blocks: bool,
+    pub bytes_left_in_cycle: u64,
+    pub time_left_in_cycle: u64,
+}
+
+// An address from the node's address manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeAddress {
+    // When the address was last seen, unix time
+    pub time: u64,
+    // Service flags
+    pub services: u64,
+    pub address: String,
+    pub port: u16,
+    // "ipv4", "ipv6", "onion", "i2p" or "cjdns". Needs 22.0 or later.
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddPeerAddressResult {
+    pub success: bool,
+    // Why the address was not added. Needs 27.0 or later.
+    pub error: Option<String>,
+}
+
+// Address manager table sizes for one network, or all of them under "all_networks"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddrManNetworkCounts {
+    pub new: u64,
+    pub tried: u64,
+    pub total: u64,
+}
+
+// What `add_node` does with the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddNodeCommand {
+    // Add to the added-node list and keep connecting to it
+    Add,
+    // Remove from the list; an open connection is left alone
+    Remove,
+    // Connect once without adding to the list
+    OneTry,
+}
+
+// The peer for `disconnect_node`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisconnectTarget {
+    // "ip:port" as shown in `PeerInfo::addr`
+    Address(String),
+    // `PeerInfo::id`
+    NodeId(u32),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddedNodeInfo {
+    pub addednode: String,
+    pub connected: bool,
+    // Only while connected
+    #[serde(default)]
+    pub addresses: Vec<AddedNodeAddress>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddedNodeAddress {
+    pub address: String,
+    pub connected: ConnectionDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionDirection {
+    Inbound,
+    Outbound,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MempoolInfo {
     pub loaded: bool,
@@ -312,6 +863,119 @@ pub struct MempoolInfo {
     pub minrelaytxfee: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolEntry {
+    pub vsize: u64,
+    pub weight: u64,
+    pub time: u64,
+    // Chain height when the transaction entered the mempool
+    pub height: u64,
+    // Counts and sizes include the transaction itself
+    pub descendantcount: u64,
+    pub descendantsize: u64,
+    pub ancestorcount: u64,
+    pub ancestorsize: u64,
+    pub wtxid: String,
+    pub fees: MempoolFees,
+    // Unconfirmed parents and children in the mempool
+    pub depends: Vec<String>,
+    pub spentby: Vec<String>,
+    #[serde(alias = "bip125-replaceable")]
+    pub bip125_replaceable: Option<bool>,
+    // Submitted locally and not yet seen announced back by a peer
+    #[serde(default)]
+    pub unbroadcast: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolFees {
+    pub base: Amount,
+    // With any prioritisetransaction delta applied
+    pub modified: Amount,
+    // Modified fees of the transaction and all its in-mempool ancestors
+    pub ancestor: Amount,
+    // Modified fees of the transaction and all its in-mempool descendants
+    pub descendant: Amount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendingPrevout {
+    pub txid: String,
+    pub vout: u32,
+    // Mempool transaction spending the outpoint, if any
+    pub spendingtxid: Option<String>,
+}
+
+// Options for `send_raw_transaction_with_options`; unset fields use the node's defaults
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SendRawTxOptions {
+    // Highest fee rate accepted, per kvB; zero disables the check. The node's default
+    // is 0.10 BTC/kvB.
+    pub max_fee_rate: Option<Amount>,
+    // Most that may go to provably unspendable outputs. Needs 25.0 or later.
+    pub max_burn_amount: Option<Amount>,
+}
+
+impl SendRawTxOptions {
+    pub fn max_fee_rate(mut self, max_fee_rate: Amount) -> Self {
+        self.max_fee_rate = Some(max_fee_rate);
+        self
+    }
+
+    pub fn max_burn_amount(mut self, max_burn_amount: Amount) -> Self {
+        self.max_burn_amount = Some(max_burn_amount);
+        self
+    }
+}
+
+// Reply to `submitpackage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitPackageResult {
+    // "success" when every transaction was accepted or already known. Needs 28.0 or later.
+    pub package_msg: Option<String>,
+    // Keyed by wtxid
+    #[serde(rename = "tx-results")]
+    pub tx_results: HashMap<String, PackageTxResult>,
+    // Txids evicted by replacements in the package
+    #[serde(rename = "replaced-transactions", default)]
+    pub replaced_transactions: Vec<String>,
+}
+
+impl SubmitPackageResult {
+    pub fn is_success(&self) -> bool {
+        self.package_msg.as_deref() == Some("success")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageTxResult {
+    pub txid: String,
+    // Set when a transaction with the same txid but different witness was in the mempool
+    #[serde(rename = "other-wtxid")]
+    pub other_wtxid: Option<String>,
+    pub vsize: Option<u64>,
+    pub fees: Option<PackageTxFees>,
+    // Why this transaction was not accepted
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageTxFees {
+    pub base: Amount,
+    // Fee rate in BTC/kvB the transaction was judged at, with the package members it
+    // was bundled with
+    #[serde(rename = "effective-feerate")]
+    pub effective_feerate: Option<Amount>,
+    #[serde(rename = "effective-includes", default)]
+    pub effective_includes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolSequenceSnapshot {
+    pub txids: Vec<String>,
+    pub mempool_sequence: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeeEstimate {
     pub feerate: Option<f64>,
@@ -328,16 +992,25 @@ pub struct MiningInfo {
     pub networkhashps: f64,
     pub pooledtx: u64,
     pub chain: String,
-    pub warnings: String,
+    pub warnings: Warnings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTxInput {
-    pub txid: String,
-    pub vout: u32,
+    #[serde(flatten)]
+    pub outpoint: OutPoint,
     pub sequence: Option<u32>,
 }
 
+impl From<OutPoint> for CreateTxInput {
+    fn from(outpoint: OutPoint) -> Self {
+        CreateTxInput {
+            outpoint,
+            sequence: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignedTransaction {
     pub hex: String,
@@ -355,35 +1028,1526 @@ pub struct SigningError {
     pub error: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// A previous output for `sign_raw_transaction_with_key`, for inputs the node cannot look up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrevTx {
+    pub txid: String,
+    pub vout: u32,
+    pub script_pub_key: String,
+    // For P2SH outputs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redeem_script: Option<String>,
+    // For P2WSH outputs, bare or nested in P2SH
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub witness_script: Option<String>,
+    // Required for segwit outputs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Amount>,
+}
+
+impl PrevTx {
+    pub fn new(txid: &str, vout: u32, script_pub_key: &str) -> Self {
+        PrevTx {
+            txid: txid.to_string(),
+            vout,
+            script_pub_key: script_pub_key.to_string(),
+            redeem_script: None,
+            witness_script: None,
+            amount: None,
+        }
+    }
+
+    pub fn redeem_script(mut self, redeem_script: &str) -> Self {
+        self.redeem_script = Some(redeem_script.to_string());
+        self
+    }
+
+    pub fn witness_script(mut self, witness_script: &str) -> Self {
+        self.witness_script = Some(witness_script.to_string());
+        self
+    }
+
+    pub fn amount(mut self, amount: Amount) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    // Whether the output is segwit, bare or nested in P2SH, so signing needs its amount
+    pub fn is_witness(&self) -> bool {
+        let is_program =
+            |hex: &str| hex::decode(hex).is_ok_and(|script| classify_script(&script).is_witness());
+        self.witness_script.is_some()
+            || is_program(&self.script_pub_key)
+            || self.redeem_script.as_deref().is_some_and(is_program)
+    }
+}
+
+// A block by height or hash, for RPCs that accept either
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BlockRef {
+    Height(u64),
+    Hash(String),
+}
+
+impl From<u64> for BlockRef {
+    fn from(height: u64) -> Self {
+        BlockRef::Height(height)
+    }
+}
+
+impl From<&str> for BlockRef {
+    fn from(hash: &str) -> Self {
+        BlockRef::Hash(hash.to_string())
+    }
+}
+
+impl From<String> for BlockRef {
+    fn from(hash: String) -> Self {
+        BlockRef::Hash(hash)
+    }
+}
+
+// Every field is optional, since `get_block_stats_filtered` returns only the stats asked for
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BlockStats {
-    pub avgfee: u64,
-    pub avgfeerate: u64,
-    pub avgtxsize: u64,
-    pub blockhash: String,
-    pub feerate_percentiles: Vec<u64>,
+    pub avgfee: Option<u64>,
+    pub avgfeerate: Option<u64>,
+    pub avgtxsize: Option<u64>,
+    pub blockhash: Option<String>,
+    // 10th, 25th, 50th, 75th and 90th percentile feerates in sat/vB
+    pub feerate_percentiles: Option<Vec<u64>>,
+    pub height: Option<u64>,
+    pub ins: Option<u64>,
+    pub maxfee: Option<u64>,
+    pub maxfeerate: Option<u64>,
+    pub maxtxsize: Option<u64>,
+    pub medianfee: Option<u64>,
+    pub mediantime: Option<u64>,
+    pub mediantxsize: Option<u64>,
+    pub minfee: Option<u64>,
+    pub minfeerate: Option<u64>,
+    pub mintxsize: Option<u64>,
+    pub outs: Option<u64>,
+    pub subsidy: Option<u64>,
+    pub swtotal_size: Option<u64>,
+    pub swtotal_weight: Option<u64>,
+    pub swtxs: Option<u64>,
+    pub time: Option<u64>,
+    pub total_out: Option<u64>,
+    pub total_size: Option<u64>,
+    pub total_weight: Option<u64>,
+    pub totalfee: Option<u64>,
+    pub txs: Option<u64>,
+    pub utxo_increase: Option<i64>,
+    pub utxo_size_inc: Option<i64>,
+    // Excluding unspendable outputs, v25 and newer
+    pub utxo_increase_actual: Option<i64>,
+    pub utxo_size_inc_actual: Option<i64>,
+}
+
+// What `scantxoutset` looks for: a descriptor, or a ranged one with the child index
+// range to derive, e.g. `ScanObject::ranged("wpkh(xpub.../0/*)", 0, 999)`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScanObject {
+    Descriptor(String),
+    Ranged { desc: String, range: (u32, u32) },
+}
+
+impl ScanObject {
+    pub fn ranged(desc: &str, begin: u32, end: u32) -> Self {
+        ScanObject::Ranged {
+            desc: desc.to_string(),
+            range: (begin, end),
+        }
+    }
+}
+
+impl From<&str> for ScanObject {
+    fn from(desc: &str) -> Self {
+        ScanObject::Descriptor(desc.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanProgress {
+    // Percent of the UTXO set scanned so far
+    pub progress: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanTxOutResult {
+    pub success: bool,
+    pub txouts: u64,
     pub height: u64,
-    pub ins: u64,
-    pub maxfee: u64,
-    pub maxfeerate: u64,
-    pub maxtxsize: u64,
-    pub medianfee: u64,
-    pub mediantime: u64,
-    pub mediantxsize: u64,
-    pub minfee: u64,
-    pub minfeerate: u64,
-    pub mintxsize: u64,
-    pub outs: u64,
-    pub subsidy: u64,
-    pub swtotal_size: u64,
-    pub swtotal_weight: u64,
-    pub swtxs: u64,
-    pub time: u64,
-    pub total_out: u64,
-    pub total_size: u64,
-    pub total_weight: u64,
-    pub totalfee: u64,
-    pub txs: u64,
-    pub utxo_increase: i64,
-    pub utxo_size_inc: i64,
+    pub bestblock: String,
+    pub unspents: Vec<ScannedUtxo>,
+    pub total_amount: Amount,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScannedUtxo {
+    pub txid: String,
+    pub vout: u32,
+    #[serde(alias = "scriptPubKey")]
+    pub script_pub_key: String,
+    pub desc: String,
+    pub amount: Amount,
+    pub coinbase: Option<bool>,
+    pub height: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryInfo {
+    pub locked: LockedMemory,
+}
+
+// Secure memory pool used for wallet keys, in bytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedMemory {
+    pub used: u64,
+    pub free: u64,
+    pub total: u64,
+    // Bytes actually locked in RAM; less than `total` when mlock was refused
+    pub locked: u64,
+    pub chunks_used: u64,
+    pub chunks_free: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveMempoolResult {
+    // Path of the written mempool.dat; None on nodes before 23.0, which do not report it
+    pub filename: Option<String>,
+}
+
+// How the wallet estimates a fee when given a confirmation target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EstimateMode {
+    Unset,
+    Economical,
+    Conservative,
+}
+
+// Options for `send_many`; unset fields take the node's defaults
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SendManyOptions {
+    // Only spend outputs with at least this many confirmations
+    pub minconf: Option<u32>,
+    // Stored in the wallet, not in the transaction
+    pub comment: Option<String>,
+    // Recipients that pay the fee between them, out of their amounts
+    pub subtract_fee_from: Vec<String>,
+    // Signal BIP125 replaceability
+    pub replaceable: Option<bool>,
+    pub conf_target: Option<u32>,
+    pub estimate_mode: Option<EstimateMode>,
+    // Overrides `conf_target` and `estimate_mode`. Needs 21.0 or later.
+    pub fee_rate: Option<FeeRate>,
+}
+
+impl SendManyOptions {
+    pub fn minconf(mut self, minconf: u32) -> Self {
+        self.minconf = Some(minconf);
+        self
+    }
+
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    pub fn subtract_fee_from(mut self, address: &str) -> Self {
+        self.subtract_fee_from.push(address.to_string());
+        self
+    }
+
+    pub fn replaceable(mut self, replaceable: bool) -> Self {
+        self.replaceable = Some(replaceable);
+        self
+    }
+
+    pub fn conf_target(mut self, conf_target: u32) -> Self {
+        self.conf_target = Some(conf_target);
+        self
+    }
+
+    pub fn estimate_mode(mut self, estimate_mode: EstimateMode) -> Self {
+        self.estimate_mode = Some(estimate_mode);
+        self
+    }
+
+    pub fn fee_rate(mut self, fee_rate: FeeRate) -> Self {
+        self.fee_rate = Some(fee_rate);
+        self
+    }
+}
+
+// Options for `send_to_address_with_options`, sent as named parameters; unset fields are
+// left out so the node's defaults apply
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SendToAddressOptions {
+    // Stored in the wallet, not in the transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    // Who the payment is to, also stored only in the wallet
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_to: Option<String>,
+    // The recipient pays the fee out of the amount
+    #[serde(
+        rename = "subtractfeefromamount",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub subtract_fee_from_amount: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_mode: Option<EstimateMode>,
+    // Only for wallets created with `avoid_reuse`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avoid_reuse: Option<bool>,
+    // Overrides `conf_target` and `estimate_mode`. Needs 21.0 or later.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<FeeRate>,
+}
+
+impl SendToAddressOptions {
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    pub fn comment_to(mut self, comment_to: &str) -> Self {
+        self.comment_to = Some(comment_to.to_string());
+        self
+    }
+
+    pub fn subtract_fee_from_amount(mut self, subtract: bool) -> Self {
+        self.subtract_fee_from_amount = Some(subtract);
+        self
+    }
+
+    pub fn replaceable(mut self, replaceable: bool) -> Self {
+        self.replaceable = Some(replaceable);
+        self
+    }
+
+    pub fn conf_target(mut self, conf_target: u32) -> Self {
+        self.conf_target = Some(conf_target);
+        self
+    }
+
+    pub fn estimate_mode(mut self, estimate_mode: EstimateMode) -> Self {
+        self.estimate_mode = Some(estimate_mode);
+        self
+    }
+
+    pub fn avoid_reuse(mut self, avoid_reuse: bool) -> Self {
+        self.avoid_reuse = Some(avoid_reuse);
+        self
+    }
+
+    pub fn fee_rate(mut self, fee_rate: FeeRate) -> Self {
+        self.fee_rate = Some(fee_rate);
+        self
+    }
+}
+
+// Reply to `sendmany` and `sendtoaddress` when asked to be verbose
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendManyVerboseResult {
+    pub txid: String,
+    // How the fee was chosen, e.g. "Fallback fee" or "Half Target 2 blocks"
+    pub fee_reason: String,
+}
+
+// One output of `send`: a payment, or an OP_RETURN carrying `data`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendOutput {
+    Address { address: String, amount: Amount },
+    Data(String),
+}
+
+impl SendOutput {
+    pub fn address(address: &str, amount: Amount) -> Self {
+        SendOutput::Address {
+            address: address.to_string(),
+            amount,
+        }
+    }
+
+    pub fn data(hex: &str) -> Self {
+        SendOutput::Data(hex.to_string())
+    }
+}
+
+// Core takes each output as a one-key object, `{address: amount}` or `{"data": hex}`
+impl Serialize for SendOutput {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(1))?;
+        match self {
+            SendOutput::Address { address, amount } => map.serialize_entry(address, amount)?,
+            SendOutput::Data(hex) => map.serialize_entry("data", hex)?,
+        }
+        map.end()
+    }
+}
+
+// Options for `send`; unset fields are left out so the node's defaults apply
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SendOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_mode: Option<EstimateMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<FeeRate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_position: Option<u32>,
+    // Also spend unconfirmed outputs from other wallets, which may be replaced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_unsafe: Option<bool>,
+    // Inputs that must be spent; the wallet adds more if they do not cover the outputs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<OutPoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locktime: Option<u32>,
+    // False to get the PSBT or hex back without storing or broadcasting the transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub add_to_wallet: Option<bool>,
+}
+
+impl SendOptions {
+    pub fn conf_target(mut self, conf_target: u32) -> Self {
+        self.conf_target = Some(conf_target);
+        self
+    }
+
+    pub fn estimate_mode(mut self, estimate_mode: EstimateMode) -> Self {
+        self.estimate_mode = Some(estimate_mode);
+        self
+    }
+
+    pub fn fee_rate(mut self, fee_rate: FeeRate) -> Self {
+        self.fee_rate = Some(fee_rate);
+        self
+    }
+
+    pub fn replaceable(mut self, replaceable: bool) -> Self {
+        self.replaceable = Some(replaceable);
+        self
+    }
+
+    pub fn change_address(mut self, address: &str) -> Self {
+        self.change_address = Some(address.to_string());
+        self
+    }
+
+    pub fn change_position(mut self, position: u32) -> Self {
+        self.change_position = Some(position);
+        self
+    }
+
+    pub fn include_unsafe(mut self, include_unsafe: bool) -> Self {
+        self.include_unsafe = Some(include_unsafe);
+        self
+    }
+
+    pub fn input(mut self, outpoint: OutPoint) -> Self {
+        self.inputs.push(outpoint);
+        self
+    }
+
+    pub fn locktime(mut self, locktime: u32) -> Self {
+        self.locktime = Some(locktime);
+        self
+    }
+
+    pub fn add_to_wallet(mut self, add_to_wallet: bool) -> Self {
+        self.add_to_wallet = Some(add_to_wallet);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendResult {
+    // Whether every input is signed
+    pub complete: bool,
+    // Set when the transaction was added to the wallet and broadcast
+    pub txid: Option<String>,
+    // Set when complete but not added to the wallet
+    pub hex: Option<String>,
+    // Set when not complete, or when asked for with `add_to_wallet` false
+    pub psbt: Option<String>,
+}
+
+// Options for `bump_fee` and `psbt_bump_fee`; unset fields are left out so the node's
+// defaults apply
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BumpFeeOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u32>,
+    // Rate for the replacement. Needs 21.0 or later.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<FeeRate>,
+    // Whether the replacement signals BIP125 in turn
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_mode: Option<EstimateMode>,
+}
+
+impl BumpFeeOptions {
+    pub fn conf_target(mut self, conf_target: u32) -> Self {
+        self.conf_target = Some(conf_target);
+        self
+    }
+
+    pub fn fee_rate(mut self, fee_rate: FeeRate) -> Self {
+        self.fee_rate = Some(fee_rate);
+        self
+    }
+
+    pub fn replaceable(mut self, replaceable: bool) -> Self {
+        self.replaceable = Some(replaceable);
+        self
+    }
+
+    pub fn estimate_mode(mut self, estimate_mode: EstimateMode) -> Self {
+        self.estimate_mode = Some(estimate_mode);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BumpFeeResult {
+    // The replacement; None only on nodes that returned a PSBT instead
+    pub txid: Option<String>,
+    pub origfee: Amount,
+    pub fee: Amount,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PsbtBumpFeeResult {
+    // The unsigned replacement, to sign and broadcast elsewhere
+    pub psbt: String,
+    pub origfee: Amount,
+    pub fee: Amount,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+// Which parts of a transaction a signature commits to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SighashType {
+    // Taproot only: ALL, without committing to the sighash byte
+    #[serde(rename = "DEFAULT")]
+    Default,
+    #[serde(rename = "ALL")]
+    All,
+    #[serde(rename = "NONE")]
+    None,
+    #[serde(rename = "SINGLE")]
+    Single,
+    #[serde(rename = "ALL|ANYONECANPAY")]
+    AllAnyoneCanPay,
+    #[serde(rename = "NONE|ANYONECANPAY")]
+    NoneAnyoneCanPay,
+    #[serde(rename = "SINGLE|ANYONECANPAY")]
+    SingleAnyoneCanPay,
+}
+
+// Options for `wallet_create_funded_psbt`; unset fields are left out so the node's
+// defaults apply
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FundedPsbtOptions {
+    // Let the wallet add inputs beyond the given ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub add_inputs: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_unsafe: Option<bool>,
+    #[serde(rename = "changeAddress", skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<String>,
+    #[serde(rename = "changePosition", skip_serializing_if = "Option::is_none")]
+    pub change_position: Option<u32>,
+    // e.g. "bech32" or "bech32m"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_type: Option<String>,
+    #[serde(rename = "includeWatching", skip_serializing_if = "Option::is_none")]
+    pub include_watching: Option<bool>,
+    // Lock the selected inputs so other spends cannot pick them before this one is sent
+    #[serde(rename = "lockUnspents", skip_serializing_if = "Option::is_none")]
+    pub lock_unspents: Option<bool>,
+    // Needs 21.0 or later.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<FeeRate>,
+    // Indexes into the outputs that pay the fee between them
+    #[serde(
+        rename = "subtractFeeFromOutputs",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub subtract_fee_from_outputs: Vec<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_mode: Option<EstimateMode>,
+}
+
+impl FundedPsbtOptions {
+    pub fn add_inputs(mut self, add_inputs: bool) -> Self {
+        self.add_inputs = Some(add_inputs);
+        self
+    }
+
+    pub fn include_unsafe(mut self, include_unsafe: bool) -> Self {
+        self.include_unsafe = Some(include_unsafe);
+        self
+    }
+
+    pub fn change_address(mut self, address: &str) -> Self {
+        self.change_address = Some(address.to_string());
+        self
+    }
+
+    pub fn change_position(mut self, position: u32) -> Self {
+        self.change_position = Some(position);
+        self
+    }
+
+    pub fn change_type(mut self, change_type: &str) -> Self {
+        self.change_type = Some(change_type.to_string());
+        self
+    }
+
+    pub fn include_watching(mut self, include_watching: bool) -> Self {
+        self.include_watching = Some(include_watching);
+        self
+    }
+
+    pub fn lock_unspents(mut self, lock_unspents: bool) -> Self {
+        self.lock_unspents = Some(lock_unspents);
+        self
+    }
+
+    pub fn fee_rate(mut self, fee_rate: FeeRate) -> Self {
+        self.fee_rate = Some(fee_rate);
+        self
+    }
+
+    pub fn subtract_fee_from_output(mut self, index: u32) -> Self {
+        self.subtract_fee_from_outputs.push(index);
+        self
+    }
+
+    pub fn replaceable(mut self, replaceable: bool) -> Self {
+        self.replaceable = Some(replaceable);
+        self
+    }
+
+    pub fn conf_target(mut self, conf_target: u32) -> Self {
+        self.conf_target = Some(conf_target);
+        self
+    }
+
+    pub fn estimate_mode(mut self, estimate_mode: EstimateMode) -> Self {
+        self.estimate_mode = Some(estimate_mode);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletCreateFundedPsbtResult {
+    // Base64 PSBT, unsigned
+    pub psbt: String,
+    pub fee: Amount,
+    // Index of the change output, -1 when there is none
+    pub changepos: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletProcessPsbtResult {
+    // Base64 PSBT with whatever the wallet could add
+    pub psbt: String,
+    // Whether every input is signed
+    pub complete: bool,
+    // The network transaction, when complete and finalized. Needs 26.0 or later.
+    pub hex: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinalizePsbtResult {
+    // Base64 PSBT, when incomplete or not extracted
+    pub psbt: Option<String>,
+    // The network transaction, when complete and extracted
+    pub hex: Option<String>,
+    pub complete: bool,
+}
+
+// Reply to `decodepsbt`. Hex and base64 fields are passed through as the node sends them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedPsbt {
+    // The unsigned transaction
+    pub tx: DecodedTransaction,
+    #[serde(default)]
+    pub global_xpubs: Vec<PsbtGlobalXpub>,
+    // 0.22 and later
+    pub psbt_version: Option<u32>,
+    // Records the node does not interpret, keyed by hex key
+    #[serde(default)]
+    pub unknown: HashMap<String, String>,
+    pub inputs: Vec<PsbtInput>,
+    pub outputs: Vec<PsbtOutput>,
+    // Only when every input has its UTXO
+    pub fee: Option<Amount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PsbtGlobalXpub {
+    pub xpub: String,
+    pub master_fingerprint: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PsbtInput {
+    // The whole previous transaction, for non-segwit inputs
+    pub non_witness_utxo: Option<DecodedTransaction>,
+    // The spent output, for segwit inputs
+    pub witness_utxo: Option<PsbtWitnessUtxo>,
+    // Public key to signature
+    #[serde(default)]
+    pub partial_signatures: HashMap<String, String>,
+    pub sighash: Option<SighashType>,
+    pub redeem_script: Option<PsbtScript>,
+    pub witness_script: Option<PsbtScript>,
+    #[serde(default)]
+    pub bip32_derivs: Vec<PsbtBip32Deriv>,
+    #[serde(rename = "final_scriptSig")]
+    pub final_script_sig: Option<ScriptSig>,
+    pub final_scriptwitness: Option<Vec<String>>,
+    pub taproot_key_path_sig: Option<String>,
+    #[serde(default)]
+    pub taproot_script_path_sigs: Vec<TaprootScriptPathSig>,
+    #[serde(default)]
+    pub taproot_scripts: Vec<TaprootLeafScript>,
+    #[serde(default)]
+    pub taproot_bip32_derivs: Vec<TaprootBip32Deriv>,
+    pub taproot_internal_key: Option<String>,
+    pub taproot_merkle_root: Option<String>,
+    #[serde(default)]
+    pub unknown: HashMap<String, String>,
+}
+
+impl PsbtInput {
+    // Finalized inputs carry their scriptSig or witness and nothing left to sign
+    pub fn is_finalized(&self) -> bool {
+        self.final_script_sig.is_some() || self.final_scriptwitness.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PsbtOutput {
+    pub redeem_script: Option<PsbtScript>,
+    pub witness_script: Option<PsbtScript>,
+    #[serde(default)]
+    pub bip32_derivs: Vec<PsbtBip32Deriv>,
+    pub taproot_internal_key: Option<String>,
+    #[serde(default)]
+    pub taproot_tree: Vec<TaprootTreeLeaf>,
+    #[serde(default)]
+    pub taproot_bip32_derivs: Vec<TaprootBip32Deriv>,
+    #[serde(default)]
+    pub unknown: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PsbtWitnessUtxo {
+    pub amount: Amount,
+    #[serde(alias = "scriptPubKey")]
+    pub script_pub_key: ScriptPubKey,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PsbtScript {
+    pub asm: String,
+    pub hex: String,
+    pub r#type: Option<ScriptType>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PsbtBip32Deriv {
+    pub pubkey: String,
+    pub master_fingerprint: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaprootScriptPathSig {
+    // X-only public key
+    pub pubkey: String,
+    pub leaf_hash: String,
+    pub sig: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaprootLeafScript {
+    pub script: String,
+    pub leaf_ver: u8,
+    pub control_blocks: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaprootTreeLeaf {
+    pub depth: u8,
+    pub leaf_ver: u8,
+    pub script: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaprootBip32Deriv {
+    // X-only public key
+    pub pubkey: String,
+    pub master_fingerprint: String,
+    pub path: String,
+    // Leaves the key appears in; empty for the internal key
+    #[serde(default)]
+    pub leaf_hashes: Vec<String>,
+}
+
+// Role that has to act on a PSBT next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PsbtRole {
+    Creator,
+    Updater,
+    Signer,
+    Finalizer,
+    Extractor,
+}
+
+// Reply to `analyzepsbt`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PsbtAnalysis {
+    #[serde(default)]
+    pub inputs: Vec<PsbtInputAnalysis>,
+    // Estimates once every input can be finalized
+    pub estimated_vsize: Option<u32>,
+    // BTC/kvB
+    pub estimated_feerate: Option<Amount>,
+    // Only when every input has its UTXO
+    pub fee: Option<Amount>,
+    pub next: PsbtRole,
+    // Why the PSBT is invalid, in which case `next` is the creator
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PsbtInputAnalysis {
+    pub has_utxo: bool,
+    pub is_final: bool,
+    pub missing: Option<PsbtMissing>,
+    pub next: Option<PsbtRole>,
+}
+
+// What an input still needs before it can be finalized
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PsbtMissing {
+    // Key ids (hash160) whose public keys are unknown
+    #[serde(default)]
+    pub pubkeys: Vec<String>,
+    // Key ids (hash160) still to sign
+    #[serde(default)]
+    pub signatures: Vec<String>,
+    // Hash160 of the missing redeem script
+    pub redeemscript: Option<String>,
+    // SHA256 of the missing witness script
+    pub witnessscript: Option<String>,
+}
+
+// When an imported key or descriptor was first used, bounding how far back the wallet
+// rescans. Serialized as `"now"` or a unix time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportTimestamp {
+    // No rescan; for fresh keys with no history
+    Now,
+    Time(u64),
+}
+
+impl Serialize for ImportTimestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ImportTimestamp::Now => serializer.serialize_str("now"),
+            ImportTimestamp::Time(time) => serializer.serialize_u64(*time),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ImportTimestamp {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Time(u64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Time(time) => Ok(ImportTimestamp::Time(time)),
+            Raw::Text(text) if text == "now" => Ok(ImportTimestamp::Now),
+            Raw::Text(text) => Err(serde::de::Error::custom(format!(
+                "expected \"now\" or a unix time, got {:?}",
+                text
+            ))),
+        }
+    }
+}
+
+// Fields `importdescriptors` and `importmulti` requests share
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportCommon {
+    pub timestamp: ImportTimestamp,
+    // Child indexes to derive for a ranged descriptor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<[u64; 2]>,
+    // Change addresses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub internal: Option<bool>,
+    // Not allowed together with `internal`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl ImportCommon {
+    pub fn new(timestamp: ImportTimestamp) -> Self {
+        ImportCommon {
+            timestamp,
+            range: None,
+            internal: None,
+            label: None,
+        }
+    }
+}
+
+// One request of `import_descriptors`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DescriptorImport {
+    pub desc: String,
+    // Use the descriptor for new addresses; needs a ranged descriptor with private keys
+    // or a watch-only wallet
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_index: Option<u64>,
+    #[serde(flatten)]
+    pub common: ImportCommon,
+}
+
+impl DescriptorImport {
+    pub fn new(desc: &str, timestamp: ImportTimestamp) -> Self {
+        DescriptorImport {
+            desc: desc.to_string(),
+            active: None,
+            next_index: None,
+            common: ImportCommon::new(timestamp),
+        }
+    }
+
+    pub fn active(mut self, active: bool) -> Self {
+        self.active = Some(active);
+        self
+    }
+
+    pub fn range(mut self, begin: u64, end: u64) -> Self {
+        self.common.range = Some([begin, end]);
+        self
+    }
+
+    pub fn next_index(mut self, next_index: u64) -> Self {
+        self.next_index = Some(next_index);
+        self
+    }
+
+    pub fn internal(mut self, internal: bool) -> Self {
+        self.common.internal = Some(internal);
+        self
+    }
+
+    pub fn label(mut self, label: &str) -> Self {
+        self.common.label = Some(label.to_string());
+        self
+    }
+}
+
+// What an `importmulti` request watches: a raw script or an address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportScript {
+    Script(String),
+    Address(String),
+}
+
+// Core takes a script as its hex and an address as `{"address": ...}`
+impl Serialize for ImportScript {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        match self {
+            ImportScript::Script(hex) => serializer.serialize_str(hex),
+            ImportScript::Address(address) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("address", address)?;
+                map.end()
+            }
+        }
+    }
+}
+
+// One request of `import_multi`, for legacy wallets. Give either `desc` or
+// `script_pub_key`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportMultiRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desc: Option<String>,
+    #[serde(rename = "scriptPubKey", skip_serializing_if = "Option::is_none")]
+    pub script_pub_key: Option<ImportScript>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redeemscript: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub witnessscript: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pubkeys: Vec<String>,
+    // WIF private keys
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<String>,
+    // Import without private keys even though they could be derived
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watchonly: Option<bool>,
+    // Add the public keys to the keypool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keypool: Option<bool>,
+    #[serde(flatten)]
+    pub common: ImportCommon,
+}
+
+impl ImportMultiRequest {
+    pub fn descriptor(desc: &str, timestamp: ImportTimestamp) -> Self {
+        ImportMultiRequest::new(Some(desc.to_string()), None, timestamp)
+    }
+
+    pub fn script(script: ImportScript, timestamp: ImportTimestamp) -> Self {
+        ImportMultiRequest::new(None, Some(script), timestamp)
+    }
+
+    fn new(
+        desc: Option<String>,
+        script_pub_key: Option<ImportScript>,
+        timestamp: ImportTimestamp,
+    ) -> Self {
+        ImportMultiRequest {
+            desc,
+            script_pub_key,
+            redeemscript: None,
+            witnessscript: None,
+            pubkeys: Vec::new(),
+            keys: Vec::new(),
+            watchonly: None,
+            keypool: None,
+            common: ImportCommon::new(timestamp),
+        }
+    }
+
+    pub fn redeemscript(mut self, hex: &str) -> Self {
+        self.redeemscript = Some(hex.to_string());
+        self
+    }
+
+    pub fn witnessscript(mut self, hex: &str) -> Self {
+        self.witnessscript = Some(hex.to_string());
+        self
+    }
+
+    pub fn pubkey(mut self, hex: &str) -> Self {
+        self.pubkeys.push(hex.to_string());
+        self
+    }
+
+    pub fn key(mut self, wif: &str) -> Self {
+        self.keys.push(wif.to_string());
+        self
+    }
+
+    pub fn watchonly(mut self, watchonly: bool) -> Self {
+        self.watchonly = Some(watchonly);
+        self
+    }
+
+    pub fn keypool(mut self, keypool: bool) -> Self {
+        self.keypool = Some(keypool);
+        self
+    }
+
+    pub fn range(mut self, begin: u64, end: u64) -> Self {
+        self.common.range = Some([begin, end]);
+        self
+    }
+
+    pub fn internal(mut self, internal: bool) -> Self {
+        self.common.internal = Some(internal);
+        self
+    }
+
+    pub fn label(mut self, label: &str) -> Self {
+        self.common.label = Some(label.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportMultiOptions {
+    // Rescan after importing; the node's default is true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rescan: Option<bool>,
+}
+
+// Outcome of one import request; a failed entry does not fail the others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub success: bool,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    pub error: Option<RpcError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListDescriptorsResult {
+    pub wallet_name: String,
+    pub descriptors: Vec<DescriptorInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DescriptorInfo {
+    // With a checksum, and with private keys when listed with `private`
+    pub desc: String,
+    pub timestamp: u64,
+    pub active: bool,
+    pub internal: Option<bool>,
+    pub range: Option<[u64; 2]>,
+    // Next index to hand out; newer nodes report it under both names
+    pub next: Option<u64>,
+    pub next_index: Option<u64>,
+}
+
+// `getbalances`, split by trust and maturity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balances {
+    pub mine: BalanceDetails,
+    // Only for wallets with watch-only addresses
+    pub watchonly: Option<BalanceDetails>,
+    // The block the balances are as of. Needs 26.0 or later.
+    pub lastprocessedblock: Option<LastProcessedBlock>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceDetails {
+    // Confirmed, or unconfirmed from the wallet itself
+    pub trusted: Amount,
+    // Unconfirmed payments from others
+    pub untrusted_pending: Amount,
+    // Coinbase outputs not yet spendable
+    pub immature: Amount,
+    // Only for wallets with `avoid_reuse`: outputs at addresses already spent from
+    pub used: Option<Amount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastProcessedBlock {
+    pub hash: String,
+    pub height: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SimulateOptions {
+    // Count watch-only outputs as the wallet's own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_watchonly: Option<bool>,
+}
+
+impl SimulateOptions {
+    pub fn include_watchonly(mut self, include_watchonly: bool) -> Self {
+        self.include_watchonly = Some(include_watchonly);
+        self
+    }
+}
+
+// Reply to `simulaterawtransaction`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulateResult {
+    // Negative when the transactions spend more of the wallet's coins than they pay it
+    pub balance_change: Amount,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateMode {
+    Template,
+    // Check a block without submitting it; see `propose_block`
+    Proposal,
+}
+
+// Request for `get_block_template`. `new` includes the "segwit" rule, which the node
+// requires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockTemplateRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<TemplateMode>,
+    // e.g. "longpoll", "coinbasetxn", "proposal"
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub capabilities: Vec<String>,
+    pub rules: Vec<String>,
+    // From the previous template; the node answers once that one is stale
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longpollid: Option<String>,
+    // Block hex, in proposal mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+impl BlockTemplateRequest {
+    pub fn new() -> Self {
+        BlockTemplateRequest {
+            mode: None,
+            capabilities: Vec::new(),
+            rules: vec!["segwit".to_string()],
+            longpollid: None,
+            data: None,
+        }
+    }
+
+    pub fn capability(mut self, capability: &str) -> Self {
+        self.capabilities.push(capability.to_string());
+        self
+    }
+
+    // Extra rule on top of "segwit", e.g. "signet" on signet
+    pub fn rule(mut self, rule: &str) -> Self {
+        self.rules.push(rule.to_string());
+        self
+    }
+
+    pub fn longpollid(mut self, longpollid: &str) -> Self {
+        self.longpollid = Some(longpollid.to_string());
+        self
+    }
+}
+
+impl Default for BlockTemplateRequest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Reply to `getblocktemplate`. Fees and the coinbase value are in satoshis here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockTemplate {
+    pub version: i32,
+    pub rules: Vec<String>,
+    // Pending deployments the miner may signal for, by name to version bit
+    #[serde(default)]
+    pub vbavailable: HashMap<String, u32>,
+    pub vbrequired: u32,
+    pub previousblockhash: String,
+    // In block order; the coinbase is not included
+    pub transactions: Vec<TemplateTransaction>,
+    // Data to put in the coinbase scriptSig, by key
+    #[serde(default)]
+    pub coinbaseaux: HashMap<String, String>,
+    pub coinbasevalue: u64,
+    pub longpollid: Option<String>,
+    // Hex target the block hash must not exceed
+    pub target: String,
+    pub mintime: u64,
+    // What the miner may change, e.g. "time", "transactions", "prevblock"
+    pub mutable: Vec<String>,
+    pub noncerange: String,
+    pub sigoplimit: u64,
+    pub sizelimit: u64,
+    pub weightlimit: u64,
+    pub curtime: u64,
+    // Compact target, hex
+    pub bits: String,
+    pub height: u64,
+    // Only on signet
+    pub signet_challenge: Option<String>,
+    // Witness commitment output script for the coinbase, when there are witness
+    // transactions to commit to
+    pub default_witness_commitment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateTransaction {
+    pub data: String,
+    pub txid: String,
+    // Wtxid
+    pub hash: String,
+    // 1-based indexes into `transactions` of in-template parents
+    pub depends: Vec<usize>,
+    pub fee: u64,
+    pub sigops: u64,
+    pub weight: u64,
+}
+
+// Where `generate_block` pays the coinbase. Both are sent as the plain string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum GenerateOutput {
+    Address(String),
+    Descriptor(String),
+}
+
+impl From<&str> for GenerateOutput {
+    // Descriptors are recognized by their parentheses, e.g. "raw(51)" or "addr(...)"
+    fn from(output: &str) -> Self {
+        if output.contains('(') {
+            GenerateOutput::Descriptor(output.to_string())
+        } else {
+            GenerateOutput::Address(output.to_string())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateBlockResult {
+    pub hash: String,
+    // Only when the block was not submitted
+    pub hex: Option<String>,
+}
+
+// Options for `create_wallet`. Left at the defaults the wallet has private keys and, on
+// 23.0 and later, descriptors.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CreateWalletOptions {
+    // Watch-only wallet
+    pub disable_private_keys: bool,
+    // No keys or HD seed until some are imported
+    pub blank: bool,
+    // Encrypt the new wallet with this passphrase
+    pub passphrase: Option<String>,
+    // Never spend from an address that was already spent from
+    pub avoid_reuse: bool,
+    // None leaves the choice to the node's version default
+    pub descriptors: Option<bool>,
+    // Add to or remove from the node's startup wallet list; None leaves it alone
+    pub load_on_startup: Option<bool>,
+    // Keys live on the signer configured with -signer; needs `disable_private_keys` and
+    // descriptors
+    pub external_signer: bool,
+}
+
+impl CreateWalletOptions {
+    pub fn disable_private_keys(mut self, value: bool) -> Self {
+        self.disable_private_keys = value;
+        self
+    }
+
+    pub fn blank(mut self, value: bool) -> Self {
+        self.blank = value;
+        self
+    }
+
+    pub fn passphrase(mut self, passphrase: &str) -> Self {
+        self.passphrase = Some(passphrase.to_string());
+        self
+    }
+
+    pub fn avoid_reuse(mut self, value: bool) -> Self {
+        self.avoid_reuse = value;
+        self
+    }
+
+    pub fn descriptors(mut self, value: bool) -> Self {
+        self.descriptors = Some(value);
+        self
+    }
+
+    pub fn load_on_startup(mut self, value: bool) -> Self {
+        self.load_on_startup = Some(value);
+        self
+    }
+
+    pub fn external_signer(mut self, value: bool) -> Self {
+        self.external_signer = value;
+        self
+    }
+}
+
+// A device found by `enumeratesigners`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signer {
+    // Master key fingerprint, 8 hex characters
+    pub fingerprint: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayAddressResult {
+    pub address: String,
+}
+
+// Reply to `createmultisig` and `addmultisigaddress`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigResult {
+    pub address: String,
+    pub redeem_script: String,
+    // 0.20 and later
+    pub descriptor: Option<String>,
+    // 23.0 and later, e.g. when uncompressed keys forced a legacy address
+    #[serde(default)]
+    pub warnings: Warnings,
+}
+
+impl MultisigResult {
+    // The node fell back to a legacy address because a key is uncompressed
+    pub fn has_uncompressed_keys(&self) -> bool {
+        self.warnings.0.iter().any(|w| w.contains("uncompressed"))
+    }
+}
+
+// Reply to `createwallet` and `loadwallet`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWalletResult {
+    pub name: String,
+    // Before 25.0; empty when there is nothing to report
+    pub warning: Option<String>,
+    // 25.0 and later
+    pub warnings: Option<Vec<String>>,
+}
+
+pub type LoadWalletResult = CreateWalletResult;
+
+pub type RestoreWalletResult = CreateWalletResult;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrateWalletResult {
+    pub wallet_name: String,
+    // Sibling wallets created for watch-only and solvable-but-not-owned scripts
+    pub watchonly_name: Option<String>,
+    pub solvables_name: Option<String>,
+    // Backup of the legacy wallet taken before migrating
+    pub backup_path: String,
+}
+
+impl MigrateWalletResult {
+    // The migrated wallet followed by any siblings
+    pub fn wallet_names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.wallet_name.as_str())
+            .chain(self.watchonly_name.as_deref())
+            .chain(self.solvables_name.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpgradeWalletResult {
+    pub wallet_name: String,
+    pub previous_version: u32,
+    pub current_version: u32,
+    // What changed, when the upgrade went through
+    pub result: Option<String>,
+    // Why the upgrade was refused
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DumpWalletResult {
+    // Absolute path of the dump on the node's filesystem
+    pub filename: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletDirEntry {
+    pub name: String,
+    // Why the wallet cannot be loaded, e.g. it is a legacy wallet on a node without BDB
+    pub warnings: Option<Vec<String>>,
+}
+
+// Options for `import_mempool`; unset fields are left out so the node's defaults apply
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportMempoolOptions {
+    // Use the import time as each transaction's entry time instead of the saved one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_current_time: Option<bool>,
+    // Apply the fee deltas set with `prioritisetransaction` in the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply_fee_delta_priority: Option<bool>,
+    // Treat the file's unbroadcast transactions as still unbroadcast
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply_unbroadcast_set: Option<bool>,
+}
+
+impl ImportMempoolOptions {
+    pub fn use_current_time(mut self, value: bool) -> Self {
+        self.use_current_time = Some(value);
+        self
+    }
+
+    pub fn apply_fee_delta_priority(mut self, value: bool) -> Self {
+        self.apply_fee_delta_priority = Some(value);
+        self
+    }
+
+    pub fn apply_unbroadcast_set(mut self, value: bool) -> Self {
+        self.apply_unbroadcast_set = Some(value);
+        self
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        *self == ImportMempoolOptions::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcInfo {
+    pub active_commands: Vec<ActiveCommand>,
+    pub logpath: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveCommand {
+    pub method: String,
+    // Microseconds the command has been running
+    pub duration: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitForBlockResult {
+    pub hash: String,
+    pub height: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DumpTxOutSetResult {
+    pub coins_written: u64,
+    pub base_hash: String,
+    pub base_height: u64,
+    pub path: String,
+    pub txoutset_hash: String,
+    pub nchaintx: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadTxOutSetResult {
+    pub coins_loaded: u64,
+    pub tip_hash: String,
+    pub base_height: u64,
+    pub path: String,
 }
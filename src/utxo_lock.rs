@@ -0,0 +1,75 @@
+use anyhow::Result;
+use serde_json::{Value, json};
+
+use crate::BitcoinClient;
+use crate::cancel::AbortOnDrop;
+use crate::types::OutPoint;
+
+// Outputs locked by `lock_utxos`, unlocked again when dropped. The unlock on drop is
+// best-effort from a background task; call `unlock` to see its result.
+pub struct UtxoLockGuard {
+    client: BitcoinClient,
+    outpoints: Vec<OutPoint>,
+    unlock_on_drop: AbortOnDrop,
+}
+
+impl UtxoLockGuard {
+    pub fn outpoints(&self) -> &[OutPoint] {
+        &self.outpoints
+    }
+
+    pub async fn unlock(self) -> Result<()> {
+        self.unlock_on_drop.disarm();
+        self.client
+            .lock_unspent(true, &self.outpoints, false)
+            .await?;
+        Ok(())
+    }
+
+    // Leave the outputs locked, e.g. once the transaction spending them is broadcast
+    pub fn keep(self) -> Vec<OutPoint> {
+        self.unlock_on_drop.disarm();
+        self.outpoints
+    }
+}
+
+impl std::fmt::Debug for UtxoLockGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UtxoLockGuard")
+            .field("outpoints", &self.outpoints)
+            .finish()
+    }
+}
+
+impl BitcoinClient {
+    // Lock outputs so coin selection skips them, or unlock them with `unlock`. Locks
+    // last until the node restarts unless `persistent`, which needs 23.0 or later.
+    pub async fn lock_unspent(
+        &self,
+        unlock: bool,
+        outpoints: &[OutPoint],
+        persistent: bool,
+    ) -> Result<bool> {
+        let params = if persistent {
+            json!([unlock, outpoints, true])
+        } else {
+            json!([unlock, outpoints])
+        };
+        self.call("lockunspent", params).await
+    }
+
+    pub async fn list_lock_unspent(&self) -> Result<Vec<OutPoint>> {
+        self.call("listlockunspent", Value::Null).await
+    }
+
+    // Lock outputs for as long as the returned guard lives, so concurrent jobs do not
+    // select them
+    pub async fn lock_utxos(&self, outpoints: &[OutPoint]) -> Result<UtxoLockGuard> {
+        self.lock_unspent(false, outpoints, false).await?;
+        Ok(UtxoLockGuard {
+            client: self.clone(),
+            outpoints: outpoints.to_vec(),
+            unlock_on_drop: AbortOnDrop::new(self, "lockunspent", json!([true, outpoints])),
+        })
+    }
+}
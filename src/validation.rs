@@ -0,0 +1,536 @@
+use serde_json::{Map, Value};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Number,
+    // A BTC value, at most 8 decimals
+    Amount,
+    String,
+    Bool,
+    Array,
+    Object,
+    Any,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FieldSpec {
+    pub name: &'static str,
+    pub kind: FieldKind,
+    pub required: bool,
+}
+
+// Fields we model for one RPC result. Array results are checked element by element.
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseSchema {
+    pub method: &'static str,
+    pub fields: &'static [FieldSpec],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResponseIssue {
+    NumericAsString,
+    ExcessAmountPrecision,
+    UnexpectedNull,
+    MissingField,
+    WrongType,
+    UnknownField,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseWarning {
+    pub method: String,
+    pub field: String,
+    pub issue: ResponseIssue,
+    pub value: String,
+}
+
+impl fmt::Display for ResponseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "method={} field={} issue={:?} value={}",
+            self.method, self.field, self.issue, self.value
+        )
+    }
+}
+
+const fn req(name: &'static str, kind: FieldKind) -> FieldSpec {
+    FieldSpec {
+        name,
+        kind,
+        required: true,
+    }
+}
+
+const fn opt(name: &'static str, kind: FieldKind) -> FieldSpec {
+    FieldSpec {
+        name,
+        kind,
+        required: false,
+    }
+}
+
+const SCHEMAS: &[ResponseSchema] = &[
+    ResponseSchema {
+        method: "getblockchaininfo",
+        fields: &[
+            req("chain", FieldKind::String),
+            req("blocks", FieldKind::Number),
+            req("headers", FieldKind::Number),
+            req("bestblockhash", FieldKind::String),
+            req("difficulty", FieldKind::Number),
+            req("time", FieldKind::Number),
+            req("mediantime", FieldKind::Number),
+            req("verificationprogress", FieldKind::Number),
+            req("initialblockdownload", FieldKind::Bool),
+            req("chainwork", FieldKind::String),
+            req("size_on_disk", FieldKind::Number),
+            req("pruned", FieldKind::Bool),
+            opt("pruneheight", FieldKind::Number),
+            opt("automatic_pruning", FieldKind::Bool),
+            opt("prune_target_size", FieldKind::Number),
+            opt("softforks", FieldKind::Object),
+            opt("signet_challenge", FieldKind::String),
+            opt("bits", FieldKind::String),
+            opt("target", FieldKind::String),
+            req("warnings", FieldKind::Any),
+        ],
+    },
+    ResponseSchema {
+        method: "getblockheader",
+        fields: &[
+            req("hash", FieldKind::String),
+            req("confirmations", FieldKind::Number),
+            req("height", FieldKind::Number),
+            req("version", FieldKind::Number),
+            req("versionHex", FieldKind::String),
+            req("merkleroot", FieldKind::String),
+            req("time", FieldKind::Number),
+            req("mediantime", FieldKind::Number),
+            req("nonce", FieldKind::Number),
+            req("bits", FieldKind::String),
+            opt("target", FieldKind::String),
+            req("difficulty", FieldKind::Number),
+            req("chainwork", FieldKind::String),
+            req("nTx", FieldKind::Number),
+            opt("previousblockhash", FieldKind::String),
+            opt("nextblockhash", FieldKind::String),
+        ],
+    },
+    ResponseSchema {
+        method: "getmempoolinfo",
+        fields: &[
+            req("loaded", FieldKind::Bool),
+            req("size", FieldKind::Number),
+            req("bytes", FieldKind::Number),
+            req("usage", FieldKind::Number),
+            opt("total_fee", FieldKind::Amount),
+            req("maxmempool", FieldKind::Number),
+            req("mempoolminfee", FieldKind::Amount),
+            req("minrelaytxfee", FieldKind::Amount),
+            opt("incrementalrelayfee", FieldKind::Amount),
+            opt("unbroadcastcount", FieldKind::Number),
+            opt("fullrbf", FieldKind::Bool),
+        ],
+    },
+    ResponseSchema {
+        method: "getmininginfo",
+        fields: &[
+            req("blocks", FieldKind::Number),
+            opt("currentblockweight", FieldKind::Number),
+            opt("currentblocktx", FieldKind::Number),
+            opt("bits", FieldKind::String),
+            req("difficulty", FieldKind::Number),
+            opt("target", FieldKind::String),
+            req("networkhashps", FieldKind::Number),
+            req("pooledtx", FieldKind::Number),
+            req("chain", FieldKind::String),
+            opt("next", FieldKind::Object),
+            req("warnings", FieldKind::Any),
+        ],
+    },
+    ResponseSchema {
+        method: "gettxout",
+        fields: &[
+            req("bestblock", FieldKind::String),
+            req("confirmations", FieldKind::Number),
+            req("value", FieldKind::Amount),
+            req("scriptPubKey", FieldKind::Object),
+            req("coinbase", FieldKind::Bool),
+        ],
+    },
+    ResponseSchema {
+        method: "listunspent",
+        fields: &[
+            req("txid", FieldKind::String),
+            req("vout", FieldKind::Number),
+            opt("address", FieldKind::String),
+            opt("label", FieldKind::String),
+            req("scriptPubKey", FieldKind::String),
+            req("amount", FieldKind::Amount),
+            req("confirmations", FieldKind::Number),
+            opt("ancestorcount", FieldKind::Number),
+            opt("ancestorsize", FieldKind::Number),
+            opt("ancestorfees", FieldKind::Number),
+            opt("redeemScript", FieldKind::String),
+            opt("witnessScript", FieldKind::String),
+            req("spendable", FieldKind::Bool),
+            req("solvable", FieldKind::Bool),
+            opt("reused", FieldKind::Bool),
+            opt("desc", FieldKind::String),
+            opt("parent_descs", FieldKind::Array),
+            req("safe", FieldKind::Bool),
+        ],
+    },
+];
+
+pub fn schema_for(method: &str) -> Option<&'static ResponseSchema> {
+    SCHEMAS.iter().find(|s| s.method == method)
+}
+
+// Check a result against the schema for its method, if there is one
+pub fn validate_response(method: &str, result: &Value) -> Vec<ResponseWarning> {
+    let Some(schema) = schema_for(method) else {
+        return Vec::new();
+    };
+    let mut warnings = Vec::new();
+    match result {
+        Value::Object(object) => check_object(schema, object, "", &mut warnings),
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                if let Value::Object(object) = item {
+                    check_object(schema, object, &format!("[{}].", i), &mut warnings);
+                }
+            }
+        }
+        _ => {}
+    }
+    warnings
+}
+
+fn check_object(
+    schema: &ResponseSchema,
+    object: &Map<String, Value>,
+    prefix: &str,
+    warnings: &mut Vec<ResponseWarning>,
+) {
+    let mut warn = |field: &str, issue: ResponseIssue, value: &Value| {
+        warnings.push(ResponseWarning {
+            method: schema.method.to_string(),
+            field: format!("{}{}", prefix, field),
+            issue,
+            value: value.to_string(),
+        })
+    };
+    for spec in schema.fields {
+        let value = match object.get(spec.name) {
+            None if spec.required => {
+                warn(spec.name, ResponseIssue::MissingField, &Value::Null);
+                continue;
+            }
+            None => continue,
+            Some(value) => value,
+        };
+        let issue = match (spec.kind, value) {
+            (_, Value::Null) if spec.required => Some(ResponseIssue::UnexpectedNull),
+            (_, Value::Null) | (FieldKind::Any, _) => None,
+            (FieldKind::Number | FieldKind::Amount, Value::String(s))
+                if s.trim().parse::<f64>().is_ok() =>
+            {
+                Some(ResponseIssue::NumericAsString)
+            }
+            (FieldKind::Amount, Value::Number(n)) if has_excess_precision(n) => {
+                Some(ResponseIssue::ExcessAmountPrecision)
+            }
+            (FieldKind::Number | FieldKind::Amount, Value::Number(_))
+            | (FieldKind::String, Value::String(_))
+            | (FieldKind::Bool, Value::Bool(_))
+            | (FieldKind::Array, Value::Array(_))
+            | (FieldKind::Object, Value::Object(_)) => None,
+            _ => Some(ResponseIssue::WrongType),
+        };
+        if let Some(issue) = issue {
+            warn(spec.name, issue, value);
+        }
+    }
+    for (key, value) in object {
+        if !schema.fields.iter().any(|f| f.name == key) {
+            warn(key, ResponseIssue::UnknownField, value);
+        }
+    }
+}
+
+fn has_excess_precision(n: &serde_json::Number) -> bool {
+    let text = n.to_string();
+    if !text.contains(['e', 'E']) {
+        return text
+            .split_once('.')
+            .is_some_and(|(_, decimals)| decimals.len() > 8);
+    }
+    let sats = n.as_f64().unwrap_or(0.0) * 1e8;
+    (sats - sats.round()).abs() > 1e-6
+}
+
+// Log every problem found in a response. With `tracing`, method, field, issue and value
+// are event fields rather than parts of the message.
+pub(crate) fn log_response_warnings(method: &str, result: &Value) {
+    for warning in validate_response(method, result) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            method = %warning.method,
+            field = %warning.field,
+            issue = ?warning.issue,
+            value = %warning.value,
+            "response validation"
+        );
+        #[cfg(not(feature = "tracing"))]
+        log::warn!("response validation: {}", warning);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tx_out() -> Value {
+        json!({
+            "bestblock": "00".repeat(32),
+            "confirmations": 3,
+            "value": 0.5,
+            "scriptPubKey": {"hex": "51"},
+            "coinbase": false,
+        })
+    }
+
+    fn issues(method: &str, result: &Value) -> Vec<(String, ResponseIssue)> {
+        validate_response(method, result)
+            .into_iter()
+            .map(|w| (w.field, w.issue))
+            .collect()
+    }
+
+    fn with(field: &str, value: Value) -> Value {
+        let mut result = tx_out();
+        result[field] = value;
+        result
+    }
+
+    #[test]
+    fn well_formed_and_unmodelled_results_pass() {
+        assert!(validate_response("gettxout", &tx_out()).is_empty());
+        assert!(validate_response("getbestblockhash", &json!("00")).is_empty());
+        assert!(validate_response("getblockcount", &json!({"x": "1"})).is_empty());
+    }
+
+    #[test]
+    fn numbers_sent_as_strings() {
+        let warnings = validate_response("gettxout", &with("value", json!("0.5")));
+        assert_eq!(
+            warnings,
+            [ResponseWarning {
+                method: "gettxout".to_string(),
+                field: "value".to_string(),
+                issue: ResponseIssue::NumericAsString,
+                value: "\"0.5\"".to_string(),
+            }]
+        );
+        assert_eq!(
+            issues("gettxout", &with("confirmations", json!(" 3 "))),
+            [("confirmations".to_string(), ResponseIssue::NumericAsString)]
+        );
+    }
+
+    #[test]
+    fn amounts_with_more_than_eight_decimals() {
+        for excess in [
+            json!(0.123456789),
+            json!(1e-9),
+            json!(1.5e-8),
+            json!(2.000000001),
+        ] {
+            assert_eq!(
+                issues("gettxout", &with("value", excess.clone())),
+                [("value".to_string(), ResponseIssue::ExcessAmountPrecision)],
+                "{}",
+                excess
+            );
+        }
+        for exact in [
+            json!(0.12345678),
+            json!(1e-8),
+            json!(2.1e7),
+            json!(0),
+            json!(5),
+        ] {
+            assert!(
+                issues("gettxout", &with("value", exact.clone())).is_empty(),
+                "{}",
+                exact
+            );
+        }
+        // Plain numbers may carry any precision
+        assert!(issues("gettxout", &with("confirmations", json!(1e-9))).is_empty());
+    }
+
+    #[test]
+    fn nulls_in_required_fields() {
+        assert_eq!(
+            issues("gettxout", &with("bestblock", Value::Null)),
+            [("bestblock".to_string(), ResponseIssue::UnexpectedNull)]
+        );
+        let mut info = json!({"loaded": true, "size": 0, "bytes": 0, "usage": 0,
+            "maxmempool": 300000000, "mempoolminfee": 0.00001, "minrelaytxfee": 0.00001});
+        info["fullrbf"] = Value::Null;
+        assert!(issues("getmempoolinfo", &info).is_empty());
+    }
+
+    #[test]
+    fn missing_fields() {
+        let mut result = tx_out();
+        result.as_object_mut().unwrap().remove("coinbase");
+        let warnings = validate_response("gettxout", &result);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            (
+                warnings[0].field.as_str(),
+                &warnings[0].issue,
+                warnings[0].value.as_str()
+            ),
+            ("coinbase", &ResponseIssue::MissingField, "null")
+        );
+    }
+
+    #[test]
+    fn wrong_types() {
+        for (field, value) in [
+            ("coinbase", json!("false")),
+            ("confirmations", json!("three")),
+            ("scriptPubKey", json!("51")),
+            ("bestblock", json!(0)),
+        ] {
+            assert_eq!(
+                issues("gettxout", &with(field, value)),
+                [(field.to_string(), ResponseIssue::WrongType)]
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_fields() {
+        assert_eq!(
+            issues("gettxout", &with("spent", json!(false))),
+            [("spent".to_string(), ResponseIssue::UnknownField)]
+        );
+    }
+
+    #[test]
+    fn array_results_are_checked_per_element() {
+        let utxo = json!({
+            "txid": "aa".repeat(32), "vout": 0, "scriptPubKey": "51", "amount": 1.0,
+            "confirmations": 1, "spendable": true, "solvable": true, "safe": true,
+        });
+        let mut bad = utxo.clone();
+        bad["amount"] = json!("1.0");
+        bad.as_object_mut().unwrap().remove("safe");
+        let result = json!([utxo, bad, "not an object"]);
+        let mut found = issues("listunspent", &result);
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            found,
+            [
+                ("[1].amount".to_string(), ResponseIssue::NumericAsString),
+                ("[1].safe".to_string(), ResponseIssue::MissingField),
+            ]
+        );
+    }
+
+    #[test]
+    fn warnings_display_every_part() {
+        let warning = &validate_response("gettxout", &with("value", json!("0.5")))[0];
+        assert_eq!(
+            warning.to_string(),
+            "method=gettxout field=value issue=NumericAsString value=\"0.5\""
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    mod events {
+        use super::*;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        #[derive(Default)]
+        struct Fields(Vec<(String, String)>);
+
+        // Keeps the fields of every event
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<Fields>>>);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                self.0
+                    .push((field.name().to_string(), format!("{:?}", value)));
+            }
+
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0.push((field.name().to_string(), value.to_string()));
+            }
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut fields = Fields::default();
+                event.record(&mut fields);
+                self.0.lock().unwrap().push(fields);
+            }
+
+            fn enter(&self, _: &Id) {}
+
+            fn exit(&self, _: &Id) {}
+        }
+
+        #[test]
+        fn warnings_are_structured_events() {
+            let recorder = Recorder::default();
+            let result = json!({
+                "bestblock": "00", "confirmations": 1, "value": "0.5",
+                "scriptPubKey": {}, "coinbase": false,
+            });
+            tracing::subscriber::with_default(recorder.clone(), || {
+                log_response_warnings("gettxout", &result)
+            });
+            let events = recorder.0.lock().unwrap();
+            let fields: Vec<(&str, &str)> = events[0]
+                .0
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect();
+            assert_eq!(
+                fields,
+                [
+                    ("message", "response validation"),
+                    ("method", "gettxout"),
+                    ("field", "value"),
+                    ("issue", "NumericAsString"),
+                    ("value", "\"0.5\""),
+                ]
+            );
+        }
+    }
+}
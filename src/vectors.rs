@@ -0,0 +1,432 @@
+use anyhow::{Result, anyhow};
+use secp256k1::ecdsa::Signature;
+use secp256k1::{Message, PublicKey, Secp256k1};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::BitcoinClientType;
+use crate::crypto::BitcoinCrypto;
+use crate::serialization::Serialization;
+
+// A vector file: a description and a list of vectors tagged by `kind`
+#[derive(Debug, Clone, Deserialize)]
+pub struct VectorFile {
+    pub description: String,
+    pub vectors: Vec<TestVector>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TestVector {
+    AddressEncode {
+        name: String,
+        hash160: String,
+        network: BitcoinClientType,
+        // "p2pkh", "p2sh" or "p2wpkh"
+        address_kind: String,
+        address: String,
+    },
+    // `script_pubkey` is null for addresses that must be rejected
+    AddressDecode {
+        name: String,
+        address: String,
+        script_pubkey: Option<String>,
+    },
+    Wif {
+        name: String,
+        private_key: String,
+        compressed: bool,
+        network: BitcoinClientType,
+        wif: String,
+    },
+    CompactSize {
+        name: String,
+        value: u64,
+        hex: String,
+    },
+    Transaction {
+        name: String,
+        hex: String,
+        txid: String,
+        wtxid: String,
+    },
+    // Hashes in display order; branches list sibling hashes from the leaf up
+    Merkle {
+        name: String,
+        txids: Vec<String>,
+        merkle_root: String,
+        #[serde(default)]
+        branches: Vec<MerkleBranchVector>,
+    },
+    // Digest in internal byte order; `amount` is required when `segwit` is set.
+    // `signature`, when given, is a published signature the digest must verify.
+    Sighash {
+        name: String,
+        tx: String,
+        input_index: usize,
+        script_code: String,
+        amount: Option<u64>,
+        segwit: bool,
+        sighash_type: u32,
+        sighash: String,
+        #[serde(default)]
+        signature: Option<SignatureVector>,
+    },
+}
+
+// A DER-encoded ECDSA signature, without the sighash type byte, and its public key
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignatureVector {
+    pub pubkey: String,
+    pub der: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MerkleBranchVector {
+    pub index: usize,
+    pub branch: Vec<String>,
+}
+
+impl TestVector {
+    pub fn name(&self) -> &str {
+        match self {
+            TestVector::AddressEncode { name, .. }
+            | TestVector::AddressDecode { name, .. }
+            | TestVector::Wif { name, .. }
+            | TestVector::CompactSize { name, .. }
+            | TestVector::Transaction { name, .. }
+            | TestVector::Merkle { name, .. }
+            | TestVector::Sighash { name, .. } => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorFailure {
+    pub file: PathBuf,
+    pub index: usize,
+    pub name: String,
+    // Which check within the vector failed, e.g. "wtxid"
+    pub check: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for VectorFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "FAIL {}#{} ({}) {}",
+            self.file.display(),
+            self.index,
+            self.name,
+            self.check
+        )?;
+        writeln!(f, "  expected: {}", self.expected)?;
+        writeln!(f, "  actual:   {}", self.actual)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VectorReport {
+    pub total: usize,
+    pub passed: usize,
+    pub failures: Vec<VectorFailure>,
+    // How many vectors exercised each function
+    pub coverage: BTreeMap<&'static str, usize>,
+}
+
+impl VectorReport {
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for VectorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for failure in &self.failures {
+            write!(f, "{}", failure)?;
+        }
+        writeln!(f, "{}/{} vectors passed", self.passed, self.total)?;
+        for (function, count) in &self.coverage {
+            writeln!(f, "  {:<48} {}", function, count)?;
+        }
+        Ok(())
+    }
+}
+
+// Collects the outcome of the checks within one vector
+struct VectorRun<'a> {
+    report: &'a mut VectorReport,
+    file: &'a Path,
+    index: usize,
+    name: &'a str,
+    failed: bool,
+}
+
+impl VectorRun<'_> {
+    fn uses(&mut self, function: &'static str) {
+        *self.report.coverage.entry(function).or_insert(0) += 1;
+    }
+
+    fn expect(&mut self, check: &str, expected: &str, actual: Result<String>) {
+        let actual = actual.unwrap_or_else(|e| format!("error: {}", e));
+        if actual != expected {
+            self.failed = true;
+            self.report.failures.push(VectorFailure {
+                file: self.file.to_path_buf(),
+                index: self.index,
+                name: self.name.to_string(),
+                check: check.to_string(),
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+    }
+}
+
+fn hash_list(hashes: &[String]) -> Result<Vec<[u8; 32]>> {
+    hashes
+        .iter()
+        .map(|h| Serialization::hex_to_hash(h))
+        .collect()
+}
+
+fn run_vector(run: &mut VectorRun, vector: &TestVector) {
+    match vector {
+        TestVector::AddressEncode {
+            hash160,
+            network,
+            address_kind,
+            address,
+            ..
+        } => {
+            let actual = (|| {
+                let hash: [u8; 20] = hex::decode(hash160)?
+                    .try_into()
+                    .map_err(|_| anyhow!("hash160 must be 20 bytes"))?;
+                match address_kind.as_str() {
+                    "p2pkh" => BitcoinCrypto::hash160_to_p2pkh_address(&hash, *network),
+                    "p2sh" => BitcoinCrypto::hash160_to_p2sh_address(&hash, *network),
+                    "p2wpkh" => BitcoinCrypto::hash160_to_bech32_address(&hash, *network),
+                    other => Err(anyhow!("Unknown address kind {}", other)),
+                }
+            })();
+            run.uses(match address_kind.as_str() {
+                "p2pkh" => "BitcoinCrypto::hash160_to_p2pkh_address",
+                "p2sh" => "BitcoinCrypto::hash160_to_p2sh_address",
+                _ => "BitcoinCrypto::hash160_to_bech32_address",
+            });
+            run.expect("address", address, actual);
+        }
+        TestVector::AddressDecode {
+            address,
+            script_pubkey,
+            ..
+        } => {
+            run.uses("BitcoinCrypto::address_to_script_pubkey");
+            let actual = BitcoinCrypto::address_to_script_pubkey(address).map(hex::encode);
+            match script_pubkey {
+                Some(expected) => run.expect("script_pubkey", expected, actual),
+                None => run.expect(
+                    "rejected",
+                    "rejected",
+                    Ok(match actual {
+                        Ok(script) => format!("accepted as {}", script),
+                        Err(_) => "rejected".to_string(),
+                    }),
+                ),
+            }
+        }
+        TestVector::Wif {
+            private_key,
+            compressed,
+            network,
+            wif,
+            ..
+        } => {
+            run.uses("BitcoinCrypto::private_key_to_wif");
+            let encoded = (|| {
+                let key: [u8; 32] = hex::decode(private_key)?
+                    .try_into()
+                    .map_err(|_| anyhow!("private key must be 32 bytes"))?;
+                BitcoinCrypto::private_key_to_wif(&key, *compressed, *network)
+            })();
+            run.expect("encode", wif, encoded);
+            run.uses("BitcoinCrypto::wif_to_private_key");
+            let decoded = BitcoinCrypto::wif_to_private_key(wif).map(|(key, compressed, _)| {
+                format!("{} compressed={}", hex::encode(key), compressed)
+            });
+            run.expect(
+                "decode",
+                &format!("{} compressed={}", private_key, compressed),
+                decoded,
+            );
+        }
+        TestVector::CompactSize { value, hex, .. } => {
+            run.uses("Serialization::serialize_varint");
+            run.expect(
+                "serialize",
+                hex,
+                Ok(hex::encode(Serialization::serialize_varint(*value))),
+            );
+            run.uses("Serialization::deserialize_varint");
+            let decoded = hex::decode(hex)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| Serialization::deserialize_varint(&bytes))
+                .map(|(v, used)| format!("{} ({} bytes)", v, used));
+            run.expect(
+                "deserialize",
+                &format!("{} ({} bytes)", value, hex.len() / 2),
+                decoded,
+            );
+        }
+        TestVector::Transaction {
+            hex, txid, wtxid, ..
+        } => {
+            run.uses("Serialization::deserialize_transaction");
+            match Serialization::deserialize_transaction(hex) {
+                Ok(tx) => {
+                    run.uses("RawTransaction::serialize");
+                    run.expect("reserialize", hex, Ok(hex::encode(tx.serialize(true))));
+                    run.uses("RawTransaction::txid");
+                    run.expect("txid", txid, Ok(tx.txid()));
+                    run.uses("RawTransaction::wtxid");
+                    run.expect("wtxid", wtxid, Ok(tx.wtxid()));
+                }
+                Err(e) => run.expect("deserialize", "ok", Err(e)),
+            }
+        }
+        TestVector::Merkle {
+            txids,
+            merkle_root,
+            branches,
+            ..
+        } => {
+            let leaves = match hash_list(txids) {
+                Ok(leaves) => leaves,
+                Err(e) => return run.expect("txids", "valid hashes", Err(e)),
+            };
+            run.uses("Serialization::merkle_root");
+            run.expect(
+                "merkle_root",
+                merkle_root,
+                Ok(Serialization::hash_to_hex(&Serialization::merkle_root(
+                    &leaves,
+                ))),
+            );
+            for branch in branches {
+                let check = format!("branch[{}]", branch.index);
+                let Some(leaf) = leaves.get(branch.index) else {
+                    run.expect(&check, "leaf in range", Err(anyhow!("no leaf")));
+                    continue;
+                };
+                run.uses("Serialization::merkle_branch");
+                let actual: Vec<String> = Serialization::merkle_branch(&leaves, branch.index)
+                    .iter()
+                    .map(Serialization::hash_to_hex)
+                    .collect();
+                run.expect(&check, &branch.branch.join(","), Ok(actual.join(",")));
+                run.uses("Serialization::merkle_root_from_branch");
+                let folded = hash_list(&branch.branch).map(|siblings| {
+                    Serialization::hash_to_hex(&Serialization::merkle_root_from_branch(
+                        leaf,
+                        &siblings,
+                        branch.index,
+                    ))
+                });
+                run.expect(&format!("{} root", check), merkle_root, folded);
+            }
+        }
+        TestVector::Sighash {
+            tx,
+            input_index,
+            script_code,
+            amount,
+            segwit,
+            sighash_type,
+            sighash,
+            signature,
+            ..
+        } => {
+            let actual = (|| {
+                let tx = Serialization::deserialize_transaction(tx)?;
+                let script_code = hex::decode(script_code)?;
+                let digest = if *segwit {
+                    let amount = amount.ok_or_else(|| anyhow!("segwit vector needs an amount"))?;
+                    tx.segwit_v0_sighash(*input_index, &script_code, amount, *sighash_type)?
+                } else {
+                    tx.legacy_sighash(*input_index, &script_code, *sighash_type)
+                };
+                Ok(hex::encode(digest))
+            })();
+            run.uses(if *segwit {
+                "RawTransaction::segwit_v0_sighash"
+            } else {
+                "RawTransaction::legacy_sighash"
+            });
+            if let (Some(signature), Ok(digest)) = (signature, &actual) {
+                let verified = verify_ecdsa(digest, signature)
+                    .map(|ok| if ok { "verifies" } else { "does not verify" }.to_string());
+                run.expect("signature", "verifies", verified);
+            }
+            run.expect("sighash", sighash, actual);
+        }
+    }
+}
+
+fn verify_ecdsa(digest_hex: &str, signature: &SignatureVector) -> Result<bool> {
+    let digest = Message::from_slice(&hex::decode(digest_hex)?)?;
+    let pubkey = PublicKey::from_slice(&hex::decode(&signature.pubkey)?)?;
+    let mut der = Signature::from_der(&hex::decode(&signature.der)?)?;
+    // Early mainnet signatures predate the low-S rule
+    der.normalize_s();
+    Ok(Secp256k1::verification_only()
+        .verify_ecdsa(&digest, &der, &pubkey)
+        .is_ok())
+}
+
+fn vector_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let file = entry?.path();
+        if file.extension().is_some_and(|e| e == "json") {
+            files.push(file);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// Run a vector file, or every .json file in a directory, against this crate's
+// implementations. Malformed files are an error; failing vectors are reported.
+pub fn run_vectors<P: AsRef<Path>>(path: P) -> Result<VectorReport> {
+    let mut report = VectorReport::default();
+    for file in vector_files(path.as_ref())? {
+        let contents = fs::read_to_string(&file)?;
+        let parsed: VectorFile =
+            serde_json::from_str(&contents).map_err(|e| anyhow!("{}: {}", file.display(), e))?;
+        for (index, vector) in parsed.vectors.iter().enumerate() {
+            let mut run = VectorRun {
+                report: &mut report,
+                file: &file,
+                index,
+                name: vector.name(),
+                failed: false,
+            };
+            run_vector(&mut run, vector);
+            let failed = run.failed;
+            report.total += 1;
+            if !failed {
+                report.passed += 1;
+            }
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,52 @@
+use anyhow::Result;
+use serde_json::json;
+use std::time::Duration;
+
+use crate::BitcoinClient;
+use crate::types::WaitForBlockResult;
+
+// Extra time for the node to answer once its own wait has run out
+const TIMEOUT_MARGIN: Duration = Duration::from_secs(5);
+// Stand-in for no client timeout when the node is told to wait indefinitely
+const WAIT_INDEFINITELY: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+impl BitcoinClient {
+    // Handle whose HTTP timeout outlasts a server-side wait of `timeout_ms`, 0 meaning
+    // no limit as for the node
+    pub(crate) fn for_wait(&self, timeout_ms: u64) -> BitcoinClient {
+        self.with_timeout(match timeout_ms {
+            0 => WAIT_INDEFINITELY,
+            ms => Duration::from_millis(ms) + TIMEOUT_MARGIN,
+        })
+    }
+
+    // Wait for the tip to change, for at most `timeout_ms` (0 waits indefinitely). On
+    // timeout the current tip is returned.
+    pub async fn wait_for_new_block(&self, timeout_ms: u64) -> Result<WaitForBlockResult> {
+        self.for_wait(timeout_ms)
+            .call("waitfornewblock", json!([timeout_ms]))
+            .await
+    }
+
+    // Wait until the best chain reaches `height`; the tip at that moment or on timeout
+    pub async fn wait_for_block_height(
+        &self,
+        height: u64,
+        timeout_ms: u64,
+    ) -> Result<WaitForBlockResult> {
+        self.for_wait(timeout_ms)
+            .call("waitforblockheight", json!([height, timeout_ms]))
+            .await
+    }
+
+    // Wait until `block_hash` is the tip; the tip at that moment or on timeout
+    pub async fn wait_for_block(
+        &self,
+        block_hash: &str,
+        timeout_ms: u64,
+    ) -> Result<WaitForBlockResult> {
+        self.for_wait(timeout_ms)
+            .call("waitforblock", json!([block_hash, timeout_ms]))
+            .await
+    }
+}
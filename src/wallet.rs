@@ -0,0 +1,307 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::BitcoinClient;
+use crate::cancel::RESCAN_TIMEOUT;
+use crate::node_version::NodeVersion;
+use crate::types::{
+    CreateWalletOptions, CreateWalletResult, DescriptorImport, DisplayAddressResult, ImportResult,
+    ListDescriptorsResult, LoadWalletResult, MigrateWalletResult, Signer, UpgradeWalletResult,
+    WalletDirEntry,
+};
+
+// RPCs served per wallet under /wallet/<name>; everything else goes to the root path
+const WALLET_METHODS: &[&str] = &[
+    "abandontransaction",
+    "abortrescan",
+    "addmultisigaddress",
+    "backupwallet",
+    "bumpfee",
+    "createwalletdescriptor",
+    "dumpprivkey",
+    "dumpwallet",
+    "encryptwallet",
+    "fundrawtransaction",
+    "getaddressesbylabel",
+    "getaddressinfo",
+    "getbalance",
+    "getbalances",
+    "gethdkeys",
+    "getnewaddress",
+    "getrawchangeaddress",
+    "getreceivedbyaddress",
+    "getreceivedbylabel",
+    "gettransaction",
+    "getunconfirmedbalance",
+    "getwalletinfo",
+    "importaddress",
+    "importdescriptors",
+    "importmulti",
+    "importprivkey",
+    "importprunedfunds",
+    "importpubkey",
+    "importwallet",
+    "keypoolrefill",
+    "listaddressgroupings",
+    "listdescriptors",
+    "listlabels",
+    "listlockunspent",
+    "listreceivedbyaddress",
+    "listreceivedbylabel",
+    "listsinceblock",
+    "listtransactions",
+    "listunspent",
+    "lockunspent",
+    "migratewallet",
+    "newkeypool",
+    "psbtbumpfee",
+    "removeprunedfunds",
+    "rescanblockchain",
+    "send",
+    "sendall",
+    "sendmany",
+    "sendtoaddress",
+    "sethdseed",
+    "setlabel",
+    "settxfee",
+    "setwalletflag",
+    "signmessage",
+    "signrawtransactionwithwallet",
+    "simulaterawtransaction",
+    "unloadwallet",
+    "upgradewallet",
+    "walletcreatefundedpsbt",
+    "walletdisplayaddress",
+    "walletlock",
+    "walletpassphrase",
+    "walletpassphrasechange",
+    "walletprocesspsbt",
+];
+
+pub fn is_wallet_method(method: &str) -> bool {
+    WALLET_METHODS.binary_search(&method).is_ok()
+}
+
+// Percent-encode a wallet name for use as a single path segment
+fn encode_wallet_name(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+impl BitcoinClient {
+    // Handle on one loaded wallet: wallet RPCs go to /wallet/<name>, chain RPCs to the
+    // root path. Every existing method can be called on it unchanged.
+    pub fn wallet(&self, name: &str) -> BitcoinClient {
+        let mut client = self.clone();
+        client.wallet_name = Some(name.to_string());
+        client
+    }
+
+    pub fn wallet_name(&self) -> Option<&str> {
+        self.wallet_name.as_deref()
+    }
+
+    // Endpoint for a request made of `methods`
+    pub(crate) fn endpoint<'a>(&self, mut methods: impl Iterator<Item = &'a str>) -> String {
+        match &self.wallet_name {
+            Some(name) if methods.any(is_wallet_method) => format!(
+                "{}/wallet/{}",
+                self.url.trim_end_matches('/'),
+                encode_wallet_name(name)
+            ),
+            _ => self.url.clone(),
+        }
+    }
+
+    // Create and load a wallet; use `wallet(name)` to call it afterwards
+    pub async fn create_wallet(
+        &self,
+        name: &str,
+        options: CreateWalletOptions,
+    ) -> Result<CreateWalletResult> {
+        let mut params = vec![
+            json!(name),
+            json!(options.disable_private_keys),
+            json!(options.blank),
+            json!(options.passphrase.as_deref().unwrap_or("")),
+            json!(options.avoid_reuse),
+            json!(options.descriptors),
+            json!(options.load_on_startup),
+        ];
+        // Only sent when set, since nodes before 22.0 reject the extra argument
+        if options.external_signer {
+            self.require_version(NodeVersion::EXTERNAL_SIGNER, "createwallet external_signer")
+                .await?;
+            params.push(json!(true));
+        }
+        self.call("createwallet", Value::Array(params)).await
+    }
+
+    // Load a wallet from the node's wallet directory
+    pub async fn load_wallet(&self, name: &str) -> Result<LoadWalletResult> {
+        self.call("loadwallet", json!([name])).await
+    }
+
+    pub async fn unload_wallet(&self, name: &str) -> Result<()> {
+        // Sent to the wallet's own path, which Core accepts together with a matching name
+        self.wallet(name)
+            .call::<Value>("unloadwallet", json!([name]))
+            .await?;
+        Ok(())
+    }
+
+    // Names of the loaded wallets
+    pub async fn list_wallets(&self) -> Result<Vec<String>> {
+        self.call("listwallets", Value::Null).await
+    }
+
+    // Every wallet in the node's wallet directory, loaded or not
+    pub async fn list_wallet_dir(&self) -> Result<Vec<WalletDirEntry>> {
+        #[derive(Deserialize)]
+        struct WalletDir {
+            wallets: Vec<WalletDirEntry>,
+        }
+        let dir: WalletDir = self.call("listwalletdir", Value::Null).await?;
+        Ok(dir.wallets)
+    }
+
+    // Import descriptors into a descriptor wallet, rescanning from the earliest
+    // timestamp. Check each result, since entries succeed or fail independently.
+    pub async fn import_descriptors(
+        &self,
+        requests: Vec<DescriptorImport>,
+    ) -> Result<Vec<ImportResult>> {
+        self.with_timeout(RESCAN_TIMEOUT)
+            .call("importdescriptors", json!([requests]))
+            .await
+    }
+
+    // The wallet's descriptors, with private keys when `private` is set and the wallet
+    // is unlocked
+    pub async fn list_descriptors(&self, private: bool) -> Result<ListDescriptorsResult> {
+        self.call("listdescriptors", json!([private])).await
+    }
+
+    // Top up the keypool to `newsize` keys, or the node's -keypool setting. Locked
+    // encrypted wallets fail with `BitcoinRpcError::WalletUnlockNeeded`.
+    pub async fn keypool_refill(&self, newsize: Option<u32>) -> Result<()> {
+        let params = match newsize {
+            Some(newsize) => json!([newsize]),
+            None => Value::Null,
+        };
+        self.call::<Value>("keypoolrefill", params).await?;
+        Ok(())
+    }
+
+    // Drop the keypool and generate a fresh one, e.g. after restoring an old backup
+    pub async fn new_keypool(&self) -> Result<()> {
+        self.call::<Value>("newkeypool", Value::Null).await?;
+        Ok(())
+    }
+
+    // Set a new HD seed for a legacy wallet. `seed_wif` takes a key from
+    // `BitcoinCrypto::private_key_to_wif`; without one the node generates a seed.
+    pub async fn set_hd_seed(
+        &self,
+        newkeypool: Option<bool>,
+        seed_wif: Option<&str>,
+    ) -> Result<()> {
+        let params = match (newkeypool, seed_wif) {
+            (newkeypool, Some(seed)) => json!([newkeypool.unwrap_or(true), seed]),
+            (Some(newkeypool), None) => json!([newkeypool]),
+            (None, None) => Value::Null,
+        };
+        self.call::<Value>("sethdseed", params).await?;
+        Ok(())
+    }
+
+    // Upgrade the wallet to `version`, or the latest the node supports
+    pub async fn upgrade_wallet(&self, version: Option<u32>) -> Result<UpgradeWalletResult> {
+        let params = match version {
+            Some(version) => json!([version]),
+            None => Value::Null,
+        };
+        self.call("upgradewallet", params).await
+    }
+
+    // Convert a legacy wallet to a descriptor wallet. `wallet_name` defaults to this
+    // handle's wallet. Needs Bitcoin Core 25.0 or later; older nodes get
+    // `BitcoinRpcError::MethodNotFound` without a request being sent.
+    pub async fn migrate_wallet(
+        &self,
+        wallet_name: Option<&str>,
+        passphrase: Option<&str>,
+    ) -> Result<MigrateWalletResult> {
+        self.require_version(NodeVersion::MIGRATE_WALLET, "migratewallet")
+            .await?;
+        let params = match (wallet_name, passphrase) {
+            (name, Some(passphrase)) => json!([name, passphrase]),
+            (Some(name), None) => json!([name]),
+            (None, None) => Value::Null,
+        };
+        // Migration rescans the chain for the new descriptors
+        self.with_timeout(RESCAN_TIMEOUT)
+            .call("migratewallet", params)
+            .await
+    }
+
+    // Handles on the wallets a migration left loaded: the migrated wallet and any
+    // watch-only and solvables siblings it split off
+    pub async fn migrated_wallets(
+        &self,
+        result: &MigrateWalletResult,
+    ) -> Result<Vec<BitcoinClient>> {
+        let loaded = self.list_wallets().await?;
+        Ok(result
+            .wallet_names()
+            .filter(|name| loaded.iter().any(|l| l == name))
+            .map(|name| self.wallet(name))
+            .collect())
+    }
+
+    // Devices reachable through the -signer command, e.g. HWI
+    pub async fn enumerate_signers(&self) -> Result<Vec<Signer>> {
+        #[derive(Deserialize)]
+        struct Signers {
+            signers: Vec<Signer>,
+        }
+        self.require_version(NodeVersion::EXTERNAL_SIGNER, "enumeratesigners")
+            .await?;
+        let signers: Signers = self.call("enumeratesigners", Value::Null).await?;
+        Ok(signers.signers)
+    }
+
+    // Show `address` on the external signer of this wallet so the user can check it
+    pub async fn wallet_display_address(&self, address: &str) -> Result<DisplayAddressResult> {
+        self.require_version(NodeVersion::EXTERNAL_SIGNER, "walletdisplayaddress")
+            .await?;
+        self.call("walletdisplayaddress", json!([address])).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `is_wallet_method` binary-searches the list
+    #[test]
+    fn wallet_methods_are_sorted_and_unique() {
+        assert!(WALLET_METHODS.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn routes_funding_to_the_wallet() {
+        assert!(is_wallet_method("fundrawtransaction"));
+        assert!(is_wallet_method("walletprocesspsbt"));
+        assert!(!is_wallet_method("getblockcount"));
+        assert!(!is_wallet_method("createwallet"));
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::BitcoinClient;
+use crate::timing::TimeHint;
+use crate::types::{
+    CreateWalletOptions, DescriptorImport, DescriptorInfo, ImportCommon, ImportTimestamp,
+};
+
+pub const WATCH_BUNDLE_VERSION: u32 = 1;
+
+// Slack for blocks whose timestamps run behind their median time
+const BIRTH_TIME_WINDOW_SECS: u64 = 7200;
+
+// Everything needed to rebuild watch-only access to a descriptor wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchBundle {
+    pub version: u32,
+    pub wallet_name: String,
+    // Earliest descriptor timestamp and the height to rescan from
+    pub birth_time: u64,
+    pub birth_height: u64,
+    pub descriptors: Vec<BundleDescriptor>,
+    // Address to label
+    pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleDescriptor {
+    pub desc: String,
+    pub timestamp: u64,
+    pub active: bool,
+    pub internal: Option<bool>,
+    pub range: Option<[u64; 2]>,
+    #[serde(alias = "next")]
+    pub next_index: Option<u64>,
+}
+
+impl From<DescriptorInfo> for BundleDescriptor {
+    fn from(info: DescriptorInfo) -> Self {
+        BundleDescriptor {
+            desc: info.desc,
+            timestamp: info.timestamp,
+            active: info.active,
+            internal: info.internal,
+            range: info.range,
+            next_index: info.next_index.or(info.next),
+        }
+    }
+}
+
+impl WatchBundle {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let bundle: WatchBundle = serde_json::from_str(json)?;
+        if bundle.version != WATCH_BUNDLE_VERSION {
+            return Err(anyhow!(
+                "Unsupported watch bundle version {}",
+                bundle.version
+            ));
+        }
+        Ok(bundle)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+impl BitcoinClient {
+    pub async fn export_watch_bundle(&self, wallet_name: &str) -> Result<WatchBundle> {
+        let wallet = self.wallet(wallet_name);
+        let mut descriptors: Vec<BundleDescriptor> = wallet
+            .list_descriptors(false)
+            .await?
+            .descriptors
+            .into_iter()
+            .map(BundleDescriptor::from)
+            .collect();
+        descriptors.sort_by(|a, b| a.desc.cmp(&b.desc));
+        let birth_time = descriptors
+            .iter()
+            .map(|d| d.timestamp)
+            .min()
+            .ok_or_else(|| anyhow!("Wallet {} has no descriptors", wallet_name))?;
+        let birth_height = match self
+            .find_block_at_time(
+                birth_time.saturating_sub(BIRTH_TIME_WINDOW_SECS),
+                TimeHint::LastBefore,
+            )
+            .await
+        {
+            Ok(header) => header.height,
+            Err(_) => 0,
+        };
+
+        let mut labels = BTreeMap::new();
+        let names = wallet.list_labels(None).await?;
+        for name in names {
+            let addresses = wallet.get_addresses_by_label(&name).await?;
+            for address in addresses.into_keys() {
+                labels.insert(address, name.clone());
+            }
+        }
+
+        Ok(WatchBundle {
+            version: WATCH_BUNDLE_VERSION,
+            wallet_name: wallet_name.to_string(),
+            birth_time,
+            birth_height,
+            descriptors,
+            labels,
+        })
+    }
+
+    // Create a blank watch-only descriptor wallet from the bundle and rescan from its
+    // birth height. If the imports fail the partial wallet is unloaded again; its files
+    // stay in the wallet directory. The rescan gets the long rescan timeout and is
+    // aborted if this future is dropped.
+    pub async fn restore_watch_bundle(
+        &self,
+        bundle: &WatchBundle,
+        new_wallet_name: &str,
+    ) -> Result<()> {
+        // A rescan below the prune height would miss history, so refuse before creating anything
+        self.prune_status().await?.check(bundle.birth_height)?;
+        self.create_wallet(
+            new_wallet_name,
+            CreateWalletOptions::default()
+                .disable_private_keys(true)
+                .blank(true)
+                .descriptors(true),
+        )
+        .await?;
+        let wallet = self.wallet(new_wallet_name);
+
+        if let Err(e) = import_bundle(&wallet, bundle).await {
+            let cleanup = match self.unload_wallet(new_wallet_name).await {
+                Ok(()) => "the partial wallet was unloaded".to_string(),
+                Err(unload) => format!("unloading the partial wallet failed too: {}", unload),
+            };
+            return Err(e.context(format!(
+                "Restoring {} into {} failed; {}",
+                bundle.wallet_name, new_wallet_name, cleanup
+            )));
+        }
+        wallet
+            .rescan_blockchain(bundle.birth_height, None)
+            .await
+            .map_err(|e| {
+                e.context(format!(
+                    "{} was restored but the rescan from height {} failed; run it again",
+                    new_wallet_name, bundle.birth_height
+                ))
+            })?;
+        Ok(())
+    }
+}
+
+// Import the bundle's descriptors and labels into a freshly created wallet
+async fn import_bundle(wallet: &BitcoinClient, bundle: &WatchBundle) -> Result<()> {
+    let requests: Vec<DescriptorImport> = bundle
+        .descriptors
+        .iter()
+        .map(|d| DescriptorImport {
+            desc: d.desc.clone(),
+            active: Some(d.active),
+            next_index: d.next_index,
+            common: ImportCommon {
+                range: d.range,
+                internal: d.internal,
+                ..ImportCommon::new(ImportTimestamp::Now)
+            },
+        })
+        .collect();
+    let results = wallet.import_descriptors(requests).await?;
+    for (descriptor, result) in bundle.descriptors.iter().zip(&results) {
+        if !result.success {
+            return Err(anyhow!(
+                "Importing {} failed: {}",
+                descriptor.desc,
+                result
+                    .error
+                    .as_ref()
+                    .map_or("unknown error".to_string(), |e| e.message.clone())
+            ));
+        }
+    }
+
+    for (address, label) in &bundle.labels {
+        wallet.set_label(address, label).await?;
+    }
+    Ok(())
+}
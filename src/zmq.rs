@@ -0,0 +1,225 @@
+use anyhow::{Result, anyhow};
+use futures_util::stream::Stream;
+use std::collections::{BTreeMap, HashMap};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use zeromq::{Socket, SocketRecv, SubSocket, ZmqMessage};
+
+use crate::mempool_mirror::SequenceEvent;
+use crate::serialization::Serialization;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ZmqTopic {
+    HashBlock,
+    HashTx,
+    RawBlock,
+    RawTx,
+    Sequence,
+}
+
+impl ZmqTopic {
+    // Topic name as in bitcoind's -zmqpub<topic> options
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ZmqTopic::HashBlock => "hashblock",
+            ZmqTopic::HashTx => "hashtx",
+            ZmqTopic::RawBlock => "rawblock",
+            ZmqTopic::RawTx => "rawtx",
+            ZmqTopic::Sequence => "sequence",
+        }
+    }
+
+    fn from_name(name: &[u8]) -> Option<Self> {
+        [
+            ZmqTopic::HashBlock,
+            ZmqTopic::HashTx,
+            ZmqTopic::RawBlock,
+            ZmqTopic::RawTx,
+            ZmqTopic::Sequence,
+        ]
+        .into_iter()
+        .find(|topic| topic.as_str().as_bytes() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZmqEvent {
+    // Hashes in display order
+    HashBlock(String),
+    HashTx(String),
+    RawBlock(Vec<u8>),
+    RawTx(Vec<u8>),
+    Sequence(SequenceEvent),
+}
+
+impl ZmqEvent {
+    pub fn topic(&self) -> ZmqTopic {
+        match self {
+            ZmqEvent::HashBlock(_) => ZmqTopic::HashBlock,
+            ZmqEvent::HashTx(_) => ZmqTopic::HashTx,
+            ZmqEvent::RawBlock(_) => ZmqTopic::RawBlock,
+            ZmqEvent::RawTx(_) => ZmqTopic::RawTx,
+            ZmqEvent::Sequence(_) => ZmqTopic::Sequence,
+        }
+    }
+
+    // Txid of a `RawTx` payload, None for other events
+    pub fn txid(&self) -> Option<Result<String>> {
+        match self {
+            ZmqEvent::RawTx(raw) => Some(Serialization::calculate_txid(&hex::encode(raw))),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZmqNotification {
+    pub event: ZmqEvent,
+    pub endpoint: String,
+    // bitcoind's per-topic message counter
+    pub sequence: u32,
+    // Messages skipped on this topic since the previous one; 0 after a reconnect,
+    // where the gap cannot be known
+    pub missed: u32,
+}
+
+fn parse_message(message: &ZmqMessage) -> Result<(ZmqEvent, u32)> {
+    let frame = |index: usize| {
+        message
+            .get(index)
+            .ok_or_else(|| anyhow!("ZMQ message has {} frames, expected 3", message.len()))
+    };
+    let name = frame(0)?;
+    let topic = ZmqTopic::from_name(name)
+        .ok_or_else(|| anyhow!("Unknown ZMQ topic {}", String::from_utf8_lossy(name)))?;
+    let body = frame(1)?;
+    let sequence_bytes: [u8; 4] = frame(2)?[..]
+        .try_into()
+        .map_err(|_| anyhow!("ZMQ sequence frame is not 4 bytes"))?;
+    let sequence = u32::from_le_bytes(sequence_bytes);
+    let event = match topic {
+        ZmqTopic::HashBlock => ZmqEvent::HashBlock(hex::encode(body)),
+        ZmqTopic::HashTx => ZmqEvent::HashTx(hex::encode(body)),
+        ZmqTopic::RawBlock => ZmqEvent::RawBlock(body.to_vec()),
+        ZmqTopic::RawTx => ZmqEvent::RawTx(body.to_vec()),
+        ZmqTopic::Sequence => ZmqEvent::Sequence(SequenceEvent::parse(body, Some(sequence))?),
+    };
+    Ok((event, sequence))
+}
+
+async fn subscribe(endpoint: &str, topics: &[ZmqTopic]) -> Result<SubSocket> {
+    let mut socket = SubSocket::new();
+    socket.connect(endpoint).await?;
+    for topic in topics {
+        socket.subscribe(topic.as_str()).await?;
+    }
+    Ok(socket)
+}
+
+// Receive from one endpoint until the listener is dropped, reconnecting on errors
+async fn run_endpoint(
+    endpoint: String,
+    topics: Vec<ZmqTopic>,
+    sender: mpsc::Sender<Result<ZmqNotification>>,
+) {
+    let mut delay = RECONNECT_DELAY;
+    loop {
+        let mut socket = match subscribe(&endpoint, &topics).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                let error = anyhow!("ZMQ connect to {} failed: {}", endpoint, e);
+                if sender.send(Err(error)).await.is_err() {
+                    return;
+                }
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                continue;
+            }
+        };
+        let mut last_sequence: HashMap<ZmqTopic, u32> = HashMap::new();
+        loop {
+            let message = match socket.recv().await {
+                Ok(message) => message,
+                Err(e) => {
+                    let error = anyhow!("ZMQ receive from {} failed: {}", endpoint, e);
+                    if sender.send(Err(error)).await.is_err() {
+                        return;
+                    }
+                    break;
+                }
+            };
+            delay = RECONNECT_DELAY;
+            let notification = parse_message(&message).map(|(event, sequence)| {
+                let missed = last_sequence
+                    .insert(event.topic(), sequence)
+                    .map_or(0, |last| sequence.wrapping_sub(last).wrapping_sub(1));
+                ZmqNotification {
+                    event,
+                    endpoint: endpoint.clone(),
+                    sequence,
+                    missed,
+                }
+            });
+            if sender.send(notification).await.is_err() {
+                return;
+            }
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+// Stream of notifications from one or more bitcoind ZMQ publishers. Connection errors
+// are yielded as items and the socket is reopened, so the stream only ends when dropped.
+pub struct BitcoinZmqListener {
+    receiver: mpsc::Receiver<Result<ZmqNotification>>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl BitcoinZmqListener {
+    // Topics sharing an endpoint share one socket, e.g.
+    // `[(ZmqTopic::RawTx, "tcp://127.0.0.1:28332"), (ZmqTopic::HashBlock, ...)]`
+    pub async fn connect(endpoints: &[(ZmqTopic, &str)]) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(anyhow!("No ZMQ endpoints given"));
+        }
+        let mut by_endpoint: BTreeMap<&str, Vec<ZmqTopic>> = BTreeMap::new();
+        for (topic, endpoint) in endpoints {
+            by_endpoint.entry(endpoint).or_default().push(*topic);
+        }
+        let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+        let tasks = by_endpoint
+            .into_iter()
+            .map(|(endpoint, topics)| {
+                tokio::spawn(run_endpoint(endpoint.to_string(), topics, sender.clone()))
+            })
+            .collect();
+        Ok(BitcoinZmqListener { receiver, tasks })
+    }
+
+    pub async fn recv(&mut self) -> Option<Result<ZmqNotification>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for BitcoinZmqListener {
+    type Item = Result<ZmqNotification>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl Drop for BitcoinZmqListener {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
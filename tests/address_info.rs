@@ -0,0 +1,58 @@
+mod common;
+
+use bitcoin_sdk::AddressDetails;
+use common::fixture;
+
+#[test]
+fn p2pkh_from_a_legacy_wallet() {
+    let info: AddressDetails = fixture("getaddressinfo/p2pkh-legacy-wallet");
+    assert!(info.ismine && info.solvable && !info.iswitness && !info.isscript);
+    assert_eq!(info.iscompressed, Some(true));
+    assert!(info.pubkey.is_some());
+    assert_eq!(info.parent_desc, None);
+    assert_eq!(info.hdkeypath.as_deref(), Some("m/0'/0'/3'"));
+    assert_eq!(info.labels, ["rent"]);
+}
+
+#[test]
+fn p2sh_multisig_watch_only() {
+    let info: AddressDetails = fixture("getaddressinfo/p2sh-multisig-watchonly");
+    assert!(!info.ismine && info.iswatchonly && info.isscript);
+    assert_eq!(info.script.as_deref(), Some("multisig"));
+    assert_eq!(info.sigsrequired, Some(2));
+    assert_eq!(info.pubkeys.as_ref().map(Vec::len), Some(2));
+    assert_eq!(info.pubkey, None);
+    assert_eq!(info.timestamp, None);
+    assert_eq!(info.hdkeypath, None);
+}
+
+#[test]
+fn p2wpkh_from_a_descriptor_wallet() {
+    let info: AddressDetails = fixture("getaddressinfo/p2wpkh-descriptor-wallet");
+    assert!(info.iswitness && !info.isscript);
+    assert_eq!(info.witness_version, Some(0));
+    assert_eq!(info.witness_program.as_ref().map(String::len), Some(40));
+    assert!(info.parent_desc.as_deref().unwrap().starts_with("wpkh("));
+    assert_eq!(info.hdmasterfingerprint.as_deref(), Some("d34db33f"));
+}
+
+#[test]
+fn p2tr_has_no_single_pubkey() {
+    let info: AddressDetails = fixture("getaddressinfo/p2tr-descriptor-wallet");
+    assert_eq!(info.witness_version, Some(1));
+    assert_eq!(info.witness_program.as_ref().map(String::len), Some(64));
+    assert_eq!(info.pubkey, None);
+    assert_eq!(info.iscompressed, None);
+    assert!(info.desc.as_deref().unwrap().starts_with("tr("));
+}
+
+#[test]
+fn p2sh_p2wpkh_change_carries_the_embedded_script() {
+    let info: AddressDetails = fixture("getaddressinfo/p2sh-p2wpkh-change");
+    assert!(info.ischange && info.isscript && !info.iswitness);
+    assert!(info.labels.is_empty());
+    let embedded = info.embedded.unwrap();
+    assert!(embedded.iswitness);
+    assert_eq!(embedded.witness_version, Some(0));
+    assert_eq!(embedded.script_pub_key, info.hex.unwrap());
+}
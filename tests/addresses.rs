@@ -0,0 +1,153 @@
+mod common;
+
+use bitcoin_sdk::{
+    AddressGenerationError, BitcoinClientType, BitcoinCrypto, GeneratedAddress, NewAddressType,
+};
+use common::{MockNode, Reply, method_not_found};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+fn bech32(n: u8) -> String {
+    BitcoinCrypto::hash160_to_bech32_address(&[n; 20], BitcoinClientType::Regtest).unwrap()
+}
+
+// A wallet handing out bech32 addresses in order, failing from the `fail_from`th call on
+async fn wallet(fail_from: u8) -> MockNode {
+    let issued = Arc::new(AtomicU8::new(0));
+    MockNode::start(move |method, _params| -> Reply {
+        match method {
+            "getnewaddress" => {
+                let n = issued.fetch_add(1, Ordering::SeqCst) + 1;
+                if n >= fail_from {
+                    return Err((
+                        -12,
+                        "Error: Keypool ran out, please call keypoolrefill first".to_string(),
+                    ));
+                }
+                Ok(json!(bech32(n)))
+            }
+            _ => method_not_found(),
+        }
+    })
+    .await
+}
+
+fn batch_sizes(node: &MockNode) -> Vec<usize> {
+    node.bodies()
+        .iter()
+        .map(|body| match serde_json::from_str(body).unwrap() {
+            Value::Array(requests) => requests.len(),
+            _ => 1,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn addresses_come_in_chunks_with_numbered_labels() {
+    let node = wallet(u8::MAX).await;
+    let created = node
+        .client()
+        .generate_addresses_chunked(5, NewAddressType::Bech32, Some("pay"), 2)
+        .await
+        .unwrap();
+    assert_eq!(batch_sizes(&node), [2, 2, 1]);
+    let expected: Vec<GeneratedAddress> = (1..=5)
+        .map(|n| GeneratedAddress {
+            address: bech32(n),
+            label: format!("pay-{:04}", n),
+            index: n as u32,
+        })
+        .collect();
+    assert_eq!(created, expected);
+    assert_eq!(
+        node.calls_to("getnewaddress")[4],
+        json!(["pay-0005", "bech32"])
+    );
+}
+
+#[tokio::test]
+async fn labels_are_empty_without_a_prefix() {
+    let node = wallet(u8::MAX).await;
+    let created = node
+        .client()
+        .generate_addresses(3, NewAddressType::Bech32, None)
+        .await
+        .unwrap();
+    assert!(created.iter().all(|a| a.label.is_empty()));
+    assert_eq!(batch_sizes(&node), [3]);
+}
+
+#[tokio::test]
+async fn a_zero_chunk_size_is_refused() {
+    let node = wallet(u8::MAX).await;
+    let error = node
+        .client()
+        .generate_addresses_chunked(5, NewAddressType::Bech32, None, 0)
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Chunk size must be positive");
+    assert!(node.bodies().is_empty());
+}
+
+#[tokio::test]
+async fn huge_chunk_sizes_do_not_overflow() {
+    let node = wallet(u8::MAX).await;
+    let created = node
+        .client()
+        .generate_addresses_chunked(3, NewAddressType::Bech32, None, u32::MAX)
+        .await
+        .unwrap();
+    assert_eq!(created.len(), 3);
+    assert_eq!(batch_sizes(&node), [3]);
+
+    let none = node
+        .client()
+        .generate_addresses_chunked(0, NewAddressType::Bech32, None, u32::MAX)
+        .await
+        .unwrap();
+    assert!(none.is_empty());
+}
+
+#[tokio::test]
+async fn a_failure_reports_the_addresses_already_created() {
+    let node = wallet(4).await;
+    let error = node
+        .client()
+        .generate_addresses_chunked(6, NewAddressType::Bech32, Some("pay"), 2)
+        .await
+        .unwrap_err();
+    let error = error.downcast_ref::<AddressGenerationError>().unwrap();
+    let created: Vec<(u32, &str)> = error
+        .created
+        .iter()
+        .map(|a| (a.index, a.address.as_str()))
+        .collect();
+    assert_eq!(
+        created,
+        [(1, bech32(1).as_str()), (2, &bech32(2)), (3, &bech32(3))]
+    );
+    assert!(
+        error.message.contains("Keypool ran out"),
+        "{}",
+        error.message
+    );
+    // The third chunk is never sent
+    assert_eq!(batch_sizes(&node), [2, 2]);
+}
+
+#[tokio::test]
+async fn addresses_of_another_type_are_reported() {
+    let node = wallet(u8::MAX).await;
+    let error = node
+        .client()
+        .generate_addresses_chunked(2, NewAddressType::Legacy, None, 2)
+        .await
+        .unwrap_err();
+    let error = error.downcast_ref::<AddressGenerationError>().unwrap();
+    assert_eq!(
+        error.message,
+        format!("Address {} is not of type legacy", bech32(1))
+    );
+    assert_eq!(error.created.len(), 2);
+}
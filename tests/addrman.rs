@@ -0,0 +1,87 @@
+mod common;
+
+use bitcoin_sdk::{AddPeerAddressResult, AddrManNetworkCounts, BitcoinRpcError};
+use common::{MockNode, fixture, fixture_value, method_not_found, network_info};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[test]
+fn addrman_info_sums_to_all_networks() {
+    for name in ["v26.0-regtest", "v27.0-mainnet"] {
+        let info: HashMap<String, AddrManNetworkCounts> =
+            fixture(&format!("getaddrmaninfo/{}", name));
+        let all = info["all_networks"];
+        let networks = info
+            .iter()
+            .filter(|(network, _)| *network != "all_networks");
+        let total: u64 = networks.map(|(_, counts)| counts.total).sum();
+        assert_eq!(total, all.total);
+        assert_eq!(all.new + all.tried, all.total);
+    }
+}
+
+#[test]
+fn add_peer_address_error_is_newer_than_success() {
+    let added: AddPeerAddressResult = fixture("addpeeraddress/v25.0-added");
+    assert!(added.success);
+    assert_eq!(added.error, None);
+    let rejected: AddPeerAddressResult = fixture("addpeeraddress/v27.0-rejected");
+    assert!(!rejected.success);
+    assert_eq!(rejected.error.as_deref(), Some("failed-adding-to-new"));
+}
+
+async fn node(version: u32) -> MockNode {
+    MockNode::start(move |method, _| match method {
+        "getnetworkinfo" => network_info(version),
+        "getaddrmaninfo" => Ok(fixture_value("getaddrmaninfo/v26.0-regtest")),
+        "addpeeraddress" => Ok(fixture_value("addpeeraddress/v25.0-added")),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn is_method_not_found(error: anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::MethodNotFound(_))
+    )
+}
+
+#[tokio::test]
+async fn gated_by_version() {
+    let node_21 = node(210000).await;
+    let client = node_21.client();
+    assert!(
+        client
+            .add_peer_address("203.0.113.7", 8333, false)
+            .await
+            .unwrap()
+            .success
+    );
+    assert!(is_method_not_found(
+        client
+            .add_peer_address("203.0.113.7", 8333, true)
+            .await
+            .unwrap_err()
+    ));
+    assert!(is_method_not_found(
+        client.get_addr_man_info().await.unwrap_err()
+    ));
+    assert_eq!(
+        node_21.calls_to("addpeeraddress"),
+        vec![json!(["203.0.113.7", 8333])]
+    );
+    assert!(node_21.calls_to("getaddrmaninfo").is_empty());
+
+    let node_26 = node(260000).await;
+    let client = node_26.client();
+    client
+        .add_peer_address("203.0.113.7", 8333, true)
+        .await
+        .unwrap();
+    assert_eq!(client.get_addr_man_info().await.unwrap()["ipv4"].tried, 1);
+    assert_eq!(
+        node_26.calls_to("addpeeraddress"),
+        vec![json!(["203.0.113.7", 8333, true])]
+    );
+}
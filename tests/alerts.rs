@@ -0,0 +1,165 @@
+mod common;
+
+use bitcoin_sdk::{Alert, AlertKind, AlertSource};
+use common::{MockNode, fixture_value, method_not_found};
+use serde_json::{Value, json};
+
+const PRE_RELEASE: &str = "This is a pre-release test build - use at your own risk - do not use for mining or merchant applications";
+const UNKNOWN_RULES: &str = "Unknown new rules activated (versionbit 28)";
+const NOT_AGREEING: &str = "Warning: We do not appear to fully agree with our peers! You may need to upgrade, or other nodes may need to upgrade.";
+
+#[test]
+fn core_warnings_are_classified() {
+    let cases = [
+        (PRE_RELEASE, AlertKind::PreReleaseBuild),
+        (UNKNOWN_RULES, AlertKind::UnknownSoftforkSignalling),
+        // v0.16 to v24
+        (
+            "Warning: unknown new rules activated (versionbit 28)",
+            AlertKind::UnknownSoftforkSignalling,
+        ),
+        (
+            "37 of last 100 blocks have unexpected version",
+            AlertKind::UnknownSoftforkSignalling,
+        ),
+        (
+            "Warning: Unknown block versions being mined! It's possible unknown rules are in effect",
+            AlertKind::UnknownSoftforkSignalling,
+        ),
+        (NOT_AGREEING, AlertKind::LargeWorkInvalidChain),
+        (
+            "Warning: The network does not appear to fully agree! Some miners appear to be experiencing issues.",
+            AlertKind::LargeWorkInvalidChain,
+        ),
+        (
+            "Warning: Large-work fork detected, forking after block 0000000000000000000264a3f6aafbba22a7d4cd68ef4e2a6e8a97d3ad2f1b42",
+            AlertKind::LargeWorkInvalidChain,
+        ),
+        (
+            "Warning: Found invalid chain at least ~6 blocks longer than our best chain.\nChain state database corruption likely.",
+            AlertKind::LargeWorkInvalidChain,
+        ),
+        (
+            "-maxtxfee is set very high! Fees this large could be paid on a single transaction.",
+            AlertKind::Other,
+        ),
+        (
+            "Disk space for \"/data/blocks\" may not accommodate the block files. Approximately 600 GB of data will be stored in this directory.",
+            AlertKind::Other,
+        ),
+    ];
+    for (message, kind) in cases {
+        assert_eq!(AlertKind::classify(message), kind, "{}", message);
+    }
+}
+
+// A node reporting the given `warnings` from each of the three RPCs
+async fn node(blockchain: Value, network: Value, mining: Value) -> MockNode {
+    MockNode::start(move |method, _| match method {
+        "getblockchaininfo" => {
+            let mut info = fixture_value("getblockchaininfo/v28.0-mainnet-pruned");
+            info["warnings"] = blockchain.clone();
+            Ok(info)
+        }
+        "getnetworkinfo" => {
+            let mut info = fixture_value("getnetworkinfo/v28.0");
+            info["warnings"] = network.clone();
+            Ok(info)
+        }
+        "getmininginfo" => Ok(json!({
+            "blocks": 865432,
+            "difficulty": 92049594548485.47,
+            "networkhashps": 6.5e20,
+            "pooledtx": 3000,
+            "chain": "main",
+            "warnings": mining.clone(),
+        })),
+        "getmempoolinfo" => Ok(json!({
+            "loaded": true,
+            "size": 3000,
+            "bytes": 1500000,
+            "usage": 8000000,
+            "maxmempool": 300000000,
+            "mempoolminfee": 0.00001,
+            "minrelaytxfee": 0.00001,
+        })),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn alert(kind: AlertKind, message: &str, sources: &[AlertSource]) -> Alert {
+    Alert {
+        kind,
+        message: message.to_string(),
+        sources: sources.to_vec(),
+    }
+}
+
+#[tokio::test]
+async fn warnings_are_merged_across_rpcs_in_first_seen_order() {
+    // v28 reports arrays; older releases, or -deprecatedrpc=warnings, one string
+    let node = node(
+        json!([UNKNOWN_RULES, PRE_RELEASE]),
+        json!(PRE_RELEASE),
+        json!([format!("  {}\n", NOT_AGREEING), UNKNOWN_RULES]),
+    )
+    .await;
+    let alerts = node.client().alerts().await.unwrap();
+    assert_eq!(
+        alerts,
+        [
+            alert(
+                AlertKind::UnknownSoftforkSignalling,
+                UNKNOWN_RULES,
+                &[AlertSource::BlockchainInfo, AlertSource::MiningInfo]
+            ),
+            alert(
+                AlertKind::PreReleaseBuild,
+                PRE_RELEASE,
+                &[AlertSource::BlockchainInfo, AlertSource::NetworkInfo]
+            ),
+            alert(
+                AlertKind::LargeWorkInvalidChain,
+                NOT_AGREEING,
+                &[AlertSource::MiningInfo]
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn repeats_within_one_rpc_list_it_once() {
+    let node = node(json!([PRE_RELEASE, PRE_RELEASE]), json!([]), json!("")).await;
+    let alerts = node.client().alerts().await.unwrap();
+    assert_eq!(
+        alerts,
+        [alert(
+            AlertKind::PreReleaseBuild,
+            PRE_RELEASE,
+            &[AlertSource::BlockchainInfo]
+        )]
+    );
+}
+
+#[tokio::test]
+async fn empty_warnings_are_no_alerts() {
+    let node = node(json!(""), json!(["", "  "]), json!([])).await;
+    assert!(node.client().alerts().await.unwrap().is_empty());
+    let health = node.client().health().await.unwrap();
+    assert!(health.alerts.is_empty());
+    assert!(!health.is_degraded());
+}
+
+#[tokio::test]
+async fn health_is_degraded_only_by_an_invalid_chain_warning() {
+    let warned = node(json!([PRE_RELEASE]), json!(UNKNOWN_RULES), json!("")).await;
+    let health = warned.client().health().await.unwrap();
+    assert_eq!(health.alerts.len(), 2);
+    assert!(!health.is_degraded());
+
+    let forked = node(json!([]), json!(""), json!(NOT_AGREEING)).await;
+    let health = forked.client().health().await.unwrap();
+    assert_eq!((health.blocks, health.connections), (865432, 11));
+    assert!(health.is_degraded());
+}
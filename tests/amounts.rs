@@ -0,0 +1,86 @@
+mod common;
+
+use bitcoin_sdk::{Amount, FeeRate, SendManyOptions, SendToAddressOptions};
+use common::{MockNode, method_not_found};
+use serde_json::json;
+use std::collections::HashMap;
+
+const ADDRESS: &str = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+
+#[tokio::test]
+async fn request_bodies_carry_exact_amounts() {
+    let node = MockNode::start(|method, _| match method {
+        "sendtoaddress" | "sendmany" => Ok(json!("00".repeat(32))),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    for sat in [10_000_000, 7_000_000, 1, 2_099_999_997_690_000] {
+        client
+            .send_to_address(ADDRESS, Amount::from_sat(sat))
+            .await
+            .unwrap();
+    }
+    let params: Vec<String> = node
+        .bodies()
+        .iter()
+        .map(|body| body[body.find("\"params\":").unwrap()..].to_string())
+        .collect();
+    assert_eq!(
+        params,
+        [
+            format!("\"params\":[\"{}\",0.10000000]}}", ADDRESS),
+            format!("\"params\":[\"{}\",0.07000000]}}", ADDRESS),
+            format!("\"params\":[\"{}\",0.00000001]}}", ADDRESS),
+            format!("\"params\":[\"{}\",20999999.97690000]}}", ADDRESS),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn create_raw_transaction_outputs_are_exact() {
+    let node = MockNode::start(|method, _| match method {
+        "createrawtransaction" => Ok(json!("0200")),
+        _ => method_not_found(),
+    })
+    .await;
+    let outputs = HashMap::from([(ADDRESS.to_string(), Amount::from_sat(7_000_000))]);
+    node.client()
+        .create_raw_transaction(Vec::new(), outputs)
+        .await
+        .unwrap();
+    assert!(
+        node.bodies()[0].ends_with(&format!("\"params\":[[],{{\"{}\":0.07000000}}]}}", ADDRESS))
+    );
+}
+
+#[tokio::test]
+async fn fee_rates_are_sent_with_three_decimals() {
+    let node = MockNode::start(|method, _| match method {
+        "sendtoaddress" | "sendmany" => Ok(json!("00".repeat(32))),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    // 0.1 + 0.2 is 0.30000000000000004 as an f64
+    let fee_rate = FeeRate::from_sat_per_vb(0.1 + 0.2).unwrap();
+    client
+        .send_to_address_with_options(
+            ADDRESS,
+            Amount::from_sat(7_000_000),
+            SendToAddressOptions::default().fee_rate(fee_rate),
+        )
+        .await
+        .unwrap();
+    let amounts = HashMap::from([(ADDRESS.to_string(), Amount::from_sat(7_000_000))]);
+    client
+        .send_many(amounts, SendManyOptions::default().fee_rate(fee_rate))
+        .await
+        .unwrap();
+    let bodies = node.bodies();
+    assert!(bodies[0].contains("\"fee_rate\":0.300"), "{}", bodies[0]);
+    assert!(bodies[1].ends_with(&format!(
+        "\"params\":[\"\",{{\"{}\":0.07000000}},null,null,null,null,null,null,0.300]}}",
+        ADDRESS
+    )));
+}
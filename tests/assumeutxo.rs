@@ -0,0 +1,96 @@
+mod common;
+
+use bitcoin_sdk::{BitcoinRpcError, BlockRef, SnapshotLoadError, SnapshotType};
+use common::{MockNode, method_not_found, network_info};
+use serde_json::{Value, json};
+
+fn dump_result() -> Value {
+    json!({
+        "coins_written": 3,
+        "base_hash": "00".repeat(32),
+        "base_height": 110,
+        "path": "/tmp/utxo.dat",
+        "txoutset_hash": "11".repeat(32),
+        "nchaintx": 111,
+    })
+}
+
+async fn node(version: u32) -> MockNode {
+    MockNode::start(move |method, _| match method {
+        "getnetworkinfo" => network_info(version),
+        "dumptxoutset" => Ok(dump_result()),
+        "loadtxoutset" => Err((
+            -32603,
+            "Unable to load UTXO snapshot: Can't activate a snapshot-based chainstate more than once"
+                .to_string(),
+        )),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn dump_sends_latest_from_28() {
+    let node = node(280000).await;
+    let client = node.client();
+    client.dump_tx_out_set("/tmp/utxo.dat", None).await.unwrap();
+    client
+        .dump_tx_out_set(
+            "/tmp/utxo.dat",
+            Some(SnapshotType::Rollback(Some(BlockRef::Height(100)))),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        node.calls_to("dumptxoutset"),
+        vec![
+            json!(["/tmp/utxo.dat", "latest"]),
+            json!(["/tmp/utxo.dat", "rollback", {"rollback": 100}]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn dump_sends_only_path_before_28() {
+    let node = node(270000).await;
+    let client = node.client();
+    client.dump_tx_out_set("/tmp/utxo.dat", None).await.unwrap();
+    let error = client
+        .dump_tx_out_set("/tmp/utxo.dat", Some(SnapshotType::Rollback(None)))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::MethodNotFound(_))
+    ));
+    assert_eq!(
+        node.calls_to("dumptxoutset"),
+        vec![json!(["/tmp/utxo.dat"])]
+    );
+}
+
+#[tokio::test]
+async fn load_is_version_gated_and_typed() {
+    let old = node(250000).await;
+    let error = old
+        .client()
+        .load_tx_out_set("/tmp/utxo.dat")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::MethodNotFound(_))
+    ));
+    assert!(old.calls_to("loadtxoutset").is_empty());
+
+    let current = node(270000).await;
+    let error = current
+        .client()
+        .load_tx_out_set("/tmp/utxo.dat")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SnapshotLoadError>(),
+        Some(SnapshotLoadError::AlreadyLoaded(_))
+    ));
+}
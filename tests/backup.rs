@@ -0,0 +1,167 @@
+mod common;
+
+use bitcoin_sdk::{BitcoinRpcError, KeyExportError};
+use common::{MockNode, method_not_found};
+use serde_json::json;
+use std::time::Duration;
+
+const ADDRESS: &str = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+
+// A node whose dumpprivkey and dumpwallet both fail with `code` and `message`
+async fn refusing(code: i32, message: &'static str) -> MockNode {
+    MockNode::start(move |method, _| match method {
+        "dumpprivkey" | "dumpwallet" => Err((code, message.to_string())),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn keys_are_exported_by_address_and_path() {
+    let node = MockNode::start(|method, _| match method {
+        "dumpprivkey" => Ok(json!(
+            "cVpF924EspNh8KjYsfhgY96mmxvT6DgdWiTYMtMjuM74hJaU5psW"
+        )),
+        "dumpwallet" => Ok(json!({"filename": "/home/node/wallet.dump"})),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    assert_eq!(
+        client.dump_priv_key(ADDRESS).await.unwrap(),
+        "cVpF924EspNh8KjYsfhgY96mmxvT6DgdWiTYMtMjuM74hJaU5psW"
+    );
+    let dump = client.dump_wallet("wallet.dump").await.unwrap();
+    assert_eq!(dump.filename, "/home/node/wallet.dump");
+    assert_eq!(node.calls_to("dumpprivkey"), [json!([ADDRESS])]);
+    assert_eq!(node.calls_to("dumpwallet"), [json!(["wallet.dump"])]);
+}
+
+#[tokio::test]
+async fn export_refusals_are_typed() {
+    let cases = [
+        (
+            -4,
+            "Private keys are disabled for this wallet",
+            KeyExportError::PrivateKeysDisabled as fn(String) -> KeyExportError,
+        ),
+        (
+            -4,
+            "Private key for address bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080 is not known",
+            KeyExportError::KeyNotKnown,
+        ),
+        (
+            -4,
+            "Only legacy wallets are supported by this command",
+            KeyExportError::LegacyOnly,
+        ),
+    ];
+    for (code, message, expected) in cases {
+        let node = refusing(code, message).await;
+        let client = node.client();
+        let error = client.dump_priv_key(ADDRESS).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<KeyExportError>(),
+            Some(&expected(message.to_string()))
+        );
+        let error = client.dump_wallet("wallet.dump").await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<KeyExportError>(),
+            Some(&expected(message.to_string()))
+        );
+    }
+}
+
+#[tokio::test]
+async fn other_failures_pass_through_unchanged() {
+    let node = refusing(
+        -13,
+        "Error: Please enter the wallet passphrase with walletpassphrase first.",
+    )
+    .await;
+    let error = node.client().dump_priv_key(ADDRESS).await.unwrap_err();
+    assert!(error.downcast_ref::<KeyExportError>().is_none());
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::WalletUnlockNeeded(_))
+    ));
+
+    let node = refusing(
+        -8,
+        "/home/node/wallet.dump already exists. If you are sure this is what you want, move it out of the way first",
+    )
+    .await;
+    let error = node.client().dump_wallet("wallet.dump").await.unwrap_err();
+    assert!(error.downcast_ref::<KeyExportError>().is_none());
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::InvalidParameter(_))
+    ));
+}
+
+#[tokio::test]
+async fn backups_are_written_and_restored_by_path() {
+    let node = MockNode::start(|method, _| match method {
+        "backupwallet" => Ok(json!(null)),
+        "restorewallet" => Ok(json!({"name": "restored", "warnings": []})),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    client.backup_wallet("/backups/alice.dat").await.unwrap();
+    let restored = client
+        .restore_wallet("restored", "/backups/alice.dat", None)
+        .await
+        .unwrap();
+    assert_eq!(restored.name, "restored");
+    client
+        .restore_wallet("again", "/backups/alice.dat", Some(true))
+        .await
+        .unwrap();
+    assert_eq!(
+        node.calls_to("backupwallet"),
+        [json!(["/backups/alice.dat"])]
+    );
+    assert_eq!(
+        node.calls_to("restorewallet"),
+        [
+            json!(["restored", "/backups/alice.dat", null]),
+            json!(["again", "/backups/alice.dat", true]),
+        ]
+    );
+}
+
+// Both rescan the chain, which takes longer than the client's own timeout allows
+#[tokio::test(flavor = "multi_thread")]
+async fn imports_and_restores_outlast_the_client_timeout() {
+    let node = MockNode::start(|method, _| match method {
+        "importwallet" => {
+            std::thread::sleep(Duration::from_millis(600));
+            Ok(json!(null))
+        }
+        "restorewallet" => {
+            std::thread::sleep(Duration::from_millis(600));
+            Ok(json!({"name": "restored", "warning": ""}))
+        }
+        "backupwallet" => {
+            std::thread::sleep(Duration::from_millis(600));
+            Ok(json!(null))
+        }
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client().with_timeout(Duration::from_millis(200));
+    assert!(client.backup_wallet("/backups/alice.dat").await.is_err());
+    client
+        .import_wallet("/home/node/wallet.dump")
+        .await
+        .unwrap();
+    client
+        .restore_wallet("restored", "/backups/alice.dat", None)
+        .await
+        .unwrap();
+    assert_eq!(
+        node.calls_to("importwallet"),
+        [json!(["/home/node/wallet.dump"])]
+    );
+}
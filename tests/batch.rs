@@ -0,0 +1,29 @@
+mod common;
+
+use common::{MockNode, method_not_found};
+use serde_json::json;
+
+#[tokio::test]
+async fn handles_only_resolve_in_their_own_batch() {
+    let node = MockNode::start(|method, _| match method {
+        "getblockcount" => Ok(json!(7)),
+        "getbestblockhash" => Ok(json!("aa".repeat(32))),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+
+    let mut first = client.batch();
+    let count = first.get_block_count();
+    let mut second = client.batch();
+    let hash = second.get_best_block_hash();
+    // Same position as `count`, different batch
+    assert_eq!(hash.index(), count.index());
+
+    let first = first.send().await.unwrap();
+    let second = second.send().await.unwrap();
+    assert_eq!(first.get(count).unwrap(), 7);
+    assert_eq!(second.get(hash).unwrap(), "aa".repeat(32));
+    assert!(second.get(count).is_err());
+    assert!(first.get(hash).is_err());
+}
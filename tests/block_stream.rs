@@ -0,0 +1,84 @@
+mod common;
+
+use bitcoin_sdk::BlockEvent;
+use common::{MockNode, block_header, method_not_found};
+use futures_util::StreamExt;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Default)]
+struct Chain {
+    // Every block ever mined: hash to (height, parent)
+    blocks: HashMap<String, (u64, Option<String>)>,
+    best: Vec<String>,
+}
+
+impl Chain {
+    fn mine(&mut self, tag: &str) -> String {
+        let height = self.best.len() as u64;
+        let hash = format!("{:0>64}", format!("{}{}", tag, height));
+        self.blocks
+            .insert(hash.clone(), (height, self.best.last().cloned()));
+        self.best.push(hash.clone());
+        hash
+    }
+
+    fn header(&self, hash: &str) -> Value {
+        let (height, parent) = &self.blocks[hash];
+        let mut header = block_header(hash, *height);
+        header["previousblockhash"] = json!(parent);
+        header
+    }
+}
+
+fn serve(chain: Arc<Mutex<Chain>>) -> impl Fn(&str, &Value) -> common::Reply {
+    move |method, params| {
+        let chain = chain.lock().unwrap();
+        match method {
+            "getbestblockhash" => Ok(json!(chain.best.last())),
+            "getblockheader" => Ok(chain.header(params[0].as_str().unwrap())),
+            "getblock" => {
+                let mut block = chain.header(params[0].as_str().unwrap());
+                block["size"] = json!(285);
+                block["weight"] = json!(1140);
+                block["tx"] = json!([]);
+                Ok(block)
+            }
+            _ => method_not_found(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn reorg_of_the_starting_tip_is_reported() {
+    let chain = Arc::new(Mutex::new(Chain::default()));
+    let old_tip = {
+        let mut chain = chain.lock().unwrap();
+        (0..10).map(|_| chain.mine("a")).last().unwrap()
+    };
+    let node = MockNode::start(serve(chain.clone())).await;
+    let client = node.client();
+    let mut events = Box::pin(client.subscribe_blocks(Duration::from_millis(10)));
+
+    let reorg = async {
+        // Let the follower take its starting view first
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut chain = chain.lock().unwrap();
+        chain.best.pop();
+        vec![chain.mine("b"), chain.mine("b")]
+    };
+    let (first, new_branch) = tokio::join!(events.next(), reorg);
+
+    match first.unwrap().unwrap() {
+        BlockEvent::Reorg { disconnected } => assert_eq!(disconnected, [old_tip]),
+        event => panic!("expected a reorg, got {:?}", event),
+    }
+    for hash in new_branch {
+        match events.next().await.unwrap().unwrap() {
+            BlockEvent::Connected(block) => assert_eq!(block.hash, hash),
+            event => panic!("expected a connected block, got {:?}", event),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use bitcoin_sdk::{BlockchainInfo, DeploymentInfo};
+use common::{MockNode, fixture, fixture_value, method_not_found, network_info};
+
+#[test]
+fn blockchain_info_before_23_carries_softforks() {
+    let info: BlockchainInfo = fixture("getblockchaininfo/v0.21-regtest");
+    assert_eq!(info.softforks.len(), 7);
+    assert_eq!(info.softforks["csv"].height, Some(432));
+    let testdummy = &info.softforks["testdummy"];
+    assert_eq!(testdummy.r#type, "bip9");
+    let bip9 = testdummy.bip9.as_ref().unwrap();
+    assert_eq!(bip9.status, "started");
+    assert_eq!(bip9.min_activation_height, None);
+    let statistics = bip9.statistics.as_ref().unwrap();
+    assert_eq!((statistics.period, statistics.threshold), (144, Some(108)));
+    assert!(info.warnings.0.is_empty());
+}
+
+#[test]
+fn blockchain_info_from_23_has_no_softforks() {
+    let info: BlockchainInfo = fixture("getblockchaininfo/v26.0-regtest");
+    assert!(info.softforks.is_empty());
+    assert!(!info.pruned);
+    assert_eq!(info.pruneheight, None);
+
+    let info: BlockchainInfo = fixture("getblockchaininfo/v28.0-mainnet-pruned");
+    assert!(info.softforks.is_empty());
+    assert_eq!(info.pruneheight, Some(861190));
+    assert_eq!(info.automatic_pruning, Some(true));
+    assert!(info.warnings.0.is_empty());
+}
+
+#[test]
+fn deployment_info_has_bip9_statistics() {
+    let info: DeploymentInfo = fixture("getdeploymentinfo/v23.0-regtest");
+    assert_eq!(info.height, 0);
+    assert!(info.deployments["taproot"].active);
+    assert!(
+        info.deployments["testdummy"]
+            .bip9
+            .as_ref()
+            .unwrap()
+            .statistics
+            .is_none()
+    );
+
+    let info: DeploymentInfo = fixture("getdeploymentinfo/v28.0-signalling");
+    let bip9 = info.deployments["testdummy"].bip9.as_ref().unwrap();
+    assert_eq!(bip9.bit, Some(28));
+    assert_eq!(bip9.status_next.as_deref(), Some("started"));
+    let statistics = bip9.statistics.as_ref().unwrap();
+    assert_eq!(statistics.elapsed, 17);
+    assert_eq!(statistics.count, 12);
+    assert_eq!(statistics.possible, Some(true));
+}
+
+#[tokio::test]
+async fn softforks_come_from_the_rpc_the_node_has() {
+    let old = MockNode::start(|method, _| match method {
+        "getnetworkinfo" => network_info(210000),
+        "getblockchaininfo" => Ok(fixture_value("getblockchaininfo/v0.21-regtest")),
+        _ => method_not_found(),
+    })
+    .await;
+    assert_eq!(old.client().softforks().await.unwrap().len(), 7);
+    assert!(old.calls_to("getdeploymentinfo").is_empty());
+
+    let new = MockNode::start(|method, _| match method {
+        "getnetworkinfo" => network_info(280000),
+        "getdeploymentinfo" => Ok(fixture_value("getdeploymentinfo/v28.0-signalling")),
+        _ => method_not_found(),
+    })
+    .await;
+    assert_eq!(new.client().softforks().await.unwrap().len(), 3);
+    assert!(new.calls_to("getblockchaininfo").is_empty());
+}
@@ -0,0 +1,347 @@
+mod common;
+
+use bitcoin_sdk::{
+    BroadcastPolicy, BroadcastStatus, BroadcastStore, Broadcaster, FileBroadcastStore,
+    QueuedTransaction, Serialization,
+};
+use common::{MockNode, Reply, method_not_found};
+use serde_json::{Value, json};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+// One input, two outputs
+const TX: &str = "010000000136641869ca081e70f394c6948e8af409e18b619df2ed74aa106c1ca29787b96e0100000000ffffffff0200e9a435000000001976a914389ffce9cd9ae88dcc0631e88a821ffdbe9bfe2688acc0832f05000000001976a9147480a33f950689af511e6e84c138dbbd3c3ee41588ac00000000";
+
+// The same spend with a different locktime, standing in for a fee bump of TX
+const REPLACEMENT: &str = "010000000136641869ca081e70f394c6948e8af409e18b619df2ed74aa106c1ca29787b96e0100000000ffffffff0200e9a435000000001976a914389ffce9cd9ae88dcc0631e88a821ffdbe9bfe2688acc0832f05000000001976a9147480a33f950689af511e6e84c138dbbd3c3ee41588ac01000000";
+
+// Clones share one queue, so a second broadcaster can resume from the first one's store
+#[derive(Default, Clone)]
+struct MemoryStore(Arc<Mutex<Vec<QueuedTransaction>>>);
+
+impl BroadcastStore for MemoryStore {
+    fn load(&self) -> anyhow::Result<Vec<QueuedTransaction>> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+
+    fn save(&self, queue: &[QueuedTransaction]) -> anyhow::Result<()> {
+        *self.0.lock().unwrap() = queue.to_vec();
+        Ok(())
+    }
+}
+
+fn no_wallet() -> Reply {
+    Err((-18, "No wallet is loaded.".to_string()))
+}
+
+fn no_txindex() -> Reply {
+    Err((
+        -5,
+        "No such mempool transaction. Use -txindex or provide a block hash to enable blockchain transaction queries.".to_string(),
+    ))
+}
+
+fn already_in_chain() -> Reply {
+    Err((-27, "Transaction already in block chain".to_string()))
+}
+
+fn tx_out(confirmations: u32) -> Value {
+    json!({
+        "bestblock": "00".repeat(32),
+        "confirmations": confirmations,
+        "value": 9.0,
+        "scriptPubKey": {
+            "asm": "",
+            "hex": "76a914389ffce9cd9ae88dcc0631e88a821ffdbe9bfe2688ac",
+            "type": "pubkeyhash",
+        },
+        "coinbase": false,
+    })
+}
+
+fn rejected(code: i32, message: &str) -> Reply {
+    Err((code, message.to_string()))
+}
+
+fn wallet_tx(hex: &str) -> Value {
+    json!({
+        "amount": -0.5,
+        "confirmations": 0,
+        "txid": "",
+        "time": 1700000000,
+        "timereceived": 1700000000,
+        "bip125-replaceable": "yes",
+        "details": [],
+        "hex": hex,
+    })
+}
+
+async fn enqueue(node: &MockNode) -> (Broadcaster, String) {
+    enqueue_with(node, BroadcastPolicy::default()).await
+}
+
+async fn enqueue_with(node: &MockNode, policy: BroadcastPolicy) -> (Broadcaster, String) {
+    let broadcaster = Broadcaster::new(node.client(), Box::new(MemoryStore::default())).unwrap();
+    let txid = broadcaster.enqueue(TX, policy).await.unwrap();
+    (broadcaster, txid)
+}
+
+// A node that knows nothing about TX and answers sendrawtransaction with `send`
+async fn unconfirmed_node(send: fn() -> Reply) -> MockNode {
+    MockNode::start(move |method, _| match method {
+        "getmempoolentry" => rejected(-5, "Transaction not in mempool"),
+        "gettransaction" => no_wallet(),
+        "gettxout" => Ok(Value::Null),
+        "getrawtransaction" => no_txindex(),
+        "sendrawtransaction" => send(),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn depth_comes_from_any_unspent_output_once_the_node_reports_it_mined() {
+    let node = MockNode::start(|method, params| match method {
+        "gettransaction" => no_wallet(),
+        // First output spent, second still unspent
+        "gettxout" if params[1] == 0 => Ok(Value::Null),
+        "gettxout" => Ok(tx_out(6)),
+        "getrawtransaction" => no_txindex(),
+        "sendrawtransaction" => already_in_chain(),
+        _ => method_not_found(),
+    })
+    .await;
+    let (broadcaster, txid) = enqueue(&node).await;
+    assert_eq!(
+        broadcaster.status(&txid),
+        Some(BroadcastStatus::Confirmed(6))
+    );
+    // Before broadcasting only the first output is probed
+    assert_eq!(node.calls_to("sendrawtransaction").len(), 1);
+    assert_eq!(
+        node.calls_to("gettxout"),
+        vec![
+            json!([txid, 0, false]),
+            json!([txid, 0, false]),
+            json!([txid, 1, false])
+        ]
+    );
+}
+
+#[tokio::test]
+async fn depth_comes_from_the_wallet() {
+    let node = MockNode::start(|method, _| match method {
+        "gettransaction" => Ok(json!({
+            "amount": -0.5,
+            "confirmations": 3,
+            "txid": "",
+            "time": 1700000000,
+            "timereceived": 1700000000,
+            "bip125-replaceable": "no",
+            "details": [],
+            "hex": TX,
+        })),
+        _ => method_not_found(),
+    })
+    .await;
+    let (broadcaster, txid) = enqueue(&node).await;
+    assert_eq!(
+        broadcaster.status(&txid),
+        Some(BroadcastStatus::Confirmed(3))
+    );
+    assert!(node.calls_to("gettxout").is_empty());
+}
+
+#[tokio::test]
+async fn already_in_chain_without_any_lookup_is_one_deep() {
+    let node = MockNode::start(|method, _| match method {
+        "gettransaction" => no_wallet(),
+        "gettxout" => Ok(Value::Null),
+        "getrawtransaction" => no_txindex(),
+        "sendrawtransaction" => already_in_chain(),
+        _ => method_not_found(),
+    })
+    .await;
+    let (broadcaster, txid) = enqueue(&node).await;
+    assert_eq!(
+        broadcaster.status(&txid),
+        Some(BroadcastStatus::Confirmed(1))
+    );
+    assert_eq!(node.calls_to("sendrawtransaction").len(), 1);
+}
+
+#[tokio::test]
+async fn unconfirmed_transactions_are_broadcast() {
+    let node = MockNode::start(|method, _| match method {
+        "gettransaction" => no_wallet(),
+        "gettxout" => Ok(Value::Null),
+        "getrawtransaction" => no_txindex(),
+        "sendrawtransaction" => Ok(json!("")),
+        _ => method_not_found(),
+    })
+    .await;
+    let (broadcaster, txid) = enqueue(&node).await;
+    assert_eq!(broadcaster.status(&txid), Some(BroadcastStatus::InMempool));
+}
+
+#[tokio::test]
+async fn a_mempool_transaction_costs_one_lookup() {
+    let node = MockNode::start(|method, _| match method {
+        "getmempoolentry" => Ok(json!({})),
+        "sendrawtransaction" => rejected(-26, "txn-already-in-mempool"),
+        _ => method_not_found(),
+    })
+    .await;
+    let (broadcaster, txid) = enqueue(&node).await;
+    assert_eq!(broadcaster.status(&txid), Some(BroadcastStatus::InMempool));
+    broadcaster.process().await.unwrap();
+    // Not yet due for a rebroadcast, so the second pass is the mempool check alone
+    let methods: Vec<String> = node.calls().into_iter().map(|(m, _)| m).collect();
+    assert_eq!(
+        methods,
+        ["getmempoolentry", "sendrawtransaction", "getmempoolentry"]
+    );
+}
+
+#[tokio::test]
+async fn missing_inputs_wait_for_the_parent() {
+    let node = unconfirmed_node(|| rejected(-25, "Inputs missing or spent")).await;
+    let (broadcaster, txid) = enqueue(&node).await;
+    assert_eq!(
+        broadcaster.status(&txid),
+        Some(BroadcastStatus::WaitingForParent)
+    );
+}
+
+#[tokio::test]
+async fn low_fee_without_bumping_is_reported() {
+    let node = unconfirmed_node(|| rejected(-26, "min relay fee not met, 100 < 141")).await;
+    let (broadcaster, txid) = enqueue(&node).await;
+    assert_eq!(broadcaster.status(&txid), Some(BroadcastStatus::FeeTooLow));
+    assert!(node.calls_to("bumpfee").is_empty());
+}
+
+#[tokio::test]
+async fn low_fee_is_bumped_and_the_replacement_tracked() {
+    let replacement = Serialization::deserialize_transaction(REPLACEMENT)
+        .unwrap()
+        .txid();
+    let bumped = replacement.clone();
+    let node = MockNode::start(move |method, params| match method {
+        "getmempoolentry" => rejected(-5, "Transaction not in mempool"),
+        "gettransaction" if params[0] == bumped.as_str() => Ok(wallet_tx(REPLACEMENT)),
+        "gettransaction" => no_wallet(),
+        "gettxout" => Ok(Value::Null),
+        "getrawtransaction" => no_txindex(),
+        "sendrawtransaction" => rejected(-26, "mempool min fee not met, 100 < 141"),
+        "bumpfee" => Ok(json!({"txid": bumped, "origfee": 0.0001, "fee": 0.0002, "errors": []})),
+        _ => method_not_found(),
+    })
+    .await;
+    let policy = BroadcastPolicy {
+        target_confirmations: 3,
+        deadline: Some(4102444800),
+        bump_on_low_fee: true,
+        ..BroadcastPolicy::default()
+    };
+    let (broadcaster, txid) = enqueue_with(&node, policy).await;
+    assert_eq!(
+        broadcaster.status(&txid),
+        Some(BroadcastStatus::Replaced(replacement.clone()))
+    );
+    let queued = broadcaster
+        .transactions()
+        .into_iter()
+        .find(|tx| tx.txid == replacement)
+        .unwrap();
+    assert_eq!(queued.hex, REPLACEMENT);
+    assert_eq!(queued.status, BroadcastStatus::InMempool);
+    assert_eq!(queued.policy.target_confirmations, 3);
+    assert_eq!(queued.policy.deadline, Some(4102444800));
+    // The replacement is not final, so pruning keeps it
+    broadcaster.prune().unwrap();
+    assert_eq!(broadcaster.transactions().len(), 1);
+}
+
+#[tokio::test]
+async fn conflicts_fail() {
+    let node = unconfirmed_node(|| rejected(-26, "txn-mempool-conflict")).await;
+    let (broadcaster, txid) = enqueue(&node).await;
+    assert!(matches!(
+        broadcaster.status(&txid),
+        Some(BroadcastStatus::Failed(_))
+    ));
+    // Final, so later passes leave it alone
+    broadcaster.process().await.unwrap();
+    assert_eq!(node.calls_to("sendrawtransaction").len(), 1);
+}
+
+#[tokio::test]
+async fn past_the_deadline_is_abandoned_without_broadcasting() {
+    let node = unconfirmed_node(|| Ok(json!(""))).await;
+    let policy = BroadcastPolicy {
+        deadline: Some(1),
+        ..BroadcastPolicy::default()
+    };
+    let (broadcaster, txid) = enqueue_with(&node, policy).await;
+    assert_eq!(broadcaster.status(&txid), Some(BroadcastStatus::Abandoned));
+    assert!(node.calls_to("sendrawtransaction").is_empty());
+}
+
+#[tokio::test]
+async fn a_restarted_broadcaster_resumes_the_stored_queue() {
+    let parent_confirmed = Arc::new(AtomicBool::new(false));
+    let confirmed = parent_confirmed.clone();
+    let node = MockNode::start(move |method, _| match method {
+        "getmempoolentry" => rejected(-5, "Transaction not in mempool"),
+        "gettransaction" => no_wallet(),
+        "gettxout" => Ok(Value::Null),
+        "getrawtransaction" => no_txindex(),
+        "sendrawtransaction" if confirmed.load(Ordering::SeqCst) => Ok(json!("")),
+        "sendrawtransaction" => rejected(-25, "Inputs missing or spent"),
+        _ => method_not_found(),
+    })
+    .await;
+    let policy = BroadcastPolicy {
+        rebroadcast_interval_secs: 0,
+        ..BroadcastPolicy::default()
+    };
+    let store = MemoryStore::default();
+    let txid = {
+        let broadcaster = Broadcaster::new(node.client(), Box::new(store.clone())).unwrap();
+        broadcaster.enqueue(TX, policy).await.unwrap()
+    };
+
+    parent_confirmed.store(true, Ordering::SeqCst);
+    let broadcaster = Broadcaster::new(node.client(), Box::new(store)).unwrap();
+    assert_eq!(
+        broadcaster.status(&txid),
+        Some(BroadcastStatus::WaitingForParent)
+    );
+    broadcaster.process().await.unwrap();
+    let resumed = broadcaster.transactions();
+    assert_eq!(resumed[0].status, BroadcastStatus::InMempool);
+    assert_eq!(resumed[0].attempts, 2);
+}
+
+#[tokio::test]
+async fn the_file_store_survives_a_restart() {
+    let path = std::env::temp_dir().join(format!("broadcast-{}.json", std::process::id()));
+    let node = unconfirmed_node(|| rejected(-26, "min relay fee not met")).await;
+    let txid = {
+        let broadcaster =
+            Broadcaster::new(node.client(), Box::new(FileBroadcastStore::new(&path))).unwrap();
+        broadcaster
+            .enqueue(TX, BroadcastPolicy::default())
+            .await
+            .unwrap()
+    };
+    let broadcaster =
+        Broadcaster::new(node.client(), Box::new(FileBroadcastStore::new(&path))).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let resumed = broadcaster.transactions();
+    assert_eq!(resumed.len(), 1);
+    assert_eq!(resumed[0].txid, txid);
+    assert_eq!(resumed[0].hex, TX);
+    assert_eq!(resumed[0].status, BroadcastStatus::FeeTooLow);
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use bitcoin_sdk::BitcoinClient;
+use common::{MockNode, method_not_found};
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::test]
+async fn builds_with_connection_options() {
+    let node = MockNode::start(|method, _| match method {
+        "getblockcount" => Ok(json!(101)),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = BitcoinClient::builder(node.url())
+        .auth("user", "pass")
+        .timeout(Duration::from_secs(120))
+        .connect_timeout(Duration::from_secs(5))
+        .pool_max_idle_per_host(4)
+        .tcp_keepalive(Duration::from_secs(30))
+        .user_agent("wallet-backend/1.0")
+        .build()
+        .unwrap();
+    assert_eq!(client.get_block_count().await.unwrap(), 101);
+    assert_eq!(client.get_block_count().await.unwrap(), 101);
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use bitcoin_sdk::{BitcoinClient, CacheStats};
+use common::{MockNode, block_header, method_not_found};
+use serde_json::{Value, json};
+
+fn hash(n: u8) -> String {
+    hex::encode([n; 32])
+}
+
+// Transactions with an even first byte are confirmed; like Core, mempool ones have no
+// block fields at all
+fn transaction(txid: &str) -> Value {
+    let mut tx = json!({
+        "txid": txid,
+        "hash": txid,
+        "version": 2,
+        "size": 10,
+        "vsize": 10,
+        "weight": 40,
+        "locktime": 0,
+        "vin": [],
+        "vout": [],
+        "hex": "02000000000000000000",
+    });
+    if u8::from_str_radix(&txid[..2], 16)
+        .unwrap()
+        .is_multiple_of(2)
+    {
+        tx["blockhash"] = json!(hash(0xbb));
+        tx["confirmations"] = json!(6);
+    }
+    tx
+}
+
+async fn node() -> MockNode {
+    MockNode::start(|method, params| match method {
+        "getblock" => Ok(json!(format!("block {}", params[0].as_str().unwrap()))),
+        "getblockheader" if params[1] == true => Ok(block_header(params[0].as_str().unwrap(), 100)),
+        "getblockheader" => Ok(json!(format!("header {}", params[0].as_str().unwrap()))),
+        "getrawtransaction" if params[1] == true => Ok(transaction(params[0].as_str().unwrap())),
+        "getrawtransaction" => Ok(json!("02000000000000000000")),
+        "getblockcount" => Ok(json!(100)),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn client(node: &MockNode, entries: usize) -> BitcoinClient {
+    BitcoinClient::builder(node.url())
+        .auth("user", "pass")
+        .cache_capacity(entries)
+        .build()
+        .unwrap()
+}
+
+fn stats(hits: u64, misses: u64, entries: usize, capacity: usize) -> CacheStats {
+    CacheStats {
+        hits,
+        misses,
+        entries,
+        capacity,
+    }
+}
+
+#[tokio::test]
+async fn the_least_recently_used_entry_is_evicted() {
+    let node = node().await;
+    let client = client(&node, 2);
+    client.get_block_hex(&hash(1)).await.unwrap();
+    client.get_block_hex(&hash(2)).await.unwrap();
+    // Touching 1 leaves 2 as the oldest
+    client.get_block_hex(&hash(1)).await.unwrap();
+    client.get_block_hex(&hash(3)).await.unwrap();
+    assert_eq!(client.cache_stats(), stats(1, 3, 2, 2));
+
+    assert_eq!(
+        client.get_block_hex(&hash(2)).await.unwrap(),
+        format!("block {}", hash(2))
+    );
+    // Fetching 2 again evicted 1; 3 is still there
+    client.get_block_hex(&hash(3)).await.unwrap();
+    client.get_block_hex(&hash(1)).await.unwrap();
+    let fetched: Vec<Value> = node
+        .calls_to("getblock")
+        .iter()
+        .map(|params| params[0].clone())
+        .collect();
+    assert_eq!(
+        fetched,
+        [
+            json!(hash(1)),
+            json!(hash(2)),
+            json!(hash(3)),
+            json!(hash(2)),
+            json!(hash(1))
+        ]
+    );
+    assert_eq!(client.cache_stats(), stats(2, 5, 2, 2));
+}
+
+#[tokio::test]
+async fn keys_include_the_verbosity() {
+    let node = node().await;
+    let client = client(&node, 10);
+    client.get_block_header_hex(&hash(1)).await.unwrap();
+    client.get_raw_transaction_hex(&hash(2)).await.unwrap();
+    client.get_block_header_hex(&hash(1)).await.unwrap();
+    client.get_raw_transaction_hex(&hash(2)).await.unwrap();
+    client.get_raw_transaction(&hash(2)).await.unwrap();
+    assert_eq!(node.calls().len(), 3);
+    assert_eq!(client.cache_stats(), stats(2, 3, 3, 10));
+}
+
+#[tokio::test]
+async fn verbose_blocks_and_headers_are_always_fetched() {
+    let node = node().await;
+    let client = client(&node, 10);
+    for _ in 0..2 {
+        let header = client.get_block_header(&hash(1)).await.unwrap();
+        assert_eq!(header.confirmations, 1);
+    }
+    assert_eq!(node.calls_to("getblockheader").len(), 2);
+    assert_eq!(client.cache_stats(), stats(0, 2, 0, 10));
+}
+
+#[tokio::test]
+async fn confirmed_transactions_are_cached_without_their_confirmations() {
+    let node = node().await;
+    let client = client(&node, 10);
+    let first = client.get_raw_transaction(&hash(2)).await.unwrap();
+    assert_eq!(first.confirmations, Some(6));
+    let cached = client.get_raw_transaction(&hash(2)).await.unwrap();
+    assert_eq!(cached.confirmations, None);
+    assert_eq!(cached.blockhash, Some(hash(0xbb)));
+    assert_eq!(node.calls_to("getrawtransaction").len(), 1);
+}
+
+#[tokio::test]
+async fn mempool_transactions_are_not_cached() {
+    let node = node().await;
+    let client = client(&node, 10);
+    for _ in 0..2 {
+        let tx = client.get_raw_transaction(&hash(1)).await.unwrap();
+        assert_eq!(tx.blockhash, None);
+    }
+    assert_eq!(node.calls_to("getrawtransaction").len(), 2);
+    assert_eq!(client.cache_stats(), stats(0, 2, 0, 10));
+}
+
+#[tokio::test]
+async fn other_methods_bypass_the_cache() {
+    let node = node().await;
+    let client = client(&node, 10);
+    client.get_block_count().await.unwrap();
+    client.get_block_count().await.unwrap();
+    assert_eq!(node.calls_to("getblockcount").len(), 2);
+    assert_eq!(client.cache_stats(), stats(0, 0, 0, 10));
+}
+
+#[tokio::test]
+async fn clearing_drops_entries_and_counters() {
+    let node = node().await;
+    let client = client(&node, 10);
+    let clone = client.clone();
+    client.get_block_hex(&hash(1)).await.unwrap();
+    clone.get_block_hex(&hash(1)).await.unwrap();
+    // Clones share one cache
+    assert_eq!(client.cache_stats(), stats(1, 1, 1, 10));
+
+    clone.clear_cache();
+    assert_eq!(client.cache_stats(), stats(0, 0, 0, 10));
+    client.get_block_hex(&hash(1)).await.unwrap();
+    assert_eq!(node.calls_to("getblock").len(), 2);
+}
+
+#[tokio::test]
+async fn clients_without_a_cache_report_nothing() {
+    let node = node().await;
+    let client = node.client();
+    client.get_block_hex(&hash(1)).await.unwrap();
+    client.get_block_hex(&hash(1)).await.unwrap();
+    assert_eq!(node.calls_to("getblock").len(), 2);
+    assert_eq!(client.cache_stats(), CacheStats::default());
+    client.clear_cache();
+}
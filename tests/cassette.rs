@@ -0,0 +1,135 @@
+mod common;
+
+use bitcoin_sdk::{BitcoinClient, BitcoinRpc, Cassette};
+use common::{MockNode, method_not_found};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+
+const WIF: &str = "cVpF924EspNh8KjYsfhgY96mmxvT6DgdWiTYMtMjuM74hJaU5psW";
+const XPRV: &str = "tprv8ZgxMBicQKsPd7Uf69XL1XwhmjHopUGep8GuEiJDZmbQz6o58LninorQAfcKZWARbtRtfnLcJ5MQ2AtHcQJCCRUcMRvmDUjyEmNUWwx8UbK";
+
+// A cassette path unique to this test process and `name`
+fn cassette_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("cassette-{}-{}.jsonl", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+// A client that has no node behind it, so every answer must come from the cassette
+fn replay_client(path: &Path) -> BitcoinClient {
+    BitcoinClient::new("http://127.0.0.1:1", "user", "pass")
+        .with_cassette(Cassette::replay(path).unwrap())
+}
+
+async fn wallet_node() -> MockNode {
+    MockNode::start(|method, params| match method {
+        "getblockcount" => Ok(json!(101)),
+        "getblockhash" => Ok(json!(format!("{:064x}", params[0].as_u64().unwrap()))),
+        "dumpprivkey" => Ok(json!(WIF)),
+        "importprivkey" | "walletpassphrase" => Ok(Value::Null),
+        "signmessagewithprivkey" => Ok(json!("H+signature=")),
+        "importdescriptors" => Ok(json!([{"success": true}])),
+        "importmulti" => Ok(json!([{"success": true}])),
+        "listdescriptors" => Ok(json!({
+            "wallet_name": "w",
+            "descriptors": [{
+                "desc": format!("wpkh({}/84h/1h/0h/0/*)#abcdefgh", XPRV),
+                "timestamp": 1700000000,
+                "active": true,
+                "internal": false,
+                "range": [0, 999],
+                "next": 0,
+            }],
+        })),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn recorded_sessions_replay_without_a_node() {
+    let path = cassette_path("replay");
+    let node = wallet_node().await;
+    let recorder = node
+        .client()
+        .with_cassette(Cassette::record(&path).unwrap());
+    assert_eq!(recorder.get_block_count().await.unwrap(), 101);
+    let hash = recorder.get_block_hash(7).await.unwrap();
+
+    let replay = replay_client(&path);
+    assert_eq!(replay.get_block_count().await.unwrap(), 101);
+    assert_eq!(replay.get_block_hash(7).await.unwrap(), hash);
+    // Served again once every recording of the call has been used
+    assert_eq!(replay.get_block_count().await.unwrap(), 101);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn unmatched_calls_name_the_nearest_recording() {
+    let path = cassette_path("unmatched");
+    let node = wallet_node().await;
+    let recorder = node
+        .client()
+        .with_cassette(Cassette::record(&path).unwrap());
+    recorder.get_block_hash(7).await.unwrap();
+
+    let error = replay_client(&path)
+        .get_block_hash(8)
+        .await
+        .unwrap_err()
+        .to_string();
+    std::fs::remove_file(&path).unwrap();
+    assert!(error.contains("No recorded response"), "{}", error);
+    assert!(
+        error.contains("nearest recorded call: getblockhash [7]"),
+        "{}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn private_keys_and_passphrases_never_reach_the_file() {
+    let path = cassette_path("redaction");
+    let node = wallet_node().await;
+    let recorder = node
+        .client()
+        .with_cassette(Cassette::record(&path).unwrap());
+    let calls = [
+        ("dumpprivkey", json!(["bcrt1qaddress"])),
+        ("importprivkey", json!([WIF, "label", false])),
+        ("importprivkey", json!({"privkey": WIF, "rescan": false})),
+        (
+            "walletpassphrase",
+            json!({"passphrase": "hunter2", "timeout": 60}),
+        ),
+        ("signmessagewithprivkey", json!([WIF, "hello"])),
+        (
+            "importdescriptors",
+            json!([[{"desc": format!("wpkh({}/0/*)", XPRV), "timestamp": "now"}]]),
+        ),
+        (
+            "importmulti",
+            json!({"requests": [{"scriptPubKey": {"address": "bcrt1qaddress"}, "keys": [WIF], "timestamp": 0}]}),
+        ),
+        ("listdescriptors", json!([true])),
+    ];
+    for (method, params) in calls.iter() {
+        recorder.call_value(method, params.clone()).await.unwrap();
+    }
+    let recorded = std::fs::read_to_string(&path).unwrap();
+    assert!(!recorded.contains(WIF), "{}", recorded);
+    assert!(!recorded.contains(XPRV), "{}", recorded);
+    assert!(!recorded.contains("hunter2"), "{}", recorded);
+    // Non-secret arguments stay, so replay can still tell calls apart
+    assert!(recorded.contains("\"timestamp\":\"now\""));
+    assert!(recorded.contains("hello"));
+
+    // Calls match their redacted recordings, and private descriptors keep their shape
+    let replay = replay_client(&path);
+    for (method, params) in calls.iter() {
+        replay.call_value(method, params.clone()).await.unwrap();
+    }
+    let descriptors = replay.list_descriptors(true).await.unwrap();
+    assert_eq!(descriptors.descriptors[0].desc, "<redacted>");
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,293 @@
+// A stand-in bitcoind for offline tests: an HTTP server on localhost that answers
+// JSON-RPC requests from a handler and records every body it receives
+#![allow(dead_code)]
+
+use bitcoin_sdk::BitcoinClient;
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+// Result for a call, or the (code, message) of an RPC error
+pub type Reply = Result<Value, (i32, String)>;
+
+// Called with the request path, method and params
+type Handler = dyn Fn(&str, &str, &Value) -> Reply + Send + Sync;
+
+pub struct MockNode {
+    url: String,
+    bodies: Arc<Mutex<Vec<String>>>,
+    paths: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockNode {
+    pub async fn start<F>(handler: F) -> MockNode
+    where
+        F: Fn(&str, &Value) -> Reply + Send + Sync + 'static,
+    {
+        Self::start_with_paths(move |_, method, params| handler(method, params)).await
+    }
+
+    // Same, with the handler also told the request path, e.g. to answer per wallet
+    pub async fn start_with_paths<F>(handler: F) -> MockNode
+    where
+        F: Fn(&str, &str, &Value) -> Reply + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let paths = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let recorded = Recorded {
+            bodies: bodies.clone(),
+            paths: paths.clone(),
+        };
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let _ = serve(stream, handler, recorded).await;
+                });
+            }
+        });
+        MockNode { url, bodies, paths }
+    }
+
+    // Same, behind TLS with the testdata/tls certificate for localhost, signed by
+    // testdata/tls/ca.pem
+    pub async fn start_tls<F>(handler: F) -> MockNode
+    where
+        F: Fn(&str, &Value) -> Reply + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "https://localhost:{}",
+            listener.local_addr().unwrap().port()
+        );
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let paths = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> =
+            Arc::new(move |_: &str, method: &str, params: &Value| handler(method, params));
+        let recorded = Recorded {
+            bodies: bodies.clone(),
+            paths: paths.clone(),
+        };
+        let identity =
+            native_tls::Identity::from_pkcs8(&tls_file("server.pem"), &tls_file("server.key"))
+                .unwrap();
+        let acceptor: tokio_native_tls::TlsAcceptor =
+            native_tls::TlsAcceptor::new(identity).unwrap().into();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    if let Ok(stream) = acceptor.accept(stream).await {
+                        let _ = serve(stream, handler, recorded).await;
+                    }
+                });
+            }
+        });
+        MockNode { url, bodies, paths }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn client(&self) -> BitcoinClient {
+        BitcoinClient::new(&self.url, "user", "pass")
+    }
+
+    // Raw request bodies in arrival order
+    pub fn bodies(&self) -> Vec<String> {
+        self.bodies.lock().unwrap().clone()
+    }
+
+    // Request paths in arrival order, e.g. "/" or "/wallet/alice"
+    pub fn paths(&self) -> Vec<String> {
+        self.paths.lock().unwrap().clone()
+    }
+
+    // (method, params) of every request, with batches flattened
+    pub fn calls(&self) -> Vec<(String, Value)> {
+        let mut calls = Vec::new();
+        for body in self.bodies() {
+            let body: Value = serde_json::from_str(&body).unwrap();
+            let requests = match body {
+                Value::Array(requests) => requests,
+                request => vec![request],
+            };
+            for request in requests {
+                calls.push((
+                    request["method"].as_str().unwrap().to_string(),
+                    request["params"].clone(),
+                ));
+            }
+        }
+        calls
+    }
+
+    pub fn calls_to(&self, method: &str) -> Vec<Value> {
+        self.calls()
+            .into_iter()
+            .filter(|(m, _)| m == method)
+            .map(|(_, params)| params)
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+struct Recorded {
+    bodies: Arc<Mutex<Vec<String>>>,
+    paths: Arc<Mutex<Vec<String>>>,
+}
+
+async fn serve<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    handler: Arc<Handler>,
+    recorded: Recorded,
+) -> std::io::Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let (header_end, length) = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&buf[..end]).to_ascii_lowercase();
+            let length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            break (end + 4, length);
+        }
+    };
+    while buf.len() < header_end + length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let head = String::from_utf8_lossy(&buf[..header_end]);
+    let path = head.split_whitespace().nth(1).unwrap_or("/").to_string();
+    let body = String::from_utf8_lossy(&buf[header_end..]).into_owned();
+    // Path first, so a test that saw the body also sees its path
+    recorded.paths.lock().unwrap().push(path.clone());
+    recorded.bodies.lock().unwrap().push(body.clone());
+
+    let request: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+    let (status, reply) = match request {
+        Value::Array(requests) => {
+            let replies: Vec<Value> = requests
+                .iter()
+                .map(|r| answer(&*handler, &path, r))
+                .collect();
+            ("200 OK", Value::Array(replies))
+        }
+        request => {
+            let reply = answer(&*handler, &path, &request);
+            // Core answers a failed single request with HTTP 500
+            let status = if reply["error"].is_null() {
+                "200 OK"
+            } else {
+                "500 Internal Server Error"
+            };
+            (status, reply)
+        }
+    };
+    let reply = reply.to_string();
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reply.len(),
+        reply
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn answer(handler: &Handler, path: &str, request: &Value) -> Value {
+    let method = request["method"].as_str().unwrap_or_default();
+    match handler(path, method, &request["params"]) {
+        Ok(result) => {
+            json!({"result": result, "error": null, "id": request["id"], "jsonrpc": "2.0"})
+        }
+        Err((code, message)) => json!({
+            "result": null,
+            "error": {"code": code, "message": message},
+            "id": request["id"],
+            "jsonrpc": "2.0",
+        }),
+    }
+}
+
+// A file from testdata/tls
+pub fn tls_file(name: &str) -> Vec<u8> {
+    let path = format!("{}/testdata/tls/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+// RPC error Core returns for a method it does not know
+pub fn method_not_found() -> Reply {
+    Err((-32601, "Method not found".to_string()))
+}
+
+// Minimal `getnetworkinfo` result reporting `version`, e.g. 280000 for 28.0
+pub fn network_info(version: u32) -> Reply {
+    Ok(json!({
+        "version": version,
+        "subversion": "/Satoshi/",
+        "protocolversion": 70016,
+        "localservices": "0000000000000409",
+        "localrelay": true,
+        "timeoffset": 0,
+        "connections": 0,
+        "networkactive": true,
+        "networks": [],
+        "relayfee": 0.00001,
+        "incrementalfee": 0.00001,
+        "localaddresses": [],
+        "warnings": "",
+    }))
+}
+
+// A captured RPC result from testdata/fixtures, e.g. "getblockchaininfo/v26.0-regtest"
+pub fn fixture_value(name: &str) -> Value {
+    let path = format!(
+        "{}/testdata/fixtures/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    serde_json::from_str(&text).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+pub fn fixture<T: serde::de::DeserializeOwned>(name: &str) -> T {
+    serde_json::from_value(fixture_value(name)).unwrap_or_else(|e| panic!("{}: {}", name, e))
+}
+
+// `getblockheader` result for a block at `height` whose hash is `hash`
+pub fn block_header(hash: &str, height: u64) -> Value {
+    json!({
+        "hash": hash,
+        "confirmations": 1,
+        "height": height,
+        "version": 536870912,
+        "versionHex": "20000000",
+        "merkleroot": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+        "time": 1700000000 + height * 600,
+        "mediantime": 1700000000 + height * 600,
+        "nonce": 0,
+        "bits": "207fffff",
+        "difficulty": 4.656542373906925e-10,
+        "chainwork": "00000000000000000000000000000000000000000000000000000000000000cc",
+        "nTx": 1,
+    })
+}
@@ -0,0 +1,14 @@
+use bitcoin_sdk::CompactFilter;
+
+// From BIP158's test vectors (testnet-19.json)
+const GENESIS_HASH: &str = "000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943";
+const GENESIS_FILTER: &str = "019dfca8";
+const GENESIS_COINBASE_SCRIPT: &str = "4104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac";
+
+#[test]
+fn genesis_filter_matches_its_coinbase_output() {
+    let filter = CompactFilter::from_hex(GENESIS_FILTER).unwrap();
+    assert_eq!(filter.len(), 1);
+    let script = hex::decode(GENESIS_COINBASE_SCRIPT).unwrap();
+    assert!(filter.matches_any(GENESIS_HASH, &[&script]).unwrap());
+}
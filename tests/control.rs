@@ -0,0 +1,26 @@
+mod common;
+
+use common::{MockNode, method_not_found};
+use serde_json::json;
+use std::time::Duration;
+
+// The node takes longer than the client's own timeout allows
+#[tokio::test(flavor = "multi_thread")]
+async fn verify_chain_outlasts_the_client_timeout() {
+    let node = MockNode::start(|method, _| match method {
+        "verifychain" => {
+            std::thread::sleep(Duration::from_millis(600));
+            Ok(json!(true))
+        }
+        "getblockcount" => {
+            std::thread::sleep(Duration::from_millis(600));
+            Ok(json!(1))
+        }
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client().with_timeout(Duration::from_millis(200));
+    assert!(client.get_block_count().await.is_err());
+    assert!(client.verify_chain(Some(4), Some(0)).await.unwrap());
+    assert_eq!(node.calls_to("verifychain"), [json!([4, 0])]);
+}
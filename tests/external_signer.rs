@@ -0,0 +1,96 @@
+mod common;
+
+use bitcoin_sdk::{BitcoinRpcError, CreateWalletOptions, DisplayAddressResult};
+use common::{MockNode, fixture, fixture_value, method_not_found, network_info};
+use serde_json::json;
+
+async fn node(version: u32) -> MockNode {
+    MockNode::start(move |method, _| match method {
+        "getnetworkinfo" => network_info(version),
+        "enumeratesigners" => Ok(fixture_value("enumeratesigners/hwi-two-devices")),
+        "walletdisplayaddress" => Ok(fixture_value("walletdisplayaddress/p2wpkh")),
+        "createwallet" => Ok(json!({"name": "hww", "warning": ""})),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn is_method_not_found(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::MethodNotFound(_))
+    )
+}
+
+#[test]
+fn display_address_fixture() {
+    let result: DisplayAddressResult = fixture("walletdisplayaddress/p2wpkh");
+    assert_eq!(
+        result.address,
+        "bcrt1qq0yhsnwjelq89q9lmxxhc2cvyq2xd0c40yywhd"
+    );
+}
+
+#[tokio::test]
+async fn enumerates_signers() {
+    let node = node(250000).await;
+    let signers = node.client().enumerate_signers().await.unwrap();
+    let names: Vec<_> = signers
+        .iter()
+        .map(|s| (s.fingerprint.as_str(), s.name.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        [("76223a6e", "trezor_t"), ("f5acc2fd", "ledger_nano_s_plus")]
+    );
+}
+
+#[tokio::test]
+async fn signer_calls_are_gated_on_old_nodes() {
+    let node = node(210000).await;
+    let client = node.client();
+    assert!(is_method_not_found(
+        &client.enumerate_signers().await.unwrap_err()
+    ));
+    assert!(is_method_not_found(
+        &client
+            .wallet("hww")
+            .wallet_display_address("bcrt1qq0yhsnwjelq89q9lmxxhc2cvyq2xd0c40yywhd")
+            .await
+            .unwrap_err()
+    ));
+    let options = CreateWalletOptions::default()
+        .disable_private_keys(true)
+        .descriptors(true)
+        .external_signer(true);
+    assert!(is_method_not_found(
+        &client.create_wallet("hww", options).await.unwrap_err()
+    ));
+    assert!(node.calls_to("enumeratesigners").is_empty());
+    assert!(node.calls_to("walletdisplayaddress").is_empty());
+    assert!(node.calls_to("createwallet").is_empty());
+}
+
+#[tokio::test]
+async fn create_wallet_sends_external_signer() {
+    let node = node(250000).await;
+    let client = node.client();
+    let options = CreateWalletOptions::default()
+        .disable_private_keys(true)
+        .descriptors(true)
+        .external_signer(true);
+    client.create_wallet("hww", options).await.unwrap();
+    let params = &node.calls_to("createwallet")[0];
+    assert_eq!(params[0], json!("hww"));
+    assert_eq!(params[1], json!(true));
+    assert_eq!(params[7], json!(true));
+    let display = client
+        .wallet("hww")
+        .wallet_display_address("bcrt1qq0yhsnwjelq89q9lmxxhc2cvyq2xd0c40yywhd")
+        .await
+        .unwrap();
+    assert_eq!(
+        display.address,
+        "bcrt1qq0yhsnwjelq89q9lmxxhc2cvyq2xd0c40yywhd"
+    );
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use bitcoin_sdk::{Amount, BitcoinRpcError, BumpFeeError, BumpFeeOptions, EstimateMode, FeeRate};
+use common::{MockNode, method_not_found};
+use serde_json::json;
+
+const TXID: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+
+// A node whose bumpfee and psbtbumpfee both fail with `code` and `message`
+async fn refusing(code: i32, message: &'static str) -> MockNode {
+    MockNode::start(move |method, _| match method {
+        "bumpfee" | "psbtbumpfee" => Err((code, message.to_string())),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn options_are_sent_with_unset_fields_left_out() {
+    let node = MockNode::start(|method, _| match method {
+        "bumpfee" => Ok(json!({
+            "txid": "22".repeat(32),
+            "origfee": 0.00000141,
+            "fee": 0.00000282,
+            "errors": [],
+        })),
+        "psbtbumpfee" => Ok(json!({
+            "psbt": "cHNidP8BAAoCAAAAAAAAAAAAAA==",
+            "origfee": 0.00000141,
+            "fee": 0.00000282,
+            "errors": ["Unable to sign"],
+        })),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    let options = BumpFeeOptions::default()
+        .fee_rate(FeeRate::from_sat_per_vb(2.5).unwrap())
+        .replaceable(false);
+    let bumped = client.bump_fee(TXID, options.clone()).await.unwrap();
+    assert_eq!(bumped.txid, Some("22".repeat(32)));
+    assert_eq!(bumped.origfee, Amount::from_sat(141));
+    assert_eq!(bumped.fee, Amount::from_sat(282));
+    let unsigned = client.psbt_bump_fee(TXID, options).await.unwrap();
+    assert_eq!(unsigned.psbt, "cHNidP8BAAoCAAAAAAAAAAAAAA==");
+    assert_eq!(unsigned.errors, ["Unable to sign"]);
+    client
+        .bump_fee(
+            TXID,
+            BumpFeeOptions::default()
+                .conf_target(6)
+                .estimate_mode(EstimateMode::Economical),
+        )
+        .await
+        .unwrap();
+    client
+        .bump_fee(TXID, BumpFeeOptions::default())
+        .await
+        .unwrap();
+
+    let bodies = node.bodies();
+    let params = format!(
+        r#""params":["{}",{{"fee_rate":2.500,"replaceable":false}}]}}"#,
+        TXID
+    );
+    assert!(bodies[0].ends_with(&params));
+    assert!(bodies[1].ends_with(&params));
+    assert_eq!(
+        node.calls_to("bumpfee")[1],
+        json!([TXID, {"conf_target": 6, "estimate_mode": "economical"}])
+    );
+    assert_eq!(node.calls_to("bumpfee")[2], json!([TXID, {}]));
+}
+
+#[tokio::test]
+async fn non_replaceable_transactions_are_typed() {
+    let node = refusing(-4, "Transaction is not BIP 125 replaceable").await;
+    let client = node.client();
+    for error in [
+        client
+            .bump_fee(TXID, BumpFeeOptions::default())
+            .await
+            .unwrap_err(),
+        client
+            .psbt_bump_fee(TXID, BumpFeeOptions::default())
+            .await
+            .unwrap_err(),
+    ] {
+        assert_eq!(
+            error.downcast_ref::<BumpFeeError>(),
+            Some(&BumpFeeError::NotReplaceable(
+                "Transaction is not BIP 125 replaceable".to_string()
+            ))
+        );
+    }
+}
+
+#[tokio::test]
+async fn fees_below_the_replacement_rules_are_typed() {
+    for message in [
+        "Insufficient total fee 0.00000141, must be at least 0.00000282 (oldFee 0.00000141 + incrementalFee 0.00000141)",
+        "New fee rate (1.00 sat/vB) is too low. It must be at least 2.00 sat/vB",
+        "New fee rate (1.00 sat/vB) is lower than the minimum fee rate (1.50 sat/vB) to get into the mempool -- ",
+    ] {
+        let node = refusing(-8, message).await;
+        let error = node
+            .client()
+            .bump_fee(TXID, BumpFeeOptions::default())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<BumpFeeError>(),
+            Some(&BumpFeeError::InsufficientFee(message.to_string())),
+            "{}",
+            message
+        );
+    }
+}
+
+#[tokio::test]
+async fn transactions_already_replaced_or_mined_are_typed() {
+    for message in [
+        "Transaction has descendants in the wallet",
+        "Transaction has descendants in the mempool",
+        "Cannot bump transaction 1111 which was already bumped by transaction 2222",
+        "Transaction has been mined, or is conflicted with a mined transaction",
+    ] {
+        let node = refusing(-8, message).await;
+        let error = node
+            .client()
+            .bump_fee(TXID, BumpFeeOptions::default())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<BumpFeeError>(),
+            Some(&BumpFeeError::AlreadyBumped(message.to_string())),
+            "{}",
+            message
+        );
+    }
+}
+
+#[tokio::test]
+async fn other_failures_pass_through_unchanged() {
+    let node = refusing(-5, "Invalid or non-wallet transaction id").await;
+    let error = node
+        .client()
+        .bump_fee(TXID, BumpFeeOptions::default())
+        .await
+        .unwrap_err();
+    assert!(error.downcast_ref::<BumpFeeError>().is_none());
+    assert_eq!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(&BitcoinRpcError::InvalidAddressOrKey(
+            "Invalid or non-wallet transaction id".to_string()
+        ))
+    );
+
+    let node = refusing(
+        -13,
+        "Error: Please enter the wallet passphrase with walletpassphrase first.",
+    )
+    .await;
+    let error = node
+        .client()
+        .psbt_bump_fee(TXID, BumpFeeOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::WalletUnlockNeeded(_))
+    ));
+}
@@ -0,0 +1,132 @@
+use bitcoin_sdk::{
+    MAINNET_POW_LIMIT_BITS, RETARGET_INTERVAL, bits_from_target, difficulty_from_bits,
+    project_retarget, retarget_bits, target_from_bits,
+};
+
+// Mainnet periods from Core's pow_tests: old bits, timestamps of the period's first and
+// last blocks, and the bits Core set for the next period
+struct Retarget {
+    last_height: u64,
+    bits: u32,
+    first_time: u64,
+    last_time: u64,
+    next_bits: u32,
+}
+
+const RETARGETS: [Retarget; 4] = [
+    // First difficulty change, at block 32256
+    Retarget {
+        last_height: 32255,
+        bits: 0x1d00ffff,
+        first_time: 1261130161,
+        last_time: 1262152739,
+        next_bits: 0x1d00d86a,
+    },
+    // Slower than two weeks at the limit already, so the limit holds
+    Retarget {
+        last_height: 2015,
+        bits: 0x1d00ffff,
+        first_time: 1231006505,
+        last_time: 1233061996,
+        next_bits: 0x1d00ffff,
+    },
+    // Under a quarter of two weeks, clamped to 4x harder
+    Retarget {
+        last_height: 68543,
+        bits: 0x1c05a3f4,
+        first_time: 1279008237,
+        last_time: 1279297671,
+        next_bits: 0x1c0168fd,
+    },
+    // Over eight weeks (the first timestamp is not a real block's), clamped to 4x easier
+    Retarget {
+        last_height: 46367,
+        bits: 0x1c387f6f,
+        first_time: 1263163443,
+        last_time: 1269211443,
+        next_bits: 0x1d00e1fd,
+    },
+];
+
+#[test]
+fn mainnet_retargets_give_the_bits_core_set() {
+    for retarget in &RETARGETS {
+        assert_eq!((retarget.last_height + 1) % RETARGET_INTERVAL, 0);
+        assert_eq!(
+            retarget_bits(
+                retarget.bits,
+                retarget.first_time,
+                retarget.last_time,
+                MAINNET_POW_LIMIT_BITS
+            ),
+            retarget.next_bits,
+            "period ending at block {}",
+            retarget.last_height
+        );
+    }
+}
+
+#[test]
+fn projection_matches_the_first_retarget() {
+    let retarget = &RETARGETS[0];
+    let average = (retarget.last_time - retarget.first_time) as f64 / 2015.0;
+    let projected = project_retarget(average);
+    let actual = difficulty_from_bits(retarget.next_bits) / difficulty_from_bits(retarget.bits);
+    // The compact encoding keeps three bytes of the target
+    assert!(
+        (projected - actual).abs() < 1e-4,
+        "{} vs {}",
+        projected,
+        actual
+    );
+    assert!((difficulty_from_bits(retarget.next_bits) - 1.182899534312841).abs() < 1e-12);
+}
+
+#[test]
+fn projection_is_clamped_to_four_times() {
+    assert_eq!(project_retarget(60.0), 4.0);
+    assert_eq!(project_retarget(6000.0), 0.25);
+    assert!((project_retarget(600.0) - 2016.0 / 2015.0).abs() < 1e-12);
+}
+
+#[test]
+fn difficulty_follows_core() {
+    assert_eq!(difficulty_from_bits(0x1d00ffff), 1.0);
+    assert_eq!(
+        difficulty_from_bits(0x1c05a3f4),
+        0xffff as f64 / 0x05a3f4 as f64 * 256.0
+    );
+    // Block 840000
+    let difficulty = difficulty_from_bits(0x17034219);
+    assert!(
+        (difficulty - 86_388_558_925_171.02).abs() < 1.0,
+        "{}",
+        difficulty
+    );
+    assert_eq!(difficulty_from_bits(0x1d000000), 0.0);
+}
+
+#[test]
+fn targets_round_trip_through_bits() {
+    let genesis = target_from_bits(0x1d00ffff);
+    assert_eq!(genesis[..6], [0, 0, 0, 0, 0xff, 0xff]);
+    assert!(genesis[6..].iter().all(|byte| *byte == 0));
+
+    for bits in [
+        0x1d00ffff, 0x1d00d86a, 0x1c05a3f4, 0x1c0168fd, 0x17034219, 0x207fffff, 0x03123456,
+    ] {
+        assert_eq!(
+            bits_from_target(&target_from_bits(bits)),
+            bits,
+            "{:#x}",
+            bits
+        );
+    }
+    // A mantissa with the sign bit set moves up a byte
+    let mut target = [0u8; 32];
+    target[29] = 0x80;
+    assert_eq!(bits_from_target(&target), 0x04008000);
+    assert_eq!(bits_from_target(&[0u8; 32]), 0);
+    // Negative encodings have no target
+    assert_eq!(target_from_bits(0x1d80ffff), [0u8; 32]);
+}
@@ -0,0 +1,207 @@
+mod common;
+
+use bitcoin_sdk::{
+    BlockDelta, BlockVerbose, OpReturnPrefixTemplate, ScriptTemplateRegistry, TemplateMatch,
+    created_outputs, created_outputs_with_templates, spent_outputs_with_templates,
+};
+use common::{MockNode, fixture_value, method_not_found};
+use serde_json::{Value, json};
+
+fn output(value: f64, n: u32) -> Value {
+    json!({
+        "value": value,
+        "n": n,
+        "scriptPubKey": {
+            "asm": "",
+            "hex": "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+            "type": "witness_v0_keyhash",
+            "address": "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+        },
+    })
+}
+
+fn block(values: &[f64]) -> BlockVerbose {
+    serde_json::from_value(block_json(values)).unwrap()
+}
+
+fn block_json(values: &[f64]) -> Value {
+    let vout: Vec<Value> = values
+        .iter()
+        .enumerate()
+        .map(|(n, value)| output(*value, n as u32))
+        .collect();
+    json!({
+        "hash": "22".repeat(32),
+        "confirmations": 1,
+        "size": 200,
+        "weight": 800,
+        "height": 101,
+        "version": 536870912,
+        "versionHex": "20000000",
+        "merkleroot": "33".repeat(32),
+        "tx": [{
+            "txid": "44".repeat(32),
+            "hash": "44".repeat(32),
+            "version": 2,
+            "size": 100,
+            "vsize": 100,
+            "weight": 400,
+            "locktime": 0,
+            "vin": [{"coinbase": "0165", "sequence": 4294967295u32}],
+            "vout": vout,
+            "hex": "",
+        }],
+        "time": 1700000000,
+        "mediantime": 1700000000,
+        "nonce": 0,
+        "bits": "207fffff",
+        "difficulty": 4.656542373906925e-10,
+        "chainwork": "00".repeat(32),
+        "nTx": 1,
+    })
+}
+
+#[test]
+fn created_outputs_carry_exact_amounts() {
+    let outputs = created_outputs(&block(&[49.99999, 0.00000001])).unwrap();
+    assert_eq!(outputs[0].amount_sat, 4_999_999_000);
+    assert_eq!(outputs[1].amount_sat, 1);
+    assert_eq!(outputs[1].outpoint.vout, 1);
+}
+
+#[test]
+fn invalid_output_values_are_errors_not_zero() {
+    for value in [21_000_001.0, 0.000000001] {
+        let error = serde_json::from_value::<BlockVerbose>(block_json(&[1.0, value]));
+        assert!(error.is_err(), "{}", value);
+    }
+    let block = block(&[1.0, -1.0]);
+    let error = created_outputs(&block).unwrap_err();
+    assert!(error.to_string().contains(":1"), "{}", error);
+    assert!(BlockDelta::from_block(&block).is_err());
+}
+
+const P2A: &str = "51024e73";
+const P2WPKH: &str = "0014751e76e8199196d454941c45d1b3a323f1433bd6";
+// OP_RETURN "omni" 0x0001
+const OMNI: &str = "6a066f6d6e690001";
+
+fn script_pub_key(hex: &str, script_type: &str) -> Value {
+    json!({"asm": "", "hex": hex, "type": script_type})
+}
+
+fn input(txid: u8, prevout: Option<(&str, &str)>) -> Value {
+    let mut vin = json!({"txid": hex::encode([txid; 32]), "vout": 0, "sequence": 4294967293u32});
+    if let Some((hex, script_type)) = prevout {
+        vin["prevout"] = json!({
+            "generated": false,
+            "height": 100,
+            "value": 0.0000024,
+            "scriptPubKey": script_pub_key(hex, script_type),
+        });
+    }
+    vin
+}
+
+// `block_json` plus a transaction spending an anchor, a P2WPKH output and an output
+// whose prevout is missing, paying an omni OP_RETURN and a P2WPKH output
+fn templated_block_json() -> Value {
+    let mut block = block_json(&[50.0]);
+    let spend = json!({
+        "txid": "55".repeat(32),
+        "hash": "55".repeat(32),
+        "version": 2,
+        "size": 100,
+        "vsize": 100,
+        "weight": 400,
+        "locktime": 0,
+        "vin": [
+            input(0x66, Some((P2A, "anchor"))),
+            input(0x77, Some((P2WPKH, "witness_v0_keyhash"))),
+            input(0x88, None),
+        ],
+        "vout": [
+            {"value": 0, "n": 0, "scriptPubKey": script_pub_key(OMNI, "nulldata")},
+            {"value": 0.001, "n": 1, "scriptPubKey": script_pub_key(P2WPKH, "witness_v0_keyhash")},
+        ],
+        "hex": "",
+    });
+    block["tx"].as_array_mut().unwrap().push(spend);
+    block["nTx"] = json!(2);
+    block
+}
+
+fn registry() -> ScriptTemplateRegistry {
+    let mut registry = ScriptTemplateRegistry::with_builtins();
+    registry.register(OpReturnPrefixTemplate::new("omni", b"omni"));
+    registry
+}
+
+fn template(name: &str, payload: &str) -> Option<TemplateMatch> {
+    Some(TemplateMatch {
+        name: name.to_string(),
+        payload: payload.to_string(),
+    })
+}
+
+#[test]
+fn created_and_spent_outputs_carry_their_template() {
+    let block: BlockVerbose = serde_json::from_value(templated_block_json()).unwrap();
+    let delta = BlockDelta::from_block_with_templates(&block, &registry()).unwrap();
+    let created: Vec<_> = delta.created.iter().map(|o| o.template.clone()).collect();
+    assert_eq!(created, [None, template("omni", "0001"), None]);
+    let spent: Vec<_> = delta
+        .spent
+        .iter()
+        .map(|o| (o.outpoint.txid[..2].to_string(), o.template.clone()))
+        .collect();
+    assert_eq!(
+        spent,
+        [
+            ("66".to_string(), template("p2a", "")),
+            ("77".to_string(), None),
+            ("88".to_string(), None),
+        ]
+    );
+    assert_eq!(
+        created_outputs_with_templates(&block, &registry()).unwrap(),
+        delta.created
+    );
+    assert_eq!(
+        spent_outputs_with_templates(&block, &registry()),
+        delta.spent
+    );
+
+    // Without a registry nothing is attributed
+    let plain = BlockDelta::from_block(&block).unwrap();
+    assert!(plain.created.iter().all(|o| o.template.is_none()));
+    assert!(plain.spent.iter().all(|o| o.template.is_none()));
+}
+
+#[tokio::test]
+async fn scans_attribute_templates_as_blocks_are_read() {
+    let node = MockNode::start(|method, params| match method {
+        "getblockchaininfo" => Ok(fixture_value("getblockchaininfo/v26.0-regtest")),
+        "getblockhash" => Ok(json!(format!("{:064x}", params[0].as_u64().unwrap()))),
+        "getblock" if params[0].as_str().unwrap().ends_with('1') => Ok(templated_block_json()),
+        "getblock" => Ok(block_json(&[50.0])),
+        _ => method_not_found(),
+    })
+    .await;
+    let scan = node
+        .client()
+        .scan_blocks_for_templates(&registry(), 0..=1)
+        .await
+        .unwrap();
+    assert_eq!(scan.clamped, None);
+    let created: Vec<_> = scan
+        .created
+        .iter()
+        .map(|o| (o.outpoint.vout, o.template.clone()))
+        .collect();
+    assert_eq!(created, [(0, template("omni", "0001"))]);
+    let spent: Vec<_> = scan.spent.iter().map(|o| o.template.clone()).collect();
+    assert_eq!(spent, [template("p2a", "")]);
+    // Spent scripts are only in blocks fetched with prevouts
+    assert!(node.calls_to("getblock").iter().all(|p| p[1] == 3));
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use bitcoin_sdk::BitcoinClient;
+use common::{MockNode, method_not_found};
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime};
+
+// A node whose first `failures` getblockcount calls fail
+async fn node(failures: u32) -> MockNode {
+    let calls = Arc::new(AtomicU32::new(0));
+    MockNode::start(move |method, _| match method {
+        "getblockcount" if calls.fetch_add(1, Ordering::SeqCst) < failures => {
+            Err((-28, "Loading block index...".to_string()))
+        }
+        "getblockcount" => Ok(json!(101)),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn client(node: &MockNode, interval: Duration) -> BitcoinClient {
+    BitcoinClient::builder(node.url())
+        .auth("user", "pass")
+        .keepalive(interval)
+        .build()
+        .unwrap()
+}
+
+async fn wait_for_pings(node: &MockNode, count: usize) {
+    for _ in 0..200 {
+        if node.calls_to("getblockcount").len() >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("expected {} pings", count);
+}
+
+#[tokio::test]
+async fn pings_stop_once_every_clone_is_dropped() {
+    let node = node(0).await;
+    let client = client(&node, Duration::from_millis(20));
+    let clone = client.clone();
+    let started = SystemTime::now();
+    wait_for_pings(&node, 3).await;
+    assert!(client.last_keepalive().unwrap() >= started);
+
+    drop(client);
+    // One clone is enough to keep it going
+    let pings = node.calls_to("getblockcount").len();
+    wait_for_pings(&node, pings + 2).await;
+    assert!(clone.last_keepalive().is_some());
+
+    drop(clone);
+    // Let a ping already in flight finish
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let pings = node.calls_to("getblockcount").len();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(node.calls_to("getblockcount").len(), pings);
+}
+
+#[tokio::test]
+async fn failed_pings_leave_the_last_success_alone() {
+    let node = node(1).await;
+    // The first ping goes out at once and fails; the next is an hour away
+    let client = client(&node, Duration::from_secs(3600));
+    wait_for_pings(&node, 1).await;
+    assert_eq!(client.last_keepalive(), None);
+
+    let before = SystemTime::now();
+    client.warm_up().await.unwrap();
+    let after = client.last_keepalive().unwrap();
+    assert!(after >= before && after <= SystemTime::now());
+}
+
+#[tokio::test]
+async fn clients_without_a_keepalive_report_none() {
+    let node = node(0).await;
+    let client = node.client();
+    client.warm_up().await.unwrap();
+    assert_eq!(client.last_keepalive(), None);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(node.calls_to("getblockcount").len(), 1);
+}
+
+#[test]
+fn building_a_keepalive_outside_a_runtime_is_an_error() {
+    let error = BitcoinClient::builder("http://127.0.0.1:8332")
+        .keepalive(Duration::from_secs(30))
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "A keepalive can only be started inside a tokio runtime"
+    );
+    // Without one, building needs no runtime
+    BitcoinClient::builder("http://127.0.0.1:8332")
+        .build()
+        .unwrap();
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use bitcoin_sdk::AddressPurpose;
+use common::{MockNode, method_not_found};
+use serde_json::json;
+
+#[tokio::test]
+async fn list_labels_sends_purpose_as_only_argument() {
+    let node = MockNode::start(|method, _| match method {
+        "listlabels" => Ok(json!(["donations"])),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    assert_eq!(
+        client.list_labels(Some("receive")).await.unwrap(),
+        vec!["donations"]
+    );
+    client.list_labels(None).await.unwrap();
+    assert_eq!(
+        node.calls_to("listlabels"),
+        vec![json!(["receive"]), json!(null)]
+    );
+}
+
+#[test]
+fn address_purpose_compares_with_old_strings() {
+    let purpose: AddressPurpose = serde_json::from_value(json!("send")).unwrap();
+    assert_eq!(purpose, AddressPurpose::Send);
+    assert!(purpose == "send");
+    assert_eq!(purpose.to_string(), "send");
+    let purpose: AddressPurpose = serde_json::from_value(json!("something-new")).unwrap();
+    assert_eq!(purpose, AddressPurpose::Unknown);
+}
+
+#[tokio::test]
+async fn unused_label_has_no_addresses() {
+    let node = MockNode::start(|method, params| match method {
+        "getaddressesbylabel" => Err((-11, format!("No addresses with label {}", params[0]))),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    assert!(
+        client
+            .labeled("fresh")
+            .addresses()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn other_label_errors_pass_through() {
+    let node = MockNode::start(|method, _| match method {
+        "getaddressesbylabel" => Err((-18, "No wallet is loaded.".to_string())),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    assert!(client.labeled("fresh").addresses().await.is_err());
+}
+
+fn received(address: &str) -> serde_json::Value {
+    json!({
+        "address": address,
+        "category": "receive",
+        "amount": 0.1,
+        "label": "ops",
+        "vout": 0,
+        "confirmations": 1,
+        "txid": "aa".repeat(32),
+        "time": 1700000000,
+        "timereceived": 1700000000,
+    })
+}
+
+#[tokio::test]
+async fn labeled_change_and_transactions_stay_in_the_label() {
+    let node = MockNode::start(|method, _| match method {
+        "getaddressesbylabel" => Ok(json!({"bcrt1qops": {"purpose": "receive"}})),
+        "getrawchangeaddress" => Ok(json!("bcrt1qchange")),
+        "setlabel" => Ok(json!(null)),
+        "listtransactions" => Ok(json!([received("bcrt1qops"), received("bcrt1qother")])),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    let ops = client.labeled("ops");
+    ops.addresses().await.unwrap();
+
+    let change = ops.get_raw_change_address(Some("bech32")).await.unwrap();
+    assert_eq!(node.calls_to("getrawchangeaddress"), [json!(["bech32"])]);
+    assert_eq!(node.calls_to("setlabel"), [json!([change, "ops"])]);
+    assert!(ops.addresses().await.unwrap().contains(&change));
+
+    let transactions = ops.list_transactions(10, 0, true).await.unwrap();
+    assert_eq!(
+        node.calls_to("listtransactions"),
+        [json!(["ops", 10, 0, true])]
+    );
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].address.as_deref(), Some("bcrt1qops"));
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use bitcoin_sdk::Amount;
+use common::{MockNode, method_not_found};
+use serde_json::json;
+
+// One input, two outputs
+const TX: &str = "010000000136641869ca081e70f394c6948e8af409e18b619df2ed74aa106c1ca29787b96e0100000000ffffffff0200e9a435000000001976a914389ffce9cd9ae88dcc0631e88a821ffdbe9bfe2688acc0832f05000000001976a9147480a33f950689af511e6e84c138dbbd3c3ee41588ac00000000";
+
+async fn node() -> MockNode {
+    MockNode::start(|method, _| match method {
+        "submitpackage" => Ok(json!({
+            "package_msg": "success",
+            "tx-results": {},
+            "replaced-transactions": [],
+        })),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn submit_package_sends_exact_limits() {
+    let node = node().await;
+    let client = node.client();
+    client
+        .submit_package(
+            &[TX],
+            Some(Amount::from_sat(10_000_000)),
+            Some(Amount::from_sat(1)),
+        )
+        .await
+        .unwrap();
+    client
+        .submit_package(&[TX], Some(Amount::ZERO), None)
+        .await
+        .unwrap();
+    client.submit_package(&[TX], None, None).await.unwrap();
+    let bodies = node.bodies();
+    assert!(bodies[0].ends_with(&format!(
+        "\"params\":[[\"{}\"],0.10000000,0.00000001]}}",
+        TX
+    )));
+    assert!(bodies[1].ends_with(&format!("\"params\":[[\"{}\"],0.00000000]}}", TX)));
+    assert!(bodies[2].ends_with(&format!("\"params\":[[\"{}\"]]}}", TX)));
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use bitcoin_sdk::{BitcoinRpcError, ImportMempoolOptions};
+use common::{MockNode, method_not_found, network_info};
+use serde_json::{Value, json};
+
+async fn node(version: u32) -> MockNode {
+    MockNode::start(move |method, _| match method {
+        "getnetworkinfo" => network_info(version),
+        "importmempool" => Ok(json!({})),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn import_mempool_before_25_is_method_not_found() {
+    let node = node(240100).await;
+    let error = node
+        .client()
+        .import_mempool("/tmp/mempool.dat", ImportMempoolOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::MethodNotFound(_))
+    ));
+    assert!(node.calls_to("importmempool").is_empty());
+}
+
+#[tokio::test]
+async fn import_mempool_sends_options_only_when_set() {
+    let node = node(250000).await;
+    let client = node.client();
+    client
+        .import_mempool("/tmp/mempool.dat", ImportMempoolOptions::default())
+        .await
+        .unwrap();
+    client
+        .import_mempool(
+            "/tmp/mempool.dat",
+            ImportMempoolOptions::default().use_current_time(false),
+        )
+        .await
+        .unwrap();
+    let calls: Vec<Value> = node.calls_to("importmempool");
+    assert_eq!(calls[0], json!(["/tmp/mempool.dat"]));
+    assert_eq!(
+        calls[1],
+        json!(["/tmp/mempool.dat", {"use_current_time": false}])
+    );
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use bitcoin_sdk::{MempoolChange, MempoolMirror, SequenceEvent, SequenceKind};
+use common::{MockNode, block_header, method_not_found};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+// What `getrawmempool` currently reports: txids and the next mempool sequence
+type Mempool = Arc<Mutex<(Vec<String>, u64)>>;
+
+fn txid(n: u8) -> String {
+    hex::encode([n; 32])
+}
+
+const BLOCK: &str = "0000000000000000000000000000000000000000000000000000000000000b0b";
+
+// A node whose block BLOCK confirms txids 1 and 2
+async fn node(mempool: &Mempool) -> MockNode {
+    let mempool = mempool.clone();
+    MockNode::start(move |method, params| match method {
+        "getrawmempool" => {
+            let (txids, sequence) = mempool.lock().unwrap().clone();
+            Ok(json!({"txids": txids, "mempool_sequence": sequence}))
+        }
+        "getblock" => {
+            let mut block = block_header(params[0].as_str().unwrap(), 100);
+            block["size"] = json!(400);
+            block["weight"] = json!(1600);
+            block["tx"] = json!([txid(0), txid(1), txid(2)]);
+            Ok(block)
+        }
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn mempool(txids: &[u8], sequence: u64) -> Mempool {
+    Arc::new(Mutex::new((
+        txids.iter().map(|n| txid(*n)).collect(),
+        sequence,
+    )))
+}
+
+fn added(n: u8, mempool_sequence: u64, zmq_sequence: u32) -> SequenceEvent {
+    SequenceEvent {
+        hash: txid(n),
+        kind: SequenceKind::TxAdded { mempool_sequence },
+        zmq_sequence: Some(zmq_sequence),
+    }
+}
+
+fn removed(n: u8, mempool_sequence: u64, zmq_sequence: u32) -> SequenceEvent {
+    SequenceEvent {
+        hash: txid(n),
+        kind: SequenceKind::TxRemoved { mempool_sequence },
+        zmq_sequence: Some(zmq_sequence),
+    }
+}
+
+#[tokio::test]
+async fn events_at_the_snapshot_sequence_are_applied() {
+    // The snapshot's sequence has not been used yet, so the event carrying it is new
+    let state = mempool(&[1], 10);
+    let node = node(&state).await;
+    let mut mirror = MempoolMirror::new(node.client()).await.unwrap();
+    assert_eq!(
+        mirror.apply(added(2, 10, 0)).await.unwrap(),
+        [MempoolChange::Added(txid(2))]
+    );
+    assert_eq!(
+        mirror.apply(removed(1, 11, 1)).await.unwrap(),
+        [MempoolChange::Removed(txid(1))]
+    );
+    assert_eq!(mirror.txids().len(), 1);
+    assert!(mirror.contains(&txid(2)));
+    assert_eq!(node.calls_to("getrawmempool"), [json!([false, true])]);
+}
+
+#[tokio::test]
+async fn events_older_than_the_snapshot_are_skipped() {
+    let state = mempool(&[1], 10);
+    let node = node(&state).await;
+    let mut mirror = MempoolMirror::new(node.client()).await.unwrap();
+    let mut changes = mirror.subscribe();
+    // Both happened before the snapshot was taken; the removal must not undo txid 1
+    assert!(mirror.apply(added(1, 8, 0)).await.unwrap().is_empty());
+    assert!(mirror.apply(removed(1, 9, 1)).await.unwrap().is_empty());
+    assert!(mirror.contains(&txid(1)));
+    assert!(changes.try_recv().is_err());
+    assert_eq!(node.calls_to("getrawmempool").len(), 1);
+}
+
+#[tokio::test]
+async fn a_zmq_gap_triggers_a_resync() {
+    let state = mempool(&[1, 2], 10);
+    let node = node(&state).await;
+    let mut mirror = MempoolMirror::new(node.client()).await.unwrap();
+    mirror.apply(added(3, 10, 5)).await.unwrap();
+
+    // Messages 6 and 7 were dropped; meanwhile 1 left and 4 arrived
+    *state.lock().unwrap() = (vec![txid(2), txid(3), txid(4)], 14);
+    let changes = mirror.apply(added(5, 13, 8)).await.unwrap();
+    assert_eq!(
+        changes,
+        [
+            MempoolChange::Removed(txid(1)),
+            MempoolChange::Added(txid(4)),
+            MempoolChange::Resynced
+        ]
+    );
+    assert_eq!(node.calls_to("getrawmempool").len(), 2);
+    // The resync's snapshot already covers sequence 13, and 14 follows on
+    assert!(mirror.apply(removed(2, 13, 9)).await.unwrap().is_empty());
+    assert_eq!(
+        mirror.apply(added(6, 14, 10)).await.unwrap(),
+        [MempoolChange::Added(txid(6))]
+    );
+    assert_eq!(mirror.len(), 4);
+}
+
+#[tokio::test]
+async fn a_mempool_sequence_jump_triggers_a_resync() {
+    let state = mempool(&[1], 10);
+    let node = node(&state).await;
+    let mut mirror = MempoolMirror::new(node.client()).await.unwrap();
+    *state.lock().unwrap() = (vec![txid(1), txid(2), txid(3)], 13);
+    // The ZMQ counter is contiguous, but sequences 10 and 11 never arrived
+    let changes = mirror.apply(added(3, 12, 0)).await.unwrap();
+    assert_eq!(changes.last(), Some(&MempoolChange::Resynced));
+    assert_eq!(mirror.len(), 3);
+    assert_eq!(node.calls_to("getrawmempool").len(), 2);
+}
+
+#[tokio::test]
+async fn connected_blocks_remove_their_transactions() {
+    let state = mempool(&[1, 2, 3], 10);
+    let node = node(&state).await;
+    let mut mirror = MempoolMirror::new(node.client()).await.unwrap();
+    let connected = SequenceEvent {
+        hash: BLOCK.to_string(),
+        kind: SequenceKind::BlockConnected,
+        zmq_sequence: Some(0),
+    };
+    assert_eq!(
+        mirror.apply(connected).await.unwrap(),
+        [MempoolChange::Confirmed {
+            block_hash: BLOCK.to_string(),
+            txids: vec![txid(1), txid(2)],
+        }]
+    );
+    assert_eq!(mirror.txids().iter().collect::<Vec<_>>(), [&txid(3)]);
+    assert_eq!(node.calls_to("getblock")[0][0], json!(BLOCK));
+    // The two confirmations used sequences 10 and 11 without 'R' events
+    assert_eq!(
+        mirror.apply(added(4, 12, 1)).await.unwrap(),
+        [MempoolChange::Added(txid(4))]
+    );
+    assert_eq!(node.calls_to("getrawmempool").len(), 1);
+}
@@ -0,0 +1,205 @@
+use bitcoin_sdk::{BlockHeader, MerkleMatch, Serialization};
+use serde_json::json;
+
+// Block 170, the first block with a spend: the coinbase and Satoshi's payment to Hal Finney
+const BLOCK_170: &str = "00000000d1145790a8694403d4063f323d499e655c83426834d4ce2f8dd4a2ee";
+const HEADER_170: &str = "0100000055bd840a78798ad0da853f68974f3d183e2bd1db6a842c1feecf222a00000000ff104ccb05421ab93e63f8c3ce5c2c2e9dbb37de2764b3a3175c8166562cac7d51b96a49ffff001d283e9e70";
+const ROOT_170: &str = "7dac2c5666815c17a3b36427de37bb9d2e2c5ccec3f8633eb91a4205cb4c10ff";
+const COINBASE_170: &str = "b1fea52486ce0c62bb442b530a3f0132b826c74e473d1f2c220bfa78111c5082";
+const PAYMENT_170: &str = "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16";
+
+// `gettxoutproof '["f4184f…"]'` on mainnet
+const PROOF_170: &str = "0100000055bd840a78798ad0da853f68974f3d183e2bd1db6a842c1feecf222a00000000ff104ccb05421ab93e63f8c3ce5c2c2e9dbb37de2764b3a3175c8166562cac7d51b96a49ffff001d283e9e70020000000282501c1178fa0b222c1f3d474ec726b832013f0a532b44bb620cce8624a5feb1169e1e83e930853391bc6f35f605c6754cfead57cf8387639d3b4096c54f18f40105";
+
+// `gettxoutproof` for the genesis coinbase, a one-leaf tree
+const GENESIS_PROOF: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c01000000013ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a0101";
+
+fn header(hash: &str, merkleroot: &str) -> BlockHeader {
+    serde_json::from_value(json!({
+        "hash": hash,
+        "confirmations": 800000,
+        "height": 170,
+        "version": 1,
+        "versionHex": "00000001",
+        "merkleroot": merkleroot,
+        "time": 1231731025,
+        "mediantime": 1231716245,
+        "nonce": 1889418792,
+        "bits": "1d00ffff",
+        "difficulty": 1.0,
+        "chainwork": "000000000000000000000000000000000000000000000000000000ab00ab00ab",
+        "nTx": 2,
+    }))
+    .unwrap()
+}
+
+// A merkle block with block 170's header and the given tree
+fn proof(total: u32, txids: &[&str], flags: &[u8]) -> String {
+    let mut bytes = hex::decode(HEADER_170).unwrap();
+    bytes.extend(total.to_le_bytes());
+    bytes.extend(Serialization::serialize_varint(txids.len() as u64));
+    for txid in txids {
+        bytes.extend(Serialization::hex_to_hash(txid).unwrap());
+    }
+    bytes.extend(Serialization::serialize_bytes(flags));
+    hex::encode(bytes)
+}
+
+fn rejection(proof_hex: &str) -> String {
+    Serialization::deserialize_merkle_block(proof_hex)
+        .unwrap()
+        .extract_matches()
+        .unwrap_err()
+        .to_string()
+}
+
+#[test]
+fn core_proofs_verify() {
+    assert_eq!(proof(2, &[COINBASE_170, PAYMENT_170], &[0b101]), PROOF_170);
+    let txids =
+        Serialization::verify_tx_out_proof(PROOF_170, &header(BLOCK_170, ROOT_170)).unwrap();
+    assert_eq!(txids, [PAYMENT_170]);
+
+    let block = Serialization::deserialize_merkle_block(PROOF_170).unwrap();
+    assert_eq!(block.header.block_hash(), BLOCK_170);
+    assert_eq!(block.total_transactions, 2);
+    let (root, matches) = block.extract_matches().unwrap();
+    assert_eq!(Serialization::hash_to_hex(&root), ROOT_170);
+    assert_eq!(
+        matches,
+        [MerkleMatch {
+            txid: PAYMENT_170.to_string(),
+            index: 1,
+        }]
+    );
+}
+
+#[test]
+fn a_single_transaction_block_proves_its_coinbase() {
+    let block = Serialization::deserialize_merkle_block(GENESIS_PROOF).unwrap();
+    assert_eq!(
+        block.header.block_hash(),
+        "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+    );
+    let (root, matches) = block.extract_matches().unwrap();
+    let coinbase = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+    assert_eq!(Serialization::hash_to_hex(&root), coinbase);
+    assert_eq!(matches.len(), 1);
+    assert_eq!((matches[0].txid.as_str(), matches[0].index), (coinbase, 0));
+}
+
+#[test]
+fn a_proof_for_another_block_is_refused() {
+    let other = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+    let error =
+        Serialization::verify_tx_out_proof(PROOF_170, &header(other, ROOT_170)).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!("Proof is for block {}, expected {}", BLOCK_170, other)
+    );
+}
+
+#[test]
+fn a_forged_tree_is_refused_even_when_merkleroot_agrees() {
+    // A tree proving a made-up txid, with a JSON header lying about the root to match it
+    let forged = "aa".repeat(32);
+    let forged_proof = proof(2, &[COINBASE_170, &forged], &[0b101]);
+    let block = Serialization::deserialize_merkle_block(&forged_proof).unwrap();
+    let (forged_root, _) = block.extract_matches().unwrap();
+    let lying = header(BLOCK_170, &Serialization::hash_to_hex(&forged_root));
+
+    let error = Serialization::verify_tx_out_proof(&forged_proof, &lying).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .ends_with(&format!("does not match its header's {}", ROOT_170)),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn a_header_with_another_merkleroot_is_refused() {
+    let error = Serialization::verify_tx_out_proof(PROOF_170, &header(BLOCK_170, &"bb".repeat(32)))
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Proof merkle root {} does not match {}",
+            ROOT_170,
+            "bb".repeat(32)
+        )
+    );
+}
+
+#[test]
+fn unused_hashes_and_flag_bits_are_refused() {
+    // A clear root bit ends the walk at the first hash
+    let extra_hash = proof(3, &[ROOT_170, COINBASE_170, PAYMENT_170], &[0]);
+    assert_eq!(rejection(&extra_hash), "Unused hashes in merkle block");
+    let extra_flags = proof(2, &[COINBASE_170, PAYMENT_170], &[0b101, 0]);
+    assert_eq!(rejection(&extra_flags), "Unused flag bytes in merkle block");
+    let missing_hash = proof(2, &[COINBASE_170], &[0b101]);
+    assert_eq!(rejection(&missing_hash), "Merkle block ran out of hashes");
+    let too_many = proof(1, &[COINBASE_170, PAYMENT_170], &[0b101]);
+    assert_eq!(rejection(&too_many), "More hashes than transactions");
+    let no_transactions = proof(0, &[], &[]);
+    assert_eq!(
+        rejection(&no_transactions),
+        "Merkle block has no transactions"
+    );
+}
+
+#[test]
+fn duplicated_siblings_are_refused() {
+    // CVE-2012-2459: a three-transaction block hashes like one that repeats its last
+    // transaction, so a proof of the repeat must not be accepted
+    let (a, b, c) = ("11".repeat(32), "22".repeat(32), "33".repeat(32));
+    // Descend to the right pair (c, c) and match the second c
+    let duplicated = proof(
+        4,
+        &[&Serialization::hash_to_hex(&node(&a, &b)), &c, &c],
+        &[0b1101],
+    );
+    assert_eq!(
+        rejection(&duplicated),
+        "Duplicate sibling hashes in merkle block"
+    );
+    // The honest three-leaf tree pairs c with itself implicitly and is fine
+    let honest = proof(
+        3,
+        &[&Serialization::hash_to_hex(&node(&a, &b)), &c],
+        &[0b1101],
+    );
+    let (_, matches) = Serialization::deserialize_merkle_block(&honest)
+        .unwrap()
+        .extract_matches()
+        .unwrap();
+    assert_eq!(
+        (matches[0].txid.as_str(), matches[0].index),
+        (c.as_str(), 2)
+    );
+}
+
+#[test]
+fn malformed_merkle_blocks_do_not_parse() {
+    let error = Serialization::deserialize_merkle_block(&format!("{}00", PROOF_170)).unwrap_err();
+    assert_eq!(error.to_string(), "Trailing data after merkle block");
+    // Claims 255 hashes but carries two
+    let inflated = PROOF_170
+        .replacen("0200000002", "02000000fd ff00", 1)
+        .replace(' ', "");
+    let error = Serialization::deserialize_merkle_block(&inflated).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Merkle block hash count exceeds its size"
+    );
+    assert!(Serialization::deserialize_merkle_block(&PROOF_170[..150]).is_err());
+}
+
+// Parent of two txids given in display order
+fn node(left: &str, right: &str) -> [u8; 32] {
+    let left = Serialization::hex_to_hash(left).unwrap();
+    let right = Serialization::hex_to_hash(right).unwrap();
+    Serialization::merkle_root(&[left, right])
+}
@@ -0,0 +1,102 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
+use bitcoin_sdk::{BitcoinClientType, BitcoinCrypto};
+
+// From Core's util_tests (message_sign / message_verify), signed with a compressed key
+const ADDRESS: &str = "15CRxFdyRpGZLW9w8HnHvVduizdL5jKNbs";
+const SIGNATURE: &str =
+    "IPojfrX2dfPnH26UegfbGQQLrdK844DlHq5157/P6h57WyuS/Qsl+h/WSVGDF4MUi4rWSswW38oimDYfNNUBUOk=";
+const MESSAGE: &str = "Trust no one";
+
+fn error(address: &str, signature: &str) -> String {
+    BitcoinCrypto::verify_message(address, signature, MESSAGE)
+        .unwrap_err()
+        .to_string()
+}
+
+// SIGNATURE with its header byte replaced
+fn with_header(header: u8) -> String {
+    let mut bytes = BASE64_STANDARD.decode(SIGNATURE).unwrap();
+    bytes[0] = header;
+    BASE64_STANDARD.encode(bytes)
+}
+
+#[test]
+fn core_signatures_verify() {
+    assert!(BitcoinCrypto::verify_message(ADDRESS, SIGNATURE, MESSAGE).unwrap());
+    // `signmessagewithprivkey` in Core's rpc_signmessage.py, on testnet
+    assert!(
+        BitcoinCrypto::verify_message(
+            "mpLQjfK79b7CCV4VMJWEWAj5Mpx8Up5zxB",
+            "INbVnW4e6PeRmsv2Qgu8NuopvrVjkcxob+sX8OcZG0SALhWybUjzMLPdAsXI46YZGb0KQTRii+wWIQzRpG/U+S0=",
+            "This is just a test message"
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn uncompressed_keys_verify_against_their_own_address() {
+    // `signmessagewithprivkey 5HxWvvfubhXpYYpS3tJkw6fq9jE9j18THftkZjHHfmFiWtmAbrj`, the
+    // uncompressed key from Core's key_tests; signing is deterministic (RFC 6979)
+    let signature =
+        "G6K+vySgPvNxcHZLUC5m/+0vC1MgFSd0ZSieNxlIwO/vbd8ma0aOKJ675deM4hamuy52Di+4sHY5J4+6Yw4LquA=";
+    assert!(
+        BitcoinCrypto::verify_message("1QFqqMUD55ZV3PJEJZtaKCsQmjLT6JkjvJ", signature, MESSAGE)
+            .unwrap()
+    );
+    // The same key's compressed address is a different address
+    assert!(
+        !BitcoinCrypto::verify_message("1NoJrossxPBKfCHuJXT4HadJrXRE9Fxiqs", signature, MESSAGE)
+            .unwrap()
+    );
+}
+
+#[test]
+fn a_different_message_or_address_is_false() {
+    assert!(!BitcoinCrypto::verify_message(ADDRESS, SIGNATURE, "I never signed this").unwrap());
+    let other =
+        BitcoinCrypto::hash160_to_p2pkh_address(&[7; 20], BitcoinClientType::Mainnet).unwrap();
+    assert!(!BitcoinCrypto::verify_message(&other, SIGNATURE, MESSAGE).unwrap());
+    // A header claiming an uncompressed key recovers a key with another hash
+    assert!(!BitcoinCrypto::verify_message(ADDRESS, &with_header(27), MESSAGE).unwrap());
+}
+
+#[test]
+fn only_p2pkh_addresses_can_be_checked() {
+    let segwit = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+    assert_eq!(
+        error(segwit, SIGNATURE),
+        format!(
+            "Message signing only supports P2PKH addresses, {} is segwit",
+            segwit
+        )
+    );
+    let p2sh =
+        BitcoinCrypto::hash160_to_p2sh_address(&[7; 20], BitcoinClientType::Mainnet).unwrap();
+    assert_eq!(
+        error(&p2sh, SIGNATURE),
+        format!(
+            "Message signing only supports P2PKH addresses, got {}",
+            p2sh
+        )
+    );
+}
+
+#[test]
+fn malformed_signatures_are_errors() {
+    assert!(
+        error(ADDRESS, "not base64!").starts_with("Malformed base64 signature: "),
+        "{}",
+        error(ADDRESS, "not base64!")
+    );
+    let short = BASE64_STANDARD.encode(&BASE64_STANDARD.decode(SIGNATURE).unwrap()[..64]);
+    assert_eq!(error(ADDRESS, &short), "Signature must be 65 bytes, got 64");
+    assert_eq!(
+        error(ADDRESS, &with_header(26)),
+        "Invalid signature header: 0x1a"
+    );
+    assert_eq!(
+        error(ADDRESS, &with_header(35)),
+        "Invalid signature header: 0x23"
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use bitcoin_sdk::BitcoinClient;
+use bitcoin_sdk::testing::CallCounter;
+use common::{MockNode, block_header, method_not_found};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+async fn node() -> MockNode {
+    MockNode::start(|method, params| match method {
+        "getblockcount" => Ok(json!(110)),
+        "getbestblockhash" => Ok(json!("aa".repeat(32))),
+        "getblockheader" => Ok(block_header(params[0].as_str().unwrap(), 100)),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn counted(node: &MockNode) -> (BitcoinClient, Arc<CallCounter>) {
+    let counter = Arc::new(CallCounter::new());
+    let client = BitcoinClient::builder(node.url())
+        .auth("user", "pass")
+        .with_middleware(counter.clone())
+        .build()
+        .unwrap();
+    (client, counter)
+}
+
+#[tokio::test]
+async fn counts_single_calls() {
+    let node = node().await;
+    let (client, counter) = counted(&node);
+    client.get_block_count().await.unwrap();
+    client.get_block_count().await.unwrap();
+    client.get_best_block_hash().await.unwrap();
+    assert_eq!(counter.count("getblockcount"), 2);
+    assert_eq!(counter.count("getbestblockhash"), 1);
+    assert_eq!(counter.total(), 3);
+}
+
+#[tokio::test]
+async fn batch_entries_pass_through_middleware() {
+    let node = node().await;
+    let (client, counter) = counted(&node);
+    let results = client
+        .batch_call(vec![
+            ("getblockcount".to_string(), Value::Null),
+            ("getbestblockhash".to_string(), Value::Null),
+            ("getblockcount".to_string(), Value::Null),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(results[0], json!(110));
+    assert_eq!(results[1], json!("aa".repeat(32)));
+    assert_eq!(counter.count("getblockcount"), 2);
+    assert_eq!(counter.count("getbestblockhash"), 1);
+
+    let mut batch = client.batch();
+    let count = batch.get_block_count();
+    let header = batch.get_block_header(&"bb".repeat(32));
+    let response = batch.send().await.unwrap();
+    assert_eq!(response.get(count).unwrap(), 110);
+    assert_eq!(response.get(header).unwrap().height, 100);
+    assert_eq!(counter.count("getblockcount"), 3);
+    assert_eq!(counter.count("getblockheader"), 1);
+}
+
+#[tokio::test]
+async fn internal_batch_users_pass_through_middleware() {
+    let node = node().await;
+    let (client, counter) = counted(&node);
+    let hashes = ["01".repeat(32), "02".repeat(32)];
+    let hashes: Vec<&str> = hashes.iter().map(String::as_str).collect();
+    let headers = client.get_block_headers_batch(&hashes).await.unwrap();
+    assert_eq!(headers[1].hash, hashes[1]);
+    assert_eq!(counter.count("getblockheader"), 2);
+}
+
+#[tokio::test]
+async fn batches_stay_one_request_without_middleware() {
+    let node = node().await;
+    let client = node.client();
+    client
+        .batch_call(vec![
+            ("getblockcount".to_string(), Value::Null),
+            ("getbestblockhash".to_string(), Value::Null),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(node.bodies().len(), 1);
+    assert_eq!(node.calls().len(), 2);
+}
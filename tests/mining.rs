@@ -0,0 +1,130 @@
+mod common;
+
+use bitcoin_sdk::{BitcoinRpcError, RawBlock, RawBlockHeader};
+use common::{MockNode, method_not_found};
+use serde_json::json;
+
+fn header() -> RawBlockHeader {
+    RawBlockHeader {
+        version: 0x20000000,
+        prev_blockhash: [0x11; 32],
+        merkle_root: [0x22; 32],
+        time: 1_700_000_000,
+        bits: 0x207fffff,
+        nonce: 7,
+    }
+}
+
+// Accepts the first block it sees and calls any later one a duplicate; refuses headers
+// of version 0 as orphans
+async fn node() -> MockNode {
+    let seen = std::sync::Mutex::new(Vec::new());
+    MockNode::start(move |method, params| match method {
+        "submitblock" => {
+            let mut seen = seen.lock().unwrap();
+            if seen.contains(&params[0]) {
+                Ok(json!("duplicate"))
+            } else if params[0].as_str().unwrap().len() < 162 {
+                Ok(json!("bad-blk-length"))
+            } else {
+                seen.push(params[0].clone());
+                Ok(json!(null))
+            }
+        }
+        "submitheader" if params[0].as_str().unwrap().starts_with("00000000") => {
+            Err((-25, "Must submit previous header (11) first".to_string()))
+        }
+        "submitheader" => Ok(json!(null)),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn submit_block_reports_acceptance_as_none() {
+    let node = node().await;
+    let client = node.client();
+    let block = hex::encode(
+        RawBlock {
+            header: header(),
+            transactions: Vec::new(),
+        }
+        .serialize(),
+    );
+    assert_eq!(client.submit_block(&block).await.unwrap(), None);
+    assert_eq!(
+        client.submit_block(&block).await.unwrap().as_deref(),
+        Some("duplicate")
+    );
+    // Upper-case hex is hex too
+    assert_eq!(
+        client
+            .submit_block(&block[..160].to_uppercase())
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("bad-blk-length")
+    );
+    assert_eq!(node.calls_to("submitblock")[0], json!([block]));
+}
+
+#[tokio::test]
+async fn malformed_block_hex_is_refused_unsent() {
+    let node = node().await;
+    let client = node.client();
+    for bad in ["0", "zz", "00g0", "0x00"] {
+        let error = client.submit_block(bad).await.unwrap_err();
+        assert!(
+            error.to_string().starts_with("Block is not valid hex: "),
+            "{}",
+            error
+        );
+    }
+    assert!(node.bodies().is_empty());
+}
+
+#[tokio::test]
+async fn submit_header_sends_80_byte_headers() {
+    let node = node().await;
+    let client = node.client();
+    let valid = hex::encode(header().serialize());
+    client.submit_header(&valid).await.unwrap();
+    assert_eq!(node.calls_to("submitheader"), [json!([valid])]);
+
+    // Rejections by the node stay RPC errors
+    let mut orphan = header();
+    orphan.version = 0;
+    let error = client
+        .submit_header(&hex::encode(orphan.serialize()))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::VerifyError(_))
+    ));
+}
+
+#[tokio::test]
+async fn headers_that_are_not_80_bytes_of_hex_are_refused_unsent() {
+    let node = node().await;
+    let client = node.client();
+    let valid = hex::encode(header().serialize());
+    let long = format!("{}00", valid);
+    for (bad, message) in [
+        (&valid[..158], "Header must be 80 bytes, got 79"),
+        (long.as_str(), "Header must be 80 bytes, got 81"),
+        ("", "Header must be 80 bytes, got 0"),
+    ] {
+        let error = client.submit_header(bad).await.unwrap_err();
+        assert_eq!(error.to_string(), message);
+    }
+    for bad in [&valid[1..], "zz"] {
+        let error = client.submit_header(bad).await.unwrap_err();
+        assert!(
+            error.to_string().starts_with("Header is not valid hex: "),
+            "{}",
+            error
+        );
+    }
+    assert!(node.bodies().is_empty());
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use bitcoin_sdk::MultisigError;
+use common::{MockNode, method_not_found};
+use serde_json::json;
+
+// G, 2G and 3G, compressed
+const KEYS: [&str; 3] = [
+    "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+    "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+    "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+];
+const UNCOMPRESSED: &str = "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+
+async fn node() -> MockNode {
+    MockNode::start(|method, params| match method {
+        "createmultisig" | "addmultisigaddress" => {
+            let uncompressed = params[1]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|key| key.as_str().unwrap().starts_with("04"));
+            // Core falls back to a legacy address and says why
+            let warnings = if uncompressed {
+                json!([
+                    "Unable to make chosen address type, please ensure no uncompressed public keys are present."
+                ])
+            } else {
+                json!([])
+            };
+            Ok(json!({
+                "address": "2N3oefVeg6stiTb5Kh3ozCSkaqmx91FDbsm",
+                "redeemScript": "5221...53ae",
+                "descriptor": "sh(multi(2,...))#00000000",
+                "warnings": warnings,
+            }))
+        }
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn thresholds_the_keys_cannot_meet_are_refused_unsent() {
+    let node = node().await;
+    let client = node.client();
+    for (required, keys) in [(3, &KEYS[..2]), (0, &KEYS[..]), (1, &[][..])] {
+        let error = client
+            .create_multisig(required, keys, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<MultisigError>(),
+            Some(&MultisigError::InvalidThreshold {
+                required,
+                keys: keys.len(),
+            })
+        );
+        let error = client
+            .add_multisig_address(required, keys, None, None)
+            .await
+            .unwrap_err();
+        assert!(error.downcast_ref::<MultisigError>().is_some());
+    }
+    // More keys than CHECKMULTISIG allows
+    let many = [KEYS[0]; 21];
+    let error = client.create_multisig(2, &many, None).await.unwrap_err();
+    assert_eq!(error.to_string(), "2 signatures required from 21 keys");
+    assert!(node.bodies().is_empty());
+}
+
+#[tokio::test]
+async fn keys_that_are_not_hex_public_keys_are_refused_unsent() {
+    let node = node().await;
+    let client = node.client();
+    let not_on_curve = format!("02{}", "00".repeat(32));
+    let misprefixed = format!("02{}", &UNCOMPRESSED[2..]);
+    for bad in [
+        "zz",
+        // Odd length
+        &KEYS[0][1..],
+        // Too short
+        &KEYS[0][..64],
+        // A compressed key's prefix on an uncompressed key
+        misprefixed.as_str(),
+        not_on_curve.as_str(),
+        "",
+    ] {
+        let keys = [KEYS[0], bad];
+        let error = client.create_multisig(1, &keys, None).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<MultisigError>(),
+            Some(&MultisigError::InvalidPublicKey(bad.to_string())),
+            "{}",
+            bad
+        );
+        let error = client
+            .add_multisig_address(1, &keys, Some("cold"), None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), format!("Invalid public key: {}", bad));
+    }
+    assert!(node.bodies().is_empty());
+}
+
+#[tokio::test]
+async fn optional_params_are_sent_only_when_needed() {
+    let node = node().await;
+    let client = node.client();
+    client.create_multisig(2, &KEYS, None).await.unwrap();
+    client
+        .create_multisig(2, &KEYS, Some("bech32"))
+        .await
+        .unwrap();
+    client
+        .add_multisig_address(2, &KEYS, None, None)
+        .await
+        .unwrap();
+    // A label must be given to reach the address type
+    client
+        .add_multisig_address(2, &KEYS, None, Some("p2sh-segwit"))
+        .await
+        .unwrap();
+    client
+        .add_multisig_address(2, &KEYS, Some("cold"), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        node.calls_to("createmultisig"),
+        [json!([2, KEYS]), json!([2, KEYS, "bech32"])]
+    );
+    assert_eq!(
+        node.calls_to("addmultisigaddress"),
+        [
+            json!([2, KEYS]),
+            json!([2, KEYS, "", "p2sh-segwit"]),
+            json!([2, KEYS, "cold"]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn uncompressed_keys_are_accepted_and_reported() {
+    let node = node().await;
+    let client = node.client();
+    let compressed = client
+        .create_multisig(2, &KEYS, Some("bech32"))
+        .await
+        .unwrap();
+    assert!(compressed.warnings.0.is_empty());
+    assert!(!compressed.has_uncompressed_keys());
+    assert_eq!(compressed.redeem_script, "5221...53ae");
+
+    let keys = [KEYS[0], UNCOMPRESSED];
+    let legacy = client
+        .create_multisig(1, &keys, Some("bech32"))
+        .await
+        .unwrap();
+    assert!(legacy.has_uncompressed_keys());
+    assert_eq!(legacy.warnings.0.len(), 1);
+    assert_eq!(node.calls_to("createmultisig").len(), 2);
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use bitcoin_sdk::{DiffRules, NodeSnapshot};
+use common::{MockNode, method_not_found, network_info};
+use serde_json::{Value, json};
+
+fn node_with_wallets(wallets: Value) -> impl Fn(&str, &Value) -> common::Reply {
+    move |method, _| match method {
+        "getblockcount" => Ok(json!(200)),
+        "getbestblockhash" => Ok(json!("aa".repeat(32))),
+        "listwallets" => Ok(wallets.clone()),
+        "getnetworkinfo" => network_info(280000),
+        "getmempoolinfo" => Ok(json!({
+            "loaded": true, "size": 0, "bytes": 0, "usage": 0, "maxmempool": 300000000,
+            "mempoolminfee": 0.00001, "minrelaytxfee": 0.00001,
+        })),
+        "listunspent" => Ok(json!([{
+            "txid": "bb".repeat(32), "vout": 0, "amount": 1.5, "label": "savings",
+        }])),
+        _ => method_not_found(),
+    }
+}
+
+#[tokio::test]
+async fn single_wallet_is_scoped_explicitly() {
+    let node = MockNode::start(node_with_wallets(json!(["alice"]))).await;
+    let snapshot = NodeSnapshot::capture(&node.client()).await.unwrap();
+    assert_eq!(snapshot.wallets["alice"].utxos.len(), 1);
+    assert!(node.paths().contains(&"/wallet/alice".to_string()));
+}
+
+#[tokio::test]
+async fn every_loaded_wallet_is_captured() {
+    let node = MockNode::start(node_with_wallets(json!(["", "alice", "bob"]))).await;
+    let snapshot = NodeSnapshot::capture(&node.client()).await.unwrap();
+    let names: Vec<&String> = snapshot.wallets.keys().collect();
+    assert_eq!(names, ["", "alice", "bob"]);
+    assert_eq!(
+        snapshot.wallets["bob"].label_balances["savings"].to_sat(),
+        150_000_000
+    );
+    let paths = node.paths();
+    for path in ["/wallet/", "/wallet/alice", "/wallet/bob"] {
+        assert!(paths.contains(&path.to_string()), "{:?}", paths);
+    }
+}
+
+#[tokio::test]
+async fn a_scoped_client_captures_only_its_wallet() {
+    let node = MockNode::start(node_with_wallets(json!(["alice", "bob"]))).await;
+    let snapshot = NodeSnapshot::capture(&node.client().wallet("bob"))
+        .await
+        .unwrap();
+    assert_eq!(snapshot.wallets.keys().collect::<Vec<_>>(), ["bob"]);
+}
+
+#[tokio::test]
+async fn funds_vanishing_from_one_of_several_wallets_are_reported() {
+    let node = MockNode::start(node_with_wallets(json!(["alice", "bob"]))).await;
+    let before = NodeSnapshot::capture(&node.client()).await.unwrap();
+    let mut after = before.clone();
+    after.wallets.get_mut("bob").unwrap().utxos.clear();
+    after.wallets.get_mut("bob").unwrap().label_balances.clear();
+    let diff = before.diff(&after);
+    let paths: Vec<&str> = diff.changes.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "wallets/bob/label_balances/savings".to_string(),
+            format!("wallets/bob/utxos/{}:0", "bb".repeat(32)),
+        ]
+    );
+
+    // A wallet that is no longer loaded loses everything it held
+    after.wallets.remove("alice");
+    assert_eq!(before.diff(&after).changes.len(), 4);
+}
+
+#[tokio::test]
+async fn same_height_with_another_hash_is_reported() {
+    let node = MockNode::start(node_with_wallets(json!([]))).await;
+    let before = NodeSnapshot::capture(&node.client()).await.unwrap();
+    let mut after = before.clone();
+    after.best_block_hash = "cc".repeat(32);
+    let diff = before.diff_with(&after, &DiffRules::default());
+    assert_eq!(diff.changes.len(), 1, "{}", diff);
+    assert_eq!(diff.changes[0].path, "best_block_hash");
+
+    // A higher tip with another hash is the expected advance
+    after.block_count += 1;
+    assert!(before.diff_with(&after, &DiffRules::default()).is_empty());
+}
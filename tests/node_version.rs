@@ -0,0 +1,83 @@
+mod common;
+
+use bitcoin_sdk::{NetworkInfo, NodeVersion, PeerInfo};
+use common::{MockNode, fixture, fixture_value, method_not_found, network_info};
+use serde_json::json;
+
+const VERSIONS: [&str; 3] = ["v0.20.1", "v24.0.1", "v28.0"];
+
+#[test]
+fn network_info_parses_across_versions() {
+    let versions: Vec<u32> = VERSIONS
+        .iter()
+        .map(|v| fixture::<NetworkInfo>(&format!("getnetworkinfo/{}", v)).version)
+        .collect();
+    assert_eq!(versions, [200100, 240001, 280000]);
+
+    // A string before 28.0, an array after; both normalize to a list
+    let old: NetworkInfo = fixture("getnetworkinfo/v0.20.1");
+    assert!(old.warnings.0.is_empty());
+    let new: NetworkInfo = fixture("getnetworkinfo/v28.0");
+    assert_eq!(new.warnings.0.len(), 1);
+}
+
+#[test]
+fn peer_info_parses_across_versions() {
+    let old: Vec<PeerInfo> = fixture("getpeerinfo/v0.20.1");
+    assert_eq!(old[0].banscore, Some(0));
+    assert_eq!(old[0].addnode, Some(false));
+    assert_eq!(old[0].whitelisted, Some(false));
+
+    for version in &VERSIONS[1..] {
+        let peers: Vec<PeerInfo> = fixture(&format!("getpeerinfo/{}", version));
+        assert!(!peers.is_empty());
+        for peer in peers {
+            assert_eq!(peer.banscore, None);
+            assert_eq!(peer.addnode, None);
+            assert_eq!(peer.whitelisted, None);
+        }
+    }
+}
+
+#[tokio::test]
+async fn detects_version_from_each_fixture() {
+    for (version, expected) in VERSIONS.iter().zip([
+        NodeVersion::new(0, 20, 1),
+        NodeVersion::new(24, 0, 1),
+        NodeVersion::new(28, 0, 0),
+    ]) {
+        let name = format!("getnetworkinfo/{}", version);
+        let node = MockNode::start(move |method, _| match method {
+            "getnetworkinfo" => Ok(fixture_value(&name)),
+            _ => method_not_found(),
+        })
+        .await;
+        let client = node.client();
+        assert_eq!(client.node_version(), None);
+        assert_eq!(client.detect_node_version().await.unwrap(), expected);
+        assert_eq!(client.node_version(), Some(expected));
+        // Cached after the first ask
+        client.detect_node_version().await.unwrap();
+        assert_eq!(node.calls_to("getnetworkinfo").len(), 1);
+    }
+}
+
+#[tokio::test]
+async fn send_raw_transaction_picks_the_calling_convention() {
+    for (version, params) in [
+        (180100, json!(["0200", false])),
+        (200100, json!(["0200", 0.25])),
+    ] {
+        let node = MockNode::start(move |method, _| match method {
+            "getnetworkinfo" => network_info(version),
+            "sendrawtransaction" => Ok(json!("11".repeat(32))),
+            _ => method_not_found(),
+        })
+        .await;
+        node.client()
+            .send_raw_transaction_with_max_fee_rate("0200", 0.25)
+            .await
+            .unwrap();
+        assert_eq!(node.calls_to("sendrawtransaction"), vec![params]);
+    }
+}
@@ -0,0 +1,213 @@
+use bitcoin_sdk::{
+    Amount, BitcoinClientType, BitcoinCrypto, DuplicatePolicy, FeePolicy, PayoutBatch, PayoutError,
+    PayoutPlan, PayoutProblem,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn address(n: u8) -> String {
+    BitcoinCrypto::hash160_to_p2pkh_address(&[n; 20], BitcoinClientType::Mainnet).unwrap()
+}
+
+fn sat(n: i64) -> Amount {
+    Amount::from_sat(n)
+}
+
+fn batch(amounts: &[(u8, i64)]) -> PayoutBatch {
+    PayoutBatch::new(
+        amounts
+            .iter()
+            .map(|(n, a)| (address(*n), sat(*a)))
+            .collect(),
+    )
+}
+
+fn problems(result: anyhow::Result<PayoutPlan>) -> Vec<PayoutProblem> {
+    result
+        .unwrap_err()
+        .downcast::<PayoutError>()
+        .unwrap()
+        .problems
+}
+
+fn shares(plan: &PayoutPlan) -> Vec<i64> {
+    plan.outputs.iter().map(|o| o.fee_share.to_sat()).collect()
+}
+
+#[test]
+fn leftover_satoshis_go_to_the_largest_remainders() {
+    let plan = batch(&[(1, 100_000), (2, 200_000), (3, 300_000)])
+        .with_fee_policy(FeePolicy::SplitProportionally)
+        .plan(sat(1001), None)
+        .unwrap();
+    // Floors are 166, 333 and 500 with remainders falling in that order
+    assert_eq!(shares(&plan), [167, 334, 500]);
+    let received: Vec<i64> = plan.outputs.iter().map(|o| o.amount.to_sat()).collect();
+    assert_eq!(received, [99_833, 199_666, 299_500]);
+    assert_eq!(plan.total_out() + plan.fee, sat(600_000));
+}
+
+#[test]
+fn tied_remainders_go_to_the_first_listed() {
+    let plan = batch(&[(1, 100_000), (2, 100_000), (3, 100_000)])
+        .with_fee_policy(FeePolicy::SplitProportionally)
+        .plan(sat(1000), None)
+        .unwrap();
+    assert_eq!(shares(&plan), [334, 333, 333]);
+}
+
+#[test]
+fn only_the_named_outputs_pay() {
+    let plan = batch(&[(1, 100_000), (2, 300_000), (3, 500_000)])
+        .with_fee_policy(FeePolicy::FromOutputs(vec![address(3), address(1)]))
+        .plan(sat(601), None)
+        .unwrap();
+    assert_eq!(shares(&plan), [100, 0, 501]);
+}
+
+#[test]
+fn shares_always_add_up_to_the_fee() {
+    let mut rng = StdRng::seed_from_u64(237);
+    for _ in 0..500 {
+        let count = rng.gen_range(1..=12u8);
+        let amounts: Vec<(u8, i64)> = (1..=count)
+            .map(|n| (n, rng.gen_range(10_000..=100_000_000_000_000)))
+            .collect();
+        let smallest = amounts.iter().map(|(_, a)| *a).min().unwrap();
+        let fee = rng.gen_range(0..=smallest - 546);
+        let plan = batch(&amounts)
+            .with_fee_policy(FeePolicy::SplitProportionally)
+            .plan(sat(fee), None)
+            .unwrap();
+        assert_eq!(shares(&plan).iter().sum::<i64>(), fee);
+        let total: i64 = amounts.iter().map(|(_, a)| a).sum();
+        for (output, (_, amount)) in plan.outputs.iter().zip(&amounts) {
+            assert_eq!(output.amount + output.fee_share, sat(*amount));
+            // Never more than one satoshi above the exact proportional share
+            let exact = fee as f64 * *amount as f64 / total as f64;
+            assert!((output.fee_share.to_sat() as f64 - exact).abs() < 1.0 + 1e-6);
+        }
+    }
+}
+
+#[test]
+fn duplicates_are_merged_in_first_seen_order() {
+    let plan = batch(&[(1, 10_000), (2, 20_000), (1, 5_000)])
+        .with_duplicate_policy(DuplicatePolicy::Merge)
+        .plan(sat(500), None)
+        .unwrap();
+    let outputs: Vec<(String, Amount)> = plan
+        .outputs
+        .iter()
+        .map(|o| (o.address.clone(), o.amount))
+        .collect();
+    assert_eq!(
+        outputs,
+        [(address(1), sat(15_000)), (address(2), sat(20_000))]
+    );
+}
+
+#[test]
+fn duplicates_are_rejected_once_by_default() {
+    let result = batch(&[(1, 10_000), (1, 10_000), (1, 10_000)]).plan(sat(500), None);
+    assert_eq!(
+        problems(result),
+        [PayoutProblem::DuplicateAddress(address(1))]
+    );
+}
+
+#[test]
+fn outputs_left_below_dust_by_their_share_are_reported() {
+    let result = batch(&[(1, 1_000), (2, 1_000_000)])
+        .with_fee_policy(FeePolicy::FromOutputs(vec![address(1)]))
+        .plan(sat(500), None);
+    assert_eq!(
+        problems(result),
+        [PayoutProblem::BelowDust {
+            address: address(1),
+            amount: sat(500),
+        }]
+    );
+
+    let plan = batch(&[(1, 1_000)])
+        .with_dust_limit(sat(330))
+        .plan(sat(500), None);
+    assert!(plan.is_ok());
+}
+
+#[test]
+fn the_sender_needs_the_fee_on_top() {
+    let amounts = [(1, 60_000), (2, 40_000)];
+    assert!(batch(&amounts).plan(sat(1_000), Some(sat(101_000))).is_ok());
+    let result = batch(&amounts).plan(sat(1_000), Some(sat(100_999)));
+    assert_eq!(
+        problems(result),
+        [PayoutProblem::InsufficientFunds {
+            required: sat(101_000),
+            available: sat(100_999),
+        }]
+    );
+
+    // When recipients pay, the requested amounts are enough
+    let plan = batch(&amounts)
+        .with_fee_policy(FeePolicy::SplitProportionally)
+        .plan(sat(1_000), Some(sat(100_000)));
+    assert!(plan.is_ok());
+}
+
+#[test]
+fn a_fee_larger_than_its_payers_is_reported() {
+    let result = batch(&[(1, 1_000), (2, 1_000_000)])
+        .with_fee_policy(FeePolicy::FromOutputs(vec![address(1)]))
+        .plan(sat(1_001), None);
+    assert_eq!(
+        problems(result),
+        [PayoutProblem::FeeExceedsOutputs {
+            fee: sat(1_001),
+            covered: sat(1_000),
+        }]
+    );
+}
+
+#[test]
+fn every_problem_is_reported_together() {
+    let recipients = vec![
+        ("not-an-address".to_string(), sat(10_000)),
+        (address(1), sat(0)),
+        (address(2), sat(100)),
+        (address(3), sat(50_000)),
+        (address(3), sat(50_000)),
+    ];
+    let result = PayoutBatch::new(recipients)
+        .with_fee_policy(FeePolicy::FromOutputs(vec![address(3), address(9)]))
+        .plan(sat(1_000), Some(sat(20_000)));
+    let error = result.unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<PayoutError>().unwrap().problems,
+        [
+            PayoutProblem::InvalidAddress("not-an-address".to_string()),
+            PayoutProblem::NonPositiveAmount(address(1)),
+            PayoutProblem::DuplicateAddress(address(3)),
+            PayoutProblem::UnknownFeeOutput(address(9)),
+            PayoutProblem::BelowDust {
+                address: address(2),
+                amount: sat(100),
+            },
+            PayoutProblem::InsufficientFunds {
+                required: sat(60_100),
+                available: sat(20_000),
+            },
+        ]
+    );
+    assert!(error.to_string().starts_with(
+        "Invalid payout batch: invalid address not-an-address; non-positive amount for"
+    ));
+}
+
+#[test]
+fn an_empty_batch_is_a_problem() {
+    assert_eq!(
+        problems(PayoutBatch::new(Vec::new()).plan(sat(0), None)),
+        [PayoutProblem::EmptyBatch]
+    );
+}
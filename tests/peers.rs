@@ -0,0 +1,129 @@
+mod common;
+
+use bitcoin_sdk::{AddNodeCommand, BitcoinRpcError, ConnectionDirection, DisconnectTarget};
+use common::{MockNode, method_not_found, network_info};
+use serde_json::json;
+
+async fn node(version: u32) -> MockNode {
+    MockNode::start(move |method, _| match method {
+        "getnetworkinfo" => network_info(version),
+        "addnode" | "disconnectnode" => Ok(json!(null)),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn add_node_commands_are_sent_by_name() {
+    let node = node(250000).await;
+    let client = node.client();
+    for command in [
+        AddNodeCommand::Add,
+        AddNodeCommand::Remove,
+        AddNodeCommand::OneTry,
+    ] {
+        client
+            .add_node("192.0.2.1:8333", command, None)
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        node.calls_to("addnode"),
+        [
+            json!(["192.0.2.1:8333", "add"]),
+            json!(["192.0.2.1:8333", "remove"]),
+            json!(["192.0.2.1:8333", "onetry"]),
+        ]
+    );
+    // No version check without v2transport
+    assert!(node.calls_to("getnetworkinfo").is_empty());
+}
+
+#[tokio::test]
+async fn v2transport_needs_26_0() {
+    let old = node(250000).await;
+    let error = old
+        .client()
+        .add_node("192.0.2.1:8333", AddNodeCommand::Add, Some(true))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::MethodNotFound(message))
+            if message.starts_with("addnode v2transport needs Bitcoin Core 26.0 or later")
+    ));
+    assert!(old.calls_to("addnode").is_empty());
+
+    let new = node(260000).await;
+    let client = new.client();
+    client
+        .add_node("192.0.2.1:8333", AddNodeCommand::OneTry, Some(true))
+        .await
+        .unwrap();
+    client
+        .add_node("192.0.2.1:8333", AddNodeCommand::Add, Some(false))
+        .await
+        .unwrap();
+    assert_eq!(
+        new.calls_to("addnode"),
+        [
+            json!(["192.0.2.1:8333", "onetry", true]),
+            json!(["192.0.2.1:8333", "add", false]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn peers_are_disconnected_by_address_or_id() {
+    let node = node(280000).await;
+    let client = node.client();
+    client
+        .disconnect_node(DisconnectTarget::Address("192.0.2.1:8333".to_string()))
+        .await
+        .unwrap();
+    client
+        .disconnect_node(DisconnectTarget::NodeId(7))
+        .await
+        .unwrap();
+    assert_eq!(
+        node.calls_to("disconnectnode"),
+        [json!(["192.0.2.1:8333"]), json!(["", 7])]
+    );
+}
+
+#[tokio::test]
+async fn added_node_info_parses_connected_and_pending_nodes() {
+    let node = MockNode::start(|method, _| match method {
+        "getaddednodeinfo" => Ok(json!([
+            {
+                "addednode": "192.0.2.1:8333",
+                "connected": true,
+                "addresses": [{"address": "192.0.2.1:8333", "connected": "outbound"}],
+            },
+            // Not connected, so no addresses
+            {"addednode": "seed.example.org", "connected": false},
+        ])),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    let added = client.get_added_node_info(None).await.unwrap();
+    assert_eq!(added.len(), 2);
+    assert!(added[0].connected);
+    assert_eq!(added[0].addresses[0].address, "192.0.2.1:8333");
+    assert_eq!(
+        added[0].addresses[0].connected,
+        ConnectionDirection::Outbound
+    );
+    assert!(!added[1].connected);
+    assert!(added[1].addresses.is_empty());
+
+    client
+        .get_added_node_info(Some("seed.example.org"))
+        .await
+        .unwrap();
+    assert_eq!(
+        node.calls_to("getaddednodeinfo"),
+        [json!(null), json!(["seed.example.org"])]
+    );
+}
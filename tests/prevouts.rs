@@ -0,0 +1,48 @@
+mod common;
+
+use common::{MockNode, method_not_found};
+use serde_json::json;
+
+#[tokio::test]
+async fn prevout_values_are_exact_amounts() {
+    let node = MockNode::start(|method, _| match method {
+        "getrawtransaction" => Ok(json!({
+            "txid": "aa".repeat(32),
+            "hash": "aa".repeat(32),
+            "version": 2,
+            "size": 110,
+            "vsize": 110,
+            "weight": 440,
+            "locktime": 0,
+            "vin": [{
+                "txid": "bb".repeat(32),
+                "vout": 1,
+                "sequence": 4294967293u32,
+                "prevout": {
+                    "generated": false,
+                    "height": 840000,
+                    "value": 0.29100001,
+                    "scriptPubKey": {
+                        "asm": "",
+                        "hex": "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+                        "type": "witness_v0_keyhash",
+                    },
+                },
+            }],
+            "vout": [],
+            "hex": "",
+            "fee": 0.00000141,
+        })),
+        _ => method_not_found(),
+    })
+    .await;
+    let tx = node
+        .client()
+        .get_raw_transaction_verbose2(&"aa".repeat(32), None)
+        .await
+        .unwrap();
+    let prevout = tx.vin[0].prevout.as_ref().unwrap();
+    // 0.29100001 is not exact as an f64
+    assert_eq!(prevout.value.to_sat(), 29_100_001);
+    assert_eq!(tx.fee.unwrap().to_sat(), 141);
+}
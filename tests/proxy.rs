@@ -0,0 +1,123 @@
+use bitcoin_sdk::{BitcoinClient, BitcoinRpcError};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+// An HTTP proxy that answers the first request itself and hands back its head
+async fn http_proxy() -> (String, oneshot::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        let body = r#"{"result":7,"error":null,"id":1,"jsonrpc":"2.0"}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        let _ = tx.send(String::from_utf8_lossy(&buf).to_ascii_lowercase());
+    });
+    (url, rx)
+}
+
+// A SOCKS5 server that accepts the greeting, records the CONNECT address type and
+// host, then refuses the connection
+async fn socks_proxy() -> (String, oneshot::Receiver<(u8, Vec<u8>)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("socks5://{}", listener.local_addr().unwrap());
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut greeting = [0u8; 2];
+        stream.read_exact(&mut greeting).await.unwrap();
+        let mut methods = vec![0u8; greeting[1] as usize];
+        stream.read_exact(&mut methods).await.unwrap();
+        stream.write_all(&[5, 0]).await.unwrap();
+        let mut request = [0u8; 4];
+        stream.read_exact(&mut request).await.unwrap();
+        let host = match request[3] {
+            3 => {
+                let mut len = [0u8; 1];
+                stream.read_exact(&mut len).await.unwrap();
+                let mut host = vec![0u8; len[0] as usize];
+                stream.read_exact(&mut host).await.unwrap();
+                host
+            }
+            _ => Vec::new(),
+        };
+        // General failure
+        let _ = stream.write_all(&[5, 1, 0, 1, 0, 0, 0, 0, 0, 0]).await;
+        let _ = tx.send((request[3], host));
+    });
+    (url, rx)
+}
+
+#[tokio::test]
+async fn proxy_credentials_apply_whatever_the_call_order() {
+    let (proxy, head) = http_proxy().await;
+    let client = BitcoinClient::builder("http://node.example:8332")
+        .auth("user", "pass")
+        .proxy_auth("alice", "secret")
+        .proxy(&proxy)
+        .build()
+        .unwrap();
+    assert_eq!(client.get_block_count().await.unwrap(), 7);
+    let head = head.await.unwrap();
+    assert!(head.starts_with("post http://node.example:8332/ "));
+    // base64("alice:secret")
+    assert!(head.contains("proxy-authorization: basic ywxpy2u6c2vjcmv0"));
+}
+
+#[tokio::test]
+async fn remote_dns_applies_whatever_the_call_order() {
+    let (proxy, connect) = socks_proxy().await;
+    let client = BitcoinClient::builder("http://abcdefghijklmnop.onion:8332")
+        .proxy_remote_dns(true)
+        .proxy(&proxy)
+        .build()
+        .unwrap();
+    assert!(client.get_block_count().await.is_err());
+    let (address_type, host) = connect.await.unwrap();
+    // Domain name, left for the proxy to resolve
+    assert_eq!(address_type, 3);
+    assert_eq!(host, b"abcdefghijklmnop.onion");
+}
+
+#[tokio::test]
+async fn proxy_options_without_a_proxy_are_rejected() {
+    let error = BitcoinClient::builder("http://127.0.0.1:8332")
+        .proxy_auth("alice", "secret")
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().contains("without a proxy URL"));
+    assert!(
+        BitcoinClient::builder("http://127.0.0.1:8332")
+            .proxy_remote_dns(true)
+            .build()
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn unreachable_proxy_is_a_proxy_error() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    drop(listener);
+    let client = BitcoinClient::builder("http://node.example:8332")
+        .proxy(&format!("socks5://{}", address))
+        .build()
+        .unwrap();
+    let error = client.get_block_count().await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::Proxy(_))
+    ));
+}
@@ -0,0 +1,242 @@
+mod common;
+
+use bitcoin_sdk::{BlockPruned, ClampedToPruneHeight, NotPruneMode};
+use common::{MockNode, block_header, fixture_value, method_not_found};
+use serde_json::{Value, json};
+
+const PRUNE_HEIGHT: u64 = 861190;
+
+fn pruned_node(
+    header_height: u64,
+    getblock_message: &'static str,
+) -> impl Fn(&str, &serde_json::Value) -> common::Reply {
+    move |method, params| match method {
+        "getblockchaininfo" => Ok(fixture_value("getblockchaininfo/v28.0-mainnet-pruned")),
+        "getblockheader" => Ok(block_header(params[0].as_str().unwrap(), header_height)),
+        "getblock" => Err((-1, getblock_message.to_string())),
+        "pruneblockchain" => Err((
+            -1,
+            "Cannot prune blocks because node is not in prune mode.".to_string(),
+        )),
+        _ => method_not_found(),
+    }
+}
+
+#[tokio::test]
+async fn pruned_block_becomes_block_pruned() {
+    let node = MockNode::start(pruned_node(
+        PRUNE_HEIGHT - 1,
+        "Block not available (pruned data)",
+    ))
+    .await;
+    let error = node.client().get_block(&"aa".repeat(32)).await.unwrap_err();
+    let pruned = error.downcast_ref::<BlockPruned>().unwrap();
+    assert_eq!(
+        (pruned.height, pruned.prune_height),
+        (PRUNE_HEIGHT - 1, PRUNE_HEIGHT)
+    );
+}
+
+// -1 is also Core's code for a block it cannot read, which pruning does not explain
+#[tokio::test]
+async fn unreadable_stored_block_keeps_its_error() {
+    let node = MockNode::start(pruned_node(PRUNE_HEIGHT + 5, "Block not found on disk")).await;
+    let error = node.client().get_block(&"aa".repeat(32)).await.unwrap_err();
+    assert!(error.downcast_ref::<BlockPruned>().is_none());
+    assert!(error.to_string().contains("Block not found on disk"));
+}
+
+#[tokio::test]
+async fn prune_refused_by_the_node_is_not_prune_mode() {
+    let node = MockNode::start(pruned_node(0, "")).await;
+    let error = node.client().prune_blockchain(861000).await.unwrap_err();
+    assert!(error.downcast_ref::<NotPruneMode>().is_some());
+    assert_eq!(node.calls_to("pruneblockchain"), [json!([861000])]);
+}
+
+const WATCHED: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+
+fn height_of(hash: &Value) -> u64 {
+    u64::from_str_radix(hash.as_str().unwrap(), 16).unwrap()
+}
+
+// Verbose block at `height` with a coinbase paying `WATCHED` on even heights
+fn block_verbose(height: u64) -> Value {
+    let address = if height.is_multiple_of(2) {
+        WATCHED
+    } else {
+        "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"
+    };
+    json!({
+        "hash": format!("{:064x}", height),
+        "confirmations": 1,
+        "size": 200,
+        "weight": 800,
+        "height": height,
+        "version": 536870912,
+        "versionHex": "20000000",
+        "merkleroot": "33".repeat(32),
+        "tx": [{
+            "txid": format!("{:064x}", height + 1_000_000),
+            "hash": format!("{:064x}", height + 1_000_000),
+            "version": 2,
+            "size": 100,
+            "vsize": 100,
+            "weight": 400,
+            "locktime": 0,
+            "vin": [{"coinbase": "0165", "sequence": 4294967295u32}],
+            "vout": [{
+                "value": 3.125,
+                "n": 0,
+                "scriptPubKey": {"asm": "", "hex": "", "type": "witness_v0_keyhash", "address": address},
+            }],
+            "hex": "",
+        }],
+        "time": 1700000000,
+        "mediantime": 1700000000,
+        "nonce": 0,
+        "bits": "17034219",
+        "difficulty": 1.0,
+        "chainwork": "00".repeat(32),
+        "nTx": 1,
+    })
+}
+
+// A pruned mainnet node whose block data starts at `PRUNE_HEIGHT`; the hash of block h
+// is h in hex
+fn pruned_chain(method: &str, params: &Value) -> common::Reply {
+    let pruned = |height: u64| height < PRUNE_HEIGHT;
+    match method {
+        "getblockchaininfo" => Ok(fixture_value("getblockchaininfo/v28.0-mainnet-pruned")),
+        "getblockhash" => Ok(json!(format!("{:064x}", params[0].as_u64().unwrap()))),
+        "getblockheader" => Ok(block_header(
+            params[0].as_str().unwrap(),
+            height_of(&params[0]),
+        )),
+        "getblock" if pruned(height_of(&params[0])) => {
+            Err((-1, "Block not available (pruned data)".to_string()))
+        }
+        "getblock" => Ok(block_verbose(height_of(&params[0]))),
+        "getblockstats" => Ok(json!({"height": params[0]})),
+        "gettxoutproof" if pruned(height_of(&params[1])) => {
+            Err((-32603, "Can't read block from disk".to_string()))
+        }
+        "gettxoutproof" => Ok(json!("00")),
+        "rescanblockchain" => Ok(json!({"start_height": params[0], "stop_height": 865432})),
+        _ => method_not_found(),
+    }
+}
+
+fn clamped_from(requested_start: u64) -> Option<ClampedToPruneHeight> {
+    Some(ClampedToPruneHeight {
+        requested_start,
+        clamped_start: PRUNE_HEIGHT,
+    })
+}
+
+#[tokio::test]
+async fn stats_backfill_is_clamped_to_the_prune_height() {
+    let node = MockNode::start(pruned_chain).await;
+    let range = node
+        .client()
+        .get_block_stats_range(PRUNE_HEIGHT - 3..=PRUNE_HEIGHT + 1)
+        .await
+        .unwrap();
+    assert_eq!(range.clamped, clamped_from(PRUNE_HEIGHT - 3));
+    let heights: Vec<Option<u64>> = range.stats.iter().map(|s| s.height).collect();
+    assert_eq!(heights, [Some(PRUNE_HEIGHT), Some(PRUNE_HEIGHT + 1)]);
+}
+
+#[tokio::test]
+async fn stored_ranges_carry_no_notice() {
+    let node = MockNode::start(pruned_chain).await;
+    let range = node
+        .client()
+        .get_blocks_range(PRUNE_HEIGHT..=PRUNE_HEIGHT + 1)
+        .await
+        .unwrap();
+    assert_eq!(range.clamped, None);
+    assert_eq!(range.blocks.len(), 2);
+}
+
+#[tokio::test]
+async fn block_ranges_skip_pruned_heights() {
+    let node = MockNode::start(pruned_chain).await;
+    let range = node
+        .client()
+        .get_blocks_range(PRUNE_HEIGHT - 10..=PRUNE_HEIGHT + 2)
+        .await
+        .unwrap();
+    assert_eq!(range.clamped, clamped_from(PRUNE_HEIGHT - 10));
+    let heights: Vec<u64> = range.blocks.iter().map(|b| b.height).collect();
+    assert_eq!(heights, [PRUNE_HEIGHT, PRUNE_HEIGHT + 1, PRUNE_HEIGHT + 2]);
+    assert!(
+        node.calls_to("getblock")
+            .iter()
+            .all(|p| height_of(&p[0]) >= PRUNE_HEIGHT)
+    );
+}
+
+#[tokio::test]
+async fn address_scans_skip_pruned_heights() {
+    let node = MockNode::start(pruned_chain).await;
+    let scan = node
+        .client()
+        .scan_blocks_for_addresses(&[WATCHED], 0..=PRUNE_HEIGHT + 3)
+        .await
+        .unwrap();
+    assert_eq!(scan.clamped, clamped_from(0));
+    let found: Vec<(u64, u64)> = scan
+        .outputs
+        .iter()
+        .map(|o| (o.created_at_height, o.amount_sat))
+        .collect();
+    assert_eq!(
+        found,
+        [(PRUNE_HEIGHT, 312_500_000), (PRUNE_HEIGHT + 2, 312_500_000)]
+    );
+}
+
+#[tokio::test]
+async fn rescans_below_the_prune_height_are_refused() {
+    let node = MockNode::start(pruned_chain).await;
+    let error = node
+        .client()
+        .rescan_blockchain(PRUNE_HEIGHT - 1, None)
+        .await
+        .unwrap_err();
+    let pruned = error.downcast_ref::<BlockPruned>().unwrap();
+    assert_eq!(
+        (pruned.height, pruned.prune_height),
+        (PRUNE_HEIGHT - 1, PRUNE_HEIGHT)
+    );
+    assert!(node.calls_to("rescanblockchain").is_empty());
+
+    let rescan = node
+        .client()
+        .rescan_blockchain(PRUNE_HEIGHT, None)
+        .await
+        .unwrap();
+    assert_eq!(rescan.start_height, PRUNE_HEIGHT);
+}
+
+#[tokio::test]
+async fn proofs_for_pruned_blocks_are_block_pruned() {
+    let node = MockNode::start(pruned_chain).await;
+    let txid = "aa".repeat(32);
+    let hash = format!("{:064x}", PRUNE_HEIGHT - 1);
+    let error = node
+        .client()
+        .get_tx_out_proof(&[&txid], Some(&hash))
+        .await
+        .unwrap_err();
+    let pruned = error.downcast_ref::<BlockPruned>().unwrap();
+    assert_eq!(pruned.height, PRUNE_HEIGHT - 1);
+
+    let stored = format!("{:064x}", PRUNE_HEIGHT);
+    let proof = node
+        .client()
+        .get_tx_out_proof(&[&txid], Some(&stored))
+        .await;
+    assert_eq!(proof.unwrap(), "00");
+}
@@ -0,0 +1,193 @@
+mod common;
+
+use bitcoin_sdk::{
+    Amount, DecodedPsbt, EstimateMode, FeeRate, FundedPsbtOptions, OutPoint, ScriptType,
+    SendOutput, SighashType,
+};
+use common::{MockNode, fixture, fixture_value, method_not_found};
+use serde_json::json;
+
+#[test]
+fn decodes_taproot_script_path_fields() {
+    let psbt: DecodedPsbt = fixture("decodepsbt/taproot-script-path-signed");
+    assert_eq!(psbt.psbt_version, Some(0));
+    assert_eq!(psbt.fee, Some(Amount::from_sat(1_000)));
+    let input = &psbt.inputs[0];
+    let utxo = input.witness_utxo.as_ref().unwrap();
+    assert_eq!(utxo.amount, Amount::from_sat(100_000));
+    assert_eq!(utxo.script_pub_key.r#type, ScriptType::P2TR);
+    assert_eq!(input.taproot_key_path_sig, None);
+    let sig = &input.taproot_script_path_sigs[0];
+    assert_eq!(sig.sig.len(), 128);
+    assert_eq!(sig.leaf_hash, input.taproot_merkle_root.clone().unwrap());
+    let leaf = &input.taproot_scripts[0];
+    assert_eq!(leaf.leaf_ver, 0xc0);
+    assert_eq!(leaf.control_blocks.len(), 1);
+    assert_eq!(input.taproot_bip32_derivs.len(), 2);
+    assert!(input.taproot_bip32_derivs[0].leaf_hashes.is_empty());
+    assert_eq!(input.taproot_bip32_derivs[1].leaf_hashes[0], sig.leaf_hash);
+    assert!(!input.is_finalized());
+
+    let output = &psbt.outputs[0];
+    assert!(output.taproot_internal_key.is_some());
+    assert_eq!(output.taproot_tree[0].depth, 0);
+    assert_eq!(output.taproot_bip32_derivs[0].path, "m/86h/1h/0h/1/0");
+}
+
+#[test]
+fn decodes_taproot_key_path_and_finalized_inputs() {
+    let psbt: DecodedPsbt = fixture("decodepsbt/taproot-key-path-signed");
+    let input = &psbt.inputs[0];
+    assert_eq!(
+        input.taproot_key_path_sig.as_ref().map(String::len),
+        Some(128)
+    );
+    assert!(input.taproot_script_path_sigs.is_empty());
+    assert!(input.partial_signatures.is_empty());
+    assert!(psbt.outputs[0].taproot_tree.is_empty());
+
+    let psbt: DecodedPsbt = fixture("decodepsbt/taproot-finalized");
+    let input = &psbt.inputs[0];
+    assert!(input.is_finalized());
+    assert_eq!(input.final_scriptwitness.as_ref().map(Vec::len), Some(1));
+    assert_eq!(input.taproot_internal_key, None);
+}
+
+#[tokio::test]
+async fn decode_psbt_sends_the_base64() {
+    let node = MockNode::start(|method, _| match method {
+        "decodepsbt" => Ok(fixture_value("decodepsbt/taproot-key-path-signed")),
+        _ => method_not_found(),
+    })
+    .await;
+    let psbt = node.client().decode_psbt("cHNidP8BAA==").await.unwrap();
+    assert_eq!(psbt.inputs.len(), 1);
+    assert_eq!(node.calls_to("decodepsbt"), vec![json!(["cHNidP8BAA=="])]);
+}
+
+const ADDRESS: &str = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+
+#[tokio::test]
+async fn wallet_create_funded_psbt_sends_outputs_and_options() {
+    let node = MockNode::start(|method, _| match method {
+        "walletcreatefundedpsbt" => Ok(json!({
+            "psbt": "cHNidP8BAAoCAAAAAAAAAAAAAA==",
+            "fee": 0.00000282,
+            "changepos": -1,
+        })),
+        _ => method_not_found(),
+    })
+    .await;
+    let inputs = [OutPoint {
+        txid: "11".repeat(32),
+        vout: 1,
+    }];
+    let outputs = [
+        SendOutput::address(ADDRESS, Amount::from_sat(100_000)),
+        SendOutput::data("68656c6c6f"),
+    ];
+    let options = FundedPsbtOptions::default()
+        .add_inputs(false)
+        .change_position(1)
+        .lock_unspents(true)
+        .fee_rate(FeeRate::from_sat_per_vb(1.5).unwrap())
+        .subtract_fee_from_output(0)
+        .estimate_mode(EstimateMode::Conservative);
+    let funded = node
+        .client()
+        .wallet_create_funded_psbt(&inputs, &outputs, 500_000, options, true)
+        .await
+        .unwrap();
+    assert_eq!(funded.psbt, "cHNidP8BAAoCAAAAAAAAAAAAAA==");
+    assert_eq!(funded.fee, Amount::from_sat(282));
+    assert_eq!(funded.changepos, -1);
+
+    let body = &node.bodies()[0];
+    assert!(body.contains(&format!(
+        r#"[{{"{}":0.00100000}},{{"data":"68656c6c6f"}}],500000,"#,
+        ADDRESS
+    )));
+    assert!(body.contains(r#""fee_rate":1.500"#));
+    let mut params = node.calls_to("walletcreatefundedpsbt").remove(0);
+    params[3].as_object_mut().unwrap().remove("fee_rate");
+    assert_eq!(params[0], json!([{"txid": "11".repeat(32), "vout": 1}]));
+    assert_eq!(
+        params[3],
+        json!({
+            "add_inputs": false,
+            "changePosition": 1,
+            "lockUnspents": true,
+            "subtractFeeFromOutputs": [0],
+            "estimate_mode": "conservative",
+        })
+    );
+    assert_eq!(params[4], json!(true));
+}
+
+#[tokio::test]
+async fn wallet_create_funded_psbt_leaves_unset_options_out() {
+    let node = MockNode::start(|method, _| match method {
+        "walletcreatefundedpsbt" => Ok(json!({
+            "psbt": "cHNidP8BAAoCAAAAAAAAAAAAAA==",
+            "fee": 0.00000141,
+            "changepos": 1,
+        })),
+        _ => method_not_found(),
+    })
+    .await;
+    let outputs = [SendOutput::address(ADDRESS, Amount::from_sat(546))];
+    let funded = node
+        .client()
+        .wallet_create_funded_psbt(&[], &outputs, 0, FundedPsbtOptions::default(), false)
+        .await
+        .unwrap();
+    assert_eq!(funded.changepos, 1);
+    assert!(node.bodies()[0].ends_with(&format!(
+        r#""params":[[],[{{"{}":0.00000546}}],0,{{}},false]}}"#,
+        ADDRESS
+    )));
+}
+
+#[tokio::test]
+async fn wallet_process_psbt_sends_the_sighash_type_by_name() {
+    let node = MockNode::start(|method, params| match method {
+        // Nodes before 26.0 return no hex
+        "walletprocesspsbt" if params[4] == json!(false) => Ok(json!({
+            "psbt": "cHNidP8BAAoCAAAAAAAAAAAAAA==",
+            "complete": false,
+        })),
+        "walletprocesspsbt" => Ok(json!({
+            "psbt": "cHNidP8BAAoCAAAAAAAAAAAAAA==",
+            "complete": true,
+            "hex": "0200000000010000000000",
+        })),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    let processed = client
+        .wallet_process_psbt("cHNidP8BAA==", false, None, false, false)
+        .await
+        .unwrap();
+    assert!(!processed.complete);
+    assert_eq!(processed.hex, None);
+    let signed = client
+        .wallet_process_psbt(
+            "cHNidP8BAA==",
+            true,
+            Some(SighashType::SingleAnyoneCanPay),
+            true,
+            true,
+        )
+        .await
+        .unwrap();
+    assert!(signed.complete);
+    assert_eq!(signed.hex.as_deref(), Some("0200000000010000000000"));
+    assert_eq!(
+        node.calls_to("walletprocesspsbt"),
+        vec![
+            json!(["cHNidP8BAA==", false, null, false, false]),
+            json!(["cHNidP8BAA==", true, "SINGLE|ANYONECANPAY", true, true]),
+        ]
+    );
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use bitcoin_sdk::testing::MockBitcoinRpc;
+use bitcoin_sdk::{BitcoinRpc, RawTransaction, RawTxInput, RawTxOutput, Serialization};
+use common::{MockNode, method_not_found};
+use serde_json::{Value, json};
+
+const TX_HEX: &str = "0200000000010000000000";
+
+fn decoded(txid: &str) -> Value {
+    json!({
+        "txid": txid,
+        "hash": txid,
+        "version": 2,
+        "size": 11,
+        "vsize": 11,
+        "weight": 44,
+        "locktime": 0,
+        "vin": [],
+        "vout": [],
+        "hex": TX_HEX,
+    })
+}
+
+#[tokio::test]
+async fn transactions_are_always_fetched_decoded() {
+    let node = MockNode::start(|method, params| match method {
+        "getrawtransaction" => Ok(decoded(params[0].as_str().unwrap())),
+        _ => method_not_found(),
+    })
+    .await;
+    let tx = node
+        .client()
+        .get_raw_transaction(&"ab".repeat(32))
+        .await
+        .unwrap();
+    assert_eq!(tx.txid, "ab".repeat(32));
+    assert_eq!(
+        node.calls_to("getrawtransaction"),
+        [json!(["ab".repeat(32), true])]
+    );
+}
+
+#[tokio::test]
+async fn trait_fetches_decoded_and_hex_forms() {
+    let rpc = MockBitcoinRpc::new();
+    rpc.respond("getrawtransaction", decoded(&"ab".repeat(32)));
+    let tx = rpc.get_raw_transaction(&"ab".repeat(32)).await.unwrap();
+    assert_eq!(tx.hex, TX_HEX);
+    rpc.assert_called_with("getrawtransaction", &json!(["ab".repeat(32), true]));
+
+    rpc.respond("getrawtransaction", TX_HEX);
+    assert_eq!(
+        rpc.get_raw_transaction_hex(&"cd".repeat(32)).await.unwrap(),
+        TX_HEX
+    );
+    rpc.assert_called_with("getrawtransaction", &json!(["cd".repeat(32), false]));
+}
+
+#[tokio::test]
+async fn batches_mix_decoded_and_hex_transactions() {
+    let node = MockNode::start(|method, params| match method {
+        "getrawtransaction" if params[1] == true => Ok(decoded(params[0].as_str().unwrap())),
+        "getrawtransaction" => Ok(json!(TX_HEX)),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    let mut batch = client.batch();
+    let tx = batch.get_raw_transaction(&"ab".repeat(32));
+    let hex = batch.get_raw_transaction_hex(&"cd".repeat(32));
+    let response = batch.send().await.unwrap();
+    assert_eq!(response.get(tx).unwrap().txid, "ab".repeat(32));
+    assert_eq!(response.get(hex).unwrap(), TX_HEX);
+    assert_eq!(
+        node.calls_to("getrawtransaction"),
+        [
+            json!(["ab".repeat(32), true]),
+            json!(["cd".repeat(32), false])
+        ]
+    );
+}
+
+// A 2-input transaction, with `signer` filling in its own input's scriptSig or witness
+fn partially_signed(signer: usize, output_value: u64) -> String {
+    let input = |vout: u32| RawTxInput {
+        prev_txid: [0x11; 32],
+        prev_vout: vout,
+        script_sig: Vec::new(),
+        sequence: 0xfffffffd,
+        witness: Vec::new(),
+    };
+    let mut tx = RawTransaction {
+        version: 2,
+        inputs: vec![input(0), input(1)],
+        outputs: vec![RawTxOutput {
+            value: output_value,
+            script_pubkey: hex::decode("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap(),
+        }],
+        lock_time: 0,
+    };
+    match signer {
+        0 => tx.inputs[0].script_sig = vec![0x00, 0x47, 0x30, 0x44],
+        _ => tx.inputs[1].witness = vec![vec![0x30, 0x44], vec![0x02; 33]],
+    }
+    hex::encode(tx.serialize(true))
+}
+
+async fn combining_node() -> MockNode {
+    MockNode::start(|method, _| match method {
+        "combinerawtransaction" => Ok(json!("0200")),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn copies_of_one_transaction_are_combined() {
+    let node = combining_node().await;
+    let copies = [
+        partially_signed(0, 50_000),
+        partially_signed(1, 50_000),
+        partially_signed(1, 50_000),
+    ];
+    let copies: Vec<&str> = copies.iter().map(String::as_str).collect();
+    let combined = node
+        .client()
+        .combine_raw_transaction(&copies)
+        .await
+        .unwrap();
+    assert_eq!(combined, "0200");
+    assert_eq!(node.calls_to("combinerawtransaction"), [json!([copies])]);
+}
+
+#[tokio::test]
+async fn copies_of_different_transactions_are_refused_unsent() {
+    let node = combining_node().await;
+    let client = node.client();
+    let first = partially_signed(0, 50_000);
+    let other_output = partially_signed(1, 49_000);
+    let error = client
+        .combine_raw_transaction(&[&first, &first, &other_output])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Transaction 2 differs from the first in more than its signatures"
+    );
+
+    let mut other_sequence = Serialization::deserialize_transaction(&first).unwrap();
+    other_sequence.inputs[1].sequence = 0xffffffff;
+    let other_sequence = hex::encode(other_sequence.serialize(true));
+    let error = client
+        .combine_raw_transaction(&[&first, &other_sequence])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Transaction 1 differs from the first in more than its signatures"
+    );
+    assert!(node.bodies().is_empty());
+}
+
+#[tokio::test]
+async fn undecodable_or_missing_copies_are_refused_unsent() {
+    let node = combining_node().await;
+    let client = node.client();
+    let first = partially_signed(0, 50_000);
+    for bad in ["zz", "0200", &first[..first.len() - 2]] {
+        let error = client
+            .combine_raw_transaction(&[&first, bad])
+            .await
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Transaction 1 could not be decoded: "),
+            "{}",
+            error
+        );
+    }
+    let trailing = format!("{}00", first);
+    let error = client
+        .combine_raw_transaction(&[&trailing, &first])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Transaction 0 could not be decoded: Trailing data after transaction"
+    );
+    let error = client.combine_raw_transaction(&[]).await.unwrap_err();
+    assert_eq!(error.to_string(), "No transactions to combine");
+    assert!(node.bodies().is_empty());
+}
@@ -0,0 +1,289 @@
+mod common;
+
+use bitcoin_sdk::{BitcoinRpcError, ChainTipStatus, ReorgHarness, ReorgScenario};
+use common::{MockNode, Reply, block_header, method_not_found};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+const MINER: &str = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+
+// Block hashes are unique across both nodes, like real ones
+static NEXT_HASH: AtomicU64 = AtomicU64::new(1);
+
+// A regtest node's block tree, active tip and mempool. Blocks travel between nodes as
+// the hex of their JSON.
+struct Chain {
+    blocks: HashMap<String, Value>,
+    tip: String,
+    mempool: Vec<String>,
+    networking: bool,
+}
+
+impl Chain {
+    fn new(mempool: &[&str]) -> Arc<Mutex<Chain>> {
+        let genesis = json!({"hash": "00".repeat(32), "prev": null, "height": 0, "txs": []});
+        Arc::new(Mutex::new(Chain {
+            blocks: HashMap::from([("00".repeat(32), genesis)]),
+            tip: "00".repeat(32),
+            mempool: mempool.iter().map(|tx| tx.to_string()).collect(),
+            networking: true,
+        }))
+    }
+
+    fn height(&self, hash: &str) -> u64 {
+        self.blocks[hash]["height"].as_u64().unwrap()
+    }
+
+    fn connect(&mut self, block: Value) {
+        let hash = block["hash"].as_str().unwrap().to_string();
+        let height = block["height"].as_u64().unwrap();
+        for tx in block["txs"].as_array().unwrap() {
+            self.mempool.retain(|m| m != tx);
+        }
+        self.blocks.insert(hash.clone(), block);
+        if height > self.height(&self.tip) {
+            self.tip = hash;
+        }
+    }
+
+    // Transactions confirmed on the active chain
+    fn confirmed(&self) -> Vec<String> {
+        let mut txs = Vec::new();
+        let mut hash = Some(self.tip.clone());
+        while let Some(block) = hash.and_then(|h| self.blocks.get(&h)) {
+            for tx in block["txs"].as_array().unwrap() {
+                txs.push(tx.as_str().unwrap().to_string());
+            }
+            hash = block["prev"].as_str().map(str::to_string);
+        }
+        txs.sort();
+        txs
+    }
+}
+
+fn handle(chain: &Mutex<Chain>, method: &str, params: &Value) -> Reply {
+    let mut chain = chain.lock().unwrap();
+    match method {
+        "setnetworkactive" => {
+            chain.networking = params[0].as_bool().unwrap();
+            Ok(json!(chain.networking))
+        }
+        "generateblock" => {
+            let hash = format!("{:064x}", NEXT_HASH.fetch_add(1, Ordering::SeqCst));
+            let tip = chain.tip.clone();
+            let block = json!({
+                "hash": hash,
+                "prev": tip,
+                "height": chain.height(&tip) + 1,
+                "txs": params[1],
+            });
+            chain.connect(block);
+            Ok(json!({"hash": hash}))
+        }
+        "getbestblockhash" => Ok(json!(chain.tip)),
+        "getblockcount" => Ok(json!(chain.height(&chain.tip))),
+        "getblockheader" => {
+            let hash = params[0].as_str().unwrap();
+            let Some(block) = chain.blocks.get(hash) else {
+                return Err((-5, "Block not found".to_string()));
+            };
+            let mut header = block_header(hash, block["height"].as_u64().unwrap());
+            header["previousblockhash"] = block["prev"].clone();
+            Ok(header)
+        }
+        "getblock" => {
+            let block = &chain.blocks[params[0].as_str().unwrap()];
+            Ok(json!(hex::encode(block.to_string())))
+        }
+        "submitblock" => {
+            let block: Value =
+                serde_json::from_slice(&hex::decode(params[0].as_str().unwrap()).unwrap()).unwrap();
+            if chain.blocks.contains_key(block["hash"].as_str().unwrap()) {
+                return Ok(json!("duplicate"));
+            }
+            chain.connect(block);
+            Ok(Value::Null)
+        }
+        _ => method_not_found(),
+    }
+}
+
+async fn node(chain: &Arc<Mutex<Chain>>) -> MockNode {
+    let chain = chain.clone();
+    MockNode::start(move |method, params| handle(&chain, method, params)).await
+}
+
+// Params of each `generateblock` call
+fn mined(node: &MockNode) -> Vec<Value> {
+    node.calls_to("generateblock")
+}
+
+#[tokio::test]
+async fn conflicting_branch_replaces_the_deposit() {
+    // Both spends reached A's mempool before the partition; B only saw the conflict
+    let chain_a = Chain::new(&["deposit", "conflict"]);
+    let chain_b = Chain::new(&["conflict"]);
+    let (node_a, node_b) = (node(&chain_a).await, node(&chain_b).await);
+    let harness = ReorgHarness::new(node_a.client(), node_b.client(), MINER);
+
+    let scenario = ReorgScenario::deposit_reorged_by_conflict("deposit", "conflict");
+    let mut run = scenario.start(&harness);
+
+    let confirmed = run.next_checkpoint().await.unwrap().unwrap();
+    assert_eq!(
+        (confirmed.name.as_str(), confirmed.height),
+        ("deposit-confirmed", 2)
+    );
+    // The plain block on top of the deposit leaves the conflict in A's mempool
+    assert_eq!(chain_a.lock().unwrap().confirmed(), ["deposit"]);
+    assert_eq!(chain_a.lock().unwrap().mempool, ["conflict"]);
+
+    let reorged = run.next_checkpoint().await.unwrap().unwrap();
+    assert_eq!(
+        (reorged.name.as_str(), reorged.height),
+        ("conflict-confirmed", 3)
+    );
+    assert_eq!(reorged.tip_hash, chain_b.lock().unwrap().tip);
+    assert_eq!(chain_a.lock().unwrap().confirmed(), ["conflict"]);
+
+    assert!(run.next_checkpoint().await.unwrap().is_none());
+    assert!(chain_a.lock().unwrap().networking);
+    assert_eq!(
+        mined(&node_a),
+        [json!([MINER, ["deposit"]]), json!([MINER, []])]
+    );
+    assert_eq!(
+        mined(&node_b),
+        [
+            json!([MINER, ["conflict"]]),
+            json!([MINER, []]),
+            json!([MINER, []])
+        ]
+    );
+    assert!(node_a.calls_to("generatetoaddress").is_empty());
+    assert_eq!(node_a.calls_to("submitblock").len(), 3);
+}
+
+#[tokio::test]
+async fn withheld_blocks_reorg_the_watched_node() {
+    let chain_a = Chain::new(&["pending"]);
+    let chain_b = Chain::new(&[]);
+    let (node_a, node_b) = (node(&chain_a).await, node(&chain_b).await);
+    let harness = ReorgHarness::new(node_a.client(), node_b.client(), MINER);
+
+    let checkpoints = ReorgScenario::withheld_blocks(3)
+        .run(&harness)
+        .await
+        .unwrap();
+    let summary: Vec<(&str, u64)> = checkpoints
+        .iter()
+        .map(|c| (c.name.as_str(), c.height))
+        .collect();
+    assert_eq!(summary, [("before-release", 3), ("after-reorg", 4)]);
+    assert_eq!(checkpoints[1].tip_hash, chain_b.lock().unwrap().tip);
+    // Empty blocks never pick up the mempool
+    assert_eq!(chain_a.lock().unwrap().mempool, ["pending"]);
+    assert_eq!(mined(&node_a), vec![json!([MINER, []]); 3]);
+}
+
+#[tokio::test]
+async fn failures_name_the_scenario_and_step() {
+    let chain = Chain::new(&[]);
+    let node_a = node(&chain).await;
+    let node_b = MockNode::start(|_, _| method_not_found()).await;
+    let harness = ReorgHarness::new(node_a.client(), node_b.client(), MINER);
+    let error = ReorgScenario::withheld_blocks(1)
+        .run(&harness)
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Scenario withheld-blocks failed at step 1 (Partition)"),
+        "{}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn block_marks_are_sent_by_hash() {
+    let node = MockNode::start(|method, params| match method {
+        "invalidateblock" | "reconsiderblock" | "preciousblock"
+            if params[0] == json!("ab".repeat(32)) =>
+        {
+            Ok(json!(null))
+        }
+        "invalidateblock" | "reconsiderblock" | "preciousblock" => {
+            Err((-5, "Block not found".to_string()))
+        }
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    let hash = "ab".repeat(32);
+    client.invalidate_block(&hash).await.unwrap();
+    client.reconsider_block(&hash).await.unwrap();
+    client.precious_block(&hash).await.unwrap();
+    for method in ["invalidateblock", "reconsiderblock", "preciousblock"] {
+        assert_eq!(node.calls_to(method), [json!([hash])]);
+    }
+
+    let error = client.invalidate_block(&"cd".repeat(32)).await.unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(&BitcoinRpcError::InvalidAddressOrKey(
+            "Block not found".to_string()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn chain_tips_parse_every_status() {
+    let tip = |height: u64, branchlen: i32, status: &str| {
+        json!({
+            "height": height,
+            "hash": format!("{:064x}", height),
+            "branchlen": branchlen,
+            "status": status,
+        })
+    };
+    let node = MockNode::start(move |method, _| match method {
+        "getchaintips" => Ok(json!([
+            tip(120, 0, "active"),
+            tip(118, 2, "valid-fork"),
+            tip(119, 3, "valid-headers"),
+            tip(125, 9, "headers-only"),
+            tip(117, 1, "invalid"),
+        ])),
+        _ => method_not_found(),
+    })
+    .await;
+    let tips = node.client().get_chain_tips().await.unwrap();
+    let statuses: Vec<ChainTipStatus> = tips.iter().map(|tip| tip.status).collect();
+    assert_eq!(
+        statuses,
+        [
+            ChainTipStatus::Active,
+            ChainTipStatus::ValidFork,
+            ChainTipStatus::ValidHeaders,
+            ChainTipStatus::HeadersOnly,
+            ChainTipStatus::Invalid,
+        ]
+    );
+    assert_eq!(tips[0].branchlen, 0);
+    assert_eq!(tips[3].height, 125);
+    assert_eq!(tips[3].hash, format!("{:064x}", 125));
+
+    let unknown = MockNode::start(move |method, _| match method {
+        "getchaintips" => Ok(json!([tip(120, 0, "orphaned")])),
+        _ => method_not_found(),
+    })
+    .await;
+    let error = unknown.client().get_chain_tips().await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::Decode(_))
+    ));
+}
@@ -0,0 +1,26 @@
+use bitcoin_sdk::RestUtxos;
+use serde_json::json;
+
+#[test]
+fn rest_utxo_values_are_exact_amounts() {
+    let utxos: RestUtxos = serde_json::from_value(json!({
+        "chainHeight": 840010,
+        "chaintipHash": "00".repeat(32),
+        "bitmap": "01",
+        "utxos": [{
+            "height": 840000,
+            "value": 0.29100001,
+            "scriptPubKey": {
+                "asm": "",
+                "desc": "addr(bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4)#8zl0zxma",
+                "hex": "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+                "address": "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+                "type": "witness_v0_keyhash",
+            },
+        }],
+    }))
+    .unwrap();
+    assert_eq!(utxos.utxos[0].value.to_sat(), 29_100_001);
+    assert!(!utxos.is_unspent(0));
+    assert!(utxos.is_unspent(1));
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use bitcoin_sdk::{Backoff, BitcoinClient, BitcoinRpc, BitcoinRpcError, RetryPolicy};
+use common::{MockNode, method_not_found};
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+fn policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        backoff: Backoff {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(5),
+            multiplier: 2.0,
+        },
+        ..RetryPolicy::default()
+    }
+}
+
+fn warming_up() -> common::Reply {
+    Err((-28, "Loading block index…".to_string()))
+}
+
+async fn node_warm_after(failures: u32) -> MockNode {
+    let calls = Arc::new(AtomicU32::new(0));
+    MockNode::start(move |method, _| match method {
+        "getblockcount" if calls.fetch_add(1, Ordering::SeqCst) < failures => warming_up(),
+        "getblockcount" => Ok(json!(120)),
+        "loadwallet"
+        | "unloadwallet"
+        | "restorewallet"
+        | "migratewallet"
+        | "sethdseed"
+        | "dumptxoutset"
+        | "dumpwallet"
+        | "newkeypool"
+        | "abandontransaction"
+        | "walletpassphrasechange"
+        | "importmempool" => warming_up(),
+        "getblockhash" => Err((-8, "Block height out of range".to_string())),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn client(node: &MockNode, max_attempts: u32) -> BitcoinClient {
+    node.client().with_retry(policy(max_attempts))
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried() {
+    let node = node_warm_after(2).await;
+    assert_eq!(client(&node, 5).get_block_count().await.unwrap(), 120);
+    assert_eq!(node.calls_to("getblockcount").len(), 3);
+}
+
+#[tokio::test]
+async fn exhausted_retries_report_the_attempts() {
+    let node = node_warm_after(10).await;
+    let error = client(&node, 3).get_block_count().await.unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("getblockcount failed after 3 attempts")
+    );
+    assert!(error.to_string().contains("Loading block index"));
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::InWarmup(_))
+    ));
+}
+
+#[tokio::test]
+async fn errors_not_worth_retrying_report_one_attempt() {
+    let node = node_warm_after(0).await;
+    let error = client(&node, 5)
+        .get_block_hash(1_000_000)
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("getblockhash failed after 1 attempt:")
+    );
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::InvalidParameter(_))
+    ));
+    assert_eq!(node.calls_to("getblockhash").len(), 1);
+}
+
+#[tokio::test]
+async fn wallet_loading_is_never_repeated() {
+    let node = node_warm_after(0).await;
+    let error = client(&node, 5).load_wallet("alice").await.unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("loadwallet failed after 1 attempt")
+    );
+    assert_eq!(node.calls_to("loadwallet").len(), 1);
+}
+
+#[tokio::test]
+async fn calls_that_change_state_are_never_repeated() {
+    let node = node_warm_after(0).await;
+    let client = client(&node, 5);
+    for method in [
+        "unloadwallet",
+        "restorewallet",
+        "migratewallet",
+        "sethdseed",
+        "dumptxoutset",
+        "dumpwallet",
+        "newkeypool",
+        "abandontransaction",
+        "walletpassphrasechange",
+        "importmempool",
+    ] {
+        let error = client.call_value(method, json!([])).await.unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with(&format!("{} failed after 1 attempt", method)),
+            "{}",
+            error
+        );
+        assert_eq!(node.calls_to(method).len(), 1, "{}", method);
+    }
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use bitcoin_sdk::ScanObject;
+use common::{MockNode, method_not_found};
+use serde_json::json;
+use std::time::Duration;
+
+fn objects() -> Vec<ScanObject> {
+    vec![ScanObject::from(
+        "addr(bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080)",
+    )]
+}
+
+async fn abort_calls(node: &MockNode) -> usize {
+    // The abort goes out from a background task
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    node.calls_to("scantxoutset")
+        .iter()
+        .filter(|params| params[0] == "abort")
+        .count()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn timed_out_scan_is_aborted() {
+    let node = MockNode::start(|method, params| match method {
+        "scantxoutset" if params[0] == "start" => {
+            std::thread::sleep(Duration::from_secs(2));
+            Ok(json!({"success": true, "txouts": 0, "height": 0, "bestblock": "", "unspents": [], "total_amount": 0.0}))
+        }
+        "scantxoutset" => Ok(json!(true)),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client().with_timeout(Duration::from_millis(200));
+    assert!(client.scan_tx_out_set(&objects()).await.is_err());
+    assert_eq!(abort_calls(&node).await, 1);
+}
+
+#[tokio::test]
+async fn rejected_scan_is_not_aborted() {
+    let node = MockNode::start(|method, _| match method {
+        "scantxoutset" => Err((
+            -8,
+            "Scan already in progress, use action \"abort\" or \"status\"".to_string(),
+        )),
+        _ => method_not_found(),
+    })
+    .await;
+    assert!(node.client().scan_tx_out_set(&objects()).await.is_err());
+    assert_eq!(abort_calls(&node).await, 0);
+}
@@ -0,0 +1,160 @@
+use bitcoin_sdk::{
+    AnchorTemplate, OpReturnPrefixTemplate, ScriptTemplate, ScriptTemplateRegistry, ScriptType,
+    TemplateMatch, op_return_data,
+};
+
+const P2A: [u8; 4] = [0x51, 0x02, 0x4e, 0x73];
+
+fn script(parts: &[&[u8]]) -> Vec<u8> {
+    parts.concat()
+}
+
+fn matched(name: &str, payload: &[u8]) -> Option<TemplateMatch> {
+    Some(TemplateMatch {
+        name: name.to_string(),
+        payload: hex::encode(payload),
+    })
+}
+
+#[test]
+fn op_return_data_reads_every_push_form() {
+    assert_eq!(op_return_data(&[0x6a]), Some(Vec::new()));
+    assert_eq!(
+        op_return_data(&script(&[&[0x6a, 0x04], b"omni"])),
+        Some(b"omni".to_vec())
+    );
+    // OP_PUSHDATA1 with 80 bytes, the most a standard data carrier held before v30
+    let data = [0xab; 80];
+    assert_eq!(
+        op_return_data(&script(&[&[0x6a, 0x4c, 80], &data])),
+        Some(data.to_vec())
+    );
+    // OP_PUSHDATA2, little-endian length 300
+    let data = [0xcd; 300];
+    assert_eq!(
+        op_return_data(&script(&[&[0x6a, 0x4d, 0x2c, 0x01], &data])),
+        Some(data.to_vec())
+    );
+    // Pushes are concatenated, and small-integer opcodes such as the runestone marker
+    // OP_13 add nothing
+    assert_eq!(
+        op_return_data(&script(&[&[
+            0x6a, 0x5d, 0x02, 0x01, 0x02, 0x00, 0x01, 0x03
+        ]])),
+        Some(vec![0x01, 0x02, 0x03])
+    );
+}
+
+#[test]
+fn op_return_data_refuses_malformed_scripts() {
+    assert_eq!(op_return_data(&[]), None);
+    // Not a data carrier
+    assert_eq!(op_return_data(&script(&[&[0x04], b"omni"])), None);
+    assert_eq!(op_return_data(&P2A), None);
+    // Pushes running past the end of the script
+    assert_eq!(op_return_data(&[0x6a, 0x05, 0x01]), None);
+    assert_eq!(op_return_data(&[0x6a, 0x4c]), None);
+    assert_eq!(op_return_data(&[0x6a, 0x4c, 0x02, 0x01]), None);
+    assert_eq!(op_return_data(&[0x6a, 0x4d, 0x01]), None);
+    assert_eq!(op_return_data(&[0x6a, 0x4d, 0x02, 0x00, 0x01]), None);
+    // Opcodes that are not pushes
+    assert_eq!(op_return_data(&[0x6a, 0x04, 1, 2, 3, 4, 0xac]), None);
+}
+
+#[test]
+fn op_return_prefixes_yield_the_rest_of_the_data() {
+    let omni = OpReturnPrefixTemplate::new("omni", b"omni");
+    assert_eq!(omni.name(), "omni");
+    let payload = [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1f];
+    assert_eq!(
+        omni.recognize(&script(&[&[0x6a, 0x0c], b"omni", &payload])),
+        Some(payload.to_vec())
+    );
+    // The prefix may span pushes, as the data is read as a whole
+    assert_eq!(
+        omni.recognize(&script(&[&[0x6a, 0x02], b"om", &[0x03], b"nix"])),
+        Some(b"x".to_vec())
+    );
+    assert_eq!(
+        omni.recognize(&script(&[&[0x6a, 0x04], b"omni"])),
+        Some(Vec::new())
+    );
+    assert_eq!(omni.recognize(&script(&[&[0x6a, 0x04], b"omn!"])), None);
+    assert_eq!(omni.recognize(&script(&[&[0x6a, 0x03], b"omn"])), None);
+    // The same bytes outside an OP_RETURN are not a match
+    assert_eq!(omni.recognize(&script(&[&[0x04], b"omni", &[0x75]])), None);
+}
+
+#[test]
+fn anchors_match_only_the_exact_script() {
+    assert_eq!(AnchorTemplate.name(), "p2a");
+    assert_eq!(AnchorTemplate.recognize(&P2A), Some(Vec::new()));
+    assert_eq!(AnchorTemplate.recognize(&[0x51, 0x02, 0x4e, 0x74]), None);
+    assert_eq!(AnchorTemplate.recognize(&[0x52, 0x02, 0x4e, 0x73]), None);
+    assert_eq!(
+        AnchorTemplate.recognize(&[0x51, 0x02, 0x4e, 0x73, 0x00]),
+        None
+    );
+}
+
+// A downstream recognizer: witness v1 outputs to the BIP341 NUMS point
+struct NumsTemplate;
+
+const NUMS: &str = "50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0";
+
+impl ScriptTemplate for NumsTemplate {
+    fn name(&self) -> &str {
+        "nums"
+    }
+
+    fn recognize(&self, script: &[u8]) -> Option<Vec<u8>> {
+        let key = script.strip_prefix(&[0x51, 0x20])?;
+        (key == hex::decode(NUMS).unwrap()).then(|| key.to_vec())
+    }
+}
+
+#[test]
+fn registries_report_the_first_matching_template() {
+    let empty = ScriptTemplateRegistry::new();
+    assert_eq!(empty.match_template(&P2A), None);
+
+    let mut registry = ScriptTemplateRegistry::with_builtins();
+    registry
+        .register(OpReturnPrefixTemplate::new("omni", b"omni"))
+        .register(OpReturnPrefixTemplate::new("any", b""))
+        .register(NumsTemplate);
+    assert_eq!(registry.match_template(&P2A), matched("p2a", b""));
+    assert_eq!(
+        registry.match_template(&script(&[&[0x6a, 0x05], b"omni!"])),
+        matched("omni", b"!")
+    );
+    // Templates are tried in order, so the catch-all only sees what "omni" left
+    assert_eq!(
+        registry.match_template(&script(&[&[0x6a, 0x06], b"hello!"])),
+        matched("any", b"hello!")
+    );
+    let nums = script(&[&[0x51, 0x20], &hex::decode(NUMS).unwrap()]);
+    assert_eq!(
+        registry.match_template(&nums),
+        matched("nums", &hex::decode(NUMS).unwrap())
+    );
+    assert_eq!(registry.match_template(&[0x51, 0x20, 0x00]), None);
+}
+
+#[test]
+fn classification_keeps_the_standard_type() {
+    let mut registry = ScriptTemplateRegistry::with_builtins();
+    registry.register(OpReturnPrefixTemplate::new("omni", b"omni"));
+
+    let anchor = registry.classify(&P2A);
+    assert_eq!(anchor.script_type, ScriptType::Anchor);
+    assert_eq!(anchor.template, matched("p2a", b""));
+
+    let omni = registry.classify(&script(&[&[0x6a, 0x06], b"omni", &[0x01, 0x02]]));
+    assert_eq!(omni.script_type, ScriptType::NullData);
+    assert_eq!(omni.template, matched("omni", &[0x01, 0x02]));
+
+    let nonstandard = registry.classify(&[0x75]);
+    assert_eq!(nonstandard.script_type, ScriptType::NonStandard);
+    assert_eq!(nonstandard.template, None);
+}
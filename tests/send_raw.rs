@@ -0,0 +1,52 @@
+mod common;
+
+use bitcoin_sdk::{Amount, SendRawError, SendRawTxOptions};
+use common::{MockNode, method_not_found};
+use serde_json::json;
+
+#[tokio::test]
+async fn options_are_sent_as_exact_amounts() {
+    let node = MockNode::start(|method, _| match method {
+        "sendrawtransaction" => Ok(json!("11".repeat(32))),
+        _ => method_not_found(),
+    })
+    .await;
+    let client = node.client();
+    let options = SendRawTxOptions::default()
+        .max_fee_rate(Amount::from_sat(25_000_000))
+        .max_burn_amount(Amount::from_sat(546));
+    client
+        .send_raw_transaction_with_options("0200", options)
+        .await
+        .unwrap();
+    let only_fee_rate = SendRawTxOptions::default().max_fee_rate(Amount::ZERO);
+    client
+        .send_raw_transaction_with_options("0200", only_fee_rate)
+        .await
+        .unwrap();
+    let bodies = node.bodies();
+    assert!(bodies[0].ends_with(r#""params":["0200",0.25000000,0.00000546]}"#));
+    assert!(bodies[1].ends_with(r#""params":["0200",0.00000000]}"#));
+}
+
+#[tokio::test]
+async fn burn_cap_rejection_is_typed() {
+    let node = MockNode::start(|method, _| match method {
+        "sendrawtransaction" => Err((
+            -25,
+            "Unspendable output exceeds maximum configured by user (maxburnamount)".to_string(),
+        )),
+        _ => method_not_found(),
+    })
+    .await;
+    let options = SendRawTxOptions::default().max_burn_amount(Amount::ZERO);
+    let error = node
+        .client()
+        .send_raw_transaction_with_options("0200", options)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SendRawError>(),
+        Some(SendRawError::MaxBurnExceeded(_))
+    ));
+}
@@ -0,0 +1,235 @@
+use bitcoin_sdk::{
+    BitcoinClientType, BitcoinCrypto, RawBlock, RawBlockHeader, RawTransaction, RawTxInput,
+    RawTxOutput, Serialization, SignetParams,
+};
+use secp256k1::{Message, PublicKey, Secp256k1, ecdsa::Signature};
+
+const PRIVATE_KEY: [u8; 32] = [0x42; 32];
+const SIGNET_HEADER: [u8; 4] = [0xec, 0xc7, 0xda, 0xa2];
+const COMMITMENT_PREFIX: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+
+// `unsigned_block()` signed with `PRIVATE_KEY` under its `<pubkey> OP_CHECKSIG` challenge.
+// Pinned after `verify_signet_solution` accepted it; the signature is RFC6979 with low-R
+// grinding, as Core's signer produces.
+const SIGNED_BLOCK: &str = concat!(
+    "000000207777777777777777777777777777777777777777777777777777777777777777598e3b13",
+    "912bb43fded2697f04127becf66596fe432fd162c3bb3c0b2409dcbe00f15365ae77031e00000000",
+    "02020000000001010000000000000000000000000000000000000000000000000000000000000000",
+    "ffffffff0403a08601ffffffff0200f2052a0100000001510000000000000000766a24aa21a9ed5a",
+    "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a4c4eecc7daa2484730",
+    "4402202e3d57bb6953a84c3168186cc1c76331bce05bad9a091eb584f83c1dce5ac2640220764879",
+    "af79e94fe2f2178b0f0643f6442f423be562c387b580f848ac0f491a140100012000000000000000",
+    "00000000000000000000000000000000000000000000000000000000000200000001111111111111",
+    "1111111111111111111111111111111111111111111111111111010000000151fdffffff01b88201",
+    "0000000000015100000000",
+);
+
+fn challenge() -> Vec<u8> {
+    let key = BitcoinCrypto::private_to_public(&PRIVATE_KEY, true).unwrap();
+    let mut script = vec![0x21];
+    script.extend(key);
+    script.push(0xac);
+    script
+}
+
+// A template as the signet miner hands it over: coinbase with a bare witness commitment
+// and one other transaction
+fn unsigned_block() -> RawBlock {
+    let mut commitment = COMMITMENT_PREFIX.to_vec();
+    commitment.extend([0x5a; 32]);
+    let coinbase = RawTransaction {
+        version: 2,
+        inputs: vec![RawTxInput {
+            prev_txid: [0; 32],
+            prev_vout: 0xFFFFFFFF,
+            script_sig: vec![0x03, 0xa0, 0x86, 0x01],
+            sequence: 0xFFFFFFFF,
+            witness: vec![vec![0; 32]],
+        }],
+        outputs: vec![
+            RawTxOutput {
+                value: 5_000_000_000,
+                script_pubkey: vec![0x51],
+            },
+            RawTxOutput {
+                value: 0,
+                script_pubkey: commitment,
+            },
+        ],
+        lock_time: 0,
+    };
+    let spend = RawTransaction {
+        version: 2,
+        inputs: vec![RawTxInput {
+            prev_txid: [0x11; 32],
+            prev_vout: 1,
+            script_sig: vec![0x51],
+            sequence: 0xFFFFFFFD,
+            witness: Vec::new(),
+        }],
+        outputs: vec![RawTxOutput {
+            value: 99_000,
+            script_pubkey: vec![0x51],
+        }],
+        lock_time: 0,
+    };
+    let mut block = RawBlock {
+        header: RawBlockHeader {
+            version: 0x20000000,
+            prev_blockhash: [0x77; 32],
+            merkle_root: [0; 32],
+            time: 1_700_000_000,
+            bits: 0x1e0377ae,
+            nonce: 0,
+        },
+        transactions: vec![coinbase, spend],
+    };
+    block.header.merkle_root = block.compute_merkle_root();
+    block
+}
+
+fn push(data: &[u8]) -> Vec<u8> {
+    let mut script = match data.len() {
+        len @ 0..=75 => vec![len as u8],
+        len => vec![0x4c, len as u8],
+    };
+    script.extend_from_slice(data);
+    script
+}
+
+fn varint_bytes(data: &[u8]) -> Vec<u8> {
+    assert!(data.len() < 0xfd);
+    let mut out = vec![data.len() as u8];
+    out.extend_from_slice(data);
+    out
+}
+
+// Non-witness serialization of a one-input, one-output version 0 transaction
+fn bip325_tx(prevout: &[u8; 32], vout: u32, script_sig: &[u8], script_pubkey: &[u8]) -> Vec<u8> {
+    let mut tx = 0u32.to_le_bytes().to_vec();
+    tx.push(1);
+    tx.extend_from_slice(prevout);
+    tx.extend_from_slice(&vout.to_le_bytes());
+    tx.extend(varint_bytes(script_sig));
+    tx.extend_from_slice(&0u32.to_le_bytes());
+    tx.push(1);
+    tx.extend_from_slice(&0u64.to_le_bytes());
+    tx.extend(varint_bytes(script_pubkey));
+    tx.extend_from_slice(&0u32.to_le_bytes());
+    tx
+}
+
+// BIP325 check written out from the spec: strip the solution, rebuild to_spend and
+// to_sign, and verify the signature against the challenge key
+fn verify_signet_solution(block: &RawBlock, challenge: &[u8]) {
+    let mut block = block.clone();
+    let coinbase = &mut block.transactions[0];
+    let output = coinbase
+        .outputs
+        .iter_mut()
+        .rev()
+        .find(|out| out.script_pubkey.starts_with(&COMMITMENT_PREFIX))
+        .unwrap();
+    let pushed = &output.script_pubkey[COMMITMENT_PREFIX.len() + 32..];
+    let section = match pushed[0] {
+        0x4c => &pushed[2..2 + pushed[1] as usize],
+        len => &pushed[1..1 + len as usize],
+    };
+    assert_eq!(section[..4], SIGNET_HEADER);
+    let script_sig = &section[5..5 + section[4] as usize];
+    // Empty witness stack
+    assert_eq!(section[5 + script_sig.len()..], [0x00]);
+    let script_sig = script_sig.to_vec();
+
+    output.script_pubkey.truncate(COMMITMENT_PREFIX.len() + 32);
+    output.script_pubkey.extend(push(&SIGNET_HEADER));
+    let signet_merkle = block.compute_merkle_root();
+
+    let mut block_data = block.header.version.to_le_bytes().to_vec();
+    block_data.extend_from_slice(&block.header.prev_blockhash);
+    block_data.extend_from_slice(&signet_merkle);
+    block_data.extend_from_slice(&block.header.time.to_le_bytes());
+    let mut to_spend_sig = vec![0x00];
+    to_spend_sig.extend(push(&block_data));
+    let to_spend = bip325_tx(&[0; 32], 0xFFFFFFFF, &to_spend_sig, challenge);
+    let to_spend_txid = BitcoinCrypto::double_sha256(&to_spend);
+
+    let mut preimage = bip325_tx(&to_spend_txid, 0, challenge, &[0x6a]);
+    preimage.extend_from_slice(&1u32.to_le_bytes());
+    let digest = BitcoinCrypto::double_sha256(&preimage);
+
+    let (sighash_type, der) = script_sig[1..1 + script_sig[0] as usize]
+        .split_last()
+        .unwrap();
+    assert_eq!(*sighash_type, 0x01);
+    let signature = Signature::from_der(der).unwrap();
+    // Low R, as Core's signer grinds for
+    assert_eq!(der[3], 32);
+    let key = PublicKey::from_slice(&challenge[1..34]).unwrap();
+    Secp256k1::verification_only()
+        .verify_ecdsa(&Message::from_slice(&digest).unwrap(), &signature, &key)
+        .unwrap();
+}
+
+#[test]
+fn signed_block_matches_the_known_answer() {
+    let params = SignetParams::new(challenge());
+    let unsigned = hex::encode(unsigned_block().serialize());
+    let signed = params.sign_signet_block(&unsigned, &PRIVATE_KEY).unwrap();
+    assert_eq!(signed, SIGNED_BLOCK);
+}
+
+#[test]
+fn signed_block_carries_a_valid_bip325_solution() {
+    let params = SignetParams::new(challenge());
+    let unsigned = unsigned_block();
+    let signed = params
+        .sign_signet_block(&hex::encode(unsigned.serialize()), &PRIVATE_KEY)
+        .unwrap();
+    let signed = Serialization::deserialize_block(&signed).unwrap();
+    verify_signet_solution(&signed, &challenge());
+
+    // Only the commitment output and the merkle root change
+    assert_eq!(signed.transactions[1], unsigned.transactions[1]);
+    assert_eq!(signed.header.merkle_root, signed.compute_merkle_root());
+    assert_eq!(
+        (signed.header.time, signed.header.bits, signed.header.nonce),
+        (
+            unsigned.header.time,
+            unsigned.header.bits,
+            unsigned.header.nonce
+        )
+    );
+
+    let again = params.sign_signet_block(&hex::encode(signed.serialize()), &PRIVATE_KEY);
+    assert_eq!(again.unwrap_err().to_string(), "Block is already signed");
+}
+
+#[test]
+fn other_keys_are_refused() {
+    let params = SignetParams::new(challenge());
+    let unsigned = hex::encode(unsigned_block().serialize());
+    let error = params
+        .sign_signet_block(&unsigned, &[0x43; 32])
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Private key does not satisfy the signet challenge"
+    );
+}
+
+#[test]
+fn only_p2pkh_addresses_of_the_key_match() {
+    let params = SignetParams::new(challenge());
+    let key = BitcoinCrypto::private_to_public(&PRIVATE_KEY, true).unwrap();
+    let hash = BitcoinCrypto::hash160(&key);
+    for network in [BitcoinClientType::Mainnet, BitcoinClientType::Signet] {
+        let p2pkh = BitcoinCrypto::hash160_to_p2pkh_address(&hash, network).unwrap();
+        assert!(params.matches_address(&p2pkh).unwrap());
+        // Same hash, but a script hash rather than the key
+        let p2sh = BitcoinCrypto::hash160_to_p2sh_address(&hash, network).unwrap();
+        assert!(!params.matches_address(&p2sh).unwrap());
+    }
+    let other = BitcoinCrypto::hash160_to_p2pkh_address(&[0; 20], BitcoinClientType::Signet);
+    assert!(!params.matches_address(&other.unwrap()).unwrap());
+}
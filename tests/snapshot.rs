@@ -0,0 +1,153 @@
+use bitcoin_sdk::{
+    BitcoinClientType, ScriptType, SnapshotEntry, UtxoSnapshot, classify_script, diff_snapshots,
+    load_utxo_snapshot,
+};
+
+const P2WPKH: &str = "0014751e76e8199196d454941c45d1b3a323f1433bd6";
+const P2TR: &str = "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+const DESCRIPTOR: &str = "wpkh(0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798)";
+
+fn entry(txid: u8, vout: u32, amount_sat: u64) -> SnapshotEntry {
+    let (script_type, script) = if vout.is_multiple_of(2) {
+        (ScriptType::P2WPKH, P2WPKH)
+    } else {
+        (ScriptType::P2TR, P2TR)
+    };
+    SnapshotEntry {
+        txid: hex::encode([txid; 32]),
+        vout,
+        amount_sat,
+        script_type,
+        script_pub_key: script.to_string(),
+    }
+}
+
+fn snapshot(entries: Vec<SnapshotEntry>) -> UtxoSnapshot {
+    UtxoSnapshot {
+        network: BitcoinClientType::Regtest,
+        descriptors: vec![DESCRIPTOR.to_string(), "addr(bcrt1qexample)".to_string()],
+        height: 850_000,
+        block_hash: "00000000000000000002a7c4c1e48d76c5a37902165a270156b7a8d72728a054".to_string(),
+        entries,
+    }
+}
+
+fn assert_same(a: &UtxoSnapshot, b: &UtxoSnapshot) {
+    assert_eq!(a.network, b.network);
+    assert_eq!(a.descriptors, b.descriptors);
+    assert_eq!(a.height, b.height);
+    assert_eq!(a.block_hash, b.block_hash);
+    assert_eq!(a.entries, b.entries);
+}
+
+#[test]
+fn snapshots_round_trip_through_bytes_and_files() {
+    let original = snapshot(vec![
+        entry(1, 0, 546),
+        entry(1, 1, 2_100_000_000_000_000),
+        entry(2, 0, 0),
+    ]);
+    let bytes = original.to_bytes().unwrap();
+    assert!(bytes.starts_with(b"BUTX\x01\x03"));
+    assert_same(&UtxoSnapshot::from_bytes(&bytes).unwrap(), &original);
+
+    let path = std::env::temp_dir().join(format!("bitcoin-sdk-{}.utxo", std::process::id()));
+    original.save(&path).unwrap();
+    let loaded = load_utxo_snapshot(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_same(&loaded.unwrap(), &original);
+
+    let empty = snapshot(Vec::new());
+    assert_same(
+        &UtxoSnapshot::from_bytes(&empty.to_bytes().unwrap()).unwrap(),
+        &empty,
+    );
+}
+
+#[test]
+fn tampered_snapshots_are_refused() {
+    let bytes = snapshot(vec![entry(1, 0, 546)]).to_bytes().unwrap();
+    // Any flipped bit, in the body or the checksum itself
+    for index in [0, 6, bytes.len() / 2, bytes.len() - 1] {
+        let mut tampered = bytes.clone();
+        tampered[index] ^= 0x01;
+        let error = UtxoSnapshot::from_bytes(&tampered).unwrap_err();
+        assert_eq!(error.to_string(), "Snapshot checksum mismatch");
+    }
+    let error = UtxoSnapshot::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert_eq!(error.to_string(), "Snapshot checksum mismatch");
+    let error = UtxoSnapshot::from_bytes(&bytes[..20]).unwrap_err();
+    assert_eq!(error.to_string(), "Snapshot too short");
+}
+
+#[test]
+fn entries_whose_type_does_not_match_their_script_are_refused() {
+    let mut mislabeled = entry(1, 0, 546);
+    mislabeled.script_type = ScriptType::P2PKH;
+    let bytes = snapshot(vec![entry(2, 1, 1_000), mislabeled])
+        .to_bytes()
+        .unwrap();
+    let error = UtxoSnapshot::from_bytes(&bytes).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Entry 1: script type does not match script"
+    );
+}
+
+#[test]
+fn diffs_report_created_and_spent_outputs() {
+    let old = snapshot(vec![
+        entry(1, 0, 546),
+        entry(1, 1, 1_000),
+        entry(2, 0, 5_000),
+    ]);
+    let mut new = snapshot(vec![
+        entry(1, 1, 1_000),
+        entry(3, 0, 4_000),
+        entry(3, 1, 900),
+    ]);
+    // The same descriptor set in another order
+    new.descriptors.reverse();
+    let diff = diff_snapshots(&old, &new).unwrap();
+    assert_eq!(diff.created, [entry(3, 0, 4_000), entry(3, 1, 900)]);
+    assert_eq!(diff.spent, [entry(1, 0, 546), entry(2, 0, 5_000)]);
+
+    let unchanged = diff_snapshots(&old, &old).unwrap();
+    assert!(unchanged.created.is_empty() && unchanged.spent.is_empty());
+}
+
+#[test]
+fn diffs_across_networks_or_descriptor_sets_are_refused() {
+    let old = snapshot(vec![entry(1, 0, 546)]);
+    let mut other_network = snapshot(Vec::new());
+    other_network.network = BitcoinClientType::Signet;
+    let error = diff_snapshots(&old, &other_network).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Snapshots were taken on different networks (Regtest vs Signet)"
+    );
+
+    let mut other_descriptors = snapshot(Vec::new());
+    other_descriptors.descriptors.pop();
+    let error = diff_snapshots(&old, &other_descriptors).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Snapshots were taken with different descriptor sets"
+    );
+}
+
+#[test]
+fn multisig_scripts_with_many_keys_are_nonstandard() {
+    let key = [&[0x21][..], &[0x02; 33]].concat();
+    let multisig = |keys: usize, n: u8| [vec![0x51], key.repeat(keys), vec![n, 0xae]].concat();
+    assert_eq!(classify_script(&multisig(1, 0x51)), ScriptType::Multisig);
+    assert_eq!(classify_script(&multisig(3, 0x53)), ScriptType::Multisig);
+    assert_eq!(classify_script(&multisig(2, 0x53)), ScriptType::NonStandard);
+    // More keys than fit in a byte used to overflow the count
+    for keys in [256, 257, 300] {
+        assert_eq!(
+            classify_script(&multisig(keys, 0x51)),
+            ScriptType::NonStandard
+        );
+    }
+}
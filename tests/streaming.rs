@@ -0,0 +1,76 @@
+mod common;
+
+use common::{MockNode, method_not_found};
+use futures_util::StreamExt;
+use serde_json::{Value, json};
+
+const LABELED: &str = "bcrt1qlabeled";
+const UNUSED: &str = "bcrt1qunused";
+const CHANGE: &str = "bcrt1qchange";
+
+fn utxo(address: &str, vout: u32) -> Value {
+    json!({
+        "txid": "11".repeat(32),
+        "vout": vout,
+        "address": address,
+        "scriptPubKey": "0014",
+        "amount": 0.5,
+        "confirmations": 2,
+        "spendable": true,
+        "solvable": true,
+        "safe": true,
+    })
+}
+
+async fn wallet() -> MockNode {
+    MockNode::start(|method, params| match method {
+        "listreceivedbyaddress" => Ok(json!([
+            {"address": LABELED, "amount": 0.5, "confirmations": 2, "label": "savings", "txids": []},
+            {"address": UNUSED, "amount": 0.0, "confirmations": 0, "label": "", "txids": []},
+        ])),
+        "listaddressgroupings" => Ok(json!([[[LABELED, 0.5, "savings"], [CHANGE, 0.25]]])),
+        "listunspent" => {
+            let owned = [(LABELED, 0), (CHANGE, 1)];
+            let filter = params[2].as_array();
+            Ok(Value::Array(
+                owned
+                    .iter()
+                    .filter(|(a, _)| filter.is_none_or(|f| f.contains(&json!(a))))
+                    .map(|(a, vout)| utxo(a, *vout))
+                    .collect(),
+            ))
+        }
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn chunked_stream_covers_change_outputs() {
+    let node = wallet().await;
+    let client = node.client();
+    let utxos: Vec<_> = client.stream_unspent(true).collect().await;
+    let mut addresses: Vec<String> = utxos
+        .into_iter()
+        .map(|u| u.unwrap().address.unwrap())
+        .collect();
+    addresses.sort();
+    assert_eq!(addresses, [CHANGE, LABELED]);
+
+    // Unused and unlabeled receive addresses are asked about too
+    assert_eq!(
+        node.calls_to("listreceivedbyaddress"),
+        [json!([0, true, true])]
+    );
+    let filter = &node.calls_to("listunspent")[0][2];
+    assert_eq!(filter, &json!([CHANGE, LABELED, UNUSED]));
+}
+
+#[tokio::test]
+async fn unchunked_stream_is_one_call() {
+    let node = wallet().await;
+    let client = node.client();
+    let utxos: Vec<_> = client.stream_unspent(false).collect().await;
+    assert_eq!(utxos.len(), 2);
+    assert_eq!(node.calls().len(), 1);
+}
@@ -0,0 +1,227 @@
+mod common;
+
+use bitcoin_sdk::{BitcoinClient, Eta, TimeHint};
+use common::{MockNode, block_header, method_not_found};
+use serde_json::{Value, json};
+
+const GENESIS_TIME: u64 = 1_700_000_000;
+
+// Block times that run backwards after every third block past the first window, as
+// miners' clocks allow, and the median-time-past Core derives from the last 11 of them
+fn chain(tip: u64) -> (Vec<u64>, Vec<u64>) {
+    let times: Vec<u64> = (0..=tip)
+        .map(|h| GENESIS_TIME + h * 600 + if h > 11 && h % 3 == 1 { 2_000 } else { 0 })
+        .collect();
+    let medians: Vec<u64> = (0..=tip as usize)
+        .map(|h| {
+            let mut window = times[h.saturating_sub(10)..=h].to_vec();
+            window.sort();
+            window[window.len() / 2]
+        })
+        .collect();
+    // Consensus keeps every block's time above its parent's median time
+    assert!((1..times.len()).all(|h| times[h] > medians[h - 1]));
+    (times, medians)
+}
+
+async fn node(tip: u64) -> MockNode {
+    let (times, medians) = chain(tip);
+    MockNode::start(move |method, params| match method {
+        "getblockcount" => Ok(json!(tip)),
+        "getblockhash" => match params[0].as_u64().unwrap() {
+            height if height > tip => Err((-8, "Block height out of range".to_string())),
+            height => Ok(json!(format!("{:064x}", height))),
+        },
+        "getblockheader" => {
+            let hash = params[0].as_str().unwrap();
+            let height = u64::from_str_radix(hash, 16).unwrap();
+            let mut header = block_header(hash, height);
+            header["time"] = json!(times[height as usize]);
+            header["mediantime"] = json!(medians[height as usize]);
+            Ok(header)
+        }
+        _ => method_not_found(),
+    })
+    .await
+}
+
+// The answer by walking the whole chain
+fn expected(medians: &[u64], unix_time: u64, hint: TimeHint) -> Option<u64> {
+    let first = medians.iter().position(|m| *m >= unix_time);
+    match hint {
+        TimeHint::FirstAtOrAfter => first.map(|h| h as u64),
+        TimeHint::LastBefore => match first {
+            Some(0) => None,
+            Some(h) => Some(h as u64 - 1),
+            None => Some(medians.len() as u64 - 1),
+        },
+    }
+}
+
+async fn found(client: &BitcoinClient, unix_time: u64, hint: TimeHint) -> Option<u64> {
+    match client.find_block_at_time(unix_time, hint).await {
+        Ok(header) => Some(header.height),
+        Err(_) => None,
+    }
+}
+
+async fn assert_matches_a_chain_walk(tip: u64) {
+    let node = node(tip).await;
+    let client = node.client();
+    let (times, medians) = chain(tip);
+    let mut timestamps = vec![0, GENESIS_TIME - 1, u64::MAX];
+    for t in medians.iter().chain(&times) {
+        timestamps.extend([t - 1, *t, t + 1]);
+    }
+    timestamps.sort();
+    timestamps.dedup();
+    for unix_time in timestamps {
+        for hint in [TimeHint::FirstAtOrAfter, TimeHint::LastBefore] {
+            assert_eq!(
+                found(&client, unix_time, hint).await,
+                expected(&medians, unix_time, hint),
+                "tip {} time {} {:?}",
+                tip,
+                unix_time,
+                hint
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn searches_agree_with_a_chain_walk() {
+    assert_matches_a_chain_walk(40).await;
+}
+
+#[tokio::test]
+async fn chains_shorter_than_a_round_of_probes_are_searched() {
+    for tip in [0, 1, 5, 14, 15, 16] {
+        assert_matches_a_chain_walk(tip).await;
+    }
+}
+
+#[tokio::test]
+async fn the_median_time_is_compared_not_the_block_time() {
+    let node = node(140).await;
+    let (times, medians) = chain(140);
+    // Block 61's own time is 2000s ahead of its neighbours, but its median time is not
+    let header = node
+        .client()
+        .find_block_at_time(times[61], TimeHint::FirstAtOrAfter)
+        .await
+        .unwrap();
+    assert!(header.mediantime >= times[61]);
+    assert!(medians[header.height as usize - 1] < times[61]);
+    assert_ne!(header.height, 61);
+}
+
+#[tokio::test]
+async fn an_exact_median_time_belongs_to_the_first_block_with_it() {
+    let node = node(140).await;
+    let (_, medians) = chain(140);
+    // Medians repeat when the window's middle time does not move
+    let height = (1..medians.len())
+        .find(|h| medians[*h] == medians[*h - 1])
+        .unwrap();
+    let first = medians.iter().position(|m| *m == medians[height]).unwrap() as u64;
+    let client = node.client();
+    let at = client
+        .find_block_at_time(medians[height], TimeHint::FirstAtOrAfter)
+        .await
+        .unwrap();
+    assert_eq!(at.height, first);
+    let before = client
+        .find_block_at_time(medians[height], TimeHint::LastBefore)
+        .await
+        .unwrap();
+    assert_eq!(before.height, first - 1);
+}
+
+#[tokio::test]
+async fn timestamps_outside_the_chain_are_errors_or_its_ends() {
+    let node = node(140).await;
+    let client = node.client();
+    let error = client
+        .find_block_at_time(GENESIS_TIME - 1, TimeHint::LastBefore)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!("No block before {}", GENESIS_TIME - 1)
+    );
+    let genesis = client
+        .find_block_at_time(GENESIS_TIME - 1, TimeHint::FirstAtOrAfter)
+        .await
+        .unwrap();
+    assert_eq!(genesis.height, 0);
+
+    let later = GENESIS_TIME + 1_000_000;
+    let error = client
+        .find_block_at_time(later, TimeHint::FirstAtOrAfter)
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), format!("No block at or after {}", later));
+    let tip = client
+        .find_block_at_time(later, TimeHint::LastBefore)
+        .await
+        .unwrap();
+    assert_eq!(tip.height, 140);
+}
+
+fn entry(vsize: u64, ancestorsize: u64, ancestor_fee: f64) -> Value {
+    json!({"vsize": vsize, "ancestorsize": ancestorsize, "fees": {"ancestor": ancestor_fee}})
+}
+
+async fn eta(mempool: Value) -> Eta {
+    let node = MockNode::start(move |method, params| match method {
+        "getmempoolentry" => Ok(mempool[params[0].as_str().unwrap()].clone()),
+        "getrawmempool" => Ok(mempool.clone()),
+        _ => method_not_found(),
+    })
+    .await;
+    node.client().estimate_confirmation_eta("aa").await.unwrap()
+}
+
+#[tokio::test]
+async fn a_transaction_alone_confirms_in_the_next_block() {
+    let eta = eta(json!({"aa": entry(200, 200, 0.00002)})).await;
+    assert_eq!(
+        eta,
+        Eta {
+            expected_blocks: 1,
+            expected_seconds: 600,
+        }
+    );
+}
+
+#[tokio::test]
+async fn only_higher_paying_packages_are_counted_ahead() {
+    // "aa" pays 10 sat/vB
+    let mempool = json!({
+        "aa": entry(200, 200, 0.00002),
+        // 20 sat/vB, 2.5 MvB in all
+        "b1": entry(1_000_000, 1_000_000, 0.2),
+        "b2": entry(1_000_000, 1_000_000, 0.2),
+        "b3": entry(500_000, 500_000, 0.1),
+        // Paying the same or less is mined after it
+        "c1": entry(200, 200, 0.00002),
+        "c2": entry(900_000, 900_000, 0.01),
+        // A cheap child of a rich parent is mined with it: 2500 sat over 200 vB
+        "d1": entry(100, 200, 0.000025),
+    });
+    let eta = eta(mempool).await;
+    // 2_500_100 vB ahead plus its own 200 fill three blocks
+    assert_eq!(eta.expected_blocks, 3);
+    assert_eq!(eta.expected_seconds, 1_800);
+}
+
+#[tokio::test]
+async fn a_transaction_is_ranked_by_its_package_feerate() {
+    // "aa" pays nothing itself but its parent pays 50 sat/vB for both
+    let mempool = json!({
+        "aa": entry(100, 400, 0.0002),
+        "b1": entry(1_000_000, 1_000_000, 0.2),
+    });
+    assert_eq!(eta(mempool).await.expected_blocks, 1);
+}
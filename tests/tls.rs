@@ -0,0 +1,61 @@
+mod common;
+
+use bitcoin_sdk::BitcoinClient;
+use common::{MockNode, method_not_found, tls_file};
+use serde_json::json;
+
+async fn node() -> MockNode {
+    MockNode::start_tls(|method, _| match method {
+        "getblockcount" => Ok(json!(840000)),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn trusts_an_added_root_certificate() {
+    let node = node().await;
+    let client = BitcoinClient::builder(node.url())
+        .auth("user", "pass")
+        .add_root_certificate(&tls_file("ca.pem"))
+        .build()
+        .unwrap();
+    assert_eq!(client.get_block_count().await.unwrap(), 840000);
+    assert_eq!(node.calls_to("getblockcount").len(), 1);
+}
+
+#[tokio::test]
+async fn rejects_a_server_signed_by_an_unknown_ca() {
+    let node = node().await;
+    let client = BitcoinClient::builder(node.url())
+        .auth("user", "pass")
+        .build()
+        .unwrap();
+    assert!(client.get_block_count().await.is_err());
+    assert!(node.calls().is_empty());
+}
+
+#[tokio::test]
+async fn accepting_invalid_certs_skips_verification() {
+    let node = node().await;
+    let client = BitcoinClient::builder(node.url())
+        .auth("user", "pass")
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+    assert_eq!(client.get_block_count().await.unwrap(), 840000);
+}
+
+#[test]
+fn reports_unparseable_root_certificates() {
+    let error = BitcoinClient::builder("https://localhost:8332")
+        .add_root_certificate(&tls_file("ca.pem"))
+        .add_root_certificate(b"not a certificate")
+        .build()
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Root certificate 1 is not valid PEM")
+    );
+}
@@ -0,0 +1,205 @@
+#![cfg(unix)]
+
+use bitcoin_sdk::{BitcoinClient, BitcoinRpcError};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixListener;
+use tokio::sync::oneshot;
+
+const RESULT: &str = r#"{"result":7,"error":null,"id":1,"jsonrpc":"2.0"}"#;
+
+fn socket_path() -> PathBuf {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "bitcoin-sdk-{}-{}.sock",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::SeqCst)
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+// A node on a Unix socket that reads one request, hands it back and writes `reply`
+// verbatim before closing
+fn node(reply: Vec<u8>) -> (PathBuf, oneshot::Receiver<String>) {
+    let path = socket_path();
+    let listener = UnixListener::bind(&path).unwrap();
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut chunk).await.unwrap();
+            request.extend_from_slice(&chunk[..n]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .to_ascii_lowercase()
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map(|value| value.trim().parse::<usize>().unwrap())
+                    .unwrap();
+                if body.len() >= length {
+                    break;
+                }
+            }
+        }
+        stream.write_all(&reply).await.unwrap();
+        let _ = tx.send(String::from_utf8_lossy(&request).to_string());
+    });
+    (path, rx)
+}
+
+fn client(path: &Path) -> BitcoinClient {
+    BitcoinClient::new_unix(path, "user", "pass").unwrap()
+}
+
+fn transport_error(error: anyhow::Error) -> String {
+    match error.downcast::<BitcoinRpcError>().unwrap() {
+        BitcoinRpcError::Transport(message) => message,
+        other => panic!("expected a transport error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn content_length_replies_are_read() {
+    let reply = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        RESULT.len(),
+        RESULT
+    );
+    let (path, request) = node(reply.into_bytes());
+    assert_eq!(client(&path).get_block_count().await.unwrap(), 7);
+    let request = request.await.unwrap();
+    assert!(request.starts_with("POST / HTTP/1.1\r\n"), "{}", request);
+    assert!(request.contains("authorization: Basic dXNlcjpwYXNz\r\n"));
+    assert!(
+        request.contains(r#""method":"getblockcount""#),
+        "{}",
+        request
+    );
+}
+
+#[tokio::test]
+async fn chunked_replies_are_reassembled() {
+    let (first, second) = RESULT.split_at(10);
+    let reply = format!(
+        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}; ext=1\r\n{}\r\n{:X}\r\n{}\r\n0\r\n\r\n",
+        first.len(),
+        first,
+        second.len(),
+        second
+    );
+    let (path, _) = node(reply.into_bytes());
+    assert_eq!(client(&path).get_block_count().await.unwrap(), 7);
+}
+
+#[tokio::test]
+async fn oversized_chunk_sizes_are_errors() {
+    let reply =
+        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nffffffffffffffff\r\n{}\r\n0\r\n\r\n";
+    let (path, _) = node(reply.as_bytes().to_vec());
+    let error = client(&path).get_block_count().await.unwrap_err();
+    assert_eq!(
+        transport_error(error),
+        format!("Unix socket {}: connection closed mid-body", path.display())
+    );
+}
+
+#[tokio::test]
+async fn truncated_bodies_are_errors() {
+    let reply = format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+        RESULT.len() + 1,
+        RESULT
+    );
+    let (path, _) = node(reply.into_bytes());
+    let error = client(&path).get_block_count().await.unwrap_err();
+    assert!(transport_error(error).ends_with("connection closed mid-body"));
+
+    // Chunked data that stops before the last chunk
+    let reply = format!(
+        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n",
+        RESULT.len(),
+        RESULT
+    );
+    let (path, _) = node(reply.into_bytes());
+    let error = client(&path).get_block_count().await.unwrap_err();
+    assert!(transport_error(error).ends_with("connection closed mid-body"));
+}
+
+#[tokio::test]
+async fn replies_without_a_head_or_status_are_errors() {
+    let (path, _) = node(b"HTTP/1.1 200 OK\r\nContent-Length: 2".to_vec());
+    let error = client(&path).get_block_count().await.unwrap_err();
+    assert_eq!(
+        transport_error(error),
+        format!(
+            "Unix socket {}: connection closed before a complete HTTP reply",
+            path.display()
+        )
+    );
+
+    let (path, _) = node(b"SSH-2.0-OpenSSH_9.6\r\n\r\n".to_vec());
+    let error = client(&path).get_block_count().await.unwrap_err();
+    assert!(transport_error(error).ends_with("malformed status line"));
+}
+
+#[tokio::test]
+async fn error_statuses_keep_their_body() {
+    let body = r#"{"result":null,"error":{"code":-8,"message":"Block height out of range"},"id":1,"jsonrpc":"2.0"}"#;
+    let reply = format!(
+        "HTTP/1.1 500 Internal Server Error\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    let (path, _) = node(reply.into_bytes());
+    let error = client(&path).get_block_hash(1_000_000).await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<BitcoinRpcError>(),
+        Some(BitcoinRpcError::InvalidParameter(message)) if message == "Block height out of range"
+    ));
+}
+
+// Linux resets a Unix socket closed with unread data, so the read fails after the head
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn read_errors_are_reported_when_the_body_is_incomplete() {
+    let path = socket_path();
+    let listener = UnixListener::bind(&path).unwrap();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        // Wait for the request to arrive, then answer without reading it
+        stream.readable().await.unwrap();
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n{")
+            .await
+            .unwrap();
+    });
+    let error = client(&path).get_block_count().await.unwrap_err();
+    let message = transport_error(error);
+    assert!(
+        message.starts_with(&format!("Unix socket {}: ", path.display()))
+            && !message.ends_with("connection closed mid-body"),
+        "{}",
+        message
+    );
+}
+
+#[tokio::test]
+async fn a_missing_socket_names_its_path() {
+    let path = socket_path();
+    let error = client(&path).get_block_count().await.unwrap_err();
+    match error.downcast::<BitcoinRpcError>().unwrap() {
+        BitcoinRpcError::Connect(message) => {
+            assert!(
+                message.starts_with(&format!("Unix socket {}: ", path.display())),
+                "{}",
+                message
+            );
+        }
+        other => panic!("expected a connect error, got {:?}", other),
+    }
+}
@@ -0,0 +1,22 @@
+use bitcoin_sdk::run_vectors;
+
+#[test]
+fn vector_corpus_passes() {
+    let report = run_vectors(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/vectors")).unwrap();
+    assert!(report.is_success(), "{}", report);
+    assert!(report.total > 0);
+    for function in [
+        "BitcoinCrypto::address_to_script_pubkey",
+        "RawTransaction::segwit_v0_sighash",
+        "RawTransaction::legacy_sighash",
+        "RawTransaction::wtxid",
+        "Serialization::merkle_branch",
+        "BitcoinCrypto::wif_to_private_key",
+    ] {
+        assert!(
+            report.coverage.contains_key(function),
+            "no vector exercises {}",
+            function
+        );
+    }
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use bitcoin_sdk::WaitForBlockResult;
+use common::{MockNode, method_not_found};
+use serde_json::json;
+use std::time::Duration;
+
+const HASH: &str = "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206";
+
+// Answers every wait after `delay`, as a node does once a block arrives
+async fn node(delay: Duration) -> MockNode {
+    MockNode::start(move |method, _| match method {
+        "waitfornewblock" | "waitforblockheight" | "waitforblock" | "getblockcount" => {
+            std::thread::sleep(delay);
+            match method {
+                "getblockcount" => Ok(json!(101)),
+                _ => Ok(json!({"hash": HASH, "height": 101})),
+            }
+        }
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn tip() -> WaitForBlockResult {
+    WaitForBlockResult {
+        hash: HASH.to_string(),
+        height: 101,
+    }
+}
+
+#[tokio::test]
+async fn waits_send_the_target_and_timeout() {
+    let node = node(Duration::ZERO).await;
+    let client = node.client();
+    assert_eq!(client.wait_for_new_block(1_000).await.unwrap(), tip());
+    assert_eq!(client.wait_for_block_height(101, 0).await.unwrap(), tip());
+    assert_eq!(client.wait_for_block(HASH, 30_000).await.unwrap(), tip());
+    assert_eq!(node.calls_to("waitfornewblock"), [json!([1_000])]);
+    assert_eq!(node.calls_to("waitforblockheight"), [json!([101, 0])]);
+    assert_eq!(node.calls_to("waitforblock"), [json!([HASH, 30_000])]);
+}
+
+// The node holds the request for as long as it was told to wait, longer than the
+// client's own timeout allows
+#[tokio::test(flavor = "multi_thread")]
+async fn waits_outlast_the_client_timeout() {
+    let node = node(Duration::from_millis(600)).await;
+    let client = node.client().with_timeout(Duration::from_millis(200));
+    assert!(client.get_block_count().await.is_err());
+    assert_eq!(client.wait_for_new_block(1_000).await.unwrap(), tip());
+    assert_eq!(client.wait_for_block_height(102, 500).await.unwrap(), tip());
+    // Without a limit on the node, none on the client either
+    assert_eq!(client.wait_for_block(HASH, 0).await.unwrap(), tip());
+}
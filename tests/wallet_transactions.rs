@@ -0,0 +1,65 @@
+mod common;
+
+use bitcoin_sdk::{GetTransactionResult, WalletTransaction, WalletTxCategory};
+use common::{MockNode, method_not_found};
+use serde_json::json;
+
+async fn wallet() -> MockNode {
+    MockNode::start(|method, _| match method {
+        "listtransactions" => Ok(json!([{
+            "address": "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+            "category": "receive",
+            "amount": 0.5,
+            "label": "",
+            "vout": 0,
+            "confirmations": 2,
+            "txid": "aa".repeat(32),
+            "time": 1700000000,
+            "timereceived": 1700000000,
+            "bip125-replaceable": "no",
+        }])),
+        "gettransaction" => Ok(json!({
+            "amount": 0.5,
+            "confirmations": 2,
+            "txid": "aa".repeat(32),
+            "time": 1700000000,
+            "timereceived": 1700000000,
+            "bip125-replaceable": "no",
+            "details": [{
+                "address": "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+                "category": "receive",
+                "amount": 0.5,
+                "label": "",
+                "vout": 0,
+            }],
+            "hex": "00",
+        })),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+// `WalletTransaction` stays the `listtransactions` entry it has always been
+#[tokio::test]
+async fn list_entries_keep_their_type_name() {
+    let node = wallet().await;
+    let entries: Vec<WalletTransaction> = node
+        .client()
+        .list_transactions(None, 10, 0, true)
+        .await
+        .unwrap();
+    assert_eq!(entries[0].category, WalletTxCategory::Receive);
+    assert_eq!(entries[0].vout, Some(0));
+}
+
+#[tokio::test]
+async fn get_transaction_has_its_own_type() {
+    let node = wallet().await;
+    let tx: GetTransactionResult = node
+        .client()
+        .get_transaction(&"aa".repeat(32), true, false)
+        .await
+        .unwrap();
+    assert_eq!(tx.details.len(), 1);
+    assert_eq!(tx.hex, "00");
+}
@@ -0,0 +1,292 @@
+mod common;
+
+use bitcoin_sdk::{BitcoinClient, WatchBundle};
+use common::{MockNode, Reply, block_header, fixture_value, method_not_found};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+const RECEIVE: &str = "wpkh(tpubD6NzVbkrYhZ4XgiXtGrdW5XDAPFCL9h7we1vwNCpn8tGbBcgfVYjXyhWo4E1xkh56hjod1RhGjxbaTLV3X4FyWuejifB9jusQ46QzG87VKp/84h/1h/0h/0/*)#a9vfmxp5";
+const CHANGE: &str = "wpkh(tpubD6NzVbkrYhZ4XgiXtGrdW5XDAPFCL9h7we1vwNCpn8tGbBcgfVYjXyhWo4E1xkh56hjod1RhGjxbaTLV3X4FyWuejifB9jusQ46QzG87VKp/84h/1h/0h/1/*)#qvp0gpnp";
+
+// Blocks at height h have median time 1700000000 + 600h, so two hours before this
+// falls just after block 149
+const WALLET_BIRTH: u64 = 1_700_097_200;
+
+#[derive(Default)]
+struct Wallet {
+    descriptors: Vec<Value>,
+    labels: BTreeMap<String, String>,
+    rescanned: bool,
+}
+
+type Wallets = Arc<Mutex<HashMap<String, Wallet>>>;
+
+// Stand-in for key derivation: the same descriptor and index always give the same address
+fn derive(desc: &str, index: u64) -> String {
+    let checksum = desc.rsplit('#').next().unwrap();
+    format!("bcrt1q{}{:04}", checksum, index)
+}
+
+// Outputs on the chain, paying addresses of the two descriptors
+fn chain_outputs() -> Vec<(String, u64)> {
+    vec![
+        (derive(RECEIVE, 0), 100_000_000),
+        (derive(RECEIVE, 3), 25_000_000),
+        (derive(CHANGE, 1), 10_000_000),
+    ]
+}
+
+fn balance(wallet: &Wallet) -> u64 {
+    if !wallet.rescanned {
+        return 0;
+    }
+    chain_outputs()
+        .into_iter()
+        .filter(|(address, _)| {
+            wallet.descriptors.iter().any(|d| {
+                let [start, end] = [
+                    d["range"][0].as_u64().unwrap(),
+                    d["range"][1].as_u64().unwrap(),
+                ];
+                (start..=end).any(|i| derive(d["desc"].as_str().unwrap(), i) == *address)
+            })
+        })
+        .map(|(_, sats)| sats)
+        .sum()
+}
+
+fn descriptor(desc: &str, internal: bool, next: u64) -> Value {
+    json!({
+        "desc": desc,
+        "timestamp": WALLET_BIRTH,
+        "active": true,
+        "internal": internal,
+        "range": [0, 999],
+        "next": next,
+        "next_index": next,
+    })
+}
+
+// A node holding the funded source wallet "alice"
+fn source_wallets() -> Wallets {
+    let alice = Wallet {
+        descriptors: vec![descriptor(RECEIVE, false, 4), descriptor(CHANGE, true, 2)],
+        labels: BTreeMap::from([
+            (derive(RECEIVE, 0), "rent".to_string()),
+            (derive(RECEIVE, 3), "savings".to_string()),
+        ]),
+        rescanned: true,
+    };
+    Arc::new(Mutex::new(HashMap::from([("alice".to_string(), alice)])))
+}
+
+fn handle(wallets: &Wallets, path: &str, method: &str, params: &Value) -> Reply {
+    let name = path
+        .strip_prefix("/wallet/")
+        .unwrap_or_default()
+        .to_string();
+    let mut wallets = wallets.lock().unwrap();
+    match method {
+        "getblockchaininfo" => Ok(fixture_value("getblockchaininfo/v26.0-regtest")),
+        "getblockcount" => Ok(json!(200)),
+        "getblockhash" => Ok(json!(format!("{:064x}", params[0].as_u64().unwrap()))),
+        "getblockheader" => {
+            let hash = params[0].as_str().unwrap();
+            Ok(block_header(hash, u64::from_str_radix(hash, 16).unwrap()))
+        }
+        "createwallet" => {
+            let name = params[0].as_str().unwrap().to_string();
+            wallets.insert(name.clone(), Wallet::default());
+            Ok(json!({"name": name, "warning": ""}))
+        }
+        "unloadwallet" => {
+            wallets.remove(&name);
+            Ok(json!({"warning": ""}))
+        }
+        _ => {
+            let Some(wallet) = wallets.get_mut(&name) else {
+                return Err((
+                    -18,
+                    "Requested wallet does not exist or is not loaded".into(),
+                ));
+            };
+            wallet_call(wallet, &name, method, params)
+        }
+    }
+}
+
+fn wallet_call(wallet: &mut Wallet, name: &str, method: &str, params: &Value) -> Reply {
+    match method {
+        "listdescriptors" => Ok(json!({"wallet_name": name, "descriptors": wallet.descriptors})),
+        "importdescriptors" => {
+            let mut results = Vec::new();
+            for request in params[0].as_array().unwrap() {
+                if request["desc"].as_str().unwrap().contains("invalid") {
+                    results.push(json!({
+                        "success": false,
+                        "error": {"code": -5, "message": "Invalid descriptor"},
+                    }));
+                    continue;
+                }
+                let mut imported = descriptor(
+                    request["desc"].as_str().unwrap(),
+                    request["internal"].as_bool().unwrap_or(false),
+                    request["next_index"].as_u64().unwrap_or(0),
+                );
+                imported["timestamp"] = json!(1_800_000_000);
+                imported["active"] = request["active"].clone();
+                imported["range"] = request["range"].clone();
+                wallet.descriptors.push(imported);
+                results.push(json!({"success": true}));
+            }
+            Ok(Value::Array(results))
+        }
+        "setlabel" => {
+            let address = params[0].as_str().unwrap().to_string();
+            wallet
+                .labels
+                .insert(address, params[1].as_str().unwrap().to_string());
+            Ok(Value::Null)
+        }
+        "listlabels" => {
+            let labels: BTreeSet<&String> = wallet.labels.values().collect();
+            Ok(json!(labels))
+        }
+        "getaddressesbylabel" => {
+            let addresses: BTreeMap<&String, Value> = wallet
+                .labels
+                .iter()
+                .filter(|(_, label)| **label == params[0])
+                .map(|(address, _)| (address, json!({"purpose": "receive"})))
+                .collect();
+            Ok(json!(addresses))
+        }
+        "rescanblockchain" => {
+            wallet.rescanned = true;
+            Ok(json!({"start_height": params[0], "stop_height": 200}))
+        }
+        "getnewaddress" => {
+            let receive = wallet
+                .descriptors
+                .iter_mut()
+                .find(|d| d["active"] == true && d["internal"] == false)
+                .unwrap();
+            let next = receive["next_index"].as_u64().unwrap();
+            receive["next_index"] = json!(next + 1);
+            Ok(json!(derive(receive["desc"].as_str().unwrap(), next)))
+        }
+        "getbalances" => {
+            let trusted = balance(wallet) as f64 / 1e8;
+            Ok(json!({"mine": {"trusted": trusted, "untrusted_pending": 0.0, "immature": 0.0}}))
+        }
+        _ => method_not_found(),
+    }
+}
+
+async fn node(wallets: &Wallets) -> MockNode {
+    let wallets = wallets.clone();
+    MockNode::start_with_paths(move |path, method, params| handle(&wallets, path, method, params))
+        .await
+}
+
+async fn restored(client: &BitcoinClient, bundle: &WatchBundle) -> BitcoinClient {
+    client.restore_watch_bundle(bundle, "copy").await.unwrap();
+    client.wallet("copy")
+}
+
+#[tokio::test]
+async fn a_restored_bundle_derives_the_same_addresses_and_balances() {
+    let source = source_wallets();
+    let source_node = node(&source).await;
+    let bundle = source_node
+        .client()
+        .export_watch_bundle("alice")
+        .await
+        .unwrap();
+    assert_eq!(bundle.birth_time, WALLET_BIRTH);
+    // Two hours of slack before the earliest descriptor
+    assert_eq!(bundle.birth_height, 149);
+    let bundle = WatchBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+
+    let target = Arc::new(Mutex::new(HashMap::new()));
+    let target_node = node(&target).await;
+    let copy = restored(&target_node.client(), &bundle).await;
+    let alice = source_node.client().wallet("alice");
+
+    assert_eq!(
+        target_node.calls_to("rescanblockchain"),
+        vec![json!([149, null])]
+    );
+    assert_eq!(
+        copy.get_balances().await.unwrap().mine.trusted,
+        alice.get_balances().await.unwrap().mine.trusted
+    );
+    assert_eq!(
+        copy.get_balances().await.unwrap().mine.trusted.to_sat(),
+        135_000_000
+    );
+    for _ in 0..3 {
+        assert_eq!(
+            copy.get_new_address(None, None).await.unwrap(),
+            alice.get_new_address(None, None).await.unwrap()
+        );
+    }
+
+    // Exporting the copy gives back the same descriptors and labels
+    let again = target_node
+        .client()
+        .export_watch_bundle("copy")
+        .await
+        .unwrap();
+    assert_eq!(again.labels, bundle.labels);
+    let shape = |b: &WatchBundle| {
+        b.descriptors
+            .iter()
+            .map(|d| (d.desc.clone(), d.active, d.internal, d.range))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(shape(&again), shape(&bundle));
+}
+
+#[tokio::test]
+async fn a_failed_import_unloads_the_partial_wallet() {
+    let source = source_wallets();
+    let source_node = node(&source).await;
+    let mut bundle = source_node
+        .client()
+        .export_watch_bundle("alice")
+        .await
+        .unwrap();
+    bundle.descriptors[1].desc = "invalid".to_string();
+
+    let target = Arc::new(Mutex::new(HashMap::new()));
+    let target_node = node(&target).await;
+    let error = target_node
+        .client()
+        .restore_watch_bundle(&bundle, "copy")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Restoring alice into copy failed; the partial wallet was unloaded"
+    );
+    assert!(format!("{:#}", error).contains("Importing invalid failed: Invalid descriptor"));
+    assert!(target.lock().unwrap().is_empty());
+    assert_eq!(target_node.calls_to("unloadwallet"), vec![json!(["copy"])]);
+    assert!(target_node.calls_to("rescanblockchain").is_empty());
+}
+
+#[test]
+fn bundles_of_another_version_are_refused() {
+    let bundle = WatchBundle {
+        version: 2,
+        wallet_name: "alice".to_string(),
+        birth_time: WALLET_BIRTH,
+        birth_height: 149,
+        descriptors: Vec::new(),
+        labels: BTreeMap::new(),
+    };
+    let error = WatchBundle::from_json(&bundle.to_json().unwrap()).unwrap_err();
+    assert_eq!(error.to_string(), "Unsupported watch bundle version 2");
+}